@@ -0,0 +1,16 @@
+-- Persistent storage for the bot dialogue state of each chat
+DO $$ BEGIN
+CREATE TABLE IF NOT EXISTS dialogues (
+    chat_id BIGINT PRIMARY KEY,
+    dialogue JSONB NOT NULL,
+    created TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+    );
+CREATE INDEX IF NOT EXISTS idx_dialogues_updated ON dialogues (updated);
+DROP TRIGGER IF EXISTS dialogues_update
+ON dialogues;
+CREATE TRIGGER dialogues_update
+    BEFORE UPDATE ON dialogues
+    FOR EACH ROW
+    EXECUTE PROCEDURE trigger_set_timestamp();
+END $$ LANGUAGE plpgsql;
@@ -0,0 +1,31 @@
+-- Configurable reminder schedule for planned availability
+DO $$ BEGIN
+CREATE TABLE IF NOT EXISTS reminder_rules (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    -- NULL applies the rule to every role/user type
+    role_type role_type_enum,
+    usr_type user_type_enum,
+    -- NULL days_prior sends the reminder immediately once planned
+    days_prior INT CHECK (days_prior IS NULL OR days_prior >= 0),
+    time_of_day TIME,
+    created TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    is_valid BOOLEAN NOT NULL DEFAULT TRUE,
+    CHECK ((days_prior IS NULL) = (time_of_day IS NULL))
+    );
+DROP TRIGGER IF EXISTS reminder_rules_update
+ON reminder_rules;
+CREATE TRIGGER reminder_rules_update
+    BEFORE UPDATE ON reminder_rules
+    FOR EACH ROW
+    EXECUTE PROCEDURE trigger_set_timestamp();
+
+-- Seed with the previously hard-coded schedule
+IF NOT EXISTS (SELECT 1 FROM reminder_rules) THEN
+    INSERT INTO reminder_rules (days_prior, time_of_day)
+    VALUES
+        (NULL, NULL),
+        (5, '09:00'),
+        (2, '09:00');
+END IF;
+END $$ LANGUAGE plpgsql;
@@ -0,0 +1,11 @@
+-- Track delivery attempts of scheduled notifications for retrying with backoff
+DO $$ BEGIN
+ALTER TABLE scheduled_notifications
+    ADD COLUMN IF NOT EXISTS attempts INT NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS last_error TEXT,
+    ADD COLUMN IF NOT EXISTS next_retry TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN IF NOT EXISTS failed BOOLEAN NOT NULL DEFAULT FALSE;
+CREATE INDEX IF NOT EXISTS idx_scheduled_notifications_pending
+    ON scheduled_notifications (scheduled_time)
+    WHERE sent = FALSE AND failed = FALSE AND is_valid = TRUE;
+END $$ LANGUAGE plpgsql;
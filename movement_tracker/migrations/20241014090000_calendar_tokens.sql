@@ -0,0 +1,21 @@
+-- Secret tokens for each user's calendar feed of planned availability
+DO $$ BEGIN
+CREATE TABLE IF NOT EXISTS calendar_tokens (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    usr_id UUID REFERENCES usrs(id) NOT NULL,
+    token TEXT NOT NULL UNIQUE,
+    created TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    is_valid BOOLEAN NOT NULL DEFAULT TRUE
+    );
+-- Ensure there is only one valid token per user
+CREATE UNIQUE INDEX IF NOT EXISTS idx_calendar_tokens_usr_id_unique_valid
+    ON calendar_tokens (usr_id)
+    WHERE is_valid = TRUE;
+DROP TRIGGER IF EXISTS calendar_tokens_update
+ON calendar_tokens;
+CREATE TRIGGER calendar_tokens_update
+    BEFORE UPDATE ON calendar_tokens
+    FOR EACH ROW
+    EXECUTE PROCEDURE trigger_set_timestamp();
+END $$ LANGUAGE plpgsql;
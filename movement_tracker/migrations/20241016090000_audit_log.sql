@@ -0,0 +1,33 @@
+-- Append-only history of changes made to users, applications and availability
+DO $$ BEGIN
+CREATE TABLE IF NOT EXISTS audit_log (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    actor_tele_id BIGINT NOT NULL,
+    subject_tele_id BIGINT,
+    action TEXT NOT NULL,
+    entity TEXT NOT NULL,
+    entity_id UUID NOT NULL,
+    before JSONB,
+    after JSONB,
+    created TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+    );
+CREATE INDEX IF NOT EXISTS idx_audit_log_subject_created
+    ON audit_log (subject_tele_id, created);
+END $$ LANGUAGE plpgsql;
+
+-- Reject any attempt to modify or remove existing audit entries
+CREATE OR REPLACE FUNCTION audit_log_reject_modification()
+RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'audit_log is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+DO $$ BEGIN
+DROP TRIGGER IF EXISTS audit_log_append_only
+ON audit_log;
+CREATE TRIGGER audit_log_append_only
+    BEFORE UPDATE OR DELETE ON audit_log
+    FOR EACH ROW
+    EXECUTE PROCEDURE audit_log_reject_modification();
+END $$ LANGUAGE plpgsql;
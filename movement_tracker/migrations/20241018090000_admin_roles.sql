@@ -0,0 +1,27 @@
+-- Admin roles granting access to subsets of the privileged commands
+DO $$ BEGIN
+CREATE TYPE admin_role_enum AS ENUM ('planner', 'saf100_clerk', 'user_manager', 'notification_manager');
+EXCEPTION
+    WHEN duplicate_object THEN null;
+END $$ LANGUAGE plpgsql;
+
+DO $$ BEGIN
+CREATE TABLE IF NOT EXISTS usr_admin_roles (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    usr_id UUID REFERENCES usrs(id) NOT NULL,
+    admin_role admin_role_enum NOT NULL,
+    created TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    is_valid BOOLEAN NOT NULL DEFAULT TRUE
+    );
+-- Ensure each role is only granted once per user
+CREATE UNIQUE INDEX IF NOT EXISTS idx_usr_admin_roles_unique_valid
+    ON usr_admin_roles (usr_id, admin_role)
+    WHERE is_valid = TRUE;
+DROP TRIGGER IF EXISTS usr_admin_roles_update
+ON usr_admin_roles;
+CREATE TRIGGER usr_admin_roles_update
+    BEFORE UPDATE ON usr_admin_roles
+    FOR EACH ROW
+    EXECUTE PROCEDURE trigger_set_timestamp();
+END $$ LANGUAGE plpgsql;
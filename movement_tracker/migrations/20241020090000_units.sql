@@ -0,0 +1,51 @@
+-- Units (e.g. squadrons) served by the deployment, users and their settings are scoped to one unit
+DO $$ BEGIN
+CREATE TABLE IF NOT EXISTS units (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    name TEXT NOT NULL,
+    created TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    is_valid BOOLEAN NOT NULL DEFAULT TRUE
+    );
+-- Ensure there is only one (name, is_valid = true) in the units table
+CREATE UNIQUE INDEX IF NOT EXISTS idx_units_name_unique_valid
+    ON units (name)
+    WHERE is_valid = TRUE;
+DROP TRIGGER IF EXISTS units_update
+ON units;
+CREATE TRIGGER units_update
+    BEFORE UPDATE ON units
+    FOR EACH ROW
+    EXECUTE PROCEDURE trigger_set_timestamp();
+END $$ LANGUAGE plpgsql;
+
+-- Existing data is moved into a default unit
+INSERT INTO units (name)
+SELECT 'DEFAULT'
+WHERE NOT EXISTS (SELECT 1 FROM units WHERE is_valid = TRUE);
+
+DO $$ BEGIN
+ALTER TABLE usrs ADD COLUMN IF NOT EXISTS unit_id UUID REFERENCES units(id);
+ALTER TABLE apply ADD COLUMN IF NOT EXISTS unit_id UUID REFERENCES units(id);
+ALTER TABLE notification_settings ADD COLUMN IF NOT EXISTS unit_id UUID REFERENCES units(id);
+
+UPDATE usrs SET unit_id = (SELECT id FROM units WHERE is_valid = TRUE ORDER BY created LIMIT 1) WHERE unit_id IS NULL;
+UPDATE apply SET unit_id = (SELECT id FROM units WHERE is_valid = TRUE ORDER BY created LIMIT 1) WHERE unit_id IS NULL;
+UPDATE notification_settings SET unit_id = (SELECT id FROM units WHERE is_valid = TRUE ORDER BY created LIMIT 1) WHERE unit_id IS NULL;
+
+ALTER TABLE usrs ALTER COLUMN unit_id SET NOT NULL;
+ALTER TABLE apply ALTER COLUMN unit_id SET NOT NULL;
+ALTER TABLE notification_settings ALTER COLUMN unit_id SET NOT NULL;
+END $$ LANGUAGE plpgsql;
+
+DO $$ BEGIN
+-- Ops names only need to be unique within a unit
+DROP INDEX IF EXISTS idx_usrs_ops_name_unique_valid;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_usrs_unit_ops_name_unique_valid
+    ON usrs (unit_id, ops_name)
+    WHERE is_valid = TRUE;
+DROP INDEX IF EXISTS idx_apply_ops_name_unique_valid;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_apply_unit_ops_name_unique_valid
+    ON apply (unit_id, ops_name)
+    WHERE is_valid = TRUE;
+END $$ LANGUAGE plpgsql;
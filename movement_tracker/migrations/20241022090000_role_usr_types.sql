@@ -0,0 +1,64 @@
+-- Crew roles and user types are reference tables managed by admins instead of fixed enums
+DO $$ BEGIN
+CREATE TABLE IF NOT EXISTS role_types (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    name TEXT NOT NULL UNIQUE,
+    created TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    is_valid BOOLEAN NOT NULL DEFAULT TRUE
+    );
+DROP TRIGGER IF EXISTS role_types_update
+ON role_types;
+CREATE TRIGGER role_types_update
+    BEFORE UPDATE ON role_types
+    FOR EACH ROW
+    EXECUTE PROCEDURE trigger_set_timestamp();
+
+CREATE TABLE IF NOT EXISTS usr_types (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    name TEXT NOT NULL UNIQUE,
+    created TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    is_valid BOOLEAN NOT NULL DEFAULT TRUE
+    );
+DROP TRIGGER IF EXISTS usr_types_update
+ON usr_types;
+CREATE TRIGGER usr_types_update
+    BEFORE UPDATE ON usr_types
+    FOR EACH ROW
+    EXECUTE PROCEDURE trigger_set_timestamp();
+
+-- Seed with the values of the previous enums
+INSERT INTO role_types (name)
+VALUES ('PILOT'), ('ARO')
+ON CONFLICT (name) DO NOTHING;
+INSERT INTO usr_types (name)
+VALUES ('ACTIVE'), ('STAFF'), ('NS')
+ON CONFLICT (name) DO NOTHING;
+END $$ LANGUAGE plpgsql;
+
+-- Replace the enum columns with references to the new tables, keeping the upper case names
+DO $$ BEGIN
+ALTER TABLE usrs
+    ALTER COLUMN role_type TYPE TEXT USING UPPER(role_type::TEXT),
+    ALTER COLUMN usr_type TYPE TEXT USING UPPER(usr_type::TEXT);
+ALTER TABLE apply
+    ALTER COLUMN role_type TYPE TEXT USING UPPER(role_type::TEXT),
+    ALTER COLUMN usr_type TYPE TEXT USING UPPER(usr_type::TEXT);
+ALTER TABLE reminder_rules
+    ALTER COLUMN role_type TYPE TEXT USING UPPER(role_type::TEXT),
+    ALTER COLUMN usr_type TYPE TEXT USING UPPER(usr_type::TEXT);
+
+ALTER TABLE usrs
+    ADD CONSTRAINT usrs_role_type_fkey FOREIGN KEY (role_type) REFERENCES role_types(name) ON UPDATE CASCADE,
+    ADD CONSTRAINT usrs_usr_type_fkey FOREIGN KEY (usr_type) REFERENCES usr_types(name) ON UPDATE CASCADE;
+ALTER TABLE apply
+    ADD CONSTRAINT apply_role_type_fkey FOREIGN KEY (role_type) REFERENCES role_types(name) ON UPDATE CASCADE,
+    ADD CONSTRAINT apply_usr_type_fkey FOREIGN KEY (usr_type) REFERENCES usr_types(name) ON UPDATE CASCADE;
+ALTER TABLE reminder_rules
+    ADD CONSTRAINT reminder_rules_role_type_fkey FOREIGN KEY (role_type) REFERENCES role_types(name) ON UPDATE CASCADE,
+    ADD CONSTRAINT reminder_rules_usr_type_fkey FOREIGN KEY (usr_type) REFERENCES usr_types(name) ON UPDATE CASCADE;
+
+DROP TYPE IF EXISTS role_type_enum;
+DROP TYPE IF EXISTS user_type_enum;
+END $$ LANGUAGE plpgsql;
@@ -0,0 +1,20 @@
+-- Crew needed per date, role and ICT type, used to suggest who to plan
+DO $$ BEGIN
+CREATE TABLE IF NOT EXISTS crew_requirements (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    unit_id UUID NOT NULL REFERENCES units(id),
+    avail DATE NOT NULL,
+    role_type TEXT NOT NULL REFERENCES role_types(name) ON UPDATE CASCADE,
+    ict_type ict_enum NOT NULL,
+    headcount INT NOT NULL CHECK (headcount >= 0),
+    created TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    UNIQUE (unit_id, avail, role_type, ict_type)
+    );
+DROP TRIGGER IF EXISTS crew_requirements_update
+ON crew_requirements;
+CREATE TRIGGER crew_requirements_update
+    BEFORE UPDATE ON crew_requirements
+    FOR EACH ROW
+    EXECUTE PROCEDURE trigger_set_timestamp();
+END $$ LANGUAGE plpgsql;
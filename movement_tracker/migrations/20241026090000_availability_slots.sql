@@ -0,0 +1,13 @@
+-- Availability may cover part of a day, NULL times cover the whole day
+DO $$ BEGIN
+ALTER TABLE availability ADD COLUMN IF NOT EXISTS start_time TIME;
+ALTER TABLE availability ADD COLUMN IF NOT EXISTS end_time TIME;
+ALTER TABLE availability DROP CONSTRAINT IF EXISTS availability_slot_check;
+ALTER TABLE availability ADD CONSTRAINT availability_slot_check
+    CHECK ((start_time IS NULL AND end_time IS NULL) OR (start_time IS NOT NULL AND end_time IS NOT NULL AND start_time < end_time));
+
+-- A user may have several slots on a date, but only one entry per slot
+DROP INDEX IF EXISTS idx_availability_usr_date;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_availability_usr_date_slot
+    ON availability (usr_id, avail, start_time) NULLS NOT DISTINCT;
+END $$ LANGUAGE plpgsql;
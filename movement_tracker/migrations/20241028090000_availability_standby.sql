@@ -0,0 +1,7 @@
+-- Crew may be planned as standby for a date, in case a planned member withdraws
+DO $$ BEGIN
+ALTER TABLE availability ADD COLUMN IF NOT EXISTS standby BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE availability DROP CONSTRAINT IF EXISTS availability_plan_status_check;
+ALTER TABLE availability ADD CONSTRAINT availability_plan_status_check
+    CHECK (NOT (planned AND standby));
+END $$ LANGUAGE plpgsql;
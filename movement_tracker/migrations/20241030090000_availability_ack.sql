@@ -0,0 +1,10 @@
+-- Responses to the reminders sent for planned and standby entries
+DO $$ BEGIN
+CREATE TYPE ack_enum AS ENUM ('acknowledged', 'declined');
+EXCEPTION
+    WHEN duplicate_object THEN null;
+END $$ LANGUAGE plpgsql;
+
+DO $$ BEGIN
+ALTER TABLE availability ADD COLUMN IF NOT EXISTS ack ack_enum;
+END $$ LANGUAGE plpgsql;
@@ -0,0 +1,4 @@
+-- Chats may opt in to a daily digest of the next day's planned crew
+DO $$ BEGIN
+ALTER TABLE notification_settings ADD COLUMN IF NOT EXISTS notif_digest BOOLEAN NOT NULL DEFAULT FALSE;
+END $$ LANGUAGE plpgsql;
@@ -0,0 +1,31 @@
+-- Requests sent to users for their availability in a date window, with the users each was sent to
+DO $$ BEGIN
+CREATE TABLE IF NOT EXISTS campaigns (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    unit_id UUID NOT NULL REFERENCES units(id),
+    start_date DATE NOT NULL,
+    end_date DATE NOT NULL,
+    role_type TEXT REFERENCES role_types(name) ON UPDATE CASCADE,
+    usr_type TEXT REFERENCES usr_types(name) ON UPDATE CASCADE,
+    nudges INT NOT NULL DEFAULT 0,
+    last_nudged TIMESTAMP WITH TIME ZONE,
+    created TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    is_valid BOOLEAN NOT NULL DEFAULT TRUE,
+    CHECK (end_date >= start_date)
+    );
+DROP TRIGGER IF EXISTS campaigns_update
+ON campaigns;
+CREATE TRIGGER campaigns_update
+    BEFORE UPDATE ON campaigns
+    FOR EACH ROW
+    EXECUTE PROCEDURE trigger_set_timestamp();
+
+CREATE TABLE IF NOT EXISTS campaign_recipients (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    campaign_id UUID NOT NULL REFERENCES campaigns(id),
+    usr_id UUID NOT NULL REFERENCES usrs(id),
+    created TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    UNIQUE (campaign_id, usr_id)
+    );
+END $$ LANGUAGE plpgsql;
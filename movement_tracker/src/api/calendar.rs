@@ -0,0 +1,121 @@
+use std::sync::Arc;
+
+use axum::extract::{Extension, Path};
+use axum::http::{header, StatusCode};
+use axum::response::IntoResponse;
+use chrono::Duration;
+
+use crate::api::ApiError;
+use crate::types::{AvailabilityDetails, Usr};
+use crate::{controllers, AppState};
+
+// Escapes text values according to RFC 5545
+fn escape_ics_text(input: &str) -> String {
+    input
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+// Folds content lines longer than 75 octets, as required by RFC 5545
+fn fold_ics_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut line_len = 0;
+
+    for ch in line.chars() {
+        if line_len + ch.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            line_len = 1;
+        }
+        folded.push(ch);
+        line_len += ch.len_utf8();
+    }
+    folded.push_str("\r\n");
+
+    folded
+}
+
+fn format_event(availability: &AvailabilityDetails, user: &Usr) -> Vec<String> {
+    let saf100_str = if user.usr_type.is_ns() {
+        if availability.saf100 { "SAF100 issued" } else { "SAF100 pending" }
+    } else {
+        ""
+    };
+
+    let mut description = Vec::new();
+    if let Some(remarks) = &availability.remarks {
+        description.push(format!("Remarks: {}", remarks));
+    }
+    if !saf100_str.is_empty() {
+        description.push(saf100_str.to_string());
+    }
+
+    let mut lines = vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}@movement_tracker", availability.id),
+        format!("DTSTAMP:{}", availability.updated.format("%Y%m%dT%H%M%SZ")),
+        format!("LAST-MODIFIED:{}", availability.updated.format("%Y%m%dT%H%M%SZ")),
+    ];
+    match (availability.start_time, availability.end_time) {
+        // Slots are in local time, so they are written as floating times
+        (Some(start), Some(end)) => {
+            lines.push(format!("DTSTART:{}", availability.avail.and_time(start).format("%Y%m%dT%H%M%S")));
+            lines.push(format!("DTEND:{}", availability.avail.and_time(end).format("%Y%m%dT%H%M%S")));
+        }
+        _ => {
+            lines.push(format!("DTSTART;VALUE=DATE:{}", availability.avail.format("%Y%m%d")));
+            lines.push(format!("DTEND;VALUE=DATE:{}", (availability.avail + Duration::days(1)).format("%Y%m%d")));
+        }
+    }
+    lines.push(format!("SUMMARY:{}", escape_ics_text(&format!("{}: {}", if availability.standby { "Standby" } else { "Planned" }, availability.ict_type.as_ref()))));
+    if !description.is_empty() {
+        lines.push(format!("DESCRIPTION:{}", escape_ics_text(&description.join("\n"))));
+    }
+    lines.push("TRANSP:TRANSPARENT".to_string());
+    lines.push("END:VEVENT".to_string());
+
+    lines
+}
+
+fn generate_calendar(user: &Usr, availability_list: &[AvailabilityDetails]) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//movement_tracker//planned availability//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!("X-WR-CALNAME:{}", escape_ics_text(&format!("Planned ({})", user.ops_name))),
+        "REFRESH-INTERVAL;VALUE=DURATION:PT1H".to_string(),
+        "X-PUBLISHED-TTL:PT1H".to_string(),
+    ];
+
+    // Unplanned or invalidated entries are left out, so they disappear from subscribed calendars
+    for availability in availability_list.iter().filter(|availability| (availability.planned || availability.standby) && availability.is_valid) {
+        lines.extend(format_event(availability, user));
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold_ics_line(line)).collect()
+}
+
+pub(crate) async fn get_calendar(
+    Path(token): Path<String>,
+    Extension(state): Extension<Arc<AppState>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let token = token.strip_suffix(".ics").unwrap_or(&token);
+
+    let user = match controllers::calendar::get_user_by_calendar_token(&state.db_pool, token).await? {
+        Some(user) => user,
+        None => return Err(ApiError::new(StatusCode::NOT_FOUND, "Calendar not found")),
+    };
+
+    let availability_list = controllers::scheduling::get_planned_availability_details_by_tele_id(&state.db_pool, user.tele_id as u64).await?;
+
+    Ok((
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, "text/calendar; charset=utf-8")],
+        generate_calendar(&user, &availability_list)
+    ))
+}
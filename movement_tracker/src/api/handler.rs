@@ -0,0 +1,118 @@
+use std::sync::Arc;
+
+use axum::extract::{Extension, Query};
+use axum::http::StatusCode;
+use axum::Json;
+use chrono::{Duration, NaiveDate, Utc};
+use serde::Deserialize;
+use sqlx::types::Uuid;
+
+use crate::api::{ApiError, ApiToken};
+use crate::types::{AvailabilityDetails, RoleType, UserStats, Usr};
+use crate::{controllers, now, utils, AppState, APP_TIMEZONE};
+
+// Maximum number of days which can be requested at once
+const MAX_RANGE_DAYS: i64 = 366;
+
+#[derive(Deserialize)]
+pub(crate) struct AvailabilityQuery {
+    role: RoleType,
+    start: Option<NaiveDate>,
+    end: Option<NaiveDate>,
+    unit: Option<Uuid>,
+}
+
+// Every role is returned if absent
+#[derive(Deserialize)]
+pub(crate) struct StatsQuery {
+    role: Option<RoleType>,
+    start: Option<NaiveDate>,
+    end: Option<NaiveDate>,
+    unit: Option<Uuid>,
+}
+
+// Limits the results to a single unit, all units are returned if absent
+#[derive(Deserialize)]
+pub(crate) struct UnitQuery {
+    unit: Option<Uuid>,
+}
+
+impl AvailabilityQuery {
+    // Defaults to the coming week, the same as the forecast command
+    fn date_range(&self) -> Result<(NaiveDate, NaiveDate), ApiError> {
+        let start = self.start.unwrap_or_else(|| now!().date_naive());
+        let end = self.end.unwrap_or_else(|| start + Duration::weeks(1));
+
+        check_date_range(start, end)
+    }
+}
+
+impl StatsQuery {
+    // Defaults to this month, the same as the stats command
+    fn date_range(&self) -> Result<(NaiveDate, NaiveDate), ApiError> {
+        let (this_month_start, this_month_end) = utils::this_month_bound();
+        let start = self.start.unwrap_or(this_month_start);
+        let end = self.end.unwrap_or(if self.start.is_some() { utils::last_day_of_month(start) } else { this_month_end });
+
+        check_date_range(start, end)
+    }
+}
+
+fn check_date_range(start: NaiveDate, end: NaiveDate) -> Result<(NaiveDate, NaiveDate), ApiError> {
+    if end < start {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "end must not be before start"));
+    }
+    if (end - start).num_days() > MAX_RANGE_DAYS {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, format!("Date range must not exceed {} days", MAX_RANGE_DAYS)));
+    }
+
+    Ok((start, end))
+}
+
+pub(crate) async fn get_users(
+    _: ApiToken,
+    Extension(state): Extension<Arc<AppState>>,
+    Query(query): Query<UnitQuery>,
+) -> Result<Json<Vec<Usr>>, ApiError> {
+    let users = controllers::user::get_all_users(&state.db_pool).await?;
+    Ok(Json(users.into_iter().filter(|user| query.unit.is_none_or(|unit_id| user.unit_id == unit_id)).collect()))
+}
+
+pub(crate) async fn get_availability(
+    _: ApiToken,
+    Extension(state): Extension<Arc<AppState>>,
+    Query(query): Query<AvailabilityQuery>,
+) -> Result<Json<Vec<AvailabilityDetails>>, ApiError> {
+    let (start, end) = query.date_range()?;
+    let availability_list = controllers::scheduling::get_availability_for_role_and_dates(&state.db_pool, query.unit, query.role, start, end).await?;
+    Ok(Json(availability_list))
+}
+
+pub(crate) async fn get_planned(
+    _: ApiToken,
+    Extension(state): Extension<Arc<AppState>>,
+    Query(query): Query<AvailabilityQuery>,
+) -> Result<Json<Vec<AvailabilityDetails>>, ApiError> {
+    let (start, end) = query.date_range()?;
+    let availability_list = controllers::scheduling::get_availability_for_role_and_dates(&state.db_pool, query.unit, query.role, start, end).await?;
+    Ok(Json(availability_list.into_iter().filter(|availability| availability.planned).collect()))
+}
+
+pub(crate) async fn get_saf100(
+    _: ApiToken,
+    Extension(state): Extension<Arc<AppState>>,
+    Query(query): Query<UnitQuery>,
+) -> Result<Json<Vec<AvailabilityDetails>>, ApiError> {
+    let availability_list = controllers::attendance::get_future_planned_availability_for_ns(&state.db_pool, query.unit).await?;
+    Ok(Json(availability_list))
+}
+
+pub(crate) async fn get_stats(
+    _: ApiToken,
+    Extension(state): Extension<Arc<AppState>>,
+    Query(query): Query<StatsQuery>,
+) -> Result<Json<Vec<UserStats>>, ApiError> {
+    let (start, end) = query.date_range()?;
+    let stats_list = controllers::stats::get_user_stats(&state.db_pool, query.unit, query.role.as_ref(), start, end, now!().date_naive()).await?;
+    Ok(Json(stats_list))
+}
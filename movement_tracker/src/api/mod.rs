@@ -0,0 +1,115 @@
+use std::env;
+
+use axum::async_trait;
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use subtle::ConstantTimeEq;
+
+pub(crate) mod handler;
+pub(crate) mod calendar;
+
+// Publicly reachable URL of the HTTP server, used to build calendar feed links
+static PUBLIC_BASE_URL: Lazy<Option<String>> = Lazy::new(|| {
+    match env::var("PUBLIC_BASE_URL") {
+        Ok(url) => Some(url.trim_end_matches('/').to_string()),
+        Err(_) => {
+            log::warn!("PUBLIC_BASE_URL is not set. Calendar feed links will be unavailable.");
+            None
+        }
+    }
+});
+
+pub(crate) fn calendar_url(token: &str) -> Option<String> {
+    PUBLIC_BASE_URL.as_ref().map(|base_url| format!("{}/api/calendar/{}.ics", base_url, token))
+}
+
+// Tokens accepted by the API, read from the comma separated API_TOKENS environment variable
+static API_TOKENS: Lazy<Vec<String>> = Lazy::new(get_api_tokens);
+
+fn get_api_tokens() -> Vec<String> {
+    match env::var("API_TOKENS") {
+        Ok(tokens_env) => {
+            let tokens: Vec<String> = tokens_env
+                .split(',')
+                .map(|token| token.trim().to_string())
+                .filter(|token| !token.is_empty())
+                .collect();
+            log::info!("Loaded {} API tokens", tokens.len());
+            tokens
+        }
+        Err(_) => {
+            log::warn!("API_TOKENS is not set. The API will reject all requests.");
+            Vec::new()
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ApiErrorResponse {
+    error: String,
+}
+
+pub(crate) struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    pub(crate) fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        ApiError { status, message: message.into() }
+    }
+}
+
+impl From<sqlx::Error> for ApiError {
+    fn from(_: sqlx::Error) -> Self {
+        ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Error occurred accessing the database")
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(ApiErrorResponse { error: self.message })).into_response()
+    }
+}
+
+// Extractor which rejects requests without a valid bearer token
+pub(crate) struct ApiToken;
+
+#[async_trait]
+impl<S> FromRequestParts<S> for ApiToken
+where
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let token = parts.headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(|token| token.trim());
+
+        match token {
+            // Compare in constant time so that response times do not reveal how much of a token matched
+            Some(token) if API_TOKENS.iter().any(|valid| bool::from(valid.as_bytes().ct_eq(token.as_bytes()))) => Ok(ApiToken),
+            Some(_) => Err(ApiError::new(StatusCode::UNAUTHORIZED, "Invalid API token")),
+            None => Err(ApiError::new(StatusCode::UNAUTHORIZED, "Missing API token")),
+        }
+    }
+}
+
+pub(crate) fn router() -> Router {
+    Router::new()
+        .route("/users", get(handler::get_users))
+        .route("/availability", get(handler::get_availability))
+        .route("/planned", get(handler::get_planned))
+        .route("/saf100", get(handler::get_saf100))
+        .route("/stats", get(handler::get_stats))
+        .route("/calendar/:token", get(calendar::get_calendar))
+}
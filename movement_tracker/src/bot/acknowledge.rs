@@ -0,0 +1,127 @@
+use sqlx::PgPool;
+
+use teloxide::prelude::*;
+use teloxide::types::ParseMode;
+
+use crate::bot::availability::get_standby_text;
+use crate::bot::{handle_error, log_try_remove_markup, retrieve_callback_data, send_msg, HandlerResult, MyDialogue};
+use crate::notifier::scheduled::{ReminderCallbacks, REMINDER_CALLBACK_PREFIX};
+use crate::types::Ack;
+use crate::{controllers, log_endpoint_hit, notifier, utils};
+
+use callback_data::CallbackDataHandler;
+
+// Reminder buttons can be pressed in any state, so they are matched on their fixed prefix instead
+pub(super) fn is_reminder_response(q: CallbackQuery) -> bool {
+    q.data
+        .as_deref()
+        .and_then(|data| ReminderCallbacks::from_callback_data(data, REMINDER_CALLBACK_PREFIX))
+        .is_some()
+}
+
+pub(super) async fn acknowledge(
+    bot: Bot,
+    dialogue: MyDialogue,
+    q: CallbackQuery,
+    pool: PgPool
+) -> HandlerResult {
+    log_endpoint_hit!(dialogue.chat_id(), "acknowledge", "Callback", q);
+
+    // Extract the callback data
+    let data = match retrieve_callback_data(&bot, dialogue.chat_id(), &q).await {
+        Ok(data) => data,
+        Err(_) => { return Ok(()); }
+    };
+
+    // Acknowledge the callback to remove the loading state
+    if let Err(e) = bot.answer_callback_query(q.id).await {
+        log::error!("Failed to answer callback query: {}", e);
+    }
+
+    let (availability_id, ack) = match ReminderCallbacks::from_callback_data(&data, REMINDER_CALLBACK_PREFIX) {
+        Some(ReminderCallbacks::Acknowledge { id }) => (id, Ack::Acknowledged),
+        Some(ReminderCallbacks::Decline { id }) => (id, Ack::Declined),
+        None => {
+            log::error!("Failed to parse callback data");
+            return Ok(());
+        }
+    };
+
+    let user = match controllers::user::get_user_by_tele_id(&pool, q.from.id.0).await {
+        Ok(user) => user,
+        Err(_) => {
+            handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await;
+            return Ok(());
+        }
+    };
+
+    let details = match controllers::scheduling::set_availability_ack(&pool, q.from.id.0, availability_id, ack).await {
+        Ok(Some(details)) => details,
+        Ok(None) => {
+            if let Some(message) = &q.message {
+                log_try_remove_markup(&bot, dialogue.chat_id(), message.id()).await;
+            }
+            send_msg(
+                bot.send_message(dialogue.chat_id(), "You are no longer planned for this date."),
+                &q.from.username,
+            ).await;
+            return Ok(());
+        }
+        Err(_) => {
+            handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await;
+            return Ok(());
+        }
+    };
+
+    // The reminder can only be answered once
+    if let Some(message) = &q.message {
+        log_try_remove_markup(&bot, dialogue.chat_id(), message.id()).await;
+    }
+
+    let date_str = utils::escape_special_characters(&format!(
+        "{}{}",
+        details.avail.format("%Y-%m-%d"),
+        utils::format_slot_suffix(details.start_time, details.end_time)
+    ));
+
+    match ack {
+        Ack::Acknowledged => {
+            send_msg(
+                bot.send_message(dialogue.chat_id(), format!("✅ Acknowledged for {}\\.", date_str))
+                    .parse_mode(ParseMode::MarkdownV2),
+                &q.from.username,
+            ).await;
+        }
+        Ack::Declined => {
+            // planned crew who drop out leave a gap, so point the planners to the standby crew
+            let standby_str = if details.planned {
+                get_standby_text(&pool, user.unit_id, &details.avail, &user.role_type).await
+            } else {
+                "".to_string()
+            };
+
+            notifier::emit::conflict_notifications(
+                &bot,
+                format!(
+                    "`{}`{} *CAN'T MAKE IT* on {}\\, but they are *{}* ⚠️{}{}",
+                    utils::escape_special_characters(&details.ops_name),
+                    if details.usr_type.is_ns() {" \\(NS\\)"} else {""},
+                    date_str,
+                    details.plan_status().as_ref(),
+                    if details.saf100 { " SAF100 ISSUED" } else { "" },
+                    standby_str
+                ).as_str(),
+                &pool,
+                Some(user.unit_id),
+            ).await;
+
+            send_msg(
+                bot.send_message(dialogue.chat_id(), format!("❌ The planners have been told you can't make it on {}\\.", date_str))
+                    .parse_mode(ParseMode::MarkdownV2),
+                &q.from.username,
+            ).await;
+        }
+    }
+
+    Ok(())
+}
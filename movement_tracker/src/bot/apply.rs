@@ -8,12 +8,11 @@ use teloxide::prelude::*;
 use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, MessageId, ParseMode, ReplyParameters};
 
 use crate::bot::state::State;
-use crate::bot::{handle_error, log_try_delete_msg, log_try_remove_markup, match_callback_data, retrieve_callback_data, send_msg, send_or_edit_msg, validate_name, validate_ops_name, HandlerResult, MyDialogue};
+use crate::bot::{check_actor_admin, handle_error, log_try_delete_msg, log_try_remove_markup, match_callback_data, retrieve_callback_data, send_msg, send_or_edit_msg, validate_name, validate_ops_name, HandlerResult, MyDialogue};
 use crate::types::{Apply, RoleType, UsrType};
 use crate::{controllers, log_endpoint_hit, notifier, now, utils, APP_TIMEZONE};
 
 use serde::{Deserialize, Serialize};
-use strum::IntoEnumIterator;
 use strum_macros::EnumProperty;
 use callback_data::CallbackData;
 use callback_data::CallbackDataHandler;
@@ -216,8 +215,8 @@ async fn display_application_edit_prompt(
     send_or_edit_msg(bot, chat_id, username, edit_id, get_application_edit_text(&application, admin), Some(InlineKeyboardMarkup::new(options)), Some(ParseMode::MarkdownV2)).await
 }
 
-async fn display_edit_role_types(bot: &Bot, chat_id: ChatId, username: &Option<String>, prefix: &String) -> Option<MessageId> {
-    let roles = RoleType::iter()
+async fn display_edit_role_types(bot: &Bot, chat_id: ChatId, username: &Option<String>, role_types: Vec<RoleType>, prefix: &String) -> Option<MessageId> {
+    let roles = role_types.into_iter()
         .map(|role| InlineKeyboardButton::callback(role.clone().as_ref(), ApplyCallbacks::SelectRoleType { role_type: role }.to_callback_data(&prefix)));
 
     send_msg(
@@ -227,8 +226,8 @@ async fn display_edit_role_types(bot: &Bot, chat_id: ChatId, username: &Option<S
     ).await
 }
 
-async fn display_edit_user_types(bot: &Bot, chat_id: ChatId, username: &Option<String>, prefix: &String) -> Option<MessageId> {
-    let usrtypes = UsrType::iter()
+async fn display_edit_user_types(bot: &Bot, chat_id: ChatId, username: &Option<String>, usr_types: Vec<UsrType>, prefix: &String) -> Option<MessageId> {
+    let usrtypes = usr_types.into_iter()
         .map(|usrtype| InlineKeyboardButton::callback(usrtype.clone().as_ref(), ApplyCallbacks::SelectUserType { user_type: usrtype }.to_callback_data(&prefix)));
 
     send_msg(
@@ -274,8 +273,17 @@ pub(super) async fn approve(bot: Bot, dialogue: MyDialogue, msg: Message, pool:
         return Ok(());
     };
 
+    // Applications are reviewed by the admins of the unit applied to
+    let unit_id = match controllers::user::get_unit_id_by_tele_id(&pool, user.id.0).await {
+        Ok(unit_id) => unit_id,
+        Err(_) => {
+            handle_error(&bot, &dialogue, dialogue.chat_id(), &user.username).await;
+            return Ok(());
+        }
+    };
+
     // Retrieve all the pending applications
-    match controllers::apply::get_all_apply_requests(&pool)
+    match controllers::apply::get_all_apply_requests(&pool, unit_id)
         .await {
         Ok(applications) => {
             if applications.is_empty() {
@@ -425,18 +433,24 @@ pub(super) async fn apply_edit_prompt(
     
     match callback {
         ApplyCallbacks::Approve => {
+            // Checked again in case the actor lost admin status after setting ADMIN
+            if admin && !check_actor_admin(&bot, &dialogue, &q.from, &pool, "Only admins can approve a user as an admin.").await {
+                return Ok(());
+            }
             // Remove the application
-            match controllers::apply::remove_apply_by_uuid(&pool, application.id).await {
+            match controllers::apply::remove_apply_by_uuid(&pool, q.from.id.0, application.id).await {
                 Ok(_) => {
                     // Add the user to the database
                     match controllers::user::add_user(
                         &pool,
+                        q.from.id.0,
                         application.tele_id as u64,
                         application.name,
                         application.ops_name,
                         application.role_type,
                         application.usr_type,
                         admin,
+                        application.unit_id,
                     ).await {
                         Ok(user) => {
                             // Emit system notification to indicate who has approved the user
@@ -463,6 +477,7 @@ pub(super) async fn apply_edit_prompt(
                                     has_username
                                 ).as_str(),
                                 &pool,
+                                Some(user.unit_id),
                                 q.from.id.0 as i64
                             ).await;
 
@@ -472,11 +487,13 @@ pub(super) async fn apply_edit_prompt(
                                 match controllers::notifications::update_notification_settings(
                                     &pool,
                                     user.tele_id as i64, // Assuming chat_id == tele_id
+                                    user.unit_id,
                                     Some(true),  // notif_system
                                     Some(true),  // notif_register
                                     None,        // notif_availability
                                     Some(true),  // notif_plan
                                     Some(true),  // notif_conflict
+                                    None,        // notif_digest
                                 ).await {
                                     Ok(_) => {
                                         log::info!("Default notification settings configured for admin user {}", &user.name);
@@ -538,7 +555,7 @@ pub(super) async fn apply_edit_prompt(
         ApplyCallbacks::Reject => {
             // Operation cancelled
             log_try_remove_markup(&bot, dialogue.chat_id(), msg_id).await;
-            match controllers::apply::remove_apply_by_uuid(&pool, application.id).await {
+            match controllers::apply::remove_apply_by_uuid(&pool, q.from.id.0, application.id).await {
                 Ok(success) => {
                     // Fetch the user's chat info dynamically using getChat
                     let user_chat = bot.get_chat(ChatId(application.tele_id)).await;
@@ -562,6 +579,7 @@ pub(super) async fn apply_edit_prompt(
                             has_username
                         ).as_str(),
                         &pool,
+                        Some(application.unit_id),
                         q.from.id.0 as i64
                     ).await;
                     
@@ -593,7 +611,14 @@ pub(super) async fn apply_edit_prompt(
         ApplyCallbacks::RoleType => {
             log_try_remove_markup(&bot, dialogue.chat_id(), msg_id).await;
             // Edit role
-            match display_edit_role_types(&bot, dialogue.chat_id(), &q.from.username, &prefix).await {
+            let role_types = match controllers::crew_types::get_role_types(&pool).await {
+                Ok(role_types) => role_types,
+                Err(_) => {
+                    handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await;
+                    return Ok(());
+                }
+            };
+            match display_edit_role_types(&bot, dialogue.chat_id(), &q.from.username, role_types, &prefix).await {
                 None => dialogue.update(State::ErrorState).await?,
                 Some(change_msg_id) => dialogue.update(State::ApplyEditRole { msg_id, prefix, change_msg_id, application, admin }).await?
             }
@@ -601,12 +626,22 @@ pub(super) async fn apply_edit_prompt(
         ApplyCallbacks::UserType => {
             log_try_remove_markup(&bot, dialogue.chat_id(), msg_id).await;
             // Edit user type
-            match display_edit_user_types(&bot, dialogue.chat_id(), &q.from.username, &prefix).await {
+            let usr_types = match controllers::crew_types::get_usr_types(&pool).await {
+                Ok(usr_types) => usr_types,
+                Err(_) => {
+                    handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await;
+                    return Ok(());
+                }
+            };
+            match display_edit_user_types(&bot, dialogue.chat_id(), &q.from.username, usr_types, &prefix).await {
                 None => dialogue.update(State::ErrorState).await?,
                 Some(change_msg_id) => dialogue.update(State::ApplyEditType { msg_id, prefix, change_msg_id, application, admin }).await?
             }
         }
         ApplyCallbacks::Admin => {
+            if !check_actor_admin(&bot, &dialogue, &q.from, &pool, "Only admins can change admin status.").await {
+                return Ok(());
+            }
             log_try_remove_markup(&bot, dialogue.chat_id(), msg_id).await;
             // Edit admin status
             match display_edit_admin(&bot, dialogue.chat_id(), &q.from.username, &prefix).await {
@@ -710,7 +745,7 @@ pub(super) async fn apply_edit_ops_name(
 
     match msg.text().map(ToOwned::to_owned) {
         Some(input_ops_name_raw) => {
-            match validate_ops_name(&bot, &dialogue, &user.username, input_ops_name_raw, &pool).await {
+            match validate_ops_name(&bot, &dialogue, &user.username, input_ops_name_raw, application.unit_id, &pool).await {
                 Ok(ops_name) => {
                     // OPS name is unique, proceed with registration
                     application.ops_name = ops_name.clone();
@@ -0,0 +1,387 @@
+use std::cmp::{max, min};
+use std::collections::HashSet;
+
+use sqlx::types::chrono::NaiveDate;
+use sqlx::types::Uuid;
+use sqlx::PgPool;
+
+use teloxide::prelude::*;
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, MessageId, ParseMode};
+
+use crate::bot::state::State;
+use crate::bot::{handle_error, match_callback_data, retrieve_callback_data, send_msg, send_or_edit_msg, HandlerResult, MyDialogue};
+use crate::types::{AvailabilityDetails, RoleType};
+use crate::{controllers, log_endpoint_hit, notifier, now, utils, APP_TIMEZONE};
+
+use chrono::Utc;
+use serde::{Serialize, Deserialize};
+use strum::EnumProperty;
+use callback_data::CallbackData;
+use callback_data::CallbackDataHandler;
+
+// Represents callback actions with optional associated data.
+#[derive(Debug, Clone, Serialize, Deserialize, EnumProperty, CallbackData)]
+enum AttendanceCallbacks {
+    // Pagination Actions
+    Prev,
+    Next,
+
+    // Completion Actions
+    Done,
+    Cancel,
+
+    // Change role being shown
+    ViewRole { role: RoleType },
+
+    // Toggle attendance of availability entry associated UUID
+    Toggle { id: Uuid },
+}
+
+fn has_attended(entry: &AvailabilityDetails, changes: &HashSet<Uuid>) -> bool {
+    entry.attended ^ changes.contains(&entry.id)
+}
+
+fn get_attendance_keyboard(
+    prefix: &str,
+    availability_list: &[AvailabilityDetails],
+    changes: &HashSet<Uuid>,
+    role_type: &RoleType,
+    role_types: Vec<RoleType>,
+    start: usize,
+    show: usize
+) -> Result<InlineKeyboardMarkup, ()> {
+    let slice_end = min(start + show, availability_list.len());
+    let shown_entries = match availability_list.get(start..slice_end) {
+        Some(entries) => entries,
+        None => {
+            log::error!("Cannot get availability entries slice");
+            return Err(());
+        }
+    };
+
+    let mut entries: Vec<Vec<InlineKeyboardButton>> = shown_entries
+        .iter()
+        .map(|entry| {
+            let formatted = format!(
+                "{} {}: {}",
+                if has_attended(entry, changes) { "✅" } else { "❌" },
+                entry.ops_name,
+                entry.ict_type.as_ref()
+            );
+            vec![InlineKeyboardButton::callback(
+                formatted,
+                AttendanceCallbacks::Toggle { id: entry.id }.to_callback_data(prefix),
+            )]
+        })
+        .collect();
+
+    // Add role change buttons if applicable
+    let change_view_roles: Vec<InlineKeyboardButton> = role_types.into_iter()
+        .filter_map(|role| {
+            if *role_type != role {
+                Some(InlineKeyboardButton::callback(
+                    format!("VIEW {}", role.as_ref()),
+                    AttendanceCallbacks::ViewRole { role }.to_callback_data(prefix),
+                ))
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    // Add "PREV", "NEXT", and "DONE" buttons
+    let mut pagination = Vec::new();
+    if start > 0 {
+        pagination.push(InlineKeyboardButton::callback("PREV", AttendanceCallbacks::Prev.to_callback_data(prefix)));
+    }
+    if slice_end < availability_list.len() {
+        pagination.push(InlineKeyboardButton::callback("NEXT", AttendanceCallbacks::Next.to_callback_data(prefix)));
+    }
+
+    entries.push(change_view_roles);
+    entries.push(pagination);
+    entries.push(vec![
+        InlineKeyboardButton::callback("DONE", AttendanceCallbacks::Done.to_callback_data(prefix)),
+        InlineKeyboardButton::callback("CANCEL", AttendanceCallbacks::Cancel.to_callback_data(prefix))
+    ]);
+
+    Ok(InlineKeyboardMarkup::new(entries))
+}
+
+fn get_attendance_text(
+    date: &NaiveDate,
+    availability_list: &[AvailabilityDetails],
+    changes: &HashSet<Uuid>,
+    role_type: &RoleType,
+    start: usize,
+    show: usize
+) -> String {
+    let date_str = utils::escape_special_characters(&date.format("%d %b, %Y").to_string());
+
+    if availability_list.is_empty() {
+        return format!("No {} planned on {}\\.", role_type.as_ref(), date_str);
+    }
+
+    let slice_end = min(start + show, availability_list.len());
+    let attended_count = availability_list.iter().filter(|entry| has_attended(entry, changes)).count();
+
+    let mut message = format!(
+        "{} planned on {}, showing entries {} to {} of {}:\n",
+        role_type.as_ref(),
+        date_str,
+        start + 1,
+        slice_end,
+        availability_list.len()
+    );
+
+    for entry in &availability_list[start..slice_end] {
+        let usrtype_str = if entry.usr_type.is_ns() { " \\(NS\\)" } else { "" };
+        message.push_str(&format!(
+            "\\- `{}`{} __{}__ {}\n",
+            utils::escape_special_characters(&entry.ops_name),
+            usrtype_str,
+            entry.ict_type.as_ref(),
+            if has_attended(entry, changes) { "*ATTENDED*" } else { "_NOT ATTENDED_" }
+        ));
+    }
+
+    message.push_str(&format!(
+        "\n{} of {} attended\\. Select entries to toggle attendance\\.",
+        attended_count,
+        availability_list.len()
+    ));
+
+    message
+}
+
+async fn handle_show_attendance(
+    bot: &Bot,
+    dialogue: &MyDialogue,
+    username: &Option<String>,
+    date: NaiveDate,
+    changes: HashSet<Uuid>,
+    role_type: RoleType,
+    prefix: String,
+    start: usize,
+    unit_id: Uuid,
+    pool: &PgPool,
+    msg_id: Option<MessageId>
+) -> HandlerResult {
+    let (availability_list, role_types) = match (
+        controllers::attendance::get_planned_availability_by_role_on_date(pool, unit_id, &date, &role_type).await,
+        controllers::crew_types::get_role_types(pool).await
+    ) {
+        (Ok(availability_list), Ok(role_types)) => (availability_list, role_types),
+        _ => {
+            handle_error(bot, dialogue, dialogue.chat_id(), username).await;
+            return Ok(());
+        }
+    };
+
+    let markup = match get_attendance_keyboard(&prefix, &availability_list, &changes, &role_type, role_types, start, utils::MAX_SHOW_ENTRIES) {
+        Ok(markup) => markup,
+        Err(_) => {
+            send_msg(
+                bot.send_message(dialogue.chat_id(), "Error encountered while getting attendance."),
+                username,
+            ).await;
+            dialogue.update(State::ErrorState).await?;
+            return Ok(());
+        }
+    };
+
+    let message_text = get_attendance_text(&date, &availability_list, &changes, &role_type, start, utils::MAX_SHOW_ENTRIES);
+    match send_or_edit_msg(bot, dialogue.chat_id(), username, msg_id, message_text, Some(markup), Some(ParseMode::MarkdownV2)).await {
+        None => dialogue.update(State::ErrorState).await?,
+        Some(msg_id) => dialogue.update(State::AttendanceView { msg_id, prefix, date, availability_list, changes, role_type, start }).await?
+    }
+
+    Ok(())
+}
+
+async fn get_no_show_summary(date: &NaiveDate, unit_id: Uuid, pool: &PgPool) -> Result<String, ()> {
+    let mut summary = String::new();
+
+    let role_types = match controllers::crew_types::get_role_types(pool).await {
+        Ok(role_types) => role_types,
+        Err(_) => return Err(())
+    };
+
+    for role_type in role_types {
+        let availability_list = match controllers::attendance::get_planned_availability_by_role_on_date(pool, unit_id, date, &role_type).await {
+            Ok(availability_list) => availability_list,
+            Err(_) => return Err(())
+        };
+
+        if availability_list.is_empty() {
+            continue;
+        }
+
+        let no_shows: Vec<&AvailabilityDetails> = availability_list.iter().filter(|entry| !entry.attended).collect();
+        summary.push_str(&format!(
+            "\n*{}*: {} of {} attended\n",
+            role_type.as_ref(),
+            availability_list.len() - no_shows.len(),
+            availability_list.len()
+        ));
+
+        for entry in no_shows {
+            summary.push_str(&format!(
+                "\\- `{}`{} __{}__\n",
+                utils::escape_special_characters(&entry.ops_name),
+                if entry.usr_type.is_ns() { " \\(NS\\)" } else { "" },
+                entry.ict_type.as_ref()
+            ));
+        }
+    }
+
+    if summary.is_empty() {
+        summary.push_str("\nNobody was planned\\.");
+    }
+
+    Ok(summary)
+}
+
+pub(super) async fn attendance(bot: Bot, dialogue: MyDialogue, msg: Message, pool: PgPool) -> HandlerResult {
+    log_endpoint_hit!(dialogue.chat_id(), "attendance", "Command", msg);
+
+    // Early return if the message has no sender (msg.from() is None)
+    let user = if let Some(user) = msg.from {
+        user
+    } else {
+        log::error!("Cannot get user from message");
+        dialogue.update(State::ErrorState).await?;
+        return Ok(());
+    };
+
+    // Generate random prefix to make the IDs only applicable to this dialogue instance
+    let prefix = utils::generate_prefix(utils::CALLBACK_PREFIX_LEN);
+    let today = now!().date_naive();
+
+    // Start with the role of the user taking attendance
+    let retrieved_user = match controllers::user::get_user_by_tele_id(&pool, user.id.0).await {
+        Ok(retrieved_user) => retrieved_user,
+        Err(_) => {
+            handle_error(&bot, &dialogue, dialogue.chat_id(), &user.username).await;
+            return Ok(());
+        }
+    };
+
+    handle_show_attendance(&bot, &dialogue, &user.username, today, HashSet::new(), retrieved_user.role_type, prefix, 0, retrieved_user.unit_id, &pool, None).await?;
+
+    Ok(())
+}
+
+pub(super) async fn attendance_view(
+    bot: Bot,
+    dialogue: MyDialogue,
+    (msg_id, prefix, date, availability_list, changes, role_type, start): (MessageId, String, NaiveDate, Vec<AvailabilityDetails>, HashSet<Uuid>, RoleType, usize),
+    q: CallbackQuery,
+    pool: PgPool
+) -> HandlerResult {
+    log_endpoint_hit!(
+        dialogue.chat_id(), "attendance_view", "Callback", q,
+        "MessageId" => msg_id,
+        "Prefix" => prefix,
+        "Date" => date,
+        "AvailabilityList" => availability_list,
+        "Changes" => changes,
+        "RoleType" => role_type,
+        "Start" => start
+    );
+
+    // Extract the callback data
+    let data = match retrieve_callback_data(&bot, dialogue.chat_id(), &q).await {
+        Ok(data) => data,
+        Err(_) => { return Ok(()); }
+    };
+
+    // Acknowledge the callback to remove the loading state
+    if let Err(e) = bot.answer_callback_query(q.id).await {
+        log::error!("Failed to answer callback query: {}", e);
+    }
+
+    // Deserialize the callback data into the enum
+    let callback = match match_callback_data(&bot, dialogue.chat_id(), &q.from.username, &data, &prefix).await {
+        Ok(callback) => callback,
+        Err(_) => { return Ok(()); }
+    };
+
+    let unit_id = match controllers::user::get_unit_id_by_tele_id(&pool, q.from.id.0).await {
+        Ok(unit_id) => unit_id,
+        Err(_) => {
+            handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await;
+            return Ok(());
+        }
+    };
+
+    match callback {
+        AttendanceCallbacks::Prev => {
+            handle_show_attendance(&bot, &dialogue, &q.from.username, date, changes, role_type, prefix, max(0, start as i64 - utils::MAX_SHOW_ENTRIES as i64) as usize, unit_id, &pool, Some(msg_id)).await?;
+        }
+        AttendanceCallbacks::Next => {
+            let entries_len = availability_list.len();
+            handle_show_attendance(&bot, &dialogue, &q.from.username, date, changes, role_type, prefix, if start+utils::MAX_SHOW_ENTRIES < entries_len { start+utils::MAX_SHOW_ENTRIES } else { start }, unit_id, &pool, Some(msg_id)).await?;
+        }
+        AttendanceCallbacks::ViewRole { role } => {
+            // Pending changes are keyed by availability UUID, so they are kept across roles
+            handle_show_attendance(&bot, &dialogue, &q.from.username, date, changes, role, prefix, 0, unit_id, &pool, Some(msg_id)).await?;
+        }
+        AttendanceCallbacks::Toggle { id } => {
+            let mut changes = changes;
+            if !changes.remove(&id) {
+                changes.insert(id);
+            }
+            handle_show_attendance(&bot, &dialogue, &q.from.username, date, changes, role_type, prefix, start, unit_id, &pool, Some(msg_id)).await?;
+        }
+        AttendanceCallbacks::Cancel => {
+            send_or_edit_msg(&bot, dialogue.chat_id(), &q.from.username, Some(msg_id), "Operation cancelled.".into(), None, None).await;
+            dialogue.update(State::Start).await?;
+        }
+        AttendanceCallbacks::Done => {
+            // commit changes
+            for id in changes {
+                let result = match controllers::scheduling::get_availability_details_by_uuid(&pool, id).await {
+                    Ok(details) => controllers::attendance::set_attendance_by_uuid(&pool, q.from.id.0, id, !details.attended).await,
+                    Err(e) => Err(e)
+                };
+                if result.is_err() {
+                    handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await;
+                    return Ok(());
+                }
+            }
+
+            let summary = match get_no_show_summary(&date, unit_id, &pool).await {
+                Ok(summary) => summary,
+                Err(_) => {
+                    handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await;
+                    return Ok(());
+                }
+            };
+
+            let message_text = format!(
+                "Attendance for {}:\n{}",
+                utils::escape_special_characters(&date.format("%Y-%m-%d").to_string()),
+                summary
+            );
+
+            // notify no-shows
+            notifier::emit::plan_notifications(
+                &bot,
+                format!(
+                    "{} updated the attendance\\. {}",
+                    utils::username_link_tag(&q.from),
+                    message_text
+                ).as_str(),
+                &pool,
+                Some(unit_id),
+                q.from.id.0 as i64
+            ).await;
+
+            send_or_edit_msg(&bot, dialogue.chat_id(), &q.from.username, Some(msg_id), message_text, None, Some(ParseMode::MarkdownV2)).await;
+            dialogue.update(State::Start).await?;
+        }
+    }
+
+    Ok(())
+}
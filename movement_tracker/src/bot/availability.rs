@@ -9,7 +9,7 @@ use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, MessageId, Par
 
 use crate::bot::state::State;
 use crate::bot::{handle_error, log_try_delete_msg, log_try_remove_markup, match_callback_data, retrieve_callback_data, send_msg, send_or_edit_msg, HandlerResult, MyDialogue};
-use crate::types::{Availability, AvailabilityDetails, Ict, UsrType};
+use crate::types::{Ack, Availability, AvailabilityDetails, Ict, RoleType, TimeSlot};
 use crate::{controllers, log_endpoint_hit, notifier, utils};
 
 use serde::{Serialize, Deserialize};
@@ -92,14 +92,15 @@ fn get_availability_edit_keyboard(
                 "".to_string()
             };
 
-            let is_planned_str = if entry.planned { " (PLAN) " } else { "" };
+            let is_planned_str = if entry.planned { " (PLAN) " } else if entry.standby { " (STANDBY) " } else { "" };
 
             // Format date as "MMM-DD" (3-letter month)
             let formatted = format!(
-                "{}: {}{}{}",
+                "{}: {}{}{}{}",
                 entry.avail.format("%b-%d"),
                 is_planned_str,
                 entry.ict_type.as_ref(),
+                utils::format_slot_suffix(entry.start_time, entry.end_time),
                 truncated_remarks
             );
 
@@ -204,12 +205,16 @@ async fn display_availability_options(bot: &Bot, chat_id: ChatId, username: &Opt
             } else {
                 if availability.planned {
                     " *\\(PLANNED\\)*"
+                } else if availability.standby {
+                    " *\\(STANDBY\\)*"
                 } else { "" }
             };
 
             output_text.push_str(&format!(
-                "\\- `{}` : {}{}{}{}\n\n",
-                formatted_date, availability.ict_type.as_ref(), state, truncated_remarks, saf_100_state
+                "\\- `{}` : {}{}{}{}{}\n\n",
+                formatted_date, availability.ict_type.as_ref(),
+                utils::escape_special_characters(&utils::format_slot_suffix(availability.start_time, availability.end_time)),
+                state, truncated_remarks, saf_100_state
             ));
         }
     }
@@ -344,7 +349,11 @@ async fn display_availability_edit_prompt(
         .map(|(text, data)| InlineKeyboardButton::callback(text, data.to_callback_data(&prefix)))
         .collect();
     
-    let formatted_date = availability_entry.avail.format("%b-%d").to_string();
+    let formatted_date = format!(
+        "{}{}",
+        availability_entry.avail.format("%b-%d"),
+        utils::format_slot_suffix(availability_entry.start_time, availability_entry.end_time)
+    );
     let availability_edit_text = format!(
         "You have indicated availability for: {}\nType: `{}`\nRemarks: {}\n\n What do you wish to edit?",
         utils::escape_special_characters(&formatted_date),
@@ -379,8 +388,9 @@ async fn update_add_availability(bot: &Bot, chat_id: ChatId, avail_type: &Ict, u
     let edit = [("CHANGE TYPE", AvailabilityCallbacks::ChangeType), ("CANCEL", AvailabilityCallbacks::Cancel)]
         .map(|(text, data)| InlineKeyboardButton::callback(text, data.to_callback_data(&prefix)));
     let message_text = format!(
-        "Available for: {}\n\nType the dates for which you want to indicate availability\\. Use commas\\(only\\) to separate dates\\. \\(e\\.g\\. Jan 2\\, 28/2\\, 17/04/24\\)",
-        utils::escape_special_characters(&avail_type.as_ref()));
+        "Available for: {}\n\nType the dates for which you want to indicate availability\\. Use commas\\(only\\) to separate dates\\. \\(e\\.g\\. Jan 2\\, 28/2\\, 17/04/24\\)\n\nRepeating dates are also accepted \\(e\\.g\\. every Sat until Dec\\, weekdays in the first two weeks of next month except 11/11\\, every 2 weeks on Mon and Thu from 4 Nov to 20 Dec\\)\\. You can check the dates before saving\\.\n\nTo indicate only part of the day\\, add a slot after the dates with @ \\(e\\.g\\. Jan 2\\, 28/2 @ AM\\, every Sat until Dec @ 1900\\-2300\\)\\. The named slots are {}\\.",
+        utils::escape_special_characters(avail_type.as_ref()),
+        utils::escape_special_characters(&utils::NAMED_SLOTS.map(|(name, start_hour, end_hour)| format!("{} ({:02}00-{:02}00)", name, start_hour, end_hour)).join(", ")));
     // Send or edit message
     send_or_edit_msg(&bot, chat_id, username, Some(msg_id), message_text, Some(InlineKeyboardMarkup::new([edit])), Some(ParseMode::MarkdownV2)).await
 }
@@ -409,6 +419,29 @@ async fn display_delete_confirmation(bot: &Bot, chat_id: ChatId, username: &Opti
     send_or_edit_msg(&bot, chat_id, username, msg_id, message_text, Some(InlineKeyboardMarkup::new([confirm])), Some(ParseMode::MarkdownV2)).await
 }
 
+// Lists the standby crew who could take the place of a planned member who withdraws
+pub(super) async fn get_standby_text(pool: &PgPool, unit_id: Uuid, date: &NaiveDate, role_type: &RoleType) -> String {
+    match controllers::scheduling::get_users_available_by_role_on_date(pool, unit_id, date, role_type).await {
+        Ok(availability_list) => {
+            let standby: Vec<String> = availability_list
+                .iter()
+                .filter(|entry| entry.standby && entry.is_valid && entry.ack != Some(Ack::Declined))
+                .map(|entry| format!("`{}`", utils::escape_special_characters(&entry.ops_name)))
+                .collect();
+            if standby.is_empty() {
+                "\nNo standby crew on this date\\.".to_string()
+            } else {
+                format!(
+                    "\nStandby: {}\nUse /plan {} to promote a standby\\.",
+                    standby.join(", "),
+                    date.format("%Y %b %d")
+                )
+            }
+        }
+        Err(_) => "".to_string()
+    }
+}
+
 async fn delete_availability_entry_and_go_back(
     bot: &Bot,
     dialogue: &MyDialogue,
@@ -426,7 +459,7 @@ async fn delete_availability_entry_and_go_back(
     match controllers::user::get_user_by_tele_id(&pool, tele_id).await {
         Ok(user) => {
             if user.id == availability_entry.user_id {
-                match controllers::scheduling::set_user_unavail(&pool, availability_entry.id).await {
+                match controllers::scheduling::set_user_unavail(pool, tele_id, availability_entry.id).await {
                     Ok(details) => {
                         // notify availability
                         notifier::emit::availability_notifications(
@@ -434,25 +467,30 @@ async fn delete_availability_entry_and_go_back(
                             format!(
                                 "`{}`{} has specified they are *NO LONGER AVAIL* on {}",
                                 utils::escape_special_characters(&details.ops_name),
-                                if details.usr_type == UsrType::NS {" \\(NS\\)"} else {""},
+                                if details.usr_type.is_ns() {" \\(NS\\)"} else {""},
                                 utils::escape_special_characters(&details.avail.format("%Y-%m-%d").to_string()),
                             ).as_str(),
                             &pool,
+                            Some(user.unit_id),
                             tele_id as i64
                         ).await;
 
                         // detect conflicts and notify
                         if details.planned {
+                            // point the planners to the standby crew who could take their place
+                            let standby_str = get_standby_text(pool, user.unit_id, &details.avail, &user.role_type).await;
                             notifier::emit::conflict_notifications(
                                 &bot,
                                 format!(
-                                    "`{}`{} has specified they are *UNAVAIL* on {}\\, but they are *PLANNED* ⚠️ {}",
+                                    "`{}`{} has specified they are *UNAVAIL* on {}\\, but they are *PLANNED* ⚠️ {}{}",
                                     utils::escape_special_characters(&details.ops_name),
-                                    if details.usr_type == UsrType::NS {" \\(NS\\)"} else {""},
+                                    if details.usr_type.is_ns() {" \\(NS\\)"} else {""},
                                     utils::escape_special_characters(&details.avail.format("%Y-%m-%d").to_string()),
-                                    if details.saf100 { " SAF100 ISSUED" } else { "" }
+                                    if details.saf100 { " SAF100 ISSUED" } else { "" },
+                                    standby_str
                                 ).as_str(),
                                 &pool,
+                                Some(user.unit_id),
                             ).await;
                         }
 
@@ -559,7 +597,7 @@ async fn modify_availability_and_go_back(
                     }
                 };
 
-                match controllers::scheduling::edit_avail_by_uuid(&pool, availability_entry.id, None, ict_type_edit, remark_edit).await {
+                match controllers::scheduling::edit_avail_by_uuid(pool, tele_id, availability_entry.id, None, ict_type_edit, remark_edit).await {
                     Ok(updated) => {
                         // Build a list of changes
                         let mut changes = Vec::new();
@@ -598,11 +636,12 @@ async fn modify_availability_and_go_back(
                             format!(
                                 "`{}`{} has updated their availability on {}:\n{}",
                                 updated.ops_name,
-                                if updated.usr_type == UsrType::NS {" \\(NS\\)"} else {""},
+                                if updated.usr_type.is_ns() {" \\(NS\\)"} else {""},
                                 utils::escape_special_characters(&updated.avail.format("%Y-%m-%d").to_string()),
                                 changes_message
                             ).as_str(),
                             &pool,
+                            Some(user.unit_id),
                             tele_id as i64
                         ).await;
                         
@@ -634,6 +673,7 @@ async fn register_availability(
     tele_id: u64,
     avail_dates: &Vec<NaiveDate>,
     avail_type: &Ict,
+    slot: Option<TimeSlot>,
     remarks: Option<String>,
     pool: &PgPool,
     msg_id: Option<MessageId>
@@ -641,11 +681,8 @@ async fn register_availability(
     // Add the availability to the database for each date
     let mut added: Vec<AvailabilityDetails> = Vec::new();
     for date in avail_dates.iter() {
-        match controllers::scheduling::add_user_avail(pool, tele_id, *date, avail_type, remarks.clone(), None).await {
-            Ok(details) => {
-                added.push(details);
-            }
-            Err(_) => {}
+        if let Ok(details) = controllers::scheduling::add_user_avail(pool, tele_id, tele_id, (*date, slot), avail_type, remarks.clone(), None).await {
+            added.push(details);
         }
     }
 
@@ -654,23 +691,29 @@ async fn register_availability(
         send_or_edit_msg(&bot, dialogue.chat_id(), username, msg_id, "Error, added no dates.".into(), None, None).await;
     } else {
         let added_dates = added.clone().into_iter().map(|availability| availability.avail).collect();
+        let slot_str = slot.map(|(start, end)| format!(" ({})", utils::format_slot(start, end))).unwrap_or_default();
 
-        notifier::emit::availability_notifications(
-            &bot,
-            format!(
-                "{}{} has specified they are AVAIL for {} on the following dates:\n{}{}",
-                added[0].ops_name,
-                if added[0].usr_type == UsrType::NS {" \\(NS\\)"} else {""},
-                avail_type.as_ref(),
-                utils::escape_special_characters(&utils::format_dates_as_markdown(&added_dates)),
-                if remarks.is_some() { "\nRemarks: ".to_owned()+utils::escape_special_characters(remarks.as_deref().unwrap_or("\nnone")).as_str() } else { "".to_string() }
-            ).as_str(),
-            &pool,
-            tele_id as i64
-        ).await;
+        // Only the admins of the user's unit are notified
+        if let Ok(unit_id) = controllers::user::get_unit_id_by_tele_id(pool, tele_id).await {
+            notifier::emit::availability_notifications(
+                bot,
+                format!(
+                    "{}{} has specified they are AVAIL for {}{} on the following dates:\n{}{}",
+                    added[0].ops_name,
+                    if added[0].usr_type.is_ns() {" \\(NS\\)"} else {""},
+                    avail_type.as_ref(),
+                    utils::escape_special_characters(&slot_str),
+                    utils::escape_special_characters(&utils::format_dates_as_markdown(&added_dates)),
+                    if remarks.is_some() { "\nRemarks: ".to_owned()+utils::escape_special_characters(remarks.as_deref().unwrap_or("\nnone")).as_str() } else { "".to_string() }
+                ).as_str(),
+                &pool,
+                Some(unit_id),
+                tele_id as i64
+            ).await;
+        }
         
         // Send or edit message
-        send_or_edit_msg(&bot, dialogue.chat_id(), username, msg_id, format!("Added the following dates:\n{}", utils::format_dates_as_markdown(&added_dates)), None, None).await;
+        send_or_edit_msg(bot, dialogue.chat_id(), username, msg_id, format!("Added the following dates{}:\n{}", slot_str, utils::format_dates_as_markdown(&added_dates)), None, None).await;
     }
 }
 
@@ -906,12 +949,32 @@ pub(super) async fn availability_add_message(
     };
 
     match msg.text().map(ToOwned::to_owned) {
-        Some(input_dates_str) => {
+        Some(input_str) => {
+            // Parse the slot after the dates, if any
+            let (input_dates_str, input_slot_str) = utils::split_slot(&input_str);
+            let slot = match input_slot_str.map(utils::parse_slot) {
+                None => None,
+                Some(Ok(slot)) => Some(slot),
+                Some(Err(_)) => {
+                    send_msg(
+                        bot.send_message(
+                            dialogue.chat_id(),
+                            format!(
+                                "Failed to parse the slot. Use one of {} or a time range like 1900-2300 after the @, or type /cancel to abort.",
+                                utils::NAMED_SLOTS.map(|(name, _, _)| name).join(", ")
+                            )
+                        ),
+                        &user.username,
+                    ).await;
+                    return Ok(());
+                }
+            };
+
             // Parse the dates with ranges
-            let (parsed_dates, failed_parsing_dates, duplicate_dates) = utils::parse_dates(&input_dates_str);
+            let (parsed_dates, failed_parsing_dates, duplicate_dates) = utils::parse_dates(input_dates_str);
 
             // Check availability for the unique parsed dates
-            let availability_results = match controllers::scheduling::check_user_avail_multiple(&pool, user.id.0, parsed_dates.clone()).await {
+            let availability_results = match controllers::scheduling::check_user_avail_multiple(&pool, user.id.0, parsed_dates.clone(), slot).await {
                 Ok(results) => results,
                 Err(e) => {
                     log::error!("Error checking availability: {}", e);
@@ -937,7 +1000,8 @@ pub(super) async fn availability_add_message(
                 "All the dates have already been registered\\.".to_string()
             } else {
                 format!(
-                    "*Selected dates:* \n{}\n",
+                    "*Selected dates{}:* \n{}\n",
+                    utils::escape_special_characters(&slot.map(|(start, end)| format!(" ({})", utils::format_slot(start, end))).unwrap_or_default()),
                     utils::escape_special_characters(&utils::format_dates_as_markdown(&available_dates))
                 )
             };
@@ -998,7 +1062,7 @@ pub(super) async fn availability_add_message(
                     if !available_dates.is_empty() {
                         match display_add_remarks(&bot, dialogue.chat_id(), &user.username, &prefix).await {
                             None => dialogue.update(State::ErrorState).await?,
-                            Some(change_msg_id) => dialogue.update(State::AvailabilityAddRemarks { msg_id, prefix, change_msg_id, avail_type, avail_dates: available_dates }).await?
+                            Some(change_msg_id) => dialogue.update(State::AvailabilityAddRemarks { msg_id, prefix, change_msg_id, avail_type, avail_dates: available_dates, slot }).await?
                         }
                     }
                 }
@@ -1018,7 +1082,7 @@ pub(super) async fn availability_add_message(
 pub(super) async fn availability_add_remarks(
     bot: Bot,
     dialogue: MyDialogue,
-    (msg_id, prefix, change_msg_id, avail_type, avail_dates): (MessageId, String, MessageId, Ict, Vec<NaiveDate>),
+    (msg_id, prefix, change_msg_id, avail_type, avail_dates, slot): (MessageId, String, MessageId, Ict, Vec<NaiveDate>, Option<TimeSlot>),
     msg: Message,
     pool: PgPool
 ) -> HandlerResult {
@@ -1028,7 +1092,8 @@ pub(super) async fn availability_add_remarks(
         "Prefix" => prefix,
         "Change MessageId" => change_msg_id,
         "Avail Type" => avail_type,
-        "Avail Dates" => avail_dates
+        "Avail Dates" => avail_dates,
+        "Slot" => slot
     );
 
     // Early return if the message has no sender (msg.from() is None)
@@ -1044,7 +1109,7 @@ pub(super) async fn availability_add_remarks(
         Some(input_remarks) => {
             // add availability to database with the specified remarks
             log_try_remove_markup(&bot, dialogue.chat_id(), change_msg_id).await;
-            register_availability(&bot, &dialogue, &user.username, user.id.0, &avail_dates, &avail_type, Some(input_remarks), &pool, None).await;
+            register_availability(&bot, &dialogue, &user.username, user.id.0, &avail_dates, &avail_type, slot, Some(input_remarks), &pool, None).await;
 
             dialogue.update(State::Start).await?;
         }
@@ -1062,7 +1127,7 @@ pub(super) async fn availability_add_remarks(
 pub(super) async fn availability_add_complete(
     bot: Bot,
     dialogue: MyDialogue,
-    (msg_id, prefix, change_msg_id, avail_type, avail_dates): (MessageId, String, MessageId, Ict, Vec<NaiveDate>),
+    (msg_id, prefix, change_msg_id, avail_type, avail_dates, slot): (MessageId, String, MessageId, Ict, Vec<NaiveDate>, Option<TimeSlot>),
     q: CallbackQuery,
     pool: PgPool
 ) -> HandlerResult {
@@ -1072,7 +1137,8 @@ pub(super) async fn availability_add_complete(
         "Prefix" => prefix,
         "Change MessageId" => change_msg_id,
         "Avail Type" => avail_type,
-        "Avail Dates" => avail_dates
+        "Avail Dates" => avail_dates,
+        "Slot" => slot
     );
 
     // Extract the callback data
@@ -1096,7 +1162,7 @@ pub(super) async fn availability_add_complete(
         AvailabilityCallbacks::Done => {
             // add availability to database no remarks
             log_try_delete_msg(&bot, dialogue.chat_id(), msg_id).await;
-            register_availability(&bot, &dialogue, &q.from.username, q.from.id.0, &avail_dates, &avail_type, None, &pool, Some(change_msg_id)).await;
+            register_availability(&bot, &dialogue, &q.from.username, q.from.id.0, &avail_dates, &avail_type, slot, None, &pool, Some(change_msg_id)).await;
 
             dialogue.update(State::Start).await?;
         }
@@ -0,0 +1,135 @@
+use sqlx::PgPool;
+
+use teloxide::prelude::*;
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, MessageId};
+
+use crate::bot::state::State;
+use crate::bot::{handle_error, log_try_remove_markup, match_callback_data, retrieve_callback_data, send_msg, send_or_edit_msg, HandlerResult, MyDialogue};
+use crate::{api, controllers, log_endpoint_hit, utils};
+
+use serde::{Serialize, Deserialize};
+use strum::EnumProperty;
+use callback_data::CallbackData;
+use callback_data::CallbackDataHandler;
+
+// Represents callback actions with optional associated data.
+#[derive(Debug, Clone, Serialize, Deserialize, EnumProperty, CallbackData)]
+enum CalendarCallbacks {
+    Reset,
+    Done,
+}
+
+async fn display_calendar_link(
+    bot: &Bot,
+    chat_id: ChatId,
+    username: &Option<String>,
+    url: &str,
+    prefix: &str,
+    msg_id: Option<MessageId>
+) -> Option<MessageId> {
+    let keyboard = InlineKeyboardMarkup::new(vec![vec![
+        InlineKeyboardButton::callback("RESET LINK", CalendarCallbacks::Reset.to_callback_data(prefix)),
+        InlineKeyboardButton::callback("DONE", CalendarCallbacks::Done.to_callback_data(prefix)),
+    ]]);
+
+    let message_text = format!(
+        "Subscribe to this link in your calendar app to see your planned and standby dates:\n{}\n\nKeep this link private. Reset it if it has been shared by mistake.",
+        url
+    );
+
+    send_or_edit_msg(bot, chat_id, username, msg_id, message_text, Some(keyboard), None).await
+}
+
+pub(super) async fn calendar(bot: Bot, dialogue: MyDialogue, msg: Message, pool: PgPool) -> HandlerResult {
+    log_endpoint_hit!(dialogue.chat_id(), "calendar", "Command", msg);
+
+    // Early return if the message has no sender (msg.from() is None)
+    let user = if let Some(ref user) = msg.from {
+        user
+    } else {
+        log::error!("Cannot get user from message");
+        dialogue.update(State::ErrorState).await?;
+        return Ok(());
+    };
+
+    let new_token = utils::generate_prefix(utils::CALENDAR_TOKEN_LEN);
+    match controllers::calendar::get_or_create_calendar_token(&pool, user.id.0, &new_token).await {
+        Ok(token) => {
+            let url = match api::calendar_url(&token) {
+                Some(url) => url,
+                None => {
+                    send_msg(
+                        bot.send_message(dialogue.chat_id(), "Calendar links are not available on this bot."),
+                        &user.username
+                    ).await;
+                    return Ok(());
+                }
+            };
+
+            let prefix = utils::generate_prefix(utils::CALLBACK_PREFIX_LEN);
+            match display_calendar_link(&bot, dialogue.chat_id(), &user.username, &url, &prefix, None).await {
+                None => dialogue.update(State::ErrorState).await?,
+                Some(msg_id) => dialogue.update(State::CalendarView { msg_id, prefix }).await?
+            }
+        }
+        Err(_) => handle_error(&bot, &dialogue, dialogue.chat_id(), &user.username).await
+    }
+
+    Ok(())
+}
+
+pub(super) async fn calendar_view(
+    bot: Bot,
+    dialogue: MyDialogue,
+    (msg_id, prefix): (MessageId, String),
+    q: CallbackQuery,
+    pool: PgPool
+) -> HandlerResult {
+    log_endpoint_hit!(dialogue.chat_id(), "calendar_view", "Callback", q,
+        "MessageId" => msg_id,
+        "Prefix" => prefix
+    );
+
+    // Extract the callback data
+    let data = match retrieve_callback_data(&bot, dialogue.chat_id(), &q).await {
+        Ok(data) => data,
+        Err(_) => { return Ok(()); }
+    };
+
+    // Acknowledge the callback to remove the loading state
+    if let Err(e) = bot.answer_callback_query(q.id).await {
+        log::error!("Failed to answer callback query: {}", e);
+    }
+
+    // Deserialize the callback data into the enum
+    let callback = match match_callback_data(&bot, dialogue.chat_id(), &q.from.username, &data, &prefix).await {
+        Ok(callback) => callback,
+        Err(_) => { return Ok(()); }
+    };
+
+    match callback {
+        CalendarCallbacks::Done => {
+            log_try_remove_markup(&bot, dialogue.chat_id(), msg_id).await;
+            dialogue.update(State::Start).await?;
+        }
+        CalendarCallbacks::Reset => {
+            let new_token = utils::generate_prefix(utils::CALENDAR_TOKEN_LEN);
+            match controllers::calendar::reset_calendar_token(&pool, q.from.id.0, &new_token).await {
+                Ok(token) => {
+                    match api::calendar_url(&token) {
+                        Some(url) => {
+                            display_calendar_link(&bot, dialogue.chat_id(), &q.from.username, &url, &prefix, Some(msg_id)).await;
+                        }
+                        None => {
+                            send_or_edit_msg(&bot, dialogue.chat_id(), &q.from.username, Some(msg_id), "Calendar links are not available on this bot.".into(), None, None).await;
+                            dialogue.update(State::Start).await?;
+                        }
+                    }
+                }
+                Err(_) => handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await
+            }
+        }
+    }
+
+    Ok(())
+}
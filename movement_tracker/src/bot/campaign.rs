@@ -0,0 +1,516 @@
+use chrono::{Duration, NaiveDate, Utc};
+use sqlx::types::Uuid;
+use sqlx::PgPool;
+
+use teloxide::prelude::*;
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, MessageId};
+
+use crate::bot::state::State;
+use crate::bot::{handle_error, log_try_remove_markup, match_callback_data, retrieve_callback_data, send_msg, send_or_edit_msg, HandlerResult, MyDialogue};
+use crate::notifier::campaign::{format_campaign_window, send_campaign_requests};
+use crate::types::{Campaign, RoleType, Usr, UsrType};
+use crate::{controllers, log_endpoint_hit, now, utils, APP_TIMEZONE};
+
+use serde::{Serialize, Deserialize};
+use strum::EnumProperty;
+use callback_data::CallbackData;
+use callback_data::CallbackDataHandler;
+
+// Represents callback actions with optional associated data.
+#[derive(Debug, Clone, Serialize, Deserialize, EnumProperty, CallbackData)]
+enum CampaignCallbacks {
+    // Campaign list
+    New,
+    Done,
+    View { id: Uuid },
+
+    // Campaign status
+    Refresh,
+    Nudge,
+    Close,
+    Back,
+}
+
+fn format_campaign(campaign: &Campaign) -> String {
+    let applies_to: Vec<&str> = [
+        campaign.role_type.as_ref().map(|role_type| role_type.as_ref()),
+        campaign.usr_type.as_ref().map(|usr_type| usr_type.as_ref())
+    ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+    if applies_to.is_empty() {
+        format!("{} (all)", format_campaign_window(campaign))
+    } else {
+        format!("{} ({})", format_campaign_window(campaign), applies_to.join(" "))
+    }
+}
+
+// Parses input such as "2024 Nov 01 to 2024 Nov 30 PILOT NS" into a campaign window and user filter
+fn parse_campaign(input: &str, role_types: &[RoleType], usr_types: &[UsrType]) -> Result<(NaiveDate, NaiveDate, Option<RoleType>, Option<UsrType>), String> {
+    let mut tokens: Vec<&str> = input.split_whitespace().collect();
+
+    // The filter follows the dates, so take role and user types off the end
+    let mut role_type = None;
+    let mut usr_type = None;
+    while let Some(token) = tokens.last().map(|token| token.to_uppercase()) {
+        if let (Some(role), None) = (role_types.iter().find(|role| role.as_ref() == token), &role_type) {
+            role_type = Some(role.clone());
+        } else if let (Some(usr), None) = (usr_types.iter().find(|usr| usr.as_ref() == token), &usr_type) {
+            usr_type = Some(usr.clone());
+        } else {
+            break;
+        }
+        tokens.pop();
+    }
+
+    let dates = tokens.join(" ");
+    if dates.is_empty() {
+        return Err("No dates provided".to_string());
+    }
+
+    let (start_date, end_date) = match dates.to_lowercase().find(" to ") {
+        Some(index) => (
+            utils::parse_single_date(&dates[..index]).map_err(|e| format!("Invalid date: {}", e))?,
+            utils::parse_single_date(&dates[index + 4..]).map_err(|e| format!("Invalid date: {}", e))?
+        ),
+        None => {
+            let date = utils::parse_single_date(&dates).map_err(|e| format!("Invalid date: {}", e))?;
+            (date, date)
+        }
+    };
+
+    if end_date < start_date {
+        return Err("The end date is before the start date".to_string());
+    }
+    if end_date < now!().date_naive() {
+        return Err("The dates have already passed".to_string());
+    }
+    if (end_date - start_date).num_days() >= utils::MAX_CAMPAIGN_DAYS {
+        return Err(format!("Campaigns cannot span more than {} days", utils::MAX_CAMPAIGN_DAYS));
+    }
+
+    Ok((start_date, end_date, role_type, usr_type))
+}
+
+fn pending_users(responses: &[(Usr, bool)]) -> Vec<Usr> {
+    responses
+        .iter()
+        .filter(|(_, responded)| !responded)
+        .map(|(user, _)| user.clone())
+        .collect()
+}
+
+// Why a manual nudge was not sent, as it is held to the same limits as the scheduled nudges
+fn get_nudge_refused_text(campaign: &Campaign) -> String {
+    if campaign.start_date <= now!().date_naive() {
+        return "The campaign has started, nobody was nudged.".to_string();
+    }
+    if campaign.nudges >= utils::MAX_CAMPAIGN_NUDGES {
+        return format!("All {} nudges for this campaign have been sent, nobody was nudged.", utils::MAX_CAMPAIGN_NUDGES);
+    }
+
+    let next_nudge = campaign.last_nudged.unwrap_or(campaign.created) + Duration::hours(utils::CAMPAIGN_NUDGE_INTERVAL_HOURS as i64);
+    format!(
+        "Users were asked less than {} hours ago, nobody was nudged. The next nudge can be sent from {}.",
+        utils::CAMPAIGN_NUDGE_INTERVAL_HOURS,
+        next_nudge.with_timezone(&*APP_TIMEZONE).format("%b-%d-%Y %H:%M")
+    )
+}
+
+async fn display_campaigns(
+    bot: &Bot,
+    chat_id: ChatId,
+    username: &Option<String>,
+    campaigns: &[Campaign],
+    prefix: &str,
+    msg_id: Option<MessageId>
+) -> Option<MessageId> {
+    let mut entries: Vec<Vec<InlineKeyboardButton>> = campaigns
+        .iter()
+        .map(|campaign| vec![InlineKeyboardButton::callback(
+            format!("VIEW: {}", format_campaign(campaign)),
+            CampaignCallbacks::View { id: campaign.id }.to_callback_data(prefix)
+        )])
+        .collect();
+    entries.push(vec![
+        InlineKeyboardButton::callback("NEW", CampaignCallbacks::New.to_callback_data(prefix)),
+        InlineKeyboardButton::callback("DONE", CampaignCallbacks::Done.to_callback_data(prefix))
+    ]);
+
+    let message_text = if campaigns.is_empty() {
+        "No availability campaigns are open.".to_string()
+    } else {
+        format!(
+            "Open availability campaigns:\n{}",
+            campaigns.iter().map(|campaign| format!("- {}", format_campaign(campaign))).collect::<Vec<String>>().join("\n")
+        )
+    };
+
+    send_or_edit_msg(bot, chat_id, username, msg_id, message_text, Some(InlineKeyboardMarkup::new(entries)), None).await
+}
+
+async fn display_campaign_status(
+    bot: &Bot,
+    chat_id: ChatId,
+    username: &Option<String>,
+    campaign: &Campaign,
+    responses: &[(Usr, bool)],
+    prefix: &str,
+    msg_id: Option<MessageId>
+) -> Option<MessageId> {
+    let pending = pending_users(responses);
+
+    let entries = vec![
+        vec![
+            InlineKeyboardButton::callback("REFRESH", CampaignCallbacks::Refresh.to_callback_data(prefix)),
+            InlineKeyboardButton::callback("NUDGE NOW", CampaignCallbacks::Nudge.to_callback_data(prefix))
+        ],
+        vec![
+            InlineKeyboardButton::callback("CLOSE CAMPAIGN", CampaignCallbacks::Close.to_callback_data(prefix)),
+            InlineKeyboardButton::callback("BACK", CampaignCallbacks::Back.to_callback_data(prefix))
+        ]
+    ];
+
+    let pending_text = if pending.is_empty() {
+        "Everyone has responded.".to_string()
+    } else {
+        format!(
+            "Not responded:\n{}",
+            pending.iter().map(|user| format!("- {}", user.ops_name)).collect::<Vec<String>>().join("\n")
+        )
+    };
+
+    let message_text = format!(
+        "Availability campaign for {}\nResponded: {} of {}\nNudges sent: {} of {}\n\n{}",
+        format_campaign(campaign),
+        responses.len() - pending.len(),
+        responses.len(),
+        campaign.nudges,
+        utils::MAX_CAMPAIGN_NUDGES,
+        pending_text
+    );
+
+    send_or_edit_msg(bot, chat_id, username, msg_id, message_text, Some(InlineKeyboardMarkup::new(entries)), None).await
+}
+
+async fn handle_show_campaigns(
+    bot: &Bot,
+    dialogue: &MyDialogue,
+    username: &Option<String>,
+    tele_id: u64,
+    prefix: String,
+    pool: &PgPool,
+    msg_id: Option<MessageId>
+) -> HandlerResult {
+    let campaigns = match controllers::user::get_unit_id_by_tele_id(pool, tele_id).await {
+        Ok(unit_id) => controllers::campaigns::get_open_campaigns(pool, unit_id).await,
+        Err(e) => Err(e)
+    };
+
+    match campaigns {
+        Ok(campaigns) => {
+            match display_campaigns(bot, dialogue.chat_id(), username, &campaigns, &prefix, msg_id).await {
+                None => dialogue.update(State::ErrorState).await?,
+                Some(new_msg_id) => dialogue.update(State::CampaignView { msg_id: new_msg_id, prefix }).await?
+            }
+        }
+        Err(_) => handle_error(bot, dialogue, dialogue.chat_id(), username).await
+    }
+
+    Ok(())
+}
+
+async fn handle_show_campaign_status(
+    bot: &Bot,
+    dialogue: &MyDialogue,
+    username: &Option<String>,
+    campaign_id: Uuid,
+    prefix: String,
+    pool: &PgPool,
+    msg_id: Option<MessageId>
+) -> HandlerResult {
+    let campaign = match controllers::campaigns::get_campaign_by_uuid(pool, campaign_id).await {
+        Ok(campaign) => campaign,
+        Err(_) => {
+            handle_error(bot, dialogue, dialogue.chat_id(), username).await;
+            return Ok(());
+        }
+    };
+
+    match controllers::campaigns::get_campaign_responses(pool, &campaign).await {
+        Ok(responses) => {
+            match display_campaign_status(bot, dialogue.chat_id(), username, &campaign, &responses, &prefix, msg_id).await {
+                None => dialogue.update(State::ErrorState).await?,
+                Some(new_msg_id) => dialogue.update(State::CampaignStatus { msg_id: new_msg_id, prefix, campaign_id }).await?
+            }
+        }
+        Err(_) => handle_error(bot, dialogue, dialogue.chat_id(), username).await
+    }
+
+    Ok(())
+}
+
+pub(super) async fn campaign(bot: Bot, dialogue: MyDialogue, msg: Message, pool: PgPool) -> HandlerResult {
+    log_endpoint_hit!(dialogue.chat_id(), "campaign", "Command", msg);
+
+    // Early return if the message has no sender (msg.from() is None)
+    let user = if let Some(ref user) = msg.from {
+        user
+    } else {
+        log::error!("Cannot get user from message");
+        dialogue.update(State::ErrorState).await?;
+        return Ok(());
+    };
+
+    let prefix = utils::generate_prefix(utils::CALLBACK_PREFIX_LEN);
+    handle_show_campaigns(&bot, &dialogue, &user.username, user.id.0, prefix, &pool, None).await
+}
+
+pub(super) async fn campaign_view(
+    bot: Bot,
+    dialogue: MyDialogue,
+    (msg_id, prefix): (MessageId, String),
+    q: CallbackQuery,
+    pool: PgPool
+) -> HandlerResult {
+    log_endpoint_hit!(dialogue.chat_id(), "campaign_view", "Callback", q,
+        "MessageId" => msg_id,
+        "Prefix" => prefix
+    );
+
+    // Extract the callback data
+    let data = match retrieve_callback_data(&bot, dialogue.chat_id(), &q).await {
+        Ok(data) => data,
+        Err(_) => { return Ok(()); }
+    };
+
+    // Acknowledge the callback to remove the loading state
+    if let Err(e) = bot.answer_callback_query(q.id).await {
+        log::error!("Failed to answer callback query: {}", e);
+    }
+
+    // Deserialize the callback data into the enum
+    let callback = match match_callback_data(&bot, dialogue.chat_id(), &q.from.username, &data, &prefix).await {
+        Ok(callback) => callback,
+        Err(_) => { return Ok(()); }
+    };
+
+    match callback {
+        CampaignCallbacks::Done => {
+            log_try_remove_markup(&bot, dialogue.chat_id(), msg_id).await;
+            dialogue.update(State::Start).await?;
+        }
+        CampaignCallbacks::New => {
+            log_try_remove_markup(&bot, dialogue.chat_id(), msg_id).await;
+            match send_msg(
+                bot.send_message(
+                    dialogue.chat_id(),
+                    "Enter the dates to request availability for as <start> to <end> [role] [user type]. Type /cancel to abort.\n\nExamples:\n2024 Nov 01 to 2024 Nov 30\nNov 01 to Nov 14 PILOT NS"
+                ),
+                &q.from.username
+            ).await {
+                None => dialogue.update(State::ErrorState).await?,
+                Some(msg_id) => dialogue.update(State::CampaignAdd { msg_id, prefix }).await?
+            }
+        }
+        CampaignCallbacks::View { id } => {
+            handle_show_campaign_status(&bot, &dialogue, &q.from.username, id, prefix, &pool, Some(msg_id)).await?;
+        }
+        _ => {
+            send_msg(
+                bot.send_message(dialogue.chat_id(), "Invalid option. Type /cancel to abort."),
+                &q.from.username,
+            ).await;
+        }
+    }
+
+    Ok(())
+}
+
+pub(super) async fn campaign_add(
+    bot: Bot,
+    dialogue: MyDialogue,
+    (msg_id, prefix): (MessageId, String),
+    msg: Message,
+    pool: PgPool
+) -> HandlerResult {
+    log_endpoint_hit!(dialogue.chat_id(), "campaign_add", "Message", msg,
+        "MessageId" => msg_id,
+        "Prefix" => prefix
+    );
+
+    // Early return if the message has no sender (msg.from() is None)
+    let user = if let Some(ref user) = msg.from {
+        user
+    } else {
+        log::error!("Cannot get user from message");
+        dialogue.update(State::ErrorState).await?;
+        return Ok(());
+    };
+
+    let (role_types, usr_types, unit_id) = match (
+        controllers::crew_types::get_role_types(&pool).await,
+        controllers::crew_types::get_usr_types(&pool).await,
+        controllers::user::get_unit_id_by_tele_id(&pool, user.id.0).await
+    ) {
+        (Ok(role_types), Ok(usr_types), Ok(unit_id)) => (role_types, usr_types, unit_id),
+        _ => {
+            handle_error(&bot, &dialogue, dialogue.chat_id(), &user.username).await;
+            return Ok(());
+        }
+    };
+
+    let (start_date, end_date, role_type, usr_type) = match msg.text().map(|text| parse_campaign(text, &role_types, &usr_types)) {
+        Some(Ok(parsed)) => parsed,
+        Some(Err(e)) => {
+            send_msg(
+                bot.send_message(dialogue.chat_id(), format!("{}. Please try again, or type /cancel to abort.", e)),
+                &user.username
+            ).await;
+            return Ok(());
+        }
+        None => {
+            send_msg(
+                bot.send_message(dialogue.chat_id(), "Please enter the dates as text, or type /cancel to abort."),
+                &user.username
+            ).await;
+            return Ok(());
+        }
+    };
+
+    let (campaign, responses) = match controllers::campaigns::add_campaign(&pool, unit_id, (start_date, end_date), role_type, usr_type).await {
+        Ok(campaign) => match controllers::campaigns::get_campaign_responses(&pool, &campaign).await {
+            Ok(responses) => (campaign, responses),
+            Err(_) => {
+                handle_error(&bot, &dialogue, dialogue.chat_id(), &user.username).await;
+                return Ok(());
+            }
+        },
+        Err(_) => {
+            handle_error(&bot, &dialogue, dialogue.chat_id(), &user.username).await;
+            return Ok(());
+        }
+    };
+
+    if responses.is_empty() {
+        // Nobody to ask, so the campaign would only linger in the list
+        if controllers::campaigns::close_campaign(&pool, campaign.id).await.is_err() {
+            handle_error(&bot, &dialogue, dialogue.chat_id(), &user.username).await;
+            return Ok(());
+        }
+        send_msg(
+            bot.send_message(dialogue.chat_id(), "No users match the campaign. Please try again, or type /cancel to abort."),
+            &user.username
+        ).await;
+        return Ok(());
+    }
+
+    // Users who already indicated their availability for the dates are not asked again
+    let pending = pending_users(&responses);
+    let sent = send_campaign_requests(&bot, &campaign, &pending, false).await;
+    let responded = responses.len() - pending.len();
+    send_msg(
+        bot.send_message(
+            dialogue.chat_id(),
+            if responded > 0 {
+                format!("Requested availability from {} users, {} had already responded.", sent, responded)
+            } else {
+                format!("Requested availability from {} users.", sent)
+            }
+        ),
+        &user.username
+    ).await;
+
+    handle_show_campaign_status(&bot, &dialogue, &user.username, campaign.id, prefix, &pool, None).await
+}
+
+pub(super) async fn campaign_status(
+    bot: Bot,
+    dialogue: MyDialogue,
+    (msg_id, prefix, campaign_id): (MessageId, String, Uuid),
+    q: CallbackQuery,
+    pool: PgPool
+) -> HandlerResult {
+    log_endpoint_hit!(dialogue.chat_id(), "campaign_status", "Callback", q,
+        "MessageId" => msg_id,
+        "Prefix" => prefix,
+        "Campaign" => campaign_id
+    );
+
+    // Extract the callback data
+    let data = match retrieve_callback_data(&bot, dialogue.chat_id(), &q).await {
+        Ok(data) => data,
+        Err(_) => { return Ok(()); }
+    };
+
+    // Acknowledge the callback to remove the loading state
+    if let Err(e) = bot.answer_callback_query(q.id).await {
+        log::error!("Failed to answer callback query: {}", e);
+    }
+
+    // Deserialize the callback data into the enum
+    let callback = match match_callback_data(&bot, dialogue.chat_id(), &q.from.username, &data, &prefix).await {
+        Ok(callback) => callback,
+        Err(_) => { return Ok(()); }
+    };
+
+    match callback {
+        CampaignCallbacks::Refresh => {
+            handle_show_campaign_status(&bot, &dialogue, &q.from.username, campaign_id, prefix, &pool, Some(msg_id)).await?;
+        }
+        CampaignCallbacks::Nudge => {
+            let responses = match controllers::campaigns::get_campaign_by_uuid(&pool, campaign_id).await {
+                Ok(campaign) => controllers::campaigns::get_campaign_responses(&pool, &campaign).await.map(|responses| (campaign, responses)),
+                Err(e) => Err(e)
+            };
+
+            let (campaign, responses) = match responses {
+                Ok(responses) => responses,
+                Err(_) => {
+                    handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await;
+                    return Ok(());
+                }
+            };
+
+            let pending = pending_users(&responses);
+            let message_text = if pending.is_empty() {
+                "Everyone has responded, nobody was nudged.".to_string()
+            } else {
+                match controllers::campaigns::claim_campaign_nudge(&pool, campaign_id).await {
+                    Ok(true) => {
+                        let sent = send_campaign_requests(&bot, &campaign, &pending, true).await;
+                        format!("Nudged {} of {} users who have not responded.", sent, pending.len())
+                    }
+                    Ok(false) => get_nudge_refused_text(&campaign),
+                    Err(_) => {
+                        handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await;
+                        return Ok(());
+                    }
+                }
+            };
+
+            log_try_remove_markup(&bot, dialogue.chat_id(), msg_id).await;
+            send_msg(bot.send_message(dialogue.chat_id(), message_text), &q.from.username).await;
+            handle_show_campaign_status(&bot, &dialogue, &q.from.username, campaign_id, prefix, &pool, None).await?;
+        }
+        CampaignCallbacks::Close => {
+            match controllers::campaigns::close_campaign(&pool, campaign_id).await {
+                Ok(_) => handle_show_campaigns(&bot, &dialogue, &q.from.username, q.from.id.0, prefix, &pool, Some(msg_id)).await?,
+                Err(_) => handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await
+            }
+        }
+        CampaignCallbacks::Back => {
+            handle_show_campaigns(&bot, &dialogue, &q.from.username, q.from.id.0, prefix, &pool, Some(msg_id)).await?;
+        }
+        _ => {
+            send_msg(
+                bot.send_message(dialogue.chat_id(), "Invalid option. Type /cancel to abort."),
+                &q.from.username,
+            ).await;
+        }
+    }
+
+    Ok(())
+}
@@ -1,6 +1,7 @@
 use super::HandlerResult;
 use super::{send_msg, MyDialogue};
 use crate::bot::state::State;
+use crate::types::AdminRole;
 use crate::{controllers, log_endpoint_hit};
 use sqlx::PgPool;
 use teloxide::types::{BotCommand, BotCommandScope, ChatKind, MenuButton, Recipient};
@@ -22,8 +23,10 @@ pub(super) enum Commands {
     Availability,
     #[command(description = "View upcoming planned for yourself")]
     Upcoming,
-    // #[command(description = "Add information about your movement")]
-    // Movement,
+    #[command(description = "Add information about your movement")]
+    Movement,
+    #[command(description = "Get a calendar link of your planned dates")]
+    Calendar,
     #[command(description = "Display information about future availability")]
     Forecast,
     #[command(description = "Cancel current action")]
@@ -48,24 +51,58 @@ pub(super) enum PrivilegedCommands {
     },
     #[command(description = "Track SAF100")]
     SAF100,
+    #[command(description = "Mark attendance for today")]
+    Attendance,
+    #[command(description = "Import availability from a CSV file")]
+    Import,
+    #[command(description = "Configure reminders for planned availability")]
+    Reminders,
+    #[command(description = "Request availability from users and track responses")]
+    Campaign,
+    #[command(description = "Manage crew roles and user types (admins only)")]
+    Roles,
+    #[command(description = "View the history of changes for a user")]
+    History {
+        ops_name: String
+    },
+    #[command(description = "View planning and attendance stats per user")]
+    Stats,
     #[command(description = "Edit notification settings for current chat")]
     Notify
 }
 
+// Role required to use each privileged command
+pub(super) fn privileged_command_role(command: &str) -> Option<AdminRole> {
+    match command {
+        "/approve" | "/user" | "/history" | "/roles" => Some(AdminRole::UserManager),
+        "/plan" | "/attendance" | "/import" | "/reminders" | "/campaign" | "/stats" => Some(AdminRole::Planner),
+        "/saf100" => Some(AdminRole::Saf100Clerk),
+        "/notify" => Some(AdminRole::NotificationManager),
+        _ => None
+    }
+}
+
+// Privileged commands available to a user holding the given roles
+fn permitted_commands(admin_roles: &[AdminRole]) -> Vec<BotCommand> {
+    PrivilegedCommands::bot_commands()
+        .into_iter()
+        .filter(|cmd| privileged_command_role(&cmd.command).is_some_and(|role| admin_roles.contains(&role)))
+        .collect()
+}
+
 // Function to set commands and menu buttons
-pub(super) async fn set_menu_buttons(bot: Bot, chat_id: ChatId, user_id: UserId, is_admin: bool, is_public: bool) {
+pub(super) async fn set_menu_buttons(bot: Bot, chat_id: ChatId, user_id: UserId, admin_roles: &[AdminRole], is_public: bool) {
     let mut commands: Vec<BotCommand> = Commands::bot_commands().to_vec();
     
-    log::debug!("Setting menu buttons for chat ({}): admin: {}, public: {}", chat_id.0, is_admin, is_public);
-    // If the user is an admin, and not in a public chat, append privileged commands
-    if is_admin && !is_public {
-        let mut privileged_commands = PrivilegedCommands::bot_commands().to_vec();
-        commands.append(&mut privileged_commands);
+    log::debug!("Setting menu buttons for chat ({}): roles: {:?}, public: {}", chat_id.0, admin_roles, is_public);
+    // If the user holds any roles, and not in a public chat, append the permitted privileged commands
+    if !is_public {
+        commands.append(&mut permitted_commands(admin_roles));
     }
     
     if is_public {
         // Set the combined commands for the chat
-        if is_admin {
+        if admin_roles.contains(&AdminRole::NotificationManager) {
             // Extract the `Notify` command from PrivilegedCommands
             if let Some(notify_cmd) = PrivilegedCommands::bot_commands().iter()
                 .find(|cmd| cmd.command == "/notify").cloned() {
@@ -119,21 +156,23 @@ pub(super) async fn help(bot: Bot, dialogue: MyDialogue, msg: Message, pool: PgP
         return Ok(());
     };
 
-    let is_admin = match controllers::user::get_user_by_tele_id(&pool, user.id.0).await {
-        Ok(retrieved_usr) => retrieved_usr.admin,
-        Err(_) => false,
-    };
+    let admin_roles = controllers::permissions::get_effective_admin_roles_by_tele_id(&pool, user.id.0).await
+        .unwrap_or_default();
 
     // Determine the kind of chat the message was sent in
     let is_public_chat = matches!(&msg.chat.kind, ChatKind::Public(_));
 
-    // Append admin commands to the help message if the user is an admin
-    if is_admin {
-        if !is_public_chat {
-            help_str = format!("{}\n\nAdmin Commands:\n{}", help_str, PrivilegedCommands::descriptions());
-        } else {
-            help_str = format!("{}\n{}", help_str, "Use /notfiy to configure notification settings for the current chat");
+    // Append the permitted admin commands to the help message
+    if !is_public_chat {
+        let descriptions: Vec<String> = permitted_commands(&admin_roles)
+            .iter()
+            .map(|cmd| format!("{} — {}", cmd.command, cmd.description))
+            .collect();
+        if !descriptions.is_empty() {
+            help_str = format!("{}\n\nAdmin Commands:\n{}", help_str, descriptions.join("\n"));
         }
+    } else if admin_roles.contains(&AdminRole::NotificationManager) {
+        help_str = format!("{}\n{}", help_str, "Use /notfiy to configure notification settings for the current chat");
     }
 
     send_msg(
@@ -141,7 +180,7 @@ pub(super) async fn help(bot: Bot, dialogue: MyDialogue, msg: Message, pool: PgP
         &(user.username)
     ).await;
     
-    set_menu_buttons(bot, dialogue.chat_id(), user.id, is_admin, is_public_chat).await;
+    set_menu_buttons(bot, dialogue.chat_id(), user.id, &admin_roles, is_public_chat).await;
 
     Ok(())
 }
@@ -4,15 +4,14 @@ use chrono::{Datelike, Duration, Utc, NaiveDate};
 use sqlx::PgPool;
 
 use teloxide::prelude::*;
-use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, MessageId, ParseMode};
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, InputFile, MessageId, ParseMode};
 
 use crate::bot::state::State;
 use crate::bot::{handle_error, log_try_delete_msg, log_try_remove_markup, match_callback_data, retrieve_callback_data, send_msg, HandlerResult, MyDialogue};
-use crate::types::{AvailabilityDetails, RoleType, UsrType};
+use crate::types::{AvailabilityDetails, PlanStatus, RoleType};
 use crate::{controllers, log_endpoint_hit, now, utils};
 
 use serde::{Deserialize, Serialize};
-use strum::IntoEnumIterator;
 use strum_macros::EnumProperty;
 use callback_data::CallbackData;
 use callback_data::CallbackDataHandler;
@@ -29,22 +28,83 @@ enum ForecastCallbackData {
     // Select Role Actions
     ChangeRole { role_type: RoleType },
 
+    // Export Action
+    Export,
+
     // Completion Action
     Done
 }
 
+fn generate_forecast_csv(role_type: &RoleType, availability_list: &Vec<AvailabilityDetails>) -> String {
+    let mut output = String::from("date,ops_name,role,usr_type,ict_type,start_time,end_time,planned,standby,saf100,attended,remarks\n");
+
+    for availability in availability_list {
+        output.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{}\n",
+            availability.avail.format("%Y-%m-%d"),
+            utils::escape_csv_field(&availability.ops_name),
+            role_type.as_ref(),
+            availability.usr_type.as_ref(),
+            availability.ict_type.as_ref(),
+            availability.start_time.map(|time| time.format("%H:%M").to_string()).unwrap_or_default(),
+            availability.end_time.map(|time| time.format("%H:%M").to_string()).unwrap_or_default(),
+            availability.planned,
+            availability.standby,
+            availability.saf100,
+            availability.attended,
+            utils::escape_csv_field(availability.remarks.as_deref().unwrap_or(""))
+        ));
+    }
+
+    output
+}
+
+async fn send_availability_forecast_export(
+    bot: &Bot,
+    chat_id: ChatId,
+    role_type: &RoleType,
+    availability_list: &Vec<AvailabilityDetails>,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Option<MessageId> {
+    let file_name = format!(
+        "forecast_{}_{}_{}.csv",
+        role_type.as_ref().to_lowercase(),
+        start.format("%Y%m%d"),
+        end.format("%Y%m%d")
+    );
+    let document = InputFile::memory(generate_forecast_csv(role_type, availability_list).into_bytes()).file_name(file_name);
+
+    match bot.send_document(chat_id, document)
+        .caption(format!(
+            "Availability forecast for {} from {} to {}",
+            role_type.as_ref(),
+            start.format("%b-%d-%Y"),
+            end.format("%b-%d-%Y")
+        ))
+        .await
+    {
+        Ok(msg) => Some(msg.id),
+        Err(e) => {
+            log::error!("Failed to send forecast export to chat ({}): {}", chat_id, e);
+            None
+        }
+    }
+}
+
 async fn display_availability_forecast(
     bot: &Bot,
     chat_id: ChatId,
     username: &Option<String>,
     role_type: &RoleType,
+    role_types: Vec<RoleType>,
     availability_list: &Vec<AvailabilityDetails>,
     start: NaiveDate,
     end: NaiveDate,
     prefix: &String,
     edit_msg: Option<MessageId>
 ) -> Option<MessageId> {
-    let change_view_roles: Vec<InlineKeyboardButton> = RoleType::iter()
+    let change_view_roles: Vec<InlineKeyboardButton> = role_types.into_iter()
         .filter_map(|role| { if *role_type != role {
                 Some(InlineKeyboardButton::callback(
                     "VIEW ".to_owned() + role.clone().as_ref(),
@@ -64,7 +124,7 @@ async fn display_availability_forecast(
             .collect(),
     );
     view_range.push(
-        [("DONE", ForecastCallbackData::Done), ("+1 MONTH", ForecastCallbackData::IncNextMonth)]
+        [("DONE", ForecastCallbackData::Done), ("EXPORT", ForecastCallbackData::Export), ("+1 MONTH", ForecastCallbackData::IncNextMonth)]
             .into_iter()
             .map(|(text, data)| InlineKeyboardButton::callback(text, data.to_callback_data(&prefix)))
             .collect(),
@@ -129,12 +189,16 @@ async fn display_availability_forecast(
             let mut per_day: String;
 
             for availability in availabilities_for_day {
-                let planned_str = if availability.planned { " \\(PLANNED\\)" } else { "" };
+                let planned_str = match availability.plan_status() {
+                    PlanStatus::Planned => " \\(PLANNED\\)",
+                    PlanStatus::Standby => " \\(STANDBY\\)",
+                    PlanStatus::Unplanned => ""
+                };
                 let avail = if !availability.is_valid { " *\\(UNAVAIL\\)*" } else { "" };
-                let usrtype_str = if availability.usr_type == UsrType::NS { " \\(NS\\)" } else { "" };
+                let usrtype_str = if availability.usr_type.is_ns() { " \\(NS\\)" } else { "" };
                 let saf100_str = if availability.saf100 {
                     " SAF100 ISSUED"
-                } else if availability.planned && availability.usr_type == UsrType::NS {
+                } else if availability.planned && availability.usr_type.is_ns() {
                     " *PENDING SAF100*"
                 } else {
                     ""
@@ -156,10 +220,11 @@ async fn display_availability_forecast(
                 };
 
                 per_day = format!(
-                    "\\- `{:<width$}` {} __{}__{}{}{}\n",
+                    "\\- `{:<width$}` {} __{}__{}{}{}{}\n",
                     availability.ops_name,
                     usrtype_str,
                     availability.ict_type.as_ref(),
+                    utils::escape_special_characters(&utils::format_slot_suffix(availability.start_time, availability.end_time)),
                     planned_str,
                     avail,
                     remarks_str,
@@ -238,15 +303,18 @@ pub(super) async fn forecast(bot: Bot, dialogue: MyDialogue, msg: Message, pool:
             let role_type = retrieved_user.role_type;
             let start = now!().date_naive(); // Get today's date in the local timezone
             let end = start.checked_add_signed(Duration::weeks(1)).expect("Overflow when adding duration");
-            match controllers::scheduling::get_availability_for_role_and_dates(&pool, role_type.clone(), start, end).await {
-                Ok(availability_list) => {
+            match (
+                controllers::scheduling::get_availability_for_role_and_dates(&pool, Some(retrieved_user.unit_id), role_type.clone(), start, end).await,
+                controllers::crew_types::get_role_types(&pool).await
+            ) {
+                (Ok(availability_list), Ok(role_types)) => {
                     let prefix = utils::generate_prefix(utils::CALLBACK_PREFIX_LEN);
-                    match display_availability_forecast(&bot, dialogue.chat_id(), &user.username, &role_type, &availability_list, start, end, &prefix, None).await {
+                    match display_availability_forecast(&bot, dialogue.chat_id(), &user.username, &role_type, role_types, &availability_list, start, end, &prefix, None).await {
                         None => dialogue.update(State::ErrorState).await?,
                         Some(msg_id) => dialogue.update(State::ForecastView { msg_id, prefix, availability_list, role_type, start, end }).await?
                     };
                 }
-                Err(_) => handle_error(&bot, &dialogue, dialogue.chat_id(), &user.username).await
+                _ => handle_error(&bot, &dialogue, dialogue.chat_id(), &user.username).await
             }
         },
         Err(_) => handle_error(&bot, &dialogue, dialogue.chat_id(), &user.username).await
@@ -288,6 +356,15 @@ pub(super) async fn forecast_view(
         Err(_) => { return Ok(()); }
     };
 
+    // The forecast only covers the user's own unit
+    let unit_id = match controllers::user::get_unit_id_by_tele_id(&pool, q.from.id.0).await {
+        Ok(unit_id) => unit_id,
+        Err(_) => {
+            handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await;
+            return Ok(());
+        }
+    };
+
     let mut new_role = role_type.clone();
     let mut new_start = start;
     let mut new_end = end;
@@ -307,6 +384,21 @@ pub(super) async fn forecast_view(
             dialogue.update(State::Start).await?;
             return Ok(());
         }
+        ForecastCallbackData::Export => {
+            // Export the full forecast currently in view, without the truncation of the message
+            match controllers::scheduling::get_availability_for_role_and_dates(&pool, Some(unit_id), role_type.clone(), start, end).await {
+                Ok(availability_list_export) => {
+                    if send_availability_forecast_export(&bot, dialogue.chat_id(), &role_type, &availability_list_export, start, end).await.is_none() {
+                        send_msg(
+                            bot.send_message(dialogue.chat_id(), "Failed to export the forecast. Please try again."),
+                            &q.from.username,
+                        ).await;
+                    }
+                }
+                Err(_) => handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await
+            }
+            return Ok(());
+        }
         ForecastCallbackData::ViewNextWeek => {
             new_start = now!().date_naive();
             new_end = end.checked_add_signed(Duration::weeks(1)).expect("Overflow when adding duration");
@@ -327,14 +419,17 @@ pub(super) async fn forecast_view(
         }
     }
 
-    match controllers::scheduling::get_availability_for_role_and_dates(&pool, new_role.clone(), new_start, new_end).await {
-        Ok(availability_list_new) => {
-            match display_availability_forecast(&bot, dialogue.chat_id(), &q.from.username, &new_role, &availability_list_new, new_start, new_end, &prefix, Some(msg_id)).await {
+    match (
+        controllers::scheduling::get_availability_for_role_and_dates(&pool, Some(unit_id), new_role.clone(), new_start, new_end).await,
+        controllers::crew_types::get_role_types(&pool).await
+    ) {
+        (Ok(availability_list_new), Ok(role_types)) => {
+            match display_availability_forecast(&bot, dialogue.chat_id(), &q.from.username, &new_role, role_types, &availability_list_new, new_start, new_end, &prefix, Some(msg_id)).await {
                 None => {}
                 Some(new_msg_id) => dialogue.update(State::ForecastView { msg_id: new_msg_id, prefix, availability_list: availability_list_new, role_type: new_role, start: new_start, end: new_end }).await?
             };
         }
-        Err(_) => handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await
+        _ => handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await
     }
     
     Ok(())
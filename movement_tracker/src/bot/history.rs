@@ -0,0 +1,250 @@
+use serde_json::Value;
+use sqlx::PgPool;
+
+use teloxide::prelude::*;
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, MessageId};
+
+use crate::bot::state::State;
+use crate::bot::{handle_error, log_try_remove_markup, match_callback_data, retrieve_callback_data, send_msg, send_or_edit_msg, HandlerResult, MyDialogue};
+use crate::types::AuditLog;
+use crate::{controllers, log_endpoint_hit, utils, APP_TIMEZONE};
+
+use serde::{Serialize, Deserialize};
+use strum::EnumProperty;
+use callback_data::CallbackData;
+use callback_data::CallbackDataHandler;
+
+// Represents callback actions with optional associated data.
+#[derive(Debug, Clone, Serialize, Deserialize, EnumProperty, CallbackData)]
+enum HistoryCallbacks {
+    Prev,
+    Next,
+    Done,
+}
+
+// Fields that change on every write and are not worth showing
+const IGNORED_FIELDS: [&str; 3] = ["id", "created", "updated"];
+
+fn format_value(value: &Value) -> String {
+    let text = match value {
+        Value::Null => "none".to_string(),
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    };
+
+    if text.chars().count() > utils::MAX_HISTORY_VALUE_CHARS {
+        format!("{}...", text.chars().take(utils::MAX_HISTORY_VALUE_CHARS).collect::<String>())
+    } else {
+        text
+    }
+}
+
+// Lists the fields that differ between the before and after snapshots
+fn format_changes(before: &Option<Value>, after: &Option<Value>) -> Vec<String> {
+    let (Some(Value::Object(before)), Some(Value::Object(after))) = (before, after) else {
+        return Vec::new();
+    };
+
+    after
+        .iter()
+        .filter(|(field, _)| !IGNORED_FIELDS.contains(&field.as_str()))
+        .filter_map(|(field, new_value)| {
+            let old_value = before.get(field).unwrap_or(&Value::Null);
+            (old_value != new_value)
+                .then(|| format!("  {}: {} -> {}", field, format_value(old_value), format_value(new_value)))
+        })
+        .collect()
+}
+
+fn format_audit_entry(entry: &AuditLog) -> String {
+    let actor = entry.actor_ops_name.clone().unwrap_or_else(|| entry.actor_tele_id.to_string());
+
+    // Availability entries are identified by their date
+    let target = entry.after.as_ref()
+        .or(entry.before.as_ref())
+        .and_then(|snapshot| snapshot.get("avail"))
+        .and_then(|avail| avail.as_str())
+        .map(|avail| format!("{} {}", entry.entity, avail))
+        .unwrap_or_else(|| entry.entity.clone());
+
+    let mut lines = vec![format!(
+        "{} {} {} by {}",
+        entry.created.with_timezone(&*APP_TIMEZONE).format("%b-%d-%Y %H:%M"),
+        entry.action,
+        target,
+        actor
+    )];
+    lines.extend(format_changes(&entry.before, &entry.after));
+
+    lines.join("\n")
+}
+
+async fn display_history(
+    bot: &Bot,
+    chat_id: ChatId,
+    username: &Option<String>,
+    ops_name: &str,
+    entries: &[AuditLog],
+    start: usize,
+    has_next: bool,
+    prefix: &str,
+    msg_id: Option<MessageId>
+) -> Option<MessageId> {
+    let mut buttons = Vec::new();
+    if start > 0 {
+        buttons.push(InlineKeyboardButton::callback("PREV", HistoryCallbacks::Prev.to_callback_data(prefix)));
+    }
+    if has_next {
+        buttons.push(InlineKeyboardButton::callback("NEXT", HistoryCallbacks::Next.to_callback_data(prefix)));
+    }
+    buttons.push(InlineKeyboardButton::callback("DONE", HistoryCallbacks::Done.to_callback_data(prefix)));
+
+    let message_text = if entries.is_empty() {
+        format!("No history recorded for {}.", ops_name)
+    } else {
+        format!(
+            "History for {} (showing {} to {}, newest first):\n\n{}",
+            ops_name,
+            start + 1,
+            start + entries.len(),
+            entries.iter().map(format_audit_entry).collect::<Vec<String>>().join("\n\n")
+        )
+    };
+
+    send_or_edit_msg(bot, chat_id, username, msg_id, message_text, Some(InlineKeyboardMarkup::new([buttons])), None).await
+}
+
+async fn handle_show_history(
+    bot: &Bot,
+    dialogue: &MyDialogue,
+    username: &Option<String>,
+    tele_id: i64,
+    ops_name: String,
+    prefix: String,
+    start: usize,
+    pool: &PgPool,
+    msg_id: Option<MessageId>
+) -> HandlerResult {
+    // Fetch one extra entry to know whether there is a next page
+    match controllers::audit::get_audit_log_by_subject(pool, tele_id, utils::MAX_HISTORY_ENTRIES as i64 + 1, start as i64).await {
+        Ok(mut entries) => {
+            let has_next = entries.len() > utils::MAX_HISTORY_ENTRIES;
+            entries.truncate(utils::MAX_HISTORY_ENTRIES);
+
+            match display_history(bot, dialogue.chat_id(), username, &ops_name, &entries, start, has_next, &prefix, msg_id).await {
+                None => dialogue.update(State::ErrorState).await?,
+                Some(new_msg_id) => dialogue.update(State::HistoryView { msg_id: new_msg_id, prefix, tele_id, ops_name, start }).await?
+            }
+        }
+        Err(_) => handle_error(bot, dialogue, dialogue.chat_id(), username).await
+    }
+
+    Ok(())
+}
+
+pub(super) async fn history(
+    bot: Bot,
+    dialogue: MyDialogue,
+    msg: Message,
+    ops_name: String,
+    pool: PgPool
+) -> HandlerResult {
+    log_endpoint_hit!(dialogue.chat_id(), "history", "Command", msg);
+
+    // Early return if the message has no sender (msg.from() is None)
+    let user = if let Some(ref user) = msg.from {
+        user
+    } else {
+        log::error!("Cannot get user from message");
+        dialogue.update(State::ErrorState).await?;
+        return Ok(());
+    };
+
+    let cleaned_ops_name = ops_name.trim().to_uppercase();
+    if cleaned_ops_name.is_empty() {
+        send_msg(
+            bot.send_message(dialogue.chat_id(), "Usage: /history <ops name>"),
+            &user.username
+        ).await;
+        return Ok(());
+    }
+
+    let unit_id = match controllers::user::get_unit_id_by_tele_id(&pool, user.id.0).await {
+        Ok(unit_id) => unit_id,
+        Err(_) => {
+            handle_error(&bot, &dialogue, dialogue.chat_id(), &user.username).await;
+            return Ok(());
+        }
+    };
+
+    match controllers::user::user_exists_ops_name(&pool, unit_id, cleaned_ops_name.as_ref()).await {
+        Ok(true) => {
+            match controllers::user::get_user_by_ops_name(&pool, unit_id, cleaned_ops_name.as_ref()).await {
+                Ok(user_details) => {
+                    let prefix = utils::generate_prefix(utils::CALLBACK_PREFIX_LEN);
+                    handle_show_history(&bot, &dialogue, &user.username, user_details.tele_id, user_details.ops_name, prefix, 0, &pool, None).await?;
+                }
+                Err(_) => handle_error(&bot, &dialogue, dialogue.chat_id(), &user.username).await
+            }
+        }
+        Ok(false) => {
+            send_msg(
+                bot.send_message(dialogue.chat_id(), format!("No user found with ops name: {}", cleaned_ops_name)),
+                &user.username
+            ).await;
+        }
+        Err(_) => handle_error(&bot, &dialogue, dialogue.chat_id(), &user.username).await
+    }
+
+    Ok(())
+}
+
+pub(super) async fn history_view(
+    bot: Bot,
+    dialogue: MyDialogue,
+    (msg_id, prefix, tele_id, ops_name, start): (MessageId, String, i64, String, usize),
+    q: CallbackQuery,
+    pool: PgPool
+) -> HandlerResult {
+    log_endpoint_hit!(dialogue.chat_id(), "history_view", "Callback", q,
+        "MessageId" => msg_id,
+        "Prefix" => prefix,
+        "Tele ID" => tele_id,
+        "Ops Name" => ops_name,
+        "Start" => start
+    );
+
+    // Extract the callback data
+    let data = match retrieve_callback_data(&bot, dialogue.chat_id(), &q).await {
+        Ok(data) => data,
+        Err(_) => { return Ok(()); }
+    };
+
+    // Acknowledge the callback to remove the loading state
+    if let Err(e) = bot.answer_callback_query(q.id).await {
+        log::error!("Failed to answer callback query: {}", e);
+    }
+
+    // Deserialize the callback data into the enum
+    let callback = match match_callback_data(&bot, dialogue.chat_id(), &q.from.username, &data, &prefix).await {
+        Ok(callback) => callback,
+        Err(_) => { return Ok(()); }
+    };
+
+    match callback {
+        HistoryCallbacks::Prev => {
+            let start = start.saturating_sub(utils::MAX_HISTORY_ENTRIES);
+            handle_show_history(&bot, &dialogue, &q.from.username, tele_id, ops_name, prefix, start, &pool, Some(msg_id)).await?;
+        }
+        HistoryCallbacks::Next => {
+            let start = start + utils::MAX_HISTORY_ENTRIES;
+            handle_show_history(&bot, &dialogue, &q.from.username, tele_id, ops_name, prefix, start, &pool, Some(msg_id)).await?;
+        }
+        HistoryCallbacks::Done => {
+            log_try_remove_markup(&bot, dialogue.chat_id(), msg_id).await;
+            dialogue.update(State::Start).await?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,465 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::str::FromStr;
+
+use sqlx::types::chrono::NaiveDate;
+use sqlx::types::Uuid;
+use sqlx::PgPool;
+
+use teloxide::net::Download;
+use teloxide::prelude::*;
+use teloxide::types::{Document, InlineKeyboardButton, InlineKeyboardMarkup, MessageId};
+
+use crate::bot::state::State;
+use crate::bot::{handle_error, match_callback_data, retrieve_callback_data, send_msg, send_or_edit_msg, HandlerResult, MyDialogue};
+use crate::types::{Ict, TimeSlot, Usr};
+use crate::{controllers, log_endpoint_hit, notifier, now, utils, APP_TIMEZONE};
+
+use chrono::Utc;
+use serde::{Serialize, Deserialize};
+use strum::EnumProperty;
+use callback_data::CallbackData;
+use callback_data::CallbackDataHandler;
+
+// Represents callback actions with optional associated data.
+#[derive(Debug, Clone, Serialize, Deserialize, EnumProperty, CallbackData)]
+enum ImportCallbacks {
+    Confirm,
+    Cancel,
+}
+
+// A validated row of the import file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct ImportEntry {
+    ops_name: String,
+    tele_id: u64,
+    date: NaiveDate,
+    ict_type: Ict,
+    remarks: Option<String>,
+    // The whole day if not given
+    #[serde(default)]
+    slot: Option<TimeSlot>,
+    // Whether the user already has availability in this slot, which will be overwritten
+    conflict: bool,
+}
+
+// Any slot overlaps a whole day
+fn slots_overlap(a: Option<TimeSlot>, b: Option<TimeSlot>) -> bool {
+    match (a, b) {
+        (Some((a_start, a_end)), Some((b_start, b_end))) => a_start < b_end && b_start < a_end,
+        _ => true
+    }
+}
+
+async fn download_document(bot: &Bot, document: &Document) -> Result<String, String> {
+    if document.file.size > utils::MAX_IMPORT_FILE_SIZE {
+        return Err(format!("File is too large (max {} KB).", utils::MAX_IMPORT_FILE_SIZE / 1024));
+    }
+
+    let file = bot.get_file(document.file.id.clone()).await.map_err(|e| {
+        log::error!("Failed to get file info for import: {}", e);
+        "Unable to retrieve the file.".to_string()
+    })?;
+
+    let mut buffer: Vec<u8> = Vec::new();
+    bot.download_file(&file.path, &mut buffer).await.map_err(|e| {
+        log::error!("Failed to download file for import: {}", e);
+        "Unable to download the file.".to_string()
+    })?;
+
+    String::from_utf8(buffer).map_err(|_| "File is not valid UTF-8 text.".to_string())
+}
+
+pub(super) async fn validate_rows(
+    rows: Vec<Vec<String>>,
+    unit_id: Uuid,
+    pool: &PgPool
+) -> Result<(Vec<ImportEntry>, Vec<String>), sqlx::Error> {
+    let today = now!().date_naive();
+
+    let mut entries: Vec<ImportEntry> = Vec::new();
+    let mut errors: Vec<String> = Vec::new();
+    let mut users: HashMap<String, Option<Usr>> = HashMap::new();
+    let mut seen: HashMap<(u64, NaiveDate), Vec<Option<TimeSlot>>> = HashMap::new();
+    // Row number of each entry, for errors found after all rows are read
+    let mut lines: Vec<usize> = Vec::new();
+
+    for (index, row) in rows.into_iter().enumerate() {
+        let line = index + 1;
+
+        // Skip blank lines and the header row if present
+        if row.iter().all(|field| field.trim().is_empty()) {
+            continue;
+        }
+        if index == 0 && row[0].trim().eq_ignore_ascii_case("ops_name") {
+            continue;
+        }
+
+        if row.len() < 3 || row.len() > 5 {
+            errors.push(format!("Row {}: expected 3 to 5 columns, found {}", line, row.len()));
+            continue;
+        }
+
+        let ops_name = row[0].trim().to_uppercase();
+        let user = match users.get(&ops_name) {
+            Some(user) => user.clone(),
+            None => {
+                let user = match controllers::user::get_user_by_ops_name(pool, unit_id, &ops_name).await {
+                    Ok(user) => Some(user),
+                    Err(sqlx::Error::RowNotFound) => None,
+                    Err(e) => return Err(e),
+                };
+                users.insert(ops_name.clone(), user.clone());
+                user
+            }
+        };
+        let user = match user {
+            Some(user) => user,
+            None => {
+                errors.push(format!("Row {}: no user with ops name {}", line, ops_name));
+                continue;
+            }
+        };
+
+        let date = match utils::parse_single_date(row[1].trim()) {
+            Ok(date) if date < today => {
+                errors.push(format!("Row {}: {} is in the past", line, date.format("%Y-%m-%d")));
+                continue;
+            }
+            Ok(date) => date,
+            Err(_) => {
+                errors.push(format!("Row {}: invalid date {}", line, row[1].trim()));
+                continue;
+            }
+        };
+
+        let ict_type = match Ict::from_str(&row[2].trim().to_uppercase()) {
+            Ok(ict_type) => ict_type,
+            Err(_) => {
+                errors.push(format!("Row {}: invalid ICT type {}", line, row[2].trim()));
+                continue;
+            }
+        };
+
+        let remarks = row.get(3)
+            .map(|remarks| remarks.trim().to_string())
+            .filter(|remarks| !remarks.is_empty());
+
+        let slot = match row.get(4).map(|slot| slot.trim()).filter(|slot| !slot.is_empty()) {
+            Some(slot_str) => match utils::parse_slot(slot_str) {
+                Ok(slot) => Some(slot),
+                Err(_) => {
+                    errors.push(format!("Row {}: invalid slot {}", line, slot_str));
+                    continue;
+                }
+            },
+            None => None
+        };
+
+        let slots = seen.entry((user.tele_id as u64, date)).or_default();
+        if slots.contains(&slot) {
+            errors.push(format!("Row {}: duplicate entry for {} on {}", line, ops_name, date.format("%Y-%m-%d")));
+            continue;
+        }
+        if slots.iter().any(|other| slots_overlap(*other, slot)) {
+            errors.push(format!("Row {}: overlaps another entry for {} on {}", line, ops_name, date.format("%Y-%m-%d")));
+            continue;
+        }
+        slots.push(slot);
+
+        entries.push(ImportEntry {
+            ops_name,
+            tele_id: user.tele_id as u64,
+            date,
+            ict_type,
+            remarks,
+            slot,
+            conflict: false,
+        });
+        lines.push(line);
+    }
+
+    // Flag entries which will overwrite existing availability in the same slot, and skip those which overlap another slot
+    let mut dates_by_slot: HashMap<(u64, Option<TimeSlot>), Vec<NaiveDate>> = HashMap::new();
+    for entry in &entries {
+        dates_by_slot.entry((entry.tele_id, entry.slot)).or_default().push(entry.date);
+    }
+    let mut overlaps: HashSet<(u64, NaiveDate, Option<TimeSlot>)> = HashSet::new();
+    for ((tele_id, slot), dates) in dates_by_slot {
+        let existing = controllers::scheduling::check_user_avail_multiple(pool, tele_id, dates.clone(), slot).await?;
+        for (date, availability) in dates.into_iter().zip(existing) {
+            let Some(availability) = availability else { continue; };
+            match entries.iter_mut().find(|entry| entry.tele_id == tele_id && entry.date == date && entry.slot == slot) {
+                Some(entry) if availability.start_time == slot.map(|(start, _)| start) => entry.conflict = true,
+                _ => { overlaps.insert((tele_id, date, slot)); }
+            }
+        }
+    }
+
+    let mut valid_entries = Vec::new();
+    for (entry, line) in entries.into_iter().zip(lines) {
+        if overlaps.contains(&(entry.tele_id, entry.date, entry.slot)) {
+            errors.push(format!("Row {}: {} already has availability on {} in another slot", line, entry.ops_name, entry.date.format("%Y-%m-%d")));
+        } else {
+            valid_entries.push(entry);
+        }
+    }
+
+    Ok((valid_entries, errors))
+}
+
+fn format_entry(entry: &ImportEntry) -> String {
+    format!(
+        "{} {}{} {}{}",
+        entry.ops_name,
+        entry.date.format("%Y-%m-%d"),
+        utils::format_slot_suffix(entry.slot.map(|(start, _)| start), entry.slot.map(|(_, end)| end)),
+        entry.ict_type.as_ref(),
+        entry.remarks.as_ref().map(|remarks| format!(" ({})", remarks)).unwrap_or_default()
+    )
+}
+
+fn format_section(title: &str, lines: Vec<String>) -> String {
+    if lines.is_empty() {
+        return String::new();
+    }
+
+    let mut output = format!("{} ({}):\n", title, lines.len());
+    for line in lines.iter().take(utils::MAX_IMPORT_PREVIEW_LINES) {
+        output.push_str(&format!("- {}\n", line));
+    }
+    if lines.len() > utils::MAX_IMPORT_PREVIEW_LINES {
+        output.push_str(&format!("...and {} more\n", lines.len() - utils::MAX_IMPORT_PREVIEW_LINES));
+    }
+    output.push('\n');
+
+    output
+}
+
+fn get_import_preview_text(entries: &[ImportEntry], errors: &[String]) -> String {
+    let inserts = entries.iter().filter(|entry| !entry.conflict).map(format_entry).collect();
+    let conflicts = entries.iter().filter(|entry| entry.conflict).map(format_entry).collect();
+
+    let mut output = String::from("Import preview\n\n");
+    output.push_str(&format_section("New entries", inserts));
+    output.push_str(&format_section("Overwriting existing entries", conflicts));
+    output.push_str(&format_section("Errors (will be skipped)", errors.to_owned()));
+
+    if entries.is_empty() {
+        output.push_str("No valid entries to import.");
+    } else {
+        output.push_str("Confirm to import all valid entries.");
+    }
+
+    output
+}
+
+pub(super) async fn import(bot: Bot, dialogue: MyDialogue, msg: Message) -> HandlerResult {
+    log_endpoint_hit!(dialogue.chat_id(), "import", "Command", msg);
+
+    // Early return if the message has no sender (msg.from() is None)
+    let user = if let Some(ref user) = msg.from {
+        user
+    } else {
+        log::error!("Cannot get user from message");
+        dialogue.update(State::ErrorState).await?;
+        return Ok(());
+    };
+
+    let prefix = utils::generate_prefix(utils::CALLBACK_PREFIX_LEN);
+    match send_msg(
+        bot.send_message(
+            dialogue.chat_id(),
+            "Send a CSV file of availability to import, or /cancel to abort.\n\nColumns: ops_name, date, ict_type, remarks (optional), slot (optional, whole day if blank)\nExample: JOHN D,12/10/2024,LIVE,morning only,AM"
+        ),
+        &user.username
+    ).await {
+        None => dialogue.update(State::ErrorState).await?,
+        Some(msg_id) => dialogue.update(State::ImportUpload { msg_id, prefix }).await?
+    }
+
+    Ok(())
+}
+
+pub(super) async fn import_upload(
+    bot: Bot,
+    dialogue: MyDialogue,
+    (msg_id, prefix): (MessageId, String),
+    msg: Message,
+    pool: PgPool
+) -> HandlerResult {
+    log_endpoint_hit!(dialogue.chat_id(), "import_upload", "Message", msg,
+        "MessageId" => msg_id,
+        "Prefix" => prefix
+    );
+
+    // Early return if the message has no sender (msg.from() is None)
+    let user = if let Some(ref user) = msg.from {
+        user
+    } else {
+        log::error!("Cannot get user from message");
+        dialogue.update(State::ErrorState).await?;
+        return Ok(());
+    };
+
+    let document = match msg.document() {
+        Some(document) => document,
+        None => {
+            send_msg(
+                bot.send_message(dialogue.chat_id(), "Please send a CSV file, or type /cancel to abort."),
+                &user.username
+            ).await;
+            return Ok(());
+        }
+    };
+
+    let content = match download_document(&bot, document).await {
+        Ok(content) => content,
+        Err(e) => {
+            send_msg(
+                bot.send_message(dialogue.chat_id(), format!("{} Please try again, or type /cancel to abort.", e)),
+                &user.username
+            ).await;
+            return Ok(());
+        }
+    };
+
+    let rows = utils::parse_csv(&content);
+    if rows.len() > utils::MAX_IMPORT_ROWS {
+        send_msg(
+            bot.send_message(dialogue.chat_id(), format!("File has too many rows (max {}). Please split it, or type /cancel to abort.", utils::MAX_IMPORT_ROWS)),
+            &user.username
+        ).await;
+        return Ok(());
+    }
+
+    // Rows may only refer to users of the importing admin's unit
+    let unit_id = match controllers::user::get_unit_id_by_tele_id(&pool, user.id.0).await {
+        Ok(unit_id) => unit_id,
+        Err(_) => {
+            handle_error(&bot, &dialogue, dialogue.chat_id(), &user.username).await;
+            return Ok(());
+        }
+    };
+
+    match validate_rows(rows, unit_id, &pool).await {
+        Ok((entries, errors)) => {
+            let message_text = get_import_preview_text(&entries, &errors);
+
+            if entries.is_empty() {
+                send_msg(bot.send_message(dialogue.chat_id(), message_text), &user.username).await;
+                dialogue.update(State::Start).await?;
+                return Ok(());
+            }
+
+            let keyboard = InlineKeyboardMarkup::new(vec![vec![
+                InlineKeyboardButton::callback("CONFIRM", ImportCallbacks::Confirm.to_callback_data(&prefix)),
+                InlineKeyboardButton::callback("CANCEL", ImportCallbacks::Cancel.to_callback_data(&prefix)),
+            ]]);
+
+            match send_msg(
+                bot.send_message(dialogue.chat_id(), message_text).reply_markup(keyboard),
+                &user.username
+            ).await {
+                None => dialogue.update(State::ErrorState).await?,
+                Some(msg_id) => dialogue.update(State::ImportConfirm { msg_id, prefix, entries }).await?
+            }
+        }
+        Err(_) => handle_error(&bot, &dialogue, dialogue.chat_id(), &user.username).await
+    }
+
+    Ok(())
+}
+
+pub(super) async fn import_confirm(
+    bot: Bot,
+    dialogue: MyDialogue,
+    (msg_id, prefix, entries): (MessageId, String, Vec<ImportEntry>),
+    q: CallbackQuery,
+    pool: PgPool
+) -> HandlerResult {
+    log_endpoint_hit!(dialogue.chat_id(), "import_confirm", "Callback", q,
+        "MessageId" => msg_id,
+        "Prefix" => prefix,
+        "Entries" => entries
+    );
+
+    // Extract the callback data
+    let data = match retrieve_callback_data(&bot, dialogue.chat_id(), &q).await {
+        Ok(data) => data,
+        Err(_) => { return Ok(()); }
+    };
+
+    // Acknowledge the callback to remove the loading state
+    if let Err(e) = bot.answer_callback_query(q.id).await {
+        log::error!("Failed to answer callback query: {}", e);
+    }
+
+    // Deserialize the callback data into the enum
+    let callback = match match_callback_data(&bot, dialogue.chat_id(), &q.from.username, &data, &prefix).await {
+        Ok(callback) => callback,
+        Err(_) => { return Ok(()); }
+    };
+
+    match callback {
+        ImportCallbacks::Cancel => {
+            send_or_edit_msg(&bot, dialogue.chat_id(), &q.from.username, Some(msg_id), "Import cancelled.".into(), None, None).await;
+            dialogue.update(State::Start).await?;
+        }
+        ImportCallbacks::Confirm => {
+            let to_add = entries.iter()
+                .map(|entry| (entry.tele_id, entry.date, entry.slot, entry.ict_type.clone(), entry.remarks.clone()))
+                .collect();
+
+            let unit_id = match controllers::user::get_unit_id_by_tele_id(&pool, q.from.id.0).await {
+                Ok(unit_id) => unit_id,
+                Err(_) => {
+                    handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await;
+                    return Ok(());
+                }
+            };
+
+            match controllers::scheduling::add_user_avail_multiple(&pool, q.from.id.0, to_add).await {
+                Ok(added) => {
+                    let ops_names: BTreeSet<&str> = entries.iter().map(|entry| entry.ops_name.as_str()).collect();
+
+                    notifier::emit::availability_notifications(
+                        &bot,
+                        format!(
+                            "{} imported {} availability entries for: {}",
+                            utils::username_link_tag(&q.from),
+                            added.len(),
+                            utils::escape_special_characters(&ops_names.into_iter().collect::<Vec<&str>>().join(", "))
+                        ).as_str(),
+                        &pool,
+                        Some(unit_id),
+                        q.from.id.0 as i64
+                    ).await;
+
+                    send_or_edit_msg(
+                        &bot,
+                        dialogue.chat_id(),
+                        &q.from.username,
+                        Some(msg_id),
+                        format!("Imported {} availability entries.", added.len()),
+                        None,
+                        None
+                    ).await;
+                }
+                Err(_) => {
+                    send_or_edit_msg(
+                        &bot,
+                        dialogue.chat_id(),
+                        &q.from.username,
+                        Some(msg_id),
+                        "Import failed, no entries were added.".into(),
+                        None,
+                        None
+                    ).await;
+                }
+            }
+            dialogue.update(State::Start).await?;
+        }
+    }
+
+    Ok(())
+}
@@ -1,18 +1,19 @@
 use std::sync::Arc;
 use sqlx::PgPool;
+use sqlx::types::Uuid;
 use state::State;
 
-use teloxide::dispatching::dialogue::InMemStorage;
 use teloxide::dispatching::Dispatcher;
 use teloxide::payloads::SendMessage;
 use teloxide::prelude::*;
 use teloxide::requests::JsonRequest;
 use teloxide::{dptree, Bot};
-use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, MessageId, ParseMode};
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, MessageId, ParseMode, User};
 use teloxide::update_listeners::UpdateListener;
 
-use crate::{controllers, healthcheck, utils, AppState};
+use crate::{controllers, healthcheck, utils, AppState, DIALOGUE_TTL_SECS};
 use crate::bot::state::schema;
+use crate::bot::storage::PgStorage;
 use callback_data::CallbackDataHandler;
 
 pub(self) mod commands;
@@ -25,9 +26,22 @@ pub(self) mod forecast;
 pub(self) mod notify;
 pub(self) mod plan;
 pub(self) mod upcoming;
+mod movement;
+mod attendance;
+mod import;
+mod reminders;
+mod roles;
+mod calendar;
+mod history;
+mod stats;
+mod storage;
 mod saf100;
+mod acknowledge;
+mod campaign;
+#[cfg(test)]
+mod tests;
 
-pub(self) type MyDialogue = Dialogue<State, InMemStorage<State>>;
+type MyDialogue = Dialogue<State, PgStorage>;
 pub(self) type HandlerResult = Result<(), Box<dyn std::error::Error + Send + Sync>>;
 
 // Ensure that U implements UpdateListener with the required associated types
@@ -45,7 +59,7 @@ where
 
     Dispatcher::builder(bot, schema())
         .dependencies(dptree::deps![
-            InMemStorage::<State>::new(),
+            PgStorage::new(pool.clone(), *DIALOGUE_TTL_SECS),
             pool
         ])
         .enable_ctrlc_handler()
@@ -69,7 +83,22 @@ pub(self) async fn send_msg(msg: JsonRequest<SendMessage>, username: &Option<Str
     }
 }
 
-pub(self) async fn send_or_edit_msg(bot: &Bot, chat_id: ChatId, username: &Option<String>, msg_id: Option<MessageId>, message_text: String, markup_input: Option<InlineKeyboardMarkup>, parse_mode_input: Option<ParseMode>) -> Option<MessageId> {
+// Some changes are reserved for admins even among holders of a role, as they affect every unit or grant roles
+async fn check_actor_admin(bot: &Bot, dialogue: &MyDialogue, actor: &User, pool: &PgPool, denied_text: &str) -> bool {
+    match controllers::user::get_user_by_tele_id(pool, actor.id.0).await {
+        Ok(retrieved_actor) if retrieved_actor.admin => true,
+        Ok(_) => {
+            send_msg(bot.send_message(dialogue.chat_id(), denied_text), &actor.username).await;
+            false
+        }
+        Err(_) => {
+            handle_error(bot, dialogue, dialogue.chat_id(), &actor.username).await;
+            false
+        }
+    }
+}
+
+async fn send_or_edit_msg(bot: &Bot, chat_id: ChatId, username: &Option<String>, msg_id: Option<MessageId>, message_text: String, markup_input: Option<InlineKeyboardMarkup>, parse_mode_input: Option<ParseMode>) -> Option<MessageId> {
     // Send or edit the message
     match msg_id {
         Some(id) => {
@@ -208,7 +237,7 @@ pub(self) async fn validate_name(bot: &Bot, dialogue: &MyDialogue, username: &Op
     Ok(cleaned_name.to_string())
 }
 
-pub(self) async fn validate_ops_name(bot: &Bot, dialogue: &MyDialogue, username: &Option<String>, input_ops_name_raw: String, pool: &PgPool) -> Result<String, ()> {
+async fn validate_ops_name(bot: &Bot, dialogue: &MyDialogue, username: &Option<String>, input_ops_name_raw: String, unit_id: Uuid, pool: &PgPool) -> Result<String, ()> {
     let cleaned_ops_name = utils::cleanup_name(&input_ops_name_raw).to_uppercase();
 
     // Validate that the OPS name contains only allowed characters and is not empty
@@ -254,8 +283,8 @@ pub(self) async fn validate_ops_name(bot: &Bot, dialogue: &MyDialogue, username:
         return Err(());
     }
 
-    // Check for OPS name uniqueness
-    match controllers::user::user_exists_ops_name(&pool, &cleaned_ops_name).await {
+    // Check for OPS name uniqueness within the unit
+    match controllers::user::user_exists_ops_name(pool, unit_id, &cleaned_ops_name).await {
         Ok(true) => {
             // OPS name already exists: Notify the user and prompt to re-enter
             send_msg(
@@ -316,6 +345,7 @@ async fn match_callback_data<T>(
 #[macro_export]
 macro_rules! log_endpoint_hit {
     ($chat_id:expr, $fn_name:expr) => {
+        $crate::healthcheck::metrics::METRICS.record_endpoint_hit($fn_name);
         log::info!(
             "Chat ID: {} triggered endpoint: {}",
             $chat_id,
@@ -323,6 +353,7 @@ macro_rules! log_endpoint_hit {
         );
     };
     ($chat_id:expr, $fn_name:expr, $endpoint_type:expr, $data_debug:expr) => {
+        $crate::healthcheck::metrics::METRICS.record_endpoint_hit($fn_name);
         log::info!(
             "Chat ID: {} triggered endpoint: {}",
             $chat_id,
@@ -331,6 +362,7 @@ macro_rules! log_endpoint_hit {
         log::debug!("Endpoint: {}, {}: {:?}", $fn_name, $endpoint_type, $data_debug);
     };
     ($chat_id:expr, $fn_name:expr, $endpoint_type:expr, $data_debug:expr, $( $name:expr => $value:expr ),* ) => {
+        $crate::healthcheck::metrics::METRICS.record_endpoint_hit($fn_name);
         log::info!(
             "Chat ID: {} triggered endpoint: {}",
             $chat_id,
@@ -0,0 +1,1207 @@
+use std::cmp::{max, min};
+
+use sqlx::types::chrono::{NaiveDate, NaiveTime};
+use sqlx::types::Uuid;
+use sqlx::PgPool;
+
+use teloxide::prelude::*;
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, MessageId, ParseMode};
+
+use crate::bot::state::State;
+use crate::bot::{handle_error, log_try_delete_msg, log_try_remove_markup, match_callback_data, retrieve_callback_data, send_msg, send_or_edit_msg, HandlerResult, MyDialogue};
+use crate::types::Movement;
+use crate::{controllers, log_endpoint_hit, utils};
+
+use serde::{Serialize, Deserialize};
+use strum::EnumProperty;
+use strum_macros::Display;
+use callback_data::CallbackData;
+use callback_data::CallbackDataHandler;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Display)]
+pub enum MovementAction {
+    #[strum(serialize = "modify")]
+    Modify,
+    #[strum(serialize = "delete")]
+    Delete
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Display)]
+pub enum MovementField {
+    #[strum(serialize = "time")]
+    Time,
+    #[strum(serialize = "activity")]
+    Activity,
+    #[strum(serialize = "location")]
+    Location,
+    #[strum(serialize = "remarks")]
+    Remarks
+}
+
+// Represents callback actions with optional associated data.
+#[derive(Debug, Clone, Serialize, Deserialize, EnumProperty, CallbackData)]
+enum MovementCallbacks {
+    // Option Actions
+    Add,
+    Modify,
+    Delete,
+    Back,
+
+    // Pagination Actions
+    PrevMonth,
+    NextMonth,
+
+    // Pagination Actions
+    Prev { action: MovementAction },
+    Next { action: MovementAction },
+
+    // Modify Actions
+    ModifyField { field: MovementField },
+    ModifyDelete,
+
+    // Completion Actions
+    Skip,
+    Done,
+    Cancel,
+
+    // Select Movement entry associated UUID
+    Select { id: Uuid, action: MovementAction },
+
+    // Confirmation Actions
+    ConfirmYes,
+    ConfirmNo,
+}
+
+fn format_movement_time(movement: &Movement) -> String {
+    format!("{}-{}", movement.start_time.format("%H%M"), movement.end_time.format("%H%M"))
+}
+
+fn get_movement_edit_keyboard(
+    movement: &[Movement],
+    prefix: &str,
+    start: usize,
+    show: usize,
+    action: &MovementAction
+) -> Result<InlineKeyboardMarkup, ()> {
+    let slice_end = min(start + show, movement.len());
+    let shown_entries = match movement.get(start..slice_end) {
+        Some(entries) => entries,
+        None => {
+            log::error!("Cannot get movement entries slice");
+            return Err(());
+        }
+    };
+
+    let mut entries: Vec<Vec<InlineKeyboardButton>> = shown_entries
+        .iter()
+        .map(|entry| {
+            let truncated_activity = if entry.activity.chars().count() > utils::MAX_REMARKS_SHOWN_CHARS_BUTTON {
+                format!("{}...", entry.activity.chars().take(utils::MAX_REMARKS_SHOWN_CHARS_BUTTON).collect::<String>())
+            } else {
+                entry.activity.clone()
+            };
+
+            // Format date as "MMM-DD" (3-letter month)
+            let formatted = format!(
+                "{}: {} {}",
+                entry.avail.format("%b-%d"),
+                format_movement_time(entry),
+                truncated_activity
+            );
+
+            vec![InlineKeyboardButton::callback(
+                formatted,
+                MovementCallbacks::Select { id: entry.id, action: action.clone() }.to_callback_data(prefix),
+            )]
+        })
+        .collect();
+
+    // Add "PREV", "NEXT", and "DONE" buttons
+    let mut pagination = Vec::new();
+    if start > 0 {
+        pagination.push(InlineKeyboardButton::callback("PREV", MovementCallbacks::Prev { action: action.clone() }.to_callback_data(prefix)));
+    }
+    if slice_end < movement.len() {
+        pagination.push(InlineKeyboardButton::callback("NEXT", MovementCallbacks::Next { action: action.clone() }.to_callback_data(prefix)));
+    }
+    pagination.push(InlineKeyboardButton::callback("DONE", MovementCallbacks::Done.to_callback_data(prefix)));
+
+    // Combine entries with pagination
+    entries.push(pagination);
+
+    Ok(InlineKeyboardMarkup::new(entries))
+}
+
+fn get_movement_edit_text(
+    movement: &[Movement],
+    start: usize,
+    show: usize,
+    action: &MovementAction,
+) -> String {
+    let slice_end = min(start + show, movement.len());
+    format!(
+        "Showing movement {} to {}, choose one to {}",
+        start + 1,
+        slice_end,
+        action
+    )
+}
+
+fn get_movement_details_text(movement: &Movement) -> String {
+    format!(
+        "Date: {}\nTime: `{}`\nActivity: {}\nLocation: {}\nRemarks: {}",
+        utils::escape_special_characters(&movement.avail.format("%b-%d-%Y").to_string()),
+        format_movement_time(movement),
+        utils::escape_special_characters(&movement.activity),
+        utils::escape_special_characters(movement.location.as_deref().unwrap_or("None")),
+        utils::escape_special_characters(movement.remarks.as_deref().unwrap_or("None"))
+    )
+}
+
+async fn display_movement_options(bot: &Bot, chat_id: ChatId, username: &Option<String>, existing: &[Movement], prefix: &str, month: &NaiveDate, msg_id: Option<MessageId>) -> Option<MessageId> {
+    let mut options: Vec<Vec<InlineKeyboardButton>> = Vec::new();
+
+    let mut control_row: Vec<InlineKeyboardButton> = vec![InlineKeyboardButton::callback("ADD", MovementCallbacks::Add.to_callback_data(prefix))];
+    let control_options: Vec<InlineKeyboardButton> = [("MODIFY", MovementCallbacks::Modify), ("DELETE", MovementCallbacks::Delete)]
+        .into_iter()
+        .map(|(text, data)| InlineKeyboardButton::callback(text, data.to_callback_data(prefix)))
+        .collect();
+    let page_options: Vec<InlineKeyboardButton> = [("PREV MONTH", MovementCallbacks::PrevMonth), ("NEXT MONTH", MovementCallbacks::NextMonth)]
+        .into_iter()
+        .map(|(text, data)| InlineKeyboardButton::callback(text, data.to_callback_data(prefix)))
+        .collect();
+
+    if !existing.is_empty() {
+        control_row.extend(control_options);
+    }
+    options.push(control_row);
+    options.push(page_options);
+    options.push(vec![InlineKeyboardButton::callback("DONE", MovementCallbacks::Done.to_callback_data(prefix))]);
+
+    let mut output_text = String::new();
+    if existing.is_empty() {
+        output_text.push_str(&format!(
+            "You do not have any movement recorded for {}\\.",
+            utils::escape_special_characters(&month.format("%B %Y").to_string())
+        ));
+    } else {
+        output_text.push_str(&format!(
+            "Here is your recorded movement in *{}*:\n",
+            utils::escape_special_characters(&month.format("%B %Y").to_string())
+        ));
+
+        for movement in existing {
+            let location = if let Some(location) = &movement.location {
+                format!(" @ {}", utils::escape_special_characters(location))
+            } else {
+                "".to_string()
+            };
+
+            let truncated_remarks = if let Some(remarks) = &movement.remarks {
+                if remarks.chars().count() > utils::MAX_REMARKS_SHOWN_CHARS_TEXT {
+                    format!("\nRemarks: {}\\.\\.\\.", utils::escape_special_characters(&remarks.chars().take(utils::MAX_REMARKS_SHOWN_CHARS_TEXT).collect::<String>()))
+                } else {
+                    format!("\nRemarks: {}", utils::escape_special_characters(remarks))
+                }
+            } else {
+                "".to_string()
+            };
+
+            // Format date as "MMM-DD" (3-letter month)
+            let formatted_date = utils::escape_special_characters(&movement.avail.format("%b-%d").to_string());
+
+            output_text.push_str(&format!(
+                "\\- `{}` `{}` : {}{}{}\n\n",
+                formatted_date,
+                format_movement_time(movement),
+                utils::escape_special_characters(&movement.activity),
+                location,
+                truncated_remarks
+            ));
+        }
+    }
+
+    send_or_edit_msg(bot, chat_id, username, msg_id, output_text, Some(InlineKeyboardMarkup::new(options)), Some(ParseMode::MarkdownV2)).await
+}
+
+async fn display_movement_edit_prompt(
+    bot: &Bot,
+    chat_id: ChatId,
+    username: &Option<String>,
+    movement_entry: &Movement,
+    prefix: &str,
+    msg_id: Option<MessageId>
+) -> Option<MessageId> {
+    let edit: Vec<InlineKeyboardButton> = [("TIME", MovementField::Time), ("ACTIVITY", MovementField::Activity)]
+        .into_iter()
+        .map(|(text, field)| InlineKeyboardButton::callback(text, MovementCallbacks::ModifyField { field }.to_callback_data(prefix)))
+        .collect();
+    let edit_optional: Vec<InlineKeyboardButton> = [("LOCATION", MovementField::Location), ("REMARKS", MovementField::Remarks)]
+        .into_iter()
+        .map(|(text, field)| InlineKeyboardButton::callback(text, MovementCallbacks::ModifyField { field }.to_callback_data(prefix)))
+        .collect();
+    let options: Vec<InlineKeyboardButton> = [("DELETE", MovementCallbacks::ModifyDelete), ("BACK", MovementCallbacks::Back)]
+        .into_iter()
+        .map(|(text, data)| InlineKeyboardButton::callback(text, data.to_callback_data(prefix)))
+        .collect();
+
+    let message_text = format!(
+        "{}\n\nWhat do you wish to edit?",
+        get_movement_details_text(movement_entry)
+    );
+
+    send_or_edit_msg(bot, chat_id, username, msg_id, message_text, Some(InlineKeyboardMarkup::new([edit, edit_optional, options])), Some(ParseMode::MarkdownV2)).await
+}
+
+async fn display_edit_field(bot: &Bot, chat_id: ChatId, username: &Option<String>, field: &MovementField) -> Option<MessageId> {
+    let message_text = match field {
+        MovementField::Time => "Type the start and end time (e.g. 0800-1730):",
+        MovementField::Activity => "Type the activity:",
+        MovementField::Location => "Type the location:",
+        MovementField::Remarks => "Type your remarks:"
+    };
+
+    send_msg(
+        bot.send_message(chat_id, message_text),
+        username,
+    ).await
+}
+
+async fn display_delete_confirmation(bot: &Bot, chat_id: ChatId, username: &Option<String>, msg_id: Option<MessageId>, entry: &Movement, prefix: &str) -> Option<MessageId> {
+    let confirm = [("YES", MovementCallbacks::ConfirmYes), ("NO", MovementCallbacks::ConfirmNo)]
+        .map(|(text, data)| InlineKeyboardButton::callback(text, data.to_callback_data(prefix)));
+
+    let message_text = format!(
+        "{}\n\nConfirm delete movement?",
+        get_movement_details_text(entry)
+    );
+
+    send_or_edit_msg(bot, chat_id, username, msg_id, message_text, Some(InlineKeyboardMarkup::new([confirm])), Some(ParseMode::MarkdownV2)).await
+}
+
+async fn display_add_prompt(bot: &Bot, chat_id: ChatId, username: &Option<String>, message_text: &str, prefix: &str, can_skip: bool, msg_id: Option<MessageId>) -> Option<MessageId> {
+    // Optional fields can be skipped, required fields can only be aborted with /cancel
+    let markup = if can_skip {
+        let options = [("SKIP", MovementCallbacks::Skip), ("CANCEL", MovementCallbacks::Cancel)]
+            .map(|(text, data)| InlineKeyboardButton::callback(text, data.to_callback_data(prefix)));
+        Some(InlineKeyboardMarkup::new([options]))
+    } else {
+        None
+    };
+
+    send_or_edit_msg(bot, chat_id, username, msg_id, message_text.to_string(), markup, None).await
+}
+
+async fn handle_show_movement(
+    bot: &Bot,
+    dialogue: &MyDialogue,
+    username: &Option<String>,
+    tele_id: u64,
+    month: NaiveDate,
+    pool: &PgPool,
+    msg_id: Option<MessageId>
+) -> HandlerResult {
+    // Generate random prefix to make the IDs only applicable to this dialogue instance
+    let prefix = utils::generate_prefix(utils::CALLBACK_PREFIX_LEN);
+
+    // Retrieve all the movement for the month
+    match controllers::movement::get_movement_by_tele_id_and_dates(pool, tele_id, month, utils::last_day_of_month(month)).await {
+        Ok(movement_list) => {
+            match display_movement_options(bot, dialogue.chat_id(), username, &movement_list, &prefix, &month, msg_id).await {
+                None => dialogue.update(State::ErrorState).await?,
+                Some(msg_id) => dialogue.update(State::MovementView { msg_id, prefix, month }).await?
+            };
+        }
+        Err(_) => handle_error(bot, dialogue, dialogue.chat_id(), username).await
+    }
+
+    Ok(())
+}
+
+async fn handle_show_options(
+    bot: &Bot,
+    dialogue: &MyDialogue,
+    username: &Option<String>,
+    movement_list: Vec<Movement>,
+    prefix: String,
+    start: usize,
+    show: usize,
+    action: MovementAction,
+    month: NaiveDate,
+    msg_id: Option<MessageId>
+) -> HandlerResult {
+    let markup = match get_movement_edit_keyboard(&movement_list, &prefix, start, show, &action) {
+        Ok(kb) => kb,
+        Err(_) => {
+            send_msg(
+                bot.send_message(dialogue.chat_id(), "Error encountered while getting movement."),
+                username,
+            ).await;
+            dialogue.update(State::ErrorState).await?;
+            return Ok(());
+        }
+    };
+
+    match send_or_edit_msg(bot, dialogue.chat_id(), username, msg_id, get_movement_edit_text(&movement_list, start, show, &action), Some(markup), None).await {
+        None => dialogue.update(State::ErrorState).await?,
+        Some(msg_id) => {
+            log::debug!("Transitioning to MovementSelect with MsgId: {:?}, Movement: {:?}, Action: {:?}, Prefix: {:?}, Start: {:?}", msg_id, movement_list, action, prefix, start);
+            dialogue.update(State::MovementSelect { msg_id, movement_list, prefix, start, month }).await?
+        }
+    }
+
+    Ok(())
+}
+
+async fn handle_go_back(
+    bot: &Bot,
+    dialogue: &MyDialogue,
+    username: &Option<String>,
+    tele_id: u64,
+    start: usize,
+    show: usize,
+    action: MovementAction,
+    month: NaiveDate,
+    pool: &PgPool,
+    msg_id: Option<MessageId>
+) -> HandlerResult {
+    // Generate random prefix to make the IDs only applicable to this dialogue instance
+    let prefix = utils::generate_prefix(utils::CALLBACK_PREFIX_LEN);
+
+    match controllers::movement::get_movement_by_tele_id_and_dates(pool, tele_id, month, utils::last_day_of_month(month)).await {
+        Ok(movement_list) => {
+            if movement_list.is_empty() {
+                handle_show_movement(bot, dialogue, username, tele_id, month, pool, msg_id).await?;
+            } else {
+                let new_start = if start >= movement_list.len() { max(0, movement_list.len() as i64 - show as i64) as usize } else { start };
+                handle_show_options(bot, dialogue, username, movement_list, prefix, new_start, show, action, month, msg_id).await?;
+            }
+        }
+        Err(_) => handle_error(bot, dialogue, dialogue.chat_id(), username).await
+    }
+
+    Ok(())
+}
+
+async fn modify_movement_and_go_back(
+    bot: &Bot,
+    dialogue: &MyDialogue,
+    username: &Option<String>,
+    tele_id: u64,
+    movement_entry: Movement,
+    start: usize,
+    field: MovementField,
+    input: String,
+    month: NaiveDate,
+    pool: &PgPool
+) -> HandlerResult {
+    let user = match controllers::user::get_user_by_tele_id(pool, tele_id).await {
+        Ok(user) => user,
+        Err(_) => {
+            handle_error(bot, dialogue, dialogue.chat_id(), username).await;
+            return Ok(());
+        }
+    };
+
+    if user.id != movement_entry.user_id {
+        dialogue.update(State::ErrorState).await?;
+        return Ok(());
+    }
+
+    let (mut start_time, mut end_time, mut activity, mut location, mut remarks) = (None, None, None, None, None);
+    match field {
+        MovementField::Time => match utils::parse_time_range(&input) {
+            Ok((parsed_start, parsed_end)) => {
+                start_time = Some(parsed_start);
+                end_time = Some(parsed_end);
+            }
+            Err(_) => {
+                send_msg(
+                    bot.send_message(dialogue.chat_id(), "Invalid time. Please enter the start and end time (e.g. 0800-1730), or type /cancel to abort."),
+                    username,
+                ).await;
+                return Ok(());
+            }
+        },
+        MovementField::Activity | MovementField::Location => {
+            let cleaned = utils::cleanup_name(&input);
+            let max_len = if let MovementField::Activity = field { utils::MAX_ACTIVITY_LENGTH } else { utils::MAX_LOCATION_LENGTH };
+            if cleaned.is_empty() || cleaned.chars().count() > max_len {
+                send_msg(
+                    bot.send_message(dialogue.chat_id(), format!("Please enter a {} with no more than {} characters, or type /cancel to abort.", field, max_len)),
+                    username,
+                ).await;
+                return Ok(());
+            }
+            if let MovementField::Activity = field { activity = Some(cleaned) } else { location = Some(cleaned) }
+        }
+        MovementField::Remarks => {
+            if input.chars().count() > utils::MAX_REMARKS_LENGTH {
+                send_msg(
+                    bot.send_message(dialogue.chat_id(), format!("Please enter remarks with no more than {} characters, or type /cancel to abort.", utils::MAX_REMARKS_LENGTH)),
+                    username,
+                ).await;
+                return Ok(());
+            }
+            remarks = Some(input)
+        }
+    }
+
+    match controllers::movement::edit_movement_by_uuid(pool, movement_entry.id, start_time, end_time, activity, location, remarks).await {
+        Ok(updated) => {
+            send_msg(
+                bot.send_message(dialogue.chat_id(), format!("Updated movement for: {}\n{}", utils::escape_special_characters(&updated.avail.format("%b-%d").to_string()), get_movement_details_text(&updated)))
+                    .parse_mode(ParseMode::MarkdownV2),
+                username,
+            ).await;
+            handle_go_back(bot, dialogue, username, tele_id, start, utils::MAX_SHOW_ENTRIES, MovementAction::Modify, month, pool, None).await?;
+        }
+        Err(_) => handle_error(bot, dialogue, dialogue.chat_id(), username).await
+    }
+
+    Ok(())
+}
+
+async fn delete_movement_entry_and_go_back(
+    bot: &Bot,
+    dialogue: &MyDialogue,
+    username: &Option<String>,
+    tele_id: u64,
+    movement_entry: Movement,
+    start: usize,
+    action: MovementAction,
+    month: NaiveDate,
+    pool: &PgPool,
+    msg_id: Option<MessageId>
+) -> HandlerResult {
+    match controllers::user::get_user_by_tele_id(pool, tele_id).await {
+        Ok(user) => {
+            if user.id == movement_entry.user_id {
+                match controllers::movement::remove_movement_by_uuid(pool, movement_entry.id).await {
+                    Ok(details) => {
+                        let message_text = format!("Deleted movement for: {}", details.avail.format("%b-%d"));
+                        send_or_edit_msg(bot, dialogue.chat_id(), username, msg_id, message_text, None, None).await;
+                        handle_go_back(bot, dialogue, username, tele_id, start, utils::MAX_SHOW_ENTRIES, action, month, pool, None).await?;
+                    }
+                    Err(_) => handle_error(bot, dialogue, dialogue.chat_id(), username).await
+                }
+            } else {
+                dialogue.update(State::ErrorState).await?;
+            }
+        }
+        Err(_) => handle_error(bot, dialogue, dialogue.chat_id(), username).await
+    }
+
+    Ok(())
+}
+
+async fn register_movement(
+    bot: &Bot,
+    dialogue: &MyDialogue,
+    username: &Option<String>,
+    tele_id: u64,
+    date: NaiveDate,
+    (start_time, end_time): (NaiveTime, NaiveTime),
+    activity: String,
+    location: Option<String>,
+    remarks: Option<String>,
+    pool: &PgPool
+) -> HandlerResult {
+    match controllers::movement::add_user_movement(pool, tele_id, date, start_time, end_time, activity, location, remarks).await {
+        Ok(movement) => {
+            send_msg(
+                bot.send_message(dialogue.chat_id(), format!("Added movement:\n{}", get_movement_details_text(&movement)))
+                    .parse_mode(ParseMode::MarkdownV2),
+                username,
+            ).await;
+            dialogue.update(State::Start).await?;
+        }
+        Err(_) => handle_error(bot, dialogue, dialogue.chat_id(), username).await
+    }
+
+    Ok(())
+}
+
+pub(super) async fn movement(bot: Bot, dialogue: MyDialogue, msg: Message, pool: PgPool) -> HandlerResult {
+    log_endpoint_hit!(dialogue.chat_id(), "movement", "Command", msg);
+    // Early return if the message has no sender (msg.from() is None)
+    let user = if let Some(user) = msg.from {
+        user
+    } else {
+        log::error!("Cannot get user from message");
+        dialogue.update(State::ErrorState).await?;
+        return Ok(());
+    };
+
+    // Retrieve all the movement for the current month
+    let (this_month_start, _) = utils::this_month_bound();
+    handle_show_movement(&bot, &dialogue, &user.username, user.id.0, this_month_start, &pool, None).await?;
+
+    Ok(())
+}
+
+pub(super) async fn movement_view(
+    bot: Bot,
+    dialogue: MyDialogue,
+    (msg_id, prefix, month): (MessageId, String, NaiveDate),
+    q: CallbackQuery,
+    pool: PgPool
+) -> HandlerResult {
+    log_endpoint_hit!(
+        dialogue.chat_id(), "movement_view", "Callback", q,
+        "MessageId" => msg_id,
+        "Prefix" => prefix,
+        "Month" => month
+    );
+
+    // Extract the callback data
+    let data = match retrieve_callback_data(&bot, dialogue.chat_id(), &q).await {
+        Ok(data) => data,
+        Err(_) => { return Ok(()); }
+    };
+
+    // Acknowledge the callback to remove the loading state
+    if let Err(e) = bot.answer_callback_query(q.id).await {
+        log::error!("Failed to answer callback query: {}", e);
+    }
+
+    // Deserialize the callback data into the enum
+    let callback = match match_callback_data(&bot, dialogue.chat_id(), &q.from.username, &data, &prefix).await {
+        Ok(callback) => callback,
+        Err(_) => { return Ok(()); }
+    };
+
+    let prefix = utils::generate_prefix(utils::CALLBACK_PREFIX_LEN);
+
+    match callback {
+        MovementCallbacks::PrevMonth => {
+            handle_show_movement(&bot, &dialogue, &q.from.username, q.from.id.0, utils::add_month_safe(month, -1), &pool, Some(msg_id)).await?;
+        }
+        MovementCallbacks::NextMonth => {
+            handle_show_movement(&bot, &dialogue, &q.from.username, q.from.id.0, utils::add_month_safe(month, 1), &pool, Some(msg_id)).await?;
+        }
+        MovementCallbacks::Add => {
+            match display_add_prompt(&bot, dialogue.chat_id(), &q.from.username, "Type the date of your movement (e.g. Jan 2, 28/2, 17/04/24), or /cancel to abort:", &prefix, false, Some(msg_id)).await {
+                None => dialogue.update(State::ErrorState).await?,
+                Some(msg_id) => dialogue.update(State::MovementAddDate { msg_id, prefix }).await?
+            };
+        }
+        MovementCallbacks::Modify => {
+            handle_go_back(&bot, &dialogue, &q.from.username, q.from.id.0, 0, utils::MAX_SHOW_ENTRIES, MovementAction::Modify, month, &pool, Some(msg_id)).await?;
+        }
+        MovementCallbacks::Delete => {
+            handle_go_back(&bot, &dialogue, &q.from.username, q.from.id.0, 0, utils::MAX_SHOW_ENTRIES, MovementAction::Delete, month, &pool, Some(msg_id)).await?;
+        }
+        MovementCallbacks::Done => {
+            log_try_remove_markup(&bot, dialogue.chat_id(), msg_id).await;
+            send_msg(
+                bot.send_message(dialogue.chat_id(), "Returned to start."),
+                &q.from.username,
+            ).await;
+            dialogue.update(State::Start).await?
+        }
+        _ => {
+            send_msg(
+                bot.send_message(dialogue.chat_id(), "Invalid option. Type /cancel to abort."),
+                &q.from.username,
+            ).await;
+        }
+    }
+
+    Ok(())
+}
+
+pub(super) async fn movement_select(
+    bot: Bot,
+    dialogue: MyDialogue,
+    (msg_id, movement_list, prefix, start, month): (MessageId, Vec<Movement>, String, usize, NaiveDate),
+    q: CallbackQuery,
+    pool: PgPool
+) -> HandlerResult {
+    log_endpoint_hit!(
+        dialogue.chat_id(), "movement_select", "Callback", q,
+        "MessageId" => msg_id,
+        "Movement" => movement_list,
+        "Prefix" => prefix,
+        "Start" => start,
+        "Month" => month
+    );
+
+    // Extract the callback data
+    let data = match retrieve_callback_data(&bot, dialogue.chat_id(), &q).await {
+        Ok(data) => data,
+        Err(_) => { return Ok(()); }
+    };
+
+    // Acknowledge the callback to remove the loading state
+    if let Err(e) = bot.answer_callback_query(q.id).await {
+        log::error!("Failed to answer callback query: {}", e);
+    }
+
+    // Deserialize the callback data into the enum
+    let callback = match match_callback_data(&bot, dialogue.chat_id(), &q.from.username, &data, &prefix).await {
+        Ok(callback) => callback,
+        Err(_) => { return Ok(()); }
+    };
+
+    match callback {
+        MovementCallbacks::Prev { action } => {
+            handle_show_options(&bot, &dialogue, &q.from.username, movement_list, prefix, max(0, start as i64 - utils::MAX_SHOW_ENTRIES as i64) as usize, utils::MAX_SHOW_ENTRIES, action, month, Some(msg_id)).await?;
+        }
+        MovementCallbacks::Next { action } => {
+            let entries_len = movement_list.len();
+            handle_show_options(&bot, &dialogue, &q.from.username, movement_list, prefix, if start+utils::MAX_SHOW_ENTRIES < entries_len { start+utils::MAX_SHOW_ENTRIES } else { start }, utils::MAX_SHOW_ENTRIES, action, month, Some(msg_id)).await?;
+        }
+        MovementCallbacks::Done => {
+            log_try_remove_markup(&bot, dialogue.chat_id(), msg_id).await;
+            send_msg(
+                bot.send_message(dialogue.chat_id(), "Done."),
+                &q.from.username,
+            ).await;
+            dialogue.update(State::Start).await?
+        }
+        MovementCallbacks::Select { id: parsed_id, action } => {
+            match controllers::movement::get_movement_by_uuid(&pool, parsed_id).await {
+                Ok(movement_entry) => {
+                    match action {
+                        MovementAction::Modify => {
+                            match display_movement_edit_prompt(&bot, dialogue.chat_id(), &q.from.username, &movement_entry, &prefix, Some(msg_id)).await {
+                                None => dialogue.update(State::ErrorState).await?,
+                                Some(msg_id) => dialogue.update(State::MovementModify { msg_id, prefix, movement_entry, start, month }).await?
+                            };
+                        }
+                        MovementAction::Delete => {
+                            match display_delete_confirmation(&bot, dialogue.chat_id(), &q.from.username, Some(msg_id), &movement_entry, &prefix).await {
+                                None => dialogue.update(State::ErrorState).await?,
+                                Some(msg_id) => dialogue.update(State::MovementDeleteConfirm { msg_id, prefix, movement_entry, start, month }).await?
+                            };
+                        }
+                    }
+                }
+                Err(_) => handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await
+            }
+        }
+        _ => {
+            send_msg(
+                bot.send_message(dialogue.chat_id(), "Invalid option. Type /cancel to abort."),
+                &q.from.username,
+            ).await;
+        }
+    }
+
+    Ok(())
+}
+
+pub(super) async fn movement_modify(
+    bot: Bot,
+    dialogue: MyDialogue,
+    (msg_id, prefix, movement_entry, start, month): (MessageId, String, Movement, usize, NaiveDate),
+    q: CallbackQuery,
+    pool: PgPool
+) -> HandlerResult {
+    log_endpoint_hit!(
+        dialogue.chat_id(), "movement_modify", "Callback", q,
+        "MessageId" => msg_id,
+        "Prefix" => prefix,
+        "Movement" => movement_entry,
+        "Start" => start,
+        "Month" => month
+    );
+
+    // Extract the callback data
+    let data = match retrieve_callback_data(&bot, dialogue.chat_id(), &q).await {
+        Ok(data) => data,
+        Err(_) => { return Ok(()); }
+    };
+
+    // Acknowledge the callback to remove the loading state
+    if let Err(e) = bot.answer_callback_query(q.id).await {
+        log::error!("Failed to answer callback query: {}", e);
+    }
+
+    // Deserialize the callback data into the enum
+    let callback = match match_callback_data(&bot, dialogue.chat_id(), &q.from.username, &data, &prefix).await {
+        Ok(callback) => callback,
+        Err(_) => { return Ok(()); }
+    };
+
+    match callback {
+        MovementCallbacks::ModifyField { field } => {
+            log_try_remove_markup(&bot, dialogue.chat_id(), msg_id).await;
+            match display_edit_field(&bot, dialogue.chat_id(), &q.from.username, &field).await {
+                None => dialogue.update(State::ErrorState).await?,
+                Some(change_msg_id) => {
+                    log::debug!("Transitioning to MovementModifyField with Movement: {:?}, Field: {:?}, Start: {:?}", movement_entry, field, start);
+                    dialogue.update(State::MovementModifyField { msg_id, change_msg_id, movement_entry, field, start, month }).await?
+                }
+            }
+        }
+        MovementCallbacks::ModifyDelete => {
+            match display_delete_confirmation(&bot, dialogue.chat_id(), &q.from.username, Some(msg_id), &movement_entry, &prefix).await {
+                None => dialogue.update(State::ErrorState).await?,
+                Some(msg_id) => dialogue.update(State::MovementDeleteConfirm { msg_id, prefix, movement_entry, start, month }).await?
+            };
+        }
+        MovementCallbacks::Back => {
+            handle_go_back(&bot, &dialogue, &q.from.username, q.from.id.0, start, utils::MAX_SHOW_ENTRIES, MovementAction::Modify, month, &pool, Some(msg_id)).await?;
+        }
+        _ => {
+            send_msg(
+                bot.send_message(dialogue.chat_id(), "Invalid option. Type /cancel to abort."),
+                &q.from.username,
+            ).await;
+        }
+    }
+
+    Ok(())
+}
+
+pub(super) async fn movement_modify_field(
+    bot: Bot,
+    dialogue: MyDialogue,
+    (msg_id, change_msg_id, movement_entry, field, start, month): (MessageId, MessageId, Movement, MovementField, usize, NaiveDate),
+    msg: Message,
+    pool: PgPool
+) -> HandlerResult {
+    log_endpoint_hit!(
+        dialogue.chat_id(), "movement_modify_field", "Message", msg,
+        "MessageId" => msg_id,
+        "Change MessageId" => change_msg_id,
+        "Movement" => movement_entry,
+        "Field" => field,
+        "Start" => start,
+        "Month" => month
+    );
+
+    // Early return if the message has no sender (msg.from() is None)
+    let user = if let Some(ref user) = msg.from {
+        user
+    } else {
+        log::error!("Cannot get user from message");
+        dialogue.update(State::Start).await?;
+        return Ok(());
+    };
+
+    match msg.text().map(ToOwned::to_owned) {
+        Some(input) => {
+            modify_movement_and_go_back(&bot, &dialogue, &user.username, user.id.0, movement_entry, start, field, input, month, &pool).await?;
+        }
+        None => {
+            send_msg(
+                bot.send_message(dialogue.chat_id(), format!("Please, enter the {}, or type /cancel to abort.", field)),
+                &user.username,
+            ).await;
+        }
+    }
+
+    Ok(())
+}
+
+pub(super) async fn movement_delete_confirm(
+    bot: Bot,
+    dialogue: MyDialogue,
+    (msg_id, prefix, movement_entry, start, month): (MessageId, String, Movement, usize, NaiveDate),
+    q: CallbackQuery,
+    pool: PgPool
+) -> HandlerResult {
+    log_endpoint_hit!(
+        dialogue.chat_id(), "movement_delete_confirm", "Callback", q,
+        "MessageId" => msg_id,
+        "Prefix" => prefix,
+        "Movement" => movement_entry,
+        "Start" => start,
+        "Month" => month
+    );
+
+    // Extract the callback data
+    let data = match retrieve_callback_data(&bot, dialogue.chat_id(), &q).await {
+        Ok(data) => data,
+        Err(_) => { return Ok(()); }
+    };
+
+    // Acknowledge the callback to remove the loading state
+    if let Err(e) = bot.answer_callback_query(q.id).await {
+        log::error!("Failed to answer callback query: {}", e);
+    }
+
+    // Deserialize the callback data into the enum
+    let callback = match match_callback_data(&bot, dialogue.chat_id(), &q.from.username, &data, &prefix).await {
+        Ok(callback) => callback,
+        Err(_) => { return Ok(()); }
+    };
+
+    match callback {
+        MovementCallbacks::ConfirmYes => {
+            delete_movement_entry_and_go_back(&bot, &dialogue, &q.from.username, q.from.id.0, movement_entry, start, MovementAction::Delete, month, &pool, Some(msg_id)).await?;
+        }
+        MovementCallbacks::ConfirmNo => {
+            handle_go_back(&bot, &dialogue, &q.from.username, q.from.id.0, start, utils::MAX_SHOW_ENTRIES, MovementAction::Delete, month, &pool, Some(msg_id)).await?;
+        }
+        _ => {
+            send_msg(
+                bot.send_message(dialogue.chat_id(), "Invalid option. Type /cancel to abort."),
+                &q.from.username,
+            ).await;
+        }
+    }
+
+    Ok(())
+}
+
+pub(super) async fn movement_add_date(
+    bot: Bot,
+    dialogue: MyDialogue,
+    (msg_id, prefix): (MessageId, String),
+    msg: Message,
+    pool: PgPool
+) -> HandlerResult {
+    log_endpoint_hit!(
+        dialogue.chat_id(), "movement_add_date", "Message", msg,
+        "MessageId" => msg_id,
+        "Prefix" => prefix
+    );
+
+    // Early return if the message has no sender (msg.from() is None)
+    let user = if let Some(ref user) = msg.from {
+        user
+    } else {
+        log::error!("Cannot get user from message");
+        dialogue.update(State::Start).await?;
+        return Ok(());
+    };
+
+    let input_date = match msg.text() {
+        Some(input_date) => input_date,
+        None => {
+            send_msg(
+                bot.send_message(dialogue.chat_id(), "Please, enter a date, or type /cancel to abort."),
+                &user.username,
+            ).await;
+            return Ok(());
+        }
+    };
+
+    let date = match utils::parse_single_date(input_date.trim()) {
+        Ok(date) => date,
+        Err(_) => {
+            send_msg(
+                bot.send_message(dialogue.chat_id(), "Invalid date. Please enter a single upcoming date (e.g. Jan 2, 28/2, 17/04/24), or type /cancel to abort."),
+                &user.username,
+            ).await;
+            return Ok(());
+        }
+    };
+
+    // Only one movement entry is allowed per day
+    match controllers::movement::get_movement_by_tele_id_and_date(&pool, user.id.0, date).await {
+        Ok(Some(_)) => {
+            send_msg(
+                bot.send_message(dialogue.chat_id(), "You have already recorded movement on this date. Please use the modify function instead, or enter a different date."),
+                &user.username,
+            ).await;
+        }
+        Ok(None) => {
+            let message_text = format!("Date: {}\n\nType the start and end time (e.g. 0800-1730), or /cancel to abort:", date.format("%b-%d-%Y"));
+            match display_add_prompt(&bot, dialogue.chat_id(), &user.username, &message_text, &prefix, false, None).await {
+                None => dialogue.update(State::ErrorState).await?,
+                Some(msg_id) => dialogue.update(State::MovementAddTime { msg_id, prefix, date }).await?
+            };
+        }
+        Err(_) => handle_error(&bot, &dialogue, dialogue.chat_id(), &user.username).await
+    }
+
+    Ok(())
+}
+
+pub(super) async fn movement_add_time(
+    bot: Bot,
+    dialogue: MyDialogue,
+    (msg_id, prefix, date): (MessageId, String, NaiveDate),
+    msg: Message
+) -> HandlerResult {
+    log_endpoint_hit!(
+        dialogue.chat_id(), "movement_add_time", "Message", msg,
+        "MessageId" => msg_id,
+        "Prefix" => prefix,
+        "Date" => date
+    );
+
+    // Early return if the message has no sender (msg.from() is None)
+    let user = if let Some(ref user) = msg.from {
+        user
+    } else {
+        log::error!("Cannot get user from message");
+        dialogue.update(State::Start).await?;
+        return Ok(());
+    };
+
+    match msg.text().map(utils::parse_time_range) {
+        Some(Ok((start_time, end_time))) => {
+            let message_text = format!("Time: {}-{}\n\nType the activity, or /cancel to abort:", start_time.format("%H%M"), end_time.format("%H%M"));
+            match display_add_prompt(&bot, dialogue.chat_id(), &user.username, &message_text, &prefix, false, None).await {
+                None => dialogue.update(State::ErrorState).await?,
+                Some(msg_id) => dialogue.update(State::MovementAddActivity { msg_id, prefix, date, start_time, end_time }).await?
+            };
+        }
+        _ => {
+            send_msg(
+                bot.send_message(dialogue.chat_id(), "Invalid time. Please enter the start and end time (e.g. 0800-1730), or type /cancel to abort."),
+                &user.username,
+            ).await;
+        }
+    }
+
+    Ok(())
+}
+
+pub(super) async fn movement_add_activity(
+    bot: Bot,
+    dialogue: MyDialogue,
+    (msg_id, prefix, date, start_time, end_time): (MessageId, String, NaiveDate, NaiveTime, NaiveTime),
+    msg: Message
+) -> HandlerResult {
+    log_endpoint_hit!(
+        dialogue.chat_id(), "movement_add_activity", "Message", msg,
+        "MessageId" => msg_id,
+        "Prefix" => prefix,
+        "Date" => date,
+        "Start Time" => start_time,
+        "End Time" => end_time
+    );
+
+    // Early return if the message has no sender (msg.from() is None)
+    let user = if let Some(ref user) = msg.from {
+        user
+    } else {
+        log::error!("Cannot get user from message");
+        dialogue.update(State::Start).await?;
+        return Ok(());
+    };
+
+    let activity = msg.text().map(utils::cleanup_name).unwrap_or_default();
+    if activity.is_empty() || activity.chars().count() > utils::MAX_ACTIVITY_LENGTH {
+        send_msg(
+            bot.send_message(dialogue.chat_id(), format!("Please enter an activity with no more than {} characters, or type /cancel to abort.", utils::MAX_ACTIVITY_LENGTH)),
+            &user.username,
+        ).await;
+        return Ok(());
+    }
+
+    match display_add_prompt(&bot, dialogue.chat_id(), &user.username, "Type the location, or SKIP if none:", &prefix, true, None).await {
+        None => dialogue.update(State::ErrorState).await?,
+        Some(msg_id) => dialogue.update(State::MovementAddLocation { msg_id, prefix, date, start_time, end_time, activity }).await?
+    };
+
+    Ok(())
+}
+
+pub(super) async fn movement_add_location(
+    bot: Bot,
+    dialogue: MyDialogue,
+    (msg_id, prefix, date, start_time, end_time, activity): (MessageId, String, NaiveDate, NaiveTime, NaiveTime, String),
+    msg: Message
+) -> HandlerResult {
+    log_endpoint_hit!(
+        dialogue.chat_id(), "movement_add_location", "Message", msg,
+        "MessageId" => msg_id,
+        "Prefix" => prefix,
+        "Date" => date,
+        "Start Time" => start_time,
+        "End Time" => end_time,
+        "Activity" => activity
+    );
+
+    // Early return if the message has no sender (msg.from() is None)
+    let user = if let Some(ref user) = msg.from {
+        user
+    } else {
+        log::error!("Cannot get user from message");
+        dialogue.update(State::Start).await?;
+        return Ok(());
+    };
+
+    let location = msg.text().map(utils::cleanup_name).unwrap_or_default();
+    if location.is_empty() || location.chars().count() > utils::MAX_LOCATION_LENGTH {
+        send_msg(
+            bot.send_message(dialogue.chat_id(), format!("Please enter a location with no more than {} characters, select SKIP if none, or type /cancel to abort.", utils::MAX_LOCATION_LENGTH)),
+            &user.username,
+        ).await;
+        return Ok(());
+    }
+
+    log_try_remove_markup(&bot, dialogue.chat_id(), msg_id).await;
+    match display_add_prompt(&bot, dialogue.chat_id(), &user.username, "Type your remarks, or SKIP if none:", &prefix, true, None).await {
+        None => dialogue.update(State::ErrorState).await?,
+        Some(msg_id) => dialogue.update(State::MovementAddRemarks { msg_id, prefix, date, start_time, end_time, activity, location: Some(location) }).await?
+    };
+
+    Ok(())
+}
+
+pub(super) async fn movement_add_location_callback(
+    bot: Bot,
+    dialogue: MyDialogue,
+    (msg_id, prefix, date, start_time, end_time, activity): (MessageId, String, NaiveDate, NaiveTime, NaiveTime, String),
+    q: CallbackQuery
+) -> HandlerResult {
+    log_endpoint_hit!(
+        dialogue.chat_id(), "movement_add_location_callback", "Callback", q,
+        "MessageId" => msg_id,
+        "Prefix" => prefix,
+        "Date" => date,
+        "Start Time" => start_time,
+        "End Time" => end_time,
+        "Activity" => activity
+    );
+
+    // Extract the callback data
+    let data = match retrieve_callback_data(&bot, dialogue.chat_id(), &q).await {
+        Ok(data) => data,
+        Err(_) => { return Ok(()); }
+    };
+
+    // Acknowledge the callback to remove the loading state
+    if let Err(e) = bot.answer_callback_query(q.id).await {
+        log::error!("Failed to answer callback query: {}", e);
+    }
+
+    // Deserialize the callback data into the enum
+    let callback = match match_callback_data(&bot, dialogue.chat_id(), &q.from.username, &data, &prefix).await {
+        Ok(callback) => callback,
+        Err(_) => { return Ok(()); }
+    };
+
+    match callback {
+        MovementCallbacks::Skip => {
+            log_try_remove_markup(&bot, dialogue.chat_id(), msg_id).await;
+            match display_add_prompt(&bot, dialogue.chat_id(), &q.from.username, "Type your remarks, or SKIP if none:", &prefix, true, None).await {
+                None => dialogue.update(State::ErrorState).await?,
+                Some(msg_id) => dialogue.update(State::MovementAddRemarks { msg_id, prefix, date, start_time, end_time, activity, location: None }).await?
+            };
+        }
+        MovementCallbacks::Cancel => {
+            log_try_delete_msg(&bot, dialogue.chat_id(), msg_id).await;
+            send_msg(
+                bot.send_message(dialogue.chat_id(), "Operation cancelled."),
+                &q.from.username,
+            ).await;
+            dialogue.update(State::Start).await?
+        }
+        _ => {
+            send_msg(
+                bot.send_message(dialogue.chat_id(), "Invalid option. Type /cancel to abort."),
+                &q.from.username,
+            ).await;
+        }
+    }
+
+    Ok(())
+}
+
+pub(super) async fn movement_add_remarks(
+    bot: Bot,
+    dialogue: MyDialogue,
+    (msg_id, prefix, date, start_time, end_time, activity, location): (MessageId, String, NaiveDate, NaiveTime, NaiveTime, String, Option<String>),
+    msg: Message,
+    pool: PgPool
+) -> HandlerResult {
+    log_endpoint_hit!(
+        dialogue.chat_id(), "movement_add_remarks", "Message", msg,
+        "MessageId" => msg_id,
+        "Prefix" => prefix,
+        "Date" => date,
+        "Start Time" => start_time,
+        "End Time" => end_time,
+        "Activity" => activity,
+        "Location" => location
+    );
+
+    // Early return if the message has no sender (msg.from() is None)
+    let user = if let Some(ref user) = msg.from {
+        user
+    } else {
+        log::error!("Cannot get user from message");
+        dialogue.update(State::Start).await?;
+        return Ok(());
+    };
+
+    match msg.text().map(ToOwned::to_owned) {
+        Some(input_remarks) if input_remarks.chars().count() > utils::MAX_REMARKS_LENGTH => {
+            send_msg(
+                bot.send_message(dialogue.chat_id(), format!("Please enter remarks with no more than {} characters, select SKIP if none, or type /cancel to abort.", utils::MAX_REMARKS_LENGTH)),
+                &user.username,
+            ).await;
+        }
+        Some(input_remarks) => {
+            log_try_remove_markup(&bot, dialogue.chat_id(), msg_id).await;
+            register_movement(&bot, &dialogue, &user.username, user.id.0, date, (start_time, end_time), activity, location, Some(input_remarks), &pool).await?;
+        }
+        None => {
+            send_msg(
+                bot.send_message(dialogue.chat_id(), "Please, enter remarks, select SKIP if none, or type /cancel to abort."),
+                &user.username,
+            ).await;
+        }
+    }
+
+    Ok(())
+}
+
+pub(super) async fn movement_add_complete(
+    bot: Bot,
+    dialogue: MyDialogue,
+    (msg_id, prefix, date, start_time, end_time, activity, location): (MessageId, String, NaiveDate, NaiveTime, NaiveTime, String, Option<String>),
+    q: CallbackQuery,
+    pool: PgPool
+) -> HandlerResult {
+    log_endpoint_hit!(
+        dialogue.chat_id(), "movement_add_complete", "Callback", q,
+        "MessageId" => msg_id,
+        "Prefix" => prefix,
+        "Date" => date,
+        "Start Time" => start_time,
+        "End Time" => end_time,
+        "Activity" => activity,
+        "Location" => location
+    );
+
+    // Extract the callback data
+    let data = match retrieve_callback_data(&bot, dialogue.chat_id(), &q).await {
+        Ok(data) => data,
+        Err(_) => { return Ok(()); }
+    };
+
+    // Acknowledge the callback to remove the loading state
+    if let Err(e) = bot.answer_callback_query(q.id).await {
+        log::error!("Failed to answer callback query: {}", e);
+    }
+
+    // Deserialize the callback data into the enum
+    let callback = match match_callback_data(&bot, dialogue.chat_id(), &q.from.username, &data, &prefix).await {
+        Ok(callback) => callback,
+        Err(_) => { return Ok(()); }
+    };
+
+    match callback {
+        MovementCallbacks::Skip => {
+            log_try_remove_markup(&bot, dialogue.chat_id(), msg_id).await;
+            register_movement(&bot, &dialogue, &q.from.username, q.from.id.0, date, (start_time, end_time), activity, location, None, &pool).await?;
+        }
+        MovementCallbacks::Cancel => {
+            log_try_delete_msg(&bot, dialogue.chat_id(), msg_id).await;
+            send_msg(
+                bot.send_message(dialogue.chat_id(), "Operation cancelled."),
+                &q.from.username,
+            ).await;
+            dialogue.update(State::Start).await?
+        }
+        _ => {
+            send_msg(
+                bot.send_message(dialogue.chat_id(), "Invalid option. Type /cancel to abort."),
+                &q.from.username,
+            ).await;
+        }
+    }
+
+    Ok(())
+}
@@ -3,12 +3,12 @@ use std::sync::Arc;
 use sqlx::PgPool;
 
 use teloxide::prelude::*;
-use teloxide::dispatching::dialogue::InMemStorage;
 use teloxide::types::{ChatKind, InlineKeyboardButton, InlineKeyboardMarkup, MessageId, ParseMode, User};
 
 use crate::bot::{handle_error, log_try_remove_markup, match_callback_data, retrieve_callback_data, send_msg, send_or_edit_msg, HandlerResult, MyDialogue};
 use crate::{controllers, log_endpoint_hit, utils};
 use crate::bot::state::State;
+use crate::bot::storage::PgStorage;
 use crate::types::NotificationSettings;
 
 use serde::{Serialize, Deserialize};
@@ -16,6 +16,9 @@ use strum::EnumProperty;
 use callback_data::CallbackData;
 use callback_data::CallbackDataHandler;
 
+// Shown when a chat already receives notifications for a different unit
+const OTHER_UNIT_CHAT_TEXT: &str = "This chat receives notifications for another unit and can only be configured from that unit.";
+
 // Represents callback actions with optional associated data.
 #[derive(Debug, Clone, Serialize, Deserialize, EnumProperty, CallbackData)]
 enum NotifyCallbackData {
@@ -25,6 +28,7 @@ enum NotifyCallbackData {
     AvailabilityNotification { enable: bool },
     PlanNotification { enable: bool },
     ConflictNotification { enable: bool },
+    DigestNotification { enable: bool },
 
     // Completion Actions
     Cancel,
@@ -36,12 +40,13 @@ enum NotifyCallbackData {
 
 fn format_notification_settings(settings: &NotificationSettings) -> String {
     format!(
-        "\\- System Notifications: {}\n\\- Register Notifications: {}\n\\- Availability Notifications: {}\n\\- Plan Notifications: {}\n\\- Conflict Notifications: {}",
+        "\\- System Notifications: {}\n\\- Register Notifications: {}\n\\- Availability Notifications: {}\n\\- Plan Notifications: {}\n\\- Conflict Notifications: {}\n\\- Daily Crew Digest: {}",
         if settings.notif_system { "🟢 *ON*" } else { "🔴 *OFF*" },
         if settings.notif_register { "🟢 *ON*" } else { "🔴 *OFF*" },
         if settings.notif_availability { "🟢 *ON*" } else { "🔴 *OFF*" },
         if settings.notif_plan { "🟢 *ON*" } else { "🔴 *OFF*" },
         if settings.notif_conflict { "🟢 *ON*" } else { "🔴 *OFF*" },
+        if settings.notif_digest { "🟢 *ON*" } else { "🔴 *OFF*" },
     )
 }
 
@@ -53,6 +58,7 @@ fn create_inline_keyboard(settings: &NotificationSettings, prefix: &String) -> I
             ("AVAILABILITY", settings.notif_availability, NotifyCallbackData::AvailabilityNotification { enable: !settings.notif_availability }),
             ("PLAN",         settings.notif_plan,         NotifyCallbackData::PlanNotification { enable: !settings.notif_plan }),
             ("CONFLICT",     settings.notif_conflict,     NotifyCallbackData::ConflictNotification { enable: !settings.notif_conflict }),
+            ("DIGEST",       settings.notif_digest,       NotifyCallbackData::DigestNotification { enable: !settings.notif_digest }),
         ].into_iter()
         .map(|(field, status, data)| vec![
             InlineKeyboardButton::callback(
@@ -112,7 +118,7 @@ async fn update_dm_config_notification(bot: &Bot, chat_id: ChatId, message_id: &
 pub(super) async fn notify(
     bot: Bot,
     dialogue: MyDialogue,
-    storage: Arc<InMemStorage<State>>,
+    storage: Arc<PgStorage>,
     msg: Message,
     pool: PgPool
 ) -> HandlerResult {
@@ -137,20 +143,51 @@ pub(super) async fn notify(
         display_inchat_config_notification(&bot, dialogue.chat_id(), user).await;
     }
 
+    // The chat receives notifications for the unit of the admin configuring it
+    let unit_id = match controllers::user::get_unit_id_by_tele_id(&pool, user.id.0).await {
+        Ok(unit_id) => unit_id,
+        Err(_) => {
+            send_msg(
+                bot.send_message(msg.chat.id, "Failed to retrieve notification settings."),
+                &user.username,
+            ).await;
+            return Ok(());
+        }
+    };
+
     // Fetch existing notification settings for the chat
     let settings = match controllers::notifications::get_notification_settings(&pool, msg.chat.id.0).await {
+        // A shared chat configured by another unit cannot be taken over, but a user's own DM follows their unit
+        Ok(Some(settings)) if settings.unit_id != unit_id && msg.chat.id.0 != user.id.0 as i64 => {
+            send_msg(
+                bot.send_message(msg.chat.id, OTHER_UNIT_CHAT_TEXT),
+                &user.username,
+            ).await;
+            return Ok(());
+        }
         Ok(Some(settings)) => settings,
         Ok(None) => {
             // If no settings exist, create default settings
-            controllers::notifications::update_notification_settings(
+            match controllers::notifications::update_notification_settings(
                 &pool,
                 msg.chat.id.0,
+                unit_id,
                 Some(false),
                 Some(false),
                 Some(false),
                 Some(false),
                 Some(false),
-            ).await?
+                Some(false),
+            ).await? {
+                Some(settings) => settings,
+                None => {
+                    send_msg(
+                        bot.send_message(msg.chat.id, OTHER_UNIT_CHAT_TEXT),
+                        &user.username,
+                    ).await;
+                    return Ok(());
+                }
+            }
         },
         Err(e) => {
             log::error!("Error fetching notification settings: {}", e);
@@ -209,16 +246,25 @@ pub(super) async fn notify_settings(
     
     match callback {
         NotifyCallbackData::Confirm => {
+            let unit_id = match controllers::user::get_unit_id_by_tele_id(&pool, q.from.id.0).await {
+                Ok(unit_id) => unit_id,
+                Err(_) => {
+                    handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await;
+                    return Ok(());
+                }
+            };
+
             // commit to database
             return match controllers::notifications::update_notification_settings(
-                &pool, chat_id.0,
+                &pool, chat_id.0, unit_id,
                 Some(notification_settings.notif_system),
                 Some(notification_settings.notif_register),
                 Some(notification_settings.notif_availability),
                 Some(notification_settings.notif_plan),
-                Some(notification_settings.notif_conflict)
+                Some(notification_settings.notif_conflict),
+                Some(notification_settings.notif_digest)
             ).await {
-                Ok(settings) => {
+                Ok(Some(settings)) => {
                     let message_text = format!(
                         "Updated notification settings for chat:\n{}",
                         format_notification_settings(&settings)
@@ -239,6 +285,12 @@ pub(super) async fn notify_settings(
                     dialogue.update(State::Start).await?;
                     Ok(())
                 }
+                Ok(None) => {
+                    // Another unit configured the chat in the meantime
+                    send_or_edit_msg(&bot, dialogue.chat_id(), &q.from.username, Some(msg_id), OTHER_UNIT_CHAT_TEXT.to_string(), None, None).await;
+                    dialogue.update(State::Start).await?;
+                    Ok(())
+                }
                 Err(_) => {
                     handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await;
                     Ok(())
@@ -305,6 +357,9 @@ pub(super) async fn notify_settings(
         NotifyCallbackData::ConflictNotification { enable } => {
             notification_settings.notif_conflict = enable;
         }
+        NotifyCallbackData::DigestNotification { enable } => {
+            notification_settings.notif_digest = enable;
+        }
     }
     
     // Intentionally continue using the same prefix to handle quick multiple actions
@@ -1,6 +1,6 @@
-use crate::APP_TIMEZONE;
+use crate::{APP_TIMEZONE, PLAN_CAPACITY, SAF100_LEAD_DAYS};
 use std::cmp::{max, min};
-use std::collections::HashSet;
+use std::collections::HashMap;
 use chrono::NaiveDate;
 use chrono::Utc;
 
@@ -12,15 +12,16 @@ use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, MessageId, Par
 
 use super::{handle_error, log_try_remove_markup, match_callback_data, retrieve_callback_data, send_msg, send_or_edit_msg, HandlerResult, MyDialogue};
 use crate::bot::state::State;
-use crate::types::{AvailabilityDetails, RoleType, Usr, UsrType};
+use crate::types::{Ack, AvailabilityDetails, CrewRequirement, Ict, PlanConflict, PlanHistory, PlanStatus, RoleType, Usr};
 use crate::{controllers, log_endpoint_hit, notifier, now, utils};
 
 use serde::{Serialize, Deserialize};
-use strum::EnumProperty;
-use strum::IntoEnumIterator;
+use strum::{EnumProperty, IntoEnumIterator};
 use callback_data::CallbackData;
 use callback_data::CallbackDataHandler;
 
+type PlanViewState = (MessageId, Option<Usr>, Option<NaiveDate>, Vec<AvailabilityDetails>, HashMap<Uuid, PlanStatus>, RoleType, String, usize);
+
 // Represents callback actions with optional associated data.
 #[derive(Debug, Clone, Serialize, Deserialize, EnumProperty, CallbackData)]
 enum PlanCallbacks {
@@ -38,17 +39,113 @@ enum PlanCallbacks {
     // Plan Toggle Actions with associated UUID
     Select { id: Uuid },
     Toggle { id: Uuid },
+    ToggleStandby { id: Uuid },
+    PromoteStandby,
 
     // Confirmation Actions
     ConfirmYes,
     ConfirmNo,
+
+    // Crew Requirement Actions
+    Suggest,
+    Requirements,
+    AddRequirement { ict_type: Ict },
+    RemoveRequirement { ict_type: Ict },
+}
+
+// Plan status of the entry after the changes
+fn get_changed_status(entry: &AvailabilityDetails, changes: &HashMap<Uuid, PlanStatus>) -> PlanStatus {
+    changes.get(&entry.id).copied().unwrap_or(entry.plan_status())
+}
+
+// Records the change of the entry to the plan status, or drops it if the entry already has that status
+fn set_changed_status(entry: &AvailabilityDetails, changes: &mut HashMap<Uuid, PlanStatus>, plan_status: PlanStatus) {
+    if entry.plan_status() == plan_status {
+        changes.remove(&entry.id);
+    } else {
+        changes.insert(entry.id, plan_status);
+    }
+}
+
+// Whether the owner declined the reminder for the entry, a change of plan status clears their response
+fn is_declined(entry: &AvailabilityDetails, changes: &HashMap<Uuid, PlanStatus>) -> bool {
+    !changes.contains_key(&entry.id) && entry.ack == Some(Ack::Declined)
+}
+
+// Response of the owner to the reminder for the entry, shown until its plan status is changed
+fn get_ack_text(entry: &AvailabilityDetails, changes: &HashMap<Uuid, PlanStatus>) -> &'static str {
+    if changes.contains_key(&entry.id) {
+        return "";
+    }
+    match entry.ack {
+        Some(Ack::Acknowledged) => " ✅ ACKNOWLEDGED",
+        Some(Ack::Declined) => " ❌ *DECLINED*",
+        None => ""
+    }
+}
+
+// Counts the entries of the ICT type that would be planned after the changes
+fn count_planned(availability_list: &[AvailabilityDetails], changes: &HashMap<Uuid, PlanStatus>, ict_type: &Ict) -> usize {
+    availability_list
+        .iter()
+        .filter(|entry| entry.ict_type == *ict_type && get_changed_status(entry, changes) == PlanStatus::Planned)
+        .count()
+}
+
+// Pairs each planned entry which is no longer available or declined with a standby entry of the same ICT type to take its place
+fn get_standby_promotions<'a>(
+    availability_list: &'a [AvailabilityDetails],
+    changes: &HashMap<Uuid, PlanStatus>
+) -> Vec<(&'a AvailabilityDetails, &'a AvailabilityDetails)> {
+    let mut standby: Vec<&'a AvailabilityDetails> = availability_list
+        .iter()
+        .filter(|entry| entry.is_valid && !is_declined(entry, changes) && get_changed_status(entry, changes) == PlanStatus::Standby)
+        .collect();
+
+    availability_list
+        .iter()
+        .filter(|entry| (!entry.is_valid || is_declined(entry, changes)) && get_changed_status(entry, changes) == PlanStatus::Planned)
+        .filter_map(|withdrawn| {
+            let position = standby.iter().position(|entry| entry.ict_type == withdrawn.ict_type)?;
+            Some((withdrawn, standby.remove(position)))
+        })
+        .collect()
+}
+
+// Picks who to plan to meet the requirements, preferring those planned the fewest times and then the longest ago
+fn suggest_selection(
+    requirements: &[CrewRequirement],
+    availability_list: &[AvailabilityDetails],
+    changes: &HashMap<Uuid, PlanStatus>,
+    history: &[PlanHistory]
+) -> Vec<Uuid> {
+    let mut suggestion = Vec::new();
+    for requirement in requirements {
+        let shortfall = (requirement.headcount.max(0) as usize).saturating_sub(count_planned(availability_list, changes, &requirement.ict_type));
+
+        // entries already changed by the planner are left alone
+        let mut candidates: Vec<(&AvailabilityDetails, Option<&PlanHistory>)> = availability_list
+            .iter()
+            .filter(|entry| entry.ict_type == requirement.ict_type && entry.is_valid && !entry.planned && !changes.contains_key(&entry.id))
+            .map(|entry| (entry, history.iter().find(|past| past.id == entry.id)))
+            .collect();
+        candidates.sort_by_key(|(entry, past)| (
+            past.map_or(0, |past| past.times_planned),
+            past.and_then(|past| past.last_planned),
+            entry.ops_name.clone()
+        ));
+
+        suggestion.extend(candidates.into_iter().take(shortfall).map(|(entry, _)| entry.id));
+    }
+
+    suggestion
 }
 
 // Generates the inline keyboard for user availability view
 fn get_user_availability_keyboard(
     prefix: &String,
     availability_list: &Vec<AvailabilityDetails>,
-    changes: &HashSet<Uuid>,
+    changes: &HashMap<Uuid, PlanStatus>,
     start: usize,
     show: usize
 ) -> Result<InlineKeyboardMarkup, ()> {
@@ -64,12 +161,13 @@ fn get_user_availability_keyboard(
     let mut entries: Vec<Vec<InlineKeyboardButton>> = shown_entries
         .iter()
         .map(|entry| {
-            // Check if the availability ID is in the changes vector
-            let is_in_changes = changes.contains(&entry.id);
-            let option_str = if entry.planned ^ is_in_changes {
-                if entry.is_valid { "UNPLAN" } else { "UNPLAN (UNAVAIL)" }
-            } else {
-                "PLAN"
+            // Show the status after the changes
+            let option_str = match (get_changed_status(entry, changes), entry.is_valid) {
+                (PlanStatus::Planned, true) => "UNPLAN",
+                (PlanStatus::Planned, false) => "UNPLAN (UNAVAIL)",
+                (PlanStatus::Standby, true) => "STANDBY",
+                (PlanStatus::Standby, false) => "STANDBY (UNAVAIL)",
+                (PlanStatus::Unplanned, _) => "PLAN"
             };
             let truncated_remarks = if let Some(remarks) = &entry.remarks {
                 if remarks.chars().count() > utils::MAX_REMARKS_SHOWN_CHARS_BUTTON {
@@ -82,10 +180,11 @@ fn get_user_availability_keyboard(
             };
             // Format date as "MMM-DD"
             let formatted = format!(
-                "{} {}: {}{}",
+                "{} {}: {}{}{}",
                 option_str,
                 entry.avail.format("%b-%d"),
                 entry.ict_type.as_ref(),
+                utils::format_slot_suffix(entry.start_time, entry.end_time),
                 truncated_remarks
             );
             vec![InlineKeyboardButton::callback(
@@ -117,8 +216,10 @@ fn get_user_availability_keyboard(
 fn get_date_availability_keyboard(
     prefix: &String,
     availability_list: &Vec<AvailabilityDetails>,
-    changes: &HashSet<Uuid>,
+    changes: &HashMap<Uuid, PlanStatus>,
     role_type: &RoleType,
+    role_types: Vec<RoleType>,
+    suggestion: &[Uuid],
     start: usize,
     show: usize
 ) -> Result<InlineKeyboardMarkup, ()> {
@@ -134,12 +235,13 @@ fn get_date_availability_keyboard(
     let mut entries: Vec<Vec<InlineKeyboardButton>> = shown_entries
         .iter()
         .map(|entry| {
-            // Check if the availability ID is in the changes vector
-            let is_in_changes = changes.contains(&entry.id);
-            let option_str = if entry.planned ^ is_in_changes {
-                if entry.is_valid { "UNPLAN" } else { "UNPLAN (UNAVAIL)" }
-            } else {
-                "PLAN"
+            // Show the status after the changes
+            let option_str = match (get_changed_status(entry, changes), entry.is_valid) {
+                (PlanStatus::Planned, true) => "UNPLAN",
+                (PlanStatus::Planned, false) => "UNPLAN (UNAVAIL)",
+                (PlanStatus::Standby, true) => "STANDBY",
+                (PlanStatus::Standby, false) => "STANDBY (UNAVAIL)",
+                (PlanStatus::Unplanned, _) => "PLAN"
             };
             let truncated_remarks = if let Some(remarks) = &entry.remarks {
                 if remarks.chars().count() > utils::MAX_REMARKS_SHOWN_CHARS_BUTTON {
@@ -152,10 +254,11 @@ fn get_date_availability_keyboard(
             };
 
             let formatted = format!(
-                "{} {}: {}{}",
+                "{} {}: {}{}{}",
                 option_str,
                 entry.ops_name,
                 entry.ict_type.as_ref(),
+                utils::format_slot_suffix(entry.start_time, entry.end_time),
                 truncated_remarks
             );
             vec![InlineKeyboardButton::callback(
@@ -166,7 +269,7 @@ fn get_date_availability_keyboard(
         .collect();
 
     // Add role change buttons if applicable
-    let change_view_roles: Vec<InlineKeyboardButton> = RoleType::iter()
+    let change_view_roles: Vec<InlineKeyboardButton> = role_types.into_iter()
         .filter_map(|role| {
             if *role_type != role {
                 Some(InlineKeyboardButton::callback(
@@ -188,7 +291,18 @@ fn get_date_availability_keyboard(
         pagination.push(InlineKeyboardButton::callback("NEXT", PlanCallbacks::Next.to_callback_data(prefix)));
     }
 
+    // Add crew requirement buttons
+    let mut requirement_options = Vec::new();
+    if !get_standby_promotions(availability_list, changes).is_empty() {
+        requirement_options.push(InlineKeyboardButton::callback("PROMOTE STANDBY", PlanCallbacks::PromoteStandby.to_callback_data(prefix)));
+    }
+    if !suggestion.is_empty() {
+        requirement_options.push(InlineKeyboardButton::callback("ACCEPT SUGGESTION", PlanCallbacks::Suggest.to_callback_data(prefix)));
+    }
+    requirement_options.push(InlineKeyboardButton::callback("REQUIREMENTS", PlanCallbacks::Requirements.to_callback_data(prefix)));
+
     entries.push(change_view_roles);
+    entries.push(requirement_options);
     entries.push(pagination);
     entries.push(vec![
         InlineKeyboardButton::callback("DONE", PlanCallbacks::Done.to_callback_data(prefix)),
@@ -198,31 +312,21 @@ fn get_date_availability_keyboard(
     Ok(InlineKeyboardMarkup::new(entries))
 }
 
-fn get_planned_change_text(availability: &AvailabilityDetails, changes: &HashSet<Uuid>) -> String {
-    // Check if the availability ID is in the changes vector
-    let is_in_changes = changes.contains(&availability.id);
-
-    // Determine the current and toggled planned states
-    let planned_str = if is_in_changes {
-        // If the ID is in the changes, toggle the planned state for display
-        if availability.planned {
-            " PLANNED ➡️ UNPLANNED"
-        } else {
-            " UNPLANNED ➡️ PLANNED"
-        }
-    } else {
-        // If no changes, display the actual planned state
-        if availability.planned { " PLANNED" } else { "" }
-    };
-
-    planned_str.to_string()
+fn get_planned_change_text(availability: &AvailabilityDetails, changes: &HashMap<Uuid, PlanStatus>) -> String {
+    match (availability.plan_status(), changes.get(&availability.id)) {
+        // If the ID is in the changes, show the change of the plan status
+        (plan_status, Some(changed_status)) => format!(" {} ➡️ {}", plan_status.as_ref(), changed_status.as_ref()),
+        // If no changes, display the actual plan status
+        (PlanStatus::Unplanned, None) => "".to_string(),
+        (plan_status, None) => format!(" {}", plan_status.as_ref())
+    }
 }
 
 // Generates the message text for user availability view
 fn get_user_availability_text(
     user_details: &Usr,
     database_list: &Vec<AvailabilityDetails>,
-    changes: &HashSet<Uuid>,
+    changes: &HashMap<Uuid, PlanStatus>,
     start: usize,
     show: usize
 ) -> String {
@@ -230,7 +334,7 @@ fn get_user_availability_text(
     if database_list.is_empty() {
         message.push_str("No upcoming availability\\.\n");
     } else {
-        let usrtype_str = if database_list[0].usr_type == UsrType::NS { " \\(NS\\)" } else { "" };
+        let usrtype_str = if database_list[0].usr_type.is_ns() { " \\(NS\\)" } else { "" };
         // Pagination logic: slicing the list based on start and show
         let slice_end = std::cmp::min(start + show, database_list.len());
         let total_entries = database_list.len();
@@ -252,11 +356,11 @@ fn get_user_availability_text(
             let ict_type_str = availability.ict_type.as_ref();
 
             // Determine the current and toggled planned states
-            let planned_str = get_planned_change_text(&availability, changes);
+            let planned_str = format!("{}{}", get_planned_change_text(availability, changes), get_ack_text(availability, changes));
             
             let avail_str = if availability.is_valid { "" } else { " *\\(UNAVAIL\\)*" };
             let saf100_str = if availability.saf100 { "\n SAF100 ISSUED" }
-            else if availability.planned && availability.usr_type == UsrType::NS { "\n *PENDING SAF100*" }
+            else if availability.planned && availability.usr_type.is_ns() { "\n *PENDING SAF100*" }
             else { "" };
 
             // Truncate remarks
@@ -271,9 +375,10 @@ fn get_user_availability_text(
             };
 
             message.push_str(&format!(
-                "\\- {} __{}__{}\n{}{}{}\n",
+                "\\- {} __{}__{}{}\n{}{}{}\n",
                 date_str,
                 ict_type_str,
+                utils::escape_special_characters(&utils::format_slot_suffix(availability.start_time, availability.end_time)),
                 remarks_str,
                 avail_str,
                 planned_str,
@@ -294,7 +399,9 @@ fn get_user_availability_text(
 fn get_date_availability_text(
     selected_date: &NaiveDate,
     database_list: &Vec<AvailabilityDetails>,
-    changes: &HashSet<Uuid>,
+    changes: &HashMap<Uuid, PlanStatus>,
+    requirements: &[CrewRequirement],
+    suggestion: &[Uuid],
     start: usize,
     show: usize
 ) -> String {
@@ -322,11 +429,11 @@ fn get_date_availability_text(
             let ict_type_str = availability.ict_type.as_ref();
 
             // Determine the current and toggled planned states
-            let planned_str = get_planned_change_text(&availability, changes);
+            let planned_str = format!("{}{}", get_planned_change_text(availability, changes), get_ack_text(availability, changes));
 
             let avail_str = if availability.is_valid { "" } else { " *\\(UNAVAIL\\)*" };
-            let usrtype_str = if availability.usr_type == UsrType::NS { " \\(NS\\)" } else { "" };
-            let saf100_str = if availability.saf100 { "\n SAF100 ISSUED" } else if availability.planned && availability.usr_type == UsrType::NS { "\n *PENDING SAF100*" } else { "" };
+            let usrtype_str = if availability.usr_type.is_ns() { " \\(NS\\)" } else { "" };
+            let saf100_str = if availability.saf100 { "\n SAF100 ISSUED" } else if availability.planned && availability.usr_type.is_ns() { "\n *PENDING SAF100*" } else { "" };
 
             // Truncate remarks
             let remarks_str = if let Some(remarks) = &availability.remarks {
@@ -340,9 +447,10 @@ fn get_date_availability_text(
             };
 
             message.push_str(&format!(
-                "\\- `{}`{} __{}__{}\n{}{}{}\n\n",
+                "\\- `{}`{} __{}__{}{}\n{}{}{}\n\n",
                 utils::escape_special_characters(&availability.ops_name), usrtype_str,
                 ict_type_str,
+                utils::escape_special_characters(&utils::format_slot_suffix(availability.start_time, availability.end_time)),
                 remarks_str,
                 avail_str,
                 planned_str,
@@ -356,20 +464,133 @@ fn get_date_availability_text(
         message.push_str(&format!("\nPage {} of {}\n", current_page, total_pages));
     }
 
+    if !requirements.is_empty() {
+        message.push_str(&format!(
+            "\nRequired: {}\n",
+            requirements
+                .iter()
+                .map(|requirement| format!(
+                    "{} {} \\({} planned\\)",
+                    requirement.ict_type.as_ref(),
+                    requirement.headcount,
+                    count_planned(database_list, changes, &requirement.ict_type)
+                ))
+                .collect::<Vec<String>>()
+                .join(", ")
+        ));
+    }
+    if !suggestion.is_empty() {
+        message.push_str(&format!(
+            "Suggested: {}\n",
+            database_list
+                .iter()
+                .filter(|entry| suggestion.contains(&entry.id))
+                .map(|entry| format!("`{}`", utils::escape_special_characters(&entry.ops_name)))
+                .collect::<Vec<String>>()
+                .join(", ")
+        ));
+    }
+
     message
 }
 
-async fn display_availability_details(bot: &Bot, chat_id: ChatId, username: &Option<String>, availability: AvailabilityDetails, changes: &HashSet<Uuid> ,prefix: &String, msg_id: Option<MessageId>) -> Option<MessageId> {
-    let plan_button_text = if availability.planned { "UNPLAN" } else { "PLAN" };
-    let options = [(plan_button_text, PlanCallbacks::Toggle { id: availability.id }), ("BACK", PlanCallbacks::Cancel)]
+fn format_plan_conflicts(conflicts: &[PlanConflict]) -> String {
+    conflicts
+        .iter()
+        .map(|conflict| match conflict {
+            PlanConflict::OverCapacity { role_type, avail, planned, capacity } => format!(
+                "\\- {} {} would be planned on {}, over the capacity of {}",
+                planned,
+                utils::escape_special_characters(role_type.as_ref()),
+                utils::escape_special_characters(&avail.format("%Y-%m-%d").to_string()),
+                capacity
+            ),
+            PlanConflict::Movement { ops_name, avail, activities } => format!(
+                "\\- `{}` has movement on {}: {}",
+                utils::escape_special_characters(ops_name),
+                utils::escape_special_characters(&avail.format("%Y-%m-%d").to_string()),
+                utils::escape_special_characters(activities)
+            ),
+            PlanConflict::Saf100LeadTime { ops_name, avail, days_left } => format!(
+                "\\- `{}` \\(NS\\) would be planned on {}, {} days away, too late for SAF100 to be issued",
+                utils::escape_special_characters(ops_name),
+                utils::escape_special_characters(&avail.format("%Y-%m-%d").to_string()),
+                utils::escape_special_characters(&days_left.to_string())
+            ),
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+async fn display_plan_conflicts(bot: &Bot, chat_id: ChatId, username: &Option<String>, conflicts: &[PlanConflict], prefix: &str, msg_id: MessageId) -> Option<MessageId> {
+    let options = [("PLAN ANYWAY", PlanCallbacks::ConfirmYes), ("BACK", PlanCallbacks::ConfirmNo)]
         .map(|(text, data)| InlineKeyboardButton::callback(text, data.to_callback_data(prefix)));
 
-    let usrtype_str = if availability.usr_type == UsrType::NS { " \\(NS\\)" } else { "" };
+    let message_text = format!(
+        "⚠️ The changes have the following conflicts:\n{}\n\nDo you want to plan anyway?",
+        format_plan_conflicts(conflicts)
+    );
+
+    send_or_edit_msg(bot, chat_id, username, Some(msg_id), message_text, Some(InlineKeyboardMarkup::new([options])), Some(ParseMode::MarkdownV2)).await
+}
+
+async fn display_crew_requirements(
+    bot: &Bot,
+    chat_id: ChatId,
+    username: &Option<String>,
+    (selected_date, role_type): (&NaiveDate, &RoleType),
+    requirements: &[CrewRequirement],
+    prefix: &str,
+    msg_id: MessageId
+) -> Option<MessageId> {
+    let mut entries: Vec<Vec<InlineKeyboardButton>> = Ict::iter()
+        .map(|ict_type| vec![
+            InlineKeyboardButton::callback(format!("- {}", ict_type.as_ref()), PlanCallbacks::RemoveRequirement { ict_type: ict_type.clone() }.to_callback_data(prefix)),
+            InlineKeyboardButton::callback(format!("+ {}", ict_type.as_ref()), PlanCallbacks::AddRequirement { ict_type }.to_callback_data(prefix))
+        ])
+        .collect();
+    entries.push(vec![InlineKeyboardButton::callback("BACK", PlanCallbacks::ConfirmNo.to_callback_data(prefix))]);
+
+    let message_text = format!(
+        "Crew required for {} on {}:\n{}",
+        role_type.as_ref(),
+        selected_date.format("%d %b, %Y"),
+        Ict::iter()
+            .map(|ict_type| format!(
+                "- {}: {}",
+                ict_type.as_ref(),
+                requirements.iter().find(|requirement| requirement.ict_type == ict_type).map_or(0, |requirement| requirement.headcount)
+            ))
+            .collect::<Vec<String>>()
+            .join("\n")
+    );
+
+    send_or_edit_msg(bot, chat_id, username, Some(msg_id), message_text, Some(InlineKeyboardMarkup::new(entries)), None).await
+}
+
+async fn display_availability_details(bot: &Bot, chat_id: ChatId, username: &Option<String>, availability: AvailabilityDetails, changes: &HashMap<Uuid, PlanStatus>, prefix: &str, msg_id: Option<MessageId>) -> Option<MessageId> {
+    let changed_status = get_changed_status(&availability, changes);
+    let plan_button_text = if changed_status == PlanStatus::Planned { "UNPLAN" } else { "PLAN" };
+    let standby_button_text = if changed_status == PlanStatus::Standby { "REMOVE STANDBY" } else { "STANDBY" };
+    let options = [
+        (plan_button_text, PlanCallbacks::Toggle { id: availability.id }),
+        (standby_button_text, PlanCallbacks::ToggleStandby { id: availability.id }),
+        ("BACK", PlanCallbacks::Cancel)
+    ]
+        .map(|(text, data)| InlineKeyboardButton::callback(text, data.to_callback_data(prefix)));
+
+    let usrtype_str = if availability.usr_type.is_ns() { " \\(NS\\)" } else { "" };
     let avail_str = if availability.is_valid { "*available*" } else { "*unavailable*" };
-    let date_str = utils::escape_special_characters(&availability.avail.format("%d %b, %Y").to_string());
+    let date_str = utils::escape_special_characters(&format!(
+        "{}{}",
+        availability.avail.format("%d %b, %Y"),
+        utils::format_slot_suffix(availability.start_time, availability.end_time)
+    ));
     let conflict_str = if availability.planned {
         if !availability.is_valid {
             "\\, but they are *planned* ⚠️\\!"
+        } else if is_declined(&availability, changes) {
+            "\\, but they *can't make it* ⚠️\\!"
         } else {
             "\\."
         }
@@ -382,19 +603,17 @@ async fn display_availability_details(bot: &Bot, chat_id: ChatId, username: &Opt
     };
 
     let saf100_str = if availability.saf100 { " *SAF100 ISSUED*" }
-    else if availability.planned && availability.usr_type == UsrType::NS { " *PENDING SAF100*" }
+    else if availability.planned && availability.usr_type.is_ns() { " *PENDING SAF100*" }
     else { "" };
     
-    let is_in_changes = changes.contains(&availability.id);
-    let plan_str = if is_in_changes { get_planned_change_text(&availability, changes) } else { if availability.planned { "PLANNED" } else { "UNPLANNED" }.into() };
-    let change_plan_str = if !(is_in_changes ^ availability.planned) { "PLANNED" } else { "UNPLANNED" };
-    let change_back_str = if is_in_changes { "back " } else { "" };
+    let plan_str = if changes.contains_key(&availability.id) { get_planned_change_text(&availability, changes) } else { availability.plan_status().as_ref().into() };
+    let ack_str = get_ack_text(&availability, changes);
     
     let message_text = format!(
-        "`{}`{} has indicated they are {} on {}{}\n{}\nPlan Status: *{}* {}\n\nDo you want to change planned status {}to *{}*?",
+        "`{}`{} has indicated they are {} on {}{}\n{}\nPlan Status: *{}*{} {}\n\nDo you want to plan them, or keep them on standby in case planned crew withdraw?",
         utils::escape_special_characters(&availability.ops_name), usrtype_str,
         avail_str, date_str, conflict_str,
-        remarks_str, plan_str, saf100_str, change_back_str, change_plan_str
+        remarks_str, plan_str, ack_str, saf100_str
     );
     
     send_or_edit_msg(bot, chat_id, username, msg_id, message_text, Some(InlineKeyboardMarkup::new([options])), Some(ParseMode::MarkdownV2)).await
@@ -414,7 +633,7 @@ async fn display_user_availability(
     username: &Option<String>,
     user_details: &Usr,
     database_list: &Vec<AvailabilityDetails>,
-    changes: &HashSet<Uuid>,
+    changes: &HashMap<Uuid, PlanStatus>,
     prefix: &String,
     start: usize,
     show: usize,
@@ -446,15 +665,17 @@ async fn display_date_availability(
     username: &Option<String>,
     selected_date: &NaiveDate,
     database_list: &Vec<AvailabilityDetails>,
-    changes: &HashSet<Uuid>,
+    changes: &HashMap<Uuid, PlanStatus>,
     role_type: &RoleType,
+    role_types: Vec<RoleType>,
+    (requirements, suggestion): (&[CrewRequirement], &[Uuid]),
     prefix: &String,
     start: usize,
     show: usize,
     msg_id: Option<MessageId>, // Optionally provide MessageId to edit
 ) -> Result<Option<MessageId>, ()> {
     // Generate the inline keyboard
-    let markup = match get_date_availability_keyboard(prefix, database_list, changes, role_type, start, show) {
+    let markup = match get_date_availability_keyboard(prefix, database_list, changes, role_type, role_types, suggestion, start, show) {
         Ok(kb) => kb,
         Err(_) => {
             send_msg(
@@ -466,19 +687,40 @@ async fn display_date_availability(
     };
 
     // Generate the message text
-    let message_text = get_date_availability_text(selected_date, database_list, changes, start, show);
+    let message_text = get_date_availability_text(selected_date, database_list, changes, requirements, suggestion, start, show);
     
     // Send or edit message
     Ok(send_or_edit_msg(bot, chat_id, username, msg_id, message_text, Some(markup), Some(ParseMode::MarkdownV2)).await)
 }
 
+// Fetches the crew requirements of the date and suggests who to plan to meet them
+async fn get_requirements_and_suggestion(
+    pool: &PgPool,
+    unit_id: Uuid,
+    selected_date: &NaiveDate,
+    role_type: &RoleType,
+    availability_list: &[AvailabilityDetails],
+    changes: &HashMap<Uuid, PlanStatus>
+) -> Result<(Vec<CrewRequirement>, Vec<Uuid>), sqlx::Error> {
+    let requirements = controllers::requirements::get_requirements(pool, unit_id, selected_date, role_type).await?;
+    if requirements.is_empty() {
+        return Ok((requirements, Vec::new()));
+    }
+
+    let availability_ids: Vec<Uuid> = availability_list.iter().map(|entry| entry.id).collect();
+    let history = controllers::scheduling::get_plan_history(pool, &availability_ids, utils::PLAN_FAIRNESS_WINDOW_DAYS).await?;
+    let suggestion = suggest_selection(&requirements, availability_list, changes, &history);
+
+    Ok((requirements, suggestion))
+}
+
 async fn handle_show_avail_by_user(
     bot: &Bot,
     dialogue: &MyDialogue,
     username: &Option<String>,
     user_details: Usr,
     availability_list: Vec<AvailabilityDetails>,
-    changes: HashSet<Uuid>,
+    changes: HashMap<Uuid, PlanStatus>,
     role_type: RoleType,
     prefix: String,
     start: usize,
@@ -514,14 +756,16 @@ async fn handle_show_avail_by_date(
     username: &Option<String>,
     selected_date: NaiveDate,
     availability_list: Vec<AvailabilityDetails>,
-    changes: HashSet<Uuid>,
+    changes: HashMap<Uuid, PlanStatus>,
     role_type: RoleType,
+    role_types: Vec<RoleType>,
+    (requirements, suggestion): (Vec<CrewRequirement>, Vec<Uuid>),
     prefix: String,
     start: usize,
     show: usize,
 ) -> HandlerResult {
     // Viewing availability by date
-    match display_date_availability(bot, dialogue.chat_id(), username, &selected_date, &availability_list, &changes, &role_type, &prefix, start, show, None)
+    match display_date_availability(bot, dialogue.chat_id(), username, &selected_date, &availability_list, &changes, &role_type, role_types, (&requirements, &suggestion), &prefix, start, show, None)
         .await {
         Ok(msg_id) => {
             match msg_id {
@@ -550,12 +794,13 @@ async fn handle_re_show_options(
     username: &Option<String>,
     user_details: Option<Usr>,
     selected_date: Option<NaiveDate>,
-    changes: HashSet<Uuid>,
+    changes: HashMap<Uuid, PlanStatus>,
     role_type: RoleType,
     prefix: String,
     start: usize,
     show: usize,
     msg_id: MessageId, // Existing MessageId to edit
+    unit_id: Uuid,
     pool: &PgPool
 ) -> HandlerResult {
     // Fetch the live availability details from the database
@@ -565,7 +810,7 @@ async fn handle_re_show_options(
         }
         None => match selected_date {
             Some(ref date) => {
-                controllers::scheduling::get_users_available_by_role_on_date(pool, date, &role_type).await
+                controllers::scheduling::get_users_available_by_role_on_date(pool, unit_id, date, &role_type).await
             }
             None => {
                 dialogue.update(State::ErrorState).await?;
@@ -609,12 +854,22 @@ async fn handle_re_show_options(
                 None => {
                     // Viewing availability by date
                     if let Some(selected_date) = selected_date {
+                        let (role_types, (requirements, suggestion)) = match (
+                            controllers::crew_types::get_role_types(pool).await,
+                            get_requirements_and_suggestion(pool, unit_id, &selected_date, &role_type, &database_list, &changes).await
+                        ) {
+                            (Ok(role_types), Ok(guide)) => (role_types, guide),
+                            _ => {
+                                handle_error(bot, dialogue, dialogue.chat_id(), username).await;
+                                return Ok(());
+                            }
+                        };
                         match display_date_availability(
                             bot, dialogue.chat_id(), username,
                             &selected_date,
                             &database_list,
                             &changes,
-                            &role_type, &prefix, start, show,
+                            &role_type, role_types, (&requirements, &suggestion), &prefix, start, show,
                             Some(msg_id),
                         ).await {
                             Ok(msg_id) => {
@@ -668,10 +923,10 @@ async fn handle_ops_name_or_date_input(bot: &Bot, dialogue: &MyDialogue, pool: &
     let prefix = utils::generate_prefix(utils::CALLBACK_PREFIX_LEN);
     // Try to interpret the argument as an OPS NAME first
     let cleaned_ops_name = ops_name_or_date.trim().to_uppercase();
-    match controllers::user::user_exists_ops_name(&pool, cleaned_ops_name.as_ref()).await{
+    match controllers::user::user_exists_ops_name(pool, query_user_details.unit_id, cleaned_ops_name.as_ref()).await{
         Ok(exists) => {
             if exists {
-                match controllers::user::get_user_by_ops_name(&pool, cleaned_ops_name.as_ref()).await {
+                match controllers::user::get_user_by_ops_name(pool, query_user_details.unit_id, cleaned_ops_name.as_ref()).await {
                     Ok(user_details) => {
                         // show the dates for which the user is available
                         // Get the user's tele_id
@@ -682,7 +937,7 @@ async fn handle_ops_name_or_date_input(bot: &Bot, dialogue: &MyDialogue, pool: &
                                 // Display the user's availability
                                 handle_show_avail_by_user(
                                     &bot, &dialogue, &user.username,
-                                    user_details, availability_list, HashSet::new(),
+                                    user_details, availability_list, HashMap::new(),
                                     query_user_details.role_type,
                                     prefix, 0, utils::MAX_SHOW_ENTRIES,
                                 ).await?;
@@ -710,17 +965,26 @@ async fn handle_ops_name_or_date_input(bot: &Bot, dialogue: &MyDialogue, pool: &
                             return Ok(());
                         }
                         // Show the available users on that day
-                        match controllers::scheduling::get_users_available_by_role_on_date(&pool, &selected_date, &query_user_details.role_type).await {
-                            Ok(availability_list) => {
-                                // Display the availability for the selected date
-                                handle_show_avail_by_date(
-                                    &bot, &dialogue, &user.username,
-                                    selected_date, availability_list, HashSet::new(),
-                                    query_user_details.role_type,
-                                    prefix, 0, utils::MAX_SHOW_ENTRIES
-                                ).await?;
+                        match (
+                            controllers::scheduling::get_users_available_by_role_on_date(pool, query_user_details.unit_id, &selected_date, &query_user_details.role_type).await,
+                            controllers::crew_types::get_role_types(pool).await
+                        ) {
+                            (Ok(availability_list), Ok(role_types)) => {
+                                let changes = HashMap::new();
+                                match get_requirements_and_suggestion(pool, query_user_details.unit_id, &selected_date, &query_user_details.role_type, &availability_list, &changes).await {
+                                    Ok(guide) => {
+                                        // Display the availability for the selected date
+                                        handle_show_avail_by_date(
+                                            bot, dialogue, &user.username,
+                                            selected_date, availability_list, changes,
+                                            query_user_details.role_type, role_types, guide,
+                                            prefix, 0, utils::MAX_SHOW_ENTRIES
+                                        ).await?;
+                                    }
+                                    Err(_) => handle_error(bot, dialogue, dialogue.chat_id(), &user.username).await
+                                }
                             }
-                            Err(_) => handle_error(&bot, &dialogue, dialogue.chat_id(), &user.username).await
+                            _ => handle_error(bot, dialogue, dialogue.chat_id(), &user.username).await
                         }
                     }
                     Err(_) => {
@@ -737,6 +1001,75 @@ async fn handle_ops_name_or_date_input(bot: &Bot, dialogue: &MyDialogue, pool: &
     Ok(())
 }
 
+// Commits the planning changes, returning whether they were saved
+async fn commit_plan_changes(
+    bot: &Bot,
+    dialogue: &MyDialogue,
+    user: &User,
+    changes: HashMap<Uuid, PlanStatus>,
+    msg_id: MessageId,
+    unit_id: Uuid,
+    pool: &PgPool
+) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+    log_try_remove_markup(bot, dialogue.chat_id(), msg_id).await;
+    send_msg(
+        bot.send_message(dialogue.chat_id(), "Done."),
+        &user.username,
+    ).await;
+
+    match controllers::scheduling::set_plan_status_multiple(
+        pool,
+        user.id.0,
+        changes,
+    ).await {
+        Ok(availability_details) => {
+            let mut summary = String::new();
+
+            for details in &availability_details {
+                let user_type_suffix = if details.usr_type.is_ns() { " \\(NS\\)" } else { "" };
+                let status_message = match details.plan_status() {
+                    PlanStatus::Planned => "has been planned",
+                    PlanStatus::Standby => "is on standby",
+                    PlanStatus::Unplanned => "is no longer planned"
+                };
+                let formatted_avail = utils::escape_special_characters(&details.avail.format("%Y-%m-%d").to_string());
+
+                // Append to summary
+                summary.push_str(&format!(
+                    "`{}`{} {} for {} on {}{}\n",
+                    details.ops_name,
+                    user_type_suffix,
+                    status_message,
+                    details.ict_type.as_ref(),
+                    formatted_avail,
+                    utils::escape_special_characters(&utils::format_slot_suffix(details.start_time, details.end_time)),
+                ));
+            }
+
+            // notify planned
+            notifier::emit::plan_notifications(
+                bot,
+                format!(
+                    "{} made the following changes:\n{}",
+                    utils::username_link_tag(user),
+                    summary
+                ).as_str(),
+                pool,
+                Some(unit_id),
+                user.id.0 as i64
+            ).await;
+
+            send_or_edit_msg(bot, dialogue.chat_id(), &user.username, Some(msg_id), summary, None, Some(ParseMode::MarkdownV2)).await;
+            dialogue.update(State::Start).await?;
+            Ok(true)
+        }
+        Err(_) => {
+            handle_error(bot, dialogue, dialogue.chat_id(), &user.username).await;
+            Ok(false)
+        }
+    }
+}
+
 pub(super) async fn plan(
     bot: Bot,
     dialogue: MyDialogue,
@@ -806,16 +1139,7 @@ pub(super) async fn plan_view(
         role_type,
         prefix,
         start
-    ): (
-        MessageId,
-        Option<Usr>,
-        Option<NaiveDate>,
-        Vec<AvailabilityDetails>,
-        HashSet<Uuid>,
-        RoleType,
-        String,
-        usize
-    ),
+    ): PlanViewState,
     pool: PgPool
 ) -> HandlerResult {
     log_endpoint_hit!(dialogue.chat_id(), "plan_view", "Callback", q,
@@ -846,6 +1170,14 @@ pub(super) async fn plan_view(
         Err(_) => { return Ok(()); }
     };
 
+    let unit_id = match controllers::user::get_unit_id_by_tele_id(&pool, q.from.id.0).await {
+        Ok(unit_id) => unit_id,
+        Err(_) => {
+            handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await;
+            return Ok(());
+        }
+    };
+
     // Handle based on the variant
     match callback {
         PlanCallbacks::Prev => {
@@ -853,7 +1185,7 @@ pub(super) async fn plan_view(
                 &bot, &dialogue, &q.from.username,
                 user_details, selected_date, changes, role_type,
                 prefix, max(0, start as i64 - utils::MAX_SHOW_ENTRIES as i64) as usize, utils::MAX_SHOW_ENTRIES,
-                msg_id, &pool
+                msg_id, unit_id, &pool
             ).await?;
         }
         PlanCallbacks::Next => {
@@ -862,7 +1194,7 @@ pub(super) async fn plan_view(
                 &bot, &dialogue, &q.from.username,
                 user_details, selected_date, changes, role_type,
                 prefix, if start+utils::MAX_SHOW_ENTRIES < entries_len { start+utils::MAX_SHOW_ENTRIES } else { start }, utils::MAX_SHOW_ENTRIES,
-                msg_id, &pool
+                msg_id, unit_id, &pool
             ).await?;
         }
         PlanCallbacks::Cancel => {
@@ -870,54 +1202,125 @@ pub(super) async fn plan_view(
             dialogue.update(State::Start).await?;
         }
         PlanCallbacks::Done => {
-            // commit changes
-            log_try_remove_markup(&bot, dialogue.chat_id(), msg_id).await;
-            send_msg(
-                bot.send_message(dialogue.chat_id(), "Done."),
-                &q.from.username,
-            ).await;
-
-            match controllers::scheduling::toggle_planned_status_multiple(
-                &pool,
-                changes,
-            ).await {
-                Ok(availability_details) => {
-                    let mut summary = String::new();
-
-                    for details in &availability_details {
-                        let user_type_suffix = if details.usr_type == UsrType::NS { " \\(NS\\)" } else { "" };
-                        let status_message = if details.planned {
-                            "has been planned"
-                        } else {
-                            "is no longer planned"
-                        };
-                        let formatted_avail = utils::escape_special_characters(&details.avail.format("%Y-%m-%d").to_string());
-
-                        // Append to summary
-                        summary.push_str(&format!(
-                            "`{}`{} {} for {} on {}\n",
-                            details.ops_name,
-                            user_type_suffix,
-                            status_message,
-                            details.ict_type.as_ref(),
-                            formatted_avail,
-                        ));
+            // warn about conflicts before committing
+            match controllers::scheduling::get_plan_conflicts(&pool, &changes, *PLAN_CAPACITY, *SAF100_LEAD_DAYS).await {
+                Ok(conflicts) if conflicts.is_empty() => {
+                    commit_plan_changes(&bot, &dialogue, &q.from, changes, msg_id, unit_id, &pool).await?;
+                }
+                Ok(conflicts) => {
+                    match display_plan_conflicts(&bot, dialogue.chat_id(), &q.from.username, &conflicts, &prefix, msg_id).await {
+                        None => dialogue.update(State::ErrorState).await?,
+                        Some(msg_id) => {
+                            dialogue.update(State::PlanView { msg_id, user_details, selected_date, availability_list, changes, role_type, prefix, start }).await?
+                        }
                     }
-
-                    // notify planned
-                    notifier::emit::plan_notifications(
-                        &bot,
-                        format!(
-                            "{} made the following changes:\n{}",
-                            utils::username_link_tag(&q.from),
-                            summary
-                        ).as_str(),
-                        &pool,
-                        q.from.id.0 as i64
+                }
+                Err(_) => handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await
+            }
+        }
+        PlanCallbacks::ConfirmYes => {
+            // plan despite the conflicts, checking again in case anything changed in the meantime
+            match controllers::scheduling::get_plan_conflicts(&pool, &changes, *PLAN_CAPACITY, *SAF100_LEAD_DAYS).await {
+                Ok(conflicts) => {
+                    let committed = commit_plan_changes(&bot, &dialogue, &q.from, changes, msg_id, unit_id, &pool).await?;
+
+                    // notify conflicts the planner went ahead with
+                    if committed && !conflicts.is_empty() {
+                        notifier::emit::conflict_notifications(
+                            &bot,
+                            format!(
+                                "{} planned despite the following conflicts ⚠️\n{}",
+                                utils::username_link_tag(&q.from),
+                                format_plan_conflicts(&conflicts)
+                            ).as_str(),
+                            &pool,
+                            Some(unit_id),
+                        ).await;
+                    }
+                }
+                Err(_) => handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await
+            }
+        }
+        PlanCallbacks::ConfirmNo => {
+            handle_re_show_options(
+                &bot, &dialogue, &q.from.username,
+                user_details, selected_date, changes, role_type,
+                prefix, start, utils::MAX_SHOW_ENTRIES,
+                msg_id, unit_id, &pool
+            ).await?;
+        }
+        PlanCallbacks::Suggest => {
+            let date = match selected_date {
+                Some(date) => date,
+                None => {
+                    send_msg(
+                        bot.send_message(dialogue.chat_id(), "Invalid option. Type /cancel to abort."),
+                        &q.from.username,
                     ).await;
-
-                    send_or_edit_msg(&bot, dialogue.chat_id(), &q.from.username, Some(msg_id), summary, None, Some(ParseMode::MarkdownV2)).await;
-                    dialogue.update(State::Start).await?;
+                    return Ok(());
+                }
+            };
+            // suggest again from the live availability in case anything changed in the meantime
+            let suggestion = match controllers::scheduling::get_users_available_by_role_on_date(&pool, unit_id, &date, &role_type).await {
+                Ok(live_list) => get_requirements_and_suggestion(&pool, unit_id, &date, &role_type, &live_list, &changes).await,
+                Err(e) => Err(e)
+            };
+            match suggestion {
+                Ok((_, suggestion)) => {
+                    let mut changes = changes;
+                    changes.extend(suggestion.into_iter().map(|id| (id, PlanStatus::Planned)));
+                    handle_re_show_options(
+                        &bot, &dialogue, &q.from.username,
+                        user_details, selected_date, changes, role_type,
+                        prefix, start, utils::MAX_SHOW_ENTRIES,
+                        msg_id, unit_id, &pool
+                    ).await?;
+                }
+                Err(_) => handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await
+            }
+        }
+        PlanCallbacks::PromoteStandby => {
+            // standby crew take the place of the planned crew who are no longer available
+            let mut changes = changes;
+            for (withdrawn, standby) in get_standby_promotions(&availability_list, &changes) {
+                set_changed_status(withdrawn, &mut changes, PlanStatus::Unplanned);
+                set_changed_status(standby, &mut changes, PlanStatus::Planned);
+            }
+            handle_re_show_options(
+                &bot, &dialogue, &q.from.username,
+                user_details, selected_date, changes, role_type,
+                prefix, start, utils::MAX_SHOW_ENTRIES,
+                msg_id, unit_id, &pool
+            ).await?;
+        }
+        PlanCallbacks::Requirements | PlanCallbacks::AddRequirement { .. } | PlanCallbacks::RemoveRequirement { .. } => {
+            let date = match selected_date {
+                Some(date) => date,
+                None => {
+                    send_msg(
+                        bot.send_message(dialogue.chat_id(), "Invalid option. Type /cancel to abort."),
+                        &q.from.username,
+                    ).await;
+                    return Ok(());
+                }
+            };
+            let changed = match callback {
+                PlanCallbacks::AddRequirement { ict_type } => controllers::requirements::change_requirement(&pool, unit_id, &date, &role_type, &ict_type, 1).await,
+                PlanCallbacks::RemoveRequirement { ict_type } => controllers::requirements::change_requirement(&pool, unit_id, &date, &role_type, &ict_type, -1).await,
+                _ => Ok(())
+            };
+            let requirements = match changed {
+                Ok(_) => controllers::requirements::get_requirements(&pool, unit_id, &date, &role_type).await,
+                Err(e) => Err(e)
+            };
+            match requirements {
+                Ok(requirements) => {
+                    match display_crew_requirements(&bot, dialogue.chat_id(), &q.from.username, (&date, &role_type), &requirements, &prefix, msg_id).await {
+                        None => dialogue.update(State::ErrorState).await?,
+                        Some(msg_id) => {
+                            dialogue.update(State::PlanView { msg_id, user_details, selected_date, availability_list, changes, role_type, prefix, start }).await?
+                        }
+                    }
                 }
                 Err(_) => handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await
             }
@@ -945,8 +1348,8 @@ pub(super) async fn plan_view(
                         &bot, &dialogue, &q.from.username,
                         None,
                         Some(selected_date),
-                        HashSet::new(), role_type_enum,
-                        prefix, 0, utils::MAX_SHOW_ENTRIES, msg_id, &pool
+                        HashMap::new(), role_type_enum,
+                        prefix, 0, utils::MAX_SHOW_ENTRIES, msg_id, unit_id, &pool
                     ).await?;
                 }
                 None => {
@@ -957,7 +1360,7 @@ pub(super) async fn plan_view(
                     handle_re_show_options(
                         &bot, &dialogue, &q.from.username,
                         user_details, selected_date, changes, role_type,
-                        prefix, start, utils::MAX_SHOW_ENTRIES, msg_id, &pool
+                        prefix, start, utils::MAX_SHOW_ENTRIES, msg_id, unit_id, &pool
                     ).await?;
                 }
             }
@@ -989,7 +1392,7 @@ pub(super) async fn plan_view_availability(
         MessageId,
         Option<Usr>,
         Option<NaiveDate>,
-        HashSet<Uuid>,
+        HashMap<Uuid, PlanStatus>,
         RoleType,
         String,
         usize
@@ -1022,29 +1425,40 @@ pub(super) async fn plan_view_availability(
         Ok(callback) => callback,
         Err(_) => { return Ok(()); }
     };
-    
+
+    let unit_id = match controllers::user::get_unit_id_by_tele_id(&pool, q.from.id.0).await {
+        Ok(unit_id) => unit_id,
+        Err(_) => {
+            handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await;
+            return Ok(());
+        }
+    };
+
     match callback {
-        PlanCallbacks::Toggle { id: parsed_avail_uuid} => {
-            // plan or unplan users
-            // if currently planned -> unplan user
-            // if currently unplanned -> plan user
-            if changes.contains(&parsed_avail_uuid) {
-                changes.remove(&parsed_avail_uuid);
-            } else {
-                changes.insert(parsed_avail_uuid);
+        PlanCallbacks::Toggle { id: parsed_avail_uuid } | PlanCallbacks::ToggleStandby { id: parsed_avail_uuid } => {
+            // plan, unplan or put users on standby
+            // if currently planned (or on standby) -> unplan user
+            // otherwise -> plan user (or put them on standby)
+            let toggled_status = if matches!(callback, PlanCallbacks::Toggle { .. }) { PlanStatus::Planned } else { PlanStatus::Standby };
+            match controllers::scheduling::get_availability_details_by_uuid(&pool, parsed_avail_uuid).await {
+                Ok(availability_entry) => {
+                    let plan_status = if get_changed_status(&availability_entry, &changes) == toggled_status { PlanStatus::Unplanned } else { toggled_status };
+                    set_changed_status(&availability_entry, &mut changes, plan_status);
+
+                    handle_re_show_options(
+                        &bot, &dialogue, &q.from.username,
+                        user_details, selected_date, changes, role_type,
+                        prefix, start, utils::MAX_SHOW_ENTRIES, msg_id, unit_id, &pool
+                    ).await?;
+                }
+                Err(_) => handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await
             }
-            
-            handle_re_show_options(
-                &bot, &dialogue, &q.from.username,
-                user_details, selected_date, changes, role_type,
-                prefix, start, utils::MAX_SHOW_ENTRIES, msg_id, &pool
-            ).await?;
         }
         PlanCallbacks::Cancel => {
             handle_re_show_options(
                 &bot, &dialogue, &q.from.username,
                 user_details, selected_date, changes, role_type,
-                prefix, start, utils::MAX_SHOW_ENTRIES, msg_id, &pool
+                prefix, start, utils::MAX_SHOW_ENTRIES, msg_id, unit_id, &pool
             ).await?;
         }
         _ => {
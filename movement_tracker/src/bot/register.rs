@@ -1,3 +1,4 @@
+use sqlx::types::Uuid;
 use sqlx::PgPool;
 
 use teloxide::prelude::*;
@@ -5,18 +6,18 @@ use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, MessageId, Par
 
 use super::{handle_error, log_try_delete_msg, match_callback_data, retrieve_callback_data, send_msg, send_or_edit_msg, validate_name, validate_ops_name, HandlerResult, MyDialogue};
 use crate::bot::state::State;
-use crate::types::{RoleType, UsrType};
+use crate::types::{RoleType, Unit, UsrType};
 use crate::{controllers, log_endpoint_hit, notifier, utils};
 
 use serde::{Deserialize, Serialize};
-use strum::IntoEnumIterator;
 use strum_macros::EnumProperty;
 use callback_data::{CallbackData, CallbackDataHandler};
 
 // Represents callback actions with optional associated data.
 #[derive(Debug, Clone, Serialize, Deserialize, EnumProperty, CallbackData)]
 enum RegisterCallbackData {
-    // Selection actions for role and user type
+    // Selection actions for unit, role and user type
+    SelectUnit { unit_id: Uuid },
     SelectRoleType { role_type: RoleType },
     SelectUserType { user_type: UsrType },
     
@@ -25,8 +26,21 @@ enum RegisterCallbackData {
     ConfirmNo,
 }
 
-async fn display_role_types(bot: &Bot, chat_id: ChatId, username: &Option<String>, prefix: &String) -> Option<MessageId> {
-    let roles = RoleType::iter()
+async fn display_units(bot: &Bot, chat_id: ChatId, username: &Option<String>, units: &[Unit], prefix: &str) -> Option<MessageId> {
+    let units: Vec<Vec<InlineKeyboardButton>> = units
+        .iter()
+        .map(|unit| vec![InlineKeyboardButton::callback(unit.name.clone(), RegisterCallbackData::SelectUnit { unit_id: unit.id }.to_callback_data(prefix))])
+        .collect();
+
+    send_msg(
+        bot.send_message(chat_id, "Please select your unit:")
+            .reply_markup(InlineKeyboardMarkup::new(units)),
+        username
+    ).await
+}
+
+async fn display_role_types(bot: &Bot, chat_id: ChatId, username: &Option<String>, role_types: Vec<RoleType>, prefix: &str) -> Option<MessageId> {
+    let roles = role_types.into_iter()
         .map(|role_type| InlineKeyboardButton::callback(role_type.clone().as_ref(), RegisterCallbackData::SelectRoleType { role_type }.to_callback_data(prefix)));
 
     send_msg(
@@ -36,8 +50,8 @@ async fn display_role_types(bot: &Bot, chat_id: ChatId, username: &Option<String
     ).await
 }
 
-async fn display_user_types(bot: &Bot, chat_id: ChatId, username: &Option<String>, prefix: &String) -> Option<MessageId> {
-    let usrtypes = UsrType::iter()
+async fn display_user_types(bot: &Bot, chat_id: ChatId, username: &Option<String>, usr_types: Vec<UsrType>, prefix: &str) -> Option<MessageId> {
+    let usrtypes = usr_types.into_iter()
         .map(|user_type| InlineKeyboardButton::callback(user_type.clone().as_ref(), RegisterCallbackData::SelectUserType { user_type }.to_callback_data(prefix)));
 
     send_msg(
@@ -116,12 +130,38 @@ pub(super) async fn register(bot: Bot, dialogue: MyDialogue, msg: Message, pool:
         }
         (Ok(false), Ok(false)) => {
             // User is neither registered nor has a pending application, proceed with registration
+            let units = match controllers::units::get_units(&pool).await {
+                Ok(units) => units,
+                Err(_) => {
+                    handle_error(&bot, &dialogue, dialogue.chat_id(), &user.username).await;
+                    return Ok(());
+                }
+            };
+
             let prefix = utils::generate_prefix(utils::CALLBACK_PREFIX_LEN);
-            match display_role_types(&bot, dialogue.chat_id(), &user.username, &prefix).await {
-                None => dialogue.update(State::ErrorState).await?,
-                Some(msg_id) => {
-                    log::debug!("Transitioning to RegisterRole");
-                    dialogue.update(State::RegisterRole { msg_id, prefix }).await?;
+            // Skip asking for the unit when there is only one to choose from
+            if let [unit] = units.as_slice() {
+                let role_types = match controllers::crew_types::get_role_types(&pool).await {
+                    Ok(role_types) => role_types,
+                    Err(_) => {
+                        handle_error(&bot, &dialogue, dialogue.chat_id(), &user.username).await;
+                        return Ok(());
+                    }
+                };
+                match display_role_types(&bot, dialogue.chat_id(), &user.username, role_types, &prefix).await {
+                    None => dialogue.update(State::ErrorState).await?,
+                    Some(msg_id) => {
+                        log::debug!("Transitioning to RegisterRole with Unit: {}", unit.id);
+                        dialogue.update(State::RegisterRole { msg_id, prefix, unit_id: unit.id }).await?;
+                    }
+                }
+            } else {
+                match display_units(&bot, dialogue.chat_id(), &user.username, &units, &prefix).await {
+                    None => dialogue.update(State::ErrorState).await?,
+                    Some(msg_id) => {
+                        log::debug!("Transitioning to RegisterUnit");
+                        dialogue.update(State::RegisterUnit { msg_id, prefix }).await?;
+                    }
                 }
             }
         }
@@ -135,13 +175,14 @@ pub(super) async fn register(bot: Bot, dialogue: MyDialogue, msg: Message, pool:
     Ok(())
 }
 
-pub(super) async fn register_role(
+pub(super) async fn register_unit(
     bot: Bot,
     dialogue: MyDialogue,
     (msg_id, prefix): (MessageId, String),
     q: CallbackQuery,
+    pool: PgPool
 ) -> HandlerResult {
-    log_endpoint_hit!(dialogue.chat_id(), "register_role", "Callback", q,
+    log_endpoint_hit!(dialogue.chat_id(), "register_unit", "Callback", q,
         "MessageId" => msg_id,
         "Prefix" => prefix
     );
@@ -157,6 +198,81 @@ pub(super) async fn register_role(
         log::error!("Failed to answer callback query: {}", e);
     }
 
+    // Deserialize the callback data into the enum
+    let callback = match match_callback_data(&bot, dialogue.chat_id(), &q.from.username, &data, &prefix).await {
+        Ok(callback) => callback,
+        Err(_) => { return Ok(()); }
+    };
+
+    match callback {
+        RegisterCallbackData::SelectUnit { unit_id } => {
+            let unit = match controllers::units::get_unit_by_uuid(&pool, unit_id).await {
+                Ok(unit) => unit,
+                Err(_) => {
+                    handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await;
+                    return Ok(());
+                }
+            };
+
+            log_try_delete_msg(&bot, dialogue.chat_id(), msg_id).await;
+            log::debug!("Selected unit: {:?}", unit.name);
+            send_msg(
+                bot.send_message(dialogue.chat_id(), format!("Selected unit: `{}`", utils::escape_special_characters(&unit.name)))
+                    .parse_mode(ParseMode::MarkdownV2),
+                &q.from.username,
+            ).await;
+
+            let role_types = match controllers::crew_types::get_role_types(&pool).await {
+                Ok(role_types) => role_types,
+                Err(_) => {
+                    handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await;
+                    return Ok(());
+                }
+            };
+            match display_role_types(&bot, dialogue.chat_id(), &q.from.username, role_types, &prefix).await {
+                None => {}
+                Some(new_msg_id) => {
+                    log::debug!("Transitioning to RegisterRole with Unit: {}", unit_id);
+                    dialogue.update(State::RegisterRole { msg_id: new_msg_id, prefix, unit_id }).await?;
+                }
+            };
+        }
+        _ => {
+            log::error!("Invalid unit received in chat ({})", dialogue.chat_id().0);
+            send_msg(
+                bot.send_message(dialogue.chat_id(), "Please select an option or type /cancel to abort"),
+                &q.from.username,
+            ).await;
+        }
+    }
+
+    Ok(())
+}
+
+pub(super) async fn register_role(
+    bot: Bot,
+    dialogue: MyDialogue,
+    (msg_id, prefix, unit_id): (MessageId, String, Uuid),
+    q: CallbackQuery,
+    pool: PgPool
+) -> HandlerResult {
+    log_endpoint_hit!(dialogue.chat_id(), "register_role", "Callback", q,
+        "MessageId" => msg_id,
+        "Prefix" => prefix,
+        "Unit" => unit_id
+    );
+
+    // Extract the callback data
+    let data = match retrieve_callback_data(&bot, dialogue.chat_id(), &q).await {
+        Ok(data) => data,
+        Err(_) => { return Ok(()); }
+    };
+
+    // Acknowledge the callback to remove the loading state
+    if let Err(e) = bot.answer_callback_query(q.id).await {
+        log::error!("Failed to answer callback query: {}", e);
+    }
+
     // Deserialize the callback data into the enum
     let callback = match match_callback_data(&bot, dialogue.chat_id(), &q.from.username, &data, &prefix).await {
         Ok(callback) => callback,
@@ -173,11 +289,18 @@ pub(super) async fn register_role(
                 &q.from.username,
             ).await;
 
-            match display_user_types(&bot, dialogue.chat_id(), &q.from.username, &prefix).await {
+            let usr_types = match controllers::crew_types::get_usr_types(&pool).await {
+                Ok(usr_types) => usr_types,
+                Err(_) => {
+                    handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await;
+                    return Ok(());
+                }
+            };
+            match display_user_types(&bot, dialogue.chat_id(), &q.from.username, usr_types, &prefix).await {
                 None => {}
                 Some(new_msg_id) => {
                     log::debug!("Transitioning to RegisterType with RoleType: {:?}", role_type);
-                    dialogue.update(State::RegisterType { msg_id: new_msg_id, prefix, role_type }).await?;
+                    dialogue.update(State::RegisterType { msg_id: new_msg_id, prefix, unit_id, role_type }).await?;
                 }
             };
         }
@@ -196,13 +319,14 @@ pub(super) async fn register_role(
 pub(super) async fn register_type(
     bot: Bot,
     dialogue: MyDialogue,
-    (msg_id, prefix, role_type): (MessageId, String, RoleType),
+    (msg_id, prefix, unit_id, role_type): (MessageId, String, Uuid, RoleType),
     q: CallbackQuery,
 ) -> HandlerResult {
     log_endpoint_hit!(
         dialogue.chat_id(), "register_type", "Callback", q, 
         "MessageId" => msg_id,
         "Prefix" => prefix,
+        "Unit" => unit_id,
         "RoleType" => role_type
     );
 
@@ -236,7 +360,7 @@ pub(super) async fn register_type(
                 None => {}
                 Some(new_msg_id) => {
                     log::debug!("Transitioning to RegisterName with RoleType: {:?}, UsrType: {:?}", role_type, user_type);
-                    dialogue.update(State::RegisterName { msg_id: new_msg_id, role_type, user_type }).await?;
+                    dialogue.update(State::RegisterName { msg_id: new_msg_id, unit_id, role_type, user_type }).await?;
                 }
             };
         }
@@ -256,11 +380,12 @@ pub(super) async fn register_name(
     bot: Bot,
     dialogue: MyDialogue,
     msg: Message,
-    (msg_id, role_type, user_type): (MessageId, RoleType, UsrType),
+    (msg_id, unit_id, role_type, user_type): (MessageId, Uuid, RoleType, UsrType),
 ) -> HandlerResult {
     log_endpoint_hit!(
         dialogue.chat_id(), "register_name", "Message", msg, 
         "MessageId" => msg_id,
+        "Unit" => unit_id,
         "RoleType" => role_type,
         "UserType" => user_type
     );
@@ -291,6 +416,7 @@ pub(super) async fn register_name(
                             // Update the dialogue state to RegisterOpsName with the sanitized name
                             dialogue.update(State::RegisterOpsName {
                                     msg_id: new_msg_id,
+                                    unit_id,
                                     role_type,
                                     user_type,
                                     name,
@@ -323,12 +449,13 @@ pub(super) async fn register_ops_name(
     bot: Bot,
     dialogue: MyDialogue,
     msg: Message,
-    (msg_id, role_type, user_type, name): (MessageId, RoleType, UsrType, String),
+    (msg_id, unit_id, role_type, user_type, name): (MessageId, Uuid, RoleType, UsrType, String),
     pool: PgPool
 ) -> HandlerResult {
     log_endpoint_hit!(
         dialogue.chat_id(), "register_ops_name", "Message", msg, 
         "MessageId" => msg_id,
+        "Unit" => unit_id,
         "RoleType" => role_type,
         "UserType" => user_type,
         "Name" => name
@@ -345,7 +472,7 @@ pub(super) async fn register_ops_name(
 
     match msg.text().map(ToOwned::to_owned) {
         Some(input_ops_name_raw) => {
-            match validate_ops_name(&bot, &dialogue, &user.username, input_ops_name_raw, &pool).await {
+            match validate_ops_name(&bot, &dialogue, &user.username, input_ops_name_raw, unit_id, &pool).await {
                 Ok(ops_name) => {
                     // OPS name is unique, proceed with registration
                     let prefix = utils::generate_prefix(utils::CALLBACK_PREFIX_LEN);
@@ -354,7 +481,7 @@ pub(super) async fn register_ops_name(
                         None => dialogue.update(State::ErrorState).await?,
                         Some(new_msg_id) => {
                             dialogue.update(State::RegisterComplete {
-                                msg_id: new_msg_id, prefix,
+                                msg_id: new_msg_id, prefix, unit_id,
                                 role_type, user_type,
                                 name, ops_name
                             }).await?;
@@ -385,7 +512,7 @@ pub(super) async fn register_ops_name(
 pub(super) async fn register_complete(
     bot: Bot,
     dialogue: MyDialogue,
-    (msg_id, prefix, role_type, user_type, name, ops_name): (MessageId, String, RoleType, UsrType, String, String),
+    (msg_id, prefix, unit_id, role_type, user_type, name, ops_name): (MessageId, String, Uuid, RoleType, UsrType, String, String),
     q: CallbackQuery,
     pool: PgPool
 ) -> HandlerResult {
@@ -393,6 +520,7 @@ pub(super) async fn register_complete(
         dialogue.chat_id(), "register_complete", "Callback", q, 
         "MessageId" => msg_id,
         "Prefix" => prefix,
+        "Unit" => unit_id,
         "RoleType" => role_type,
         "UserType" => user_type,
         "Name" => name,
@@ -427,6 +555,7 @@ pub(super) async fn register_complete(
                 ops_name.clone(),
                 role_type.clone(),
                 user_type.clone(),
+                unit_id,
             )
                 .await {
                 Ok(true) => {
@@ -438,6 +567,7 @@ pub(super) async fn register_complete(
                             utils::escape_special_characters(&ops_name), utils::escape_special_characters(&name)
                         ).as_str(),
                         &pool,
+                        Some(unit_id),
                     ).await;
 
                     let registration_text_str = format!(
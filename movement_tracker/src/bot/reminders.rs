@@ -0,0 +1,268 @@
+use sqlx::types::chrono::NaiveTime;
+use sqlx::types::Uuid;
+use sqlx::PgPool;
+
+use teloxide::prelude::*;
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, MessageId};
+
+use crate::bot::state::State;
+use crate::bot::{handle_error, log_try_remove_markup, match_callback_data, retrieve_callback_data, send_msg, send_or_edit_msg, HandlerResult, MyDialogue};
+use crate::types::{ReminderRule, RoleType, UsrType};
+use crate::{controllers, log_endpoint_hit, utils};
+
+use serde::{Serialize, Deserialize};
+use strum::EnumProperty;
+use callback_data::CallbackData;
+use callback_data::CallbackDataHandler;
+
+type ParsedReminderRule = (Option<RoleType>, Option<UsrType>, Option<i32>, Option<NaiveTime>);
+
+// Represents callback actions with optional associated data.
+#[derive(Debug, Clone, Serialize, Deserialize, EnumProperty, CallbackData)]
+enum ReminderCallbacks {
+    Add,
+    Done,
+
+    // Remove the reminder rule with associated UUID
+    Delete { id: Uuid },
+}
+
+fn format_reminder_rule(rule: &ReminderRule) -> String {
+    let when = match (rule.days_prior, rule.time_of_day) {
+        (Some(days_prior), Some(time_of_day)) => format!("{} days prior at {}", days_prior, time_of_day.format("%H%M")),
+        _ => "Immediately when planned".to_string(),
+    };
+
+    let applies_to: Vec<&str> = [
+        rule.role_type.as_ref().map(|role_type| role_type.as_ref()),
+        rule.usr_type.as_ref().map(|usr_type| usr_type.as_ref())
+    ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+    if applies_to.is_empty() {
+        format!("{} (all)", when)
+    } else {
+        format!("{} ({})", when, applies_to.join(" "))
+    }
+}
+
+// Parses input such as "5 0900 PILOT NS" or "now ARO" into a reminder rule
+fn parse_reminder_rule(input: &str, role_types: &[RoleType], usr_types: &[UsrType]) -> Result<ParsedReminderRule, String> {
+    let mut tokens = input.split_whitespace();
+
+    let (days_prior, time_of_day) = match tokens.next() {
+        Some(token) if token.eq_ignore_ascii_case("now") => (None, None),
+        Some(token) => {
+            let days_prior = token.parse::<i32>()
+                .ok()
+                .filter(|days| (0..=utils::MAX_REMINDER_DAYS_PRIOR).contains(days))
+                .ok_or(format!("Invalid number of days: {}", token))?;
+            let time_str = tokens.next().ok_or("Missing time of day".to_string())?;
+            let time_of_day = NaiveTime::parse_from_str(time_str, "%H%M")
+                .or_else(|_| NaiveTime::parse_from_str(time_str, "%H:%M"))
+                .map_err(|_| format!("Invalid time of day: {}", time_str))?;
+            (Some(days_prior), Some(time_of_day))
+        }
+        None => return Err("No input provided".to_string()),
+    };
+
+    let mut role_type = None;
+    let mut usr_type = None;
+    for token in tokens {
+        let token = token.to_uppercase();
+        if let (Some(role), None) = (role_types.iter().find(|role| role.as_ref() == token), &role_type) {
+            role_type = Some(role.clone());
+        } else if let (Some(usr), None) = (usr_types.iter().find(|usr| usr.as_ref() == token), &usr_type) {
+            usr_type = Some(usr.clone());
+        } else {
+            return Err(format!("Invalid role or user type: {}", token));
+        }
+    }
+
+    Ok((role_type, usr_type, days_prior, time_of_day))
+}
+
+async fn display_reminder_rules(
+    bot: &Bot,
+    chat_id: ChatId,
+    username: &Option<String>,
+    reminder_rules: &[ReminderRule],
+    prefix: &str,
+    msg_id: Option<MessageId>
+) -> Option<MessageId> {
+    let mut entries: Vec<Vec<InlineKeyboardButton>> = reminder_rules
+        .iter()
+        .map(|rule| vec![InlineKeyboardButton::callback(
+            format!("DELETE: {}", format_reminder_rule(rule)),
+            ReminderCallbacks::Delete { id: rule.id }.to_callback_data(prefix)
+        )])
+        .collect();
+    entries.push(vec![
+        InlineKeyboardButton::callback("ADD", ReminderCallbacks::Add.to_callback_data(prefix)),
+        InlineKeyboardButton::callback("DONE", ReminderCallbacks::Done.to_callback_data(prefix))
+    ]);
+
+    let message_text = if reminder_rules.is_empty() {
+        "No reminders are configured for planned availability.".to_string()
+    } else {
+        format!(
+            "Reminders sent for planned availability:\n{}\n\nChanges apply to availability planned from now on.",
+            reminder_rules.iter().map(|rule| format!("- {}", format_reminder_rule(rule))).collect::<Vec<String>>().join("\n")
+        )
+    };
+
+    send_or_edit_msg(bot, chat_id, username, msg_id, message_text, Some(InlineKeyboardMarkup::new(entries)), None).await
+}
+
+async fn handle_show_reminder_rules(
+    bot: &Bot,
+    dialogue: &MyDialogue,
+    username: &Option<String>,
+    prefix: String,
+    pool: &PgPool,
+    msg_id: Option<MessageId>
+) -> HandlerResult {
+    match controllers::reminders::get_reminder_rules(pool).await {
+        Ok(reminder_rules) => {
+            match display_reminder_rules(bot, dialogue.chat_id(), username, &reminder_rules, &prefix, msg_id).await {
+                None => dialogue.update(State::ErrorState).await?,
+                Some(new_msg_id) => dialogue.update(State::ReminderView { msg_id: new_msg_id, prefix }).await?
+            }
+        }
+        Err(_) => handle_error(bot, dialogue, dialogue.chat_id(), username).await
+    }
+
+    Ok(())
+}
+
+pub(super) async fn reminders(bot: Bot, dialogue: MyDialogue, msg: Message, pool: PgPool) -> HandlerResult {
+    log_endpoint_hit!(dialogue.chat_id(), "reminders", "Command", msg);
+
+    // Early return if the message has no sender (msg.from() is None)
+    let user = if let Some(ref user) = msg.from {
+        user
+    } else {
+        log::error!("Cannot get user from message");
+        dialogue.update(State::ErrorState).await?;
+        return Ok(());
+    };
+
+    let prefix = utils::generate_prefix(utils::CALLBACK_PREFIX_LEN);
+    handle_show_reminder_rules(&bot, &dialogue, &user.username, prefix, &pool, None).await
+}
+
+pub(super) async fn reminder_view(
+    bot: Bot,
+    dialogue: MyDialogue,
+    (msg_id, prefix): (MessageId, String),
+    q: CallbackQuery,
+    pool: PgPool
+) -> HandlerResult {
+    log_endpoint_hit!(dialogue.chat_id(), "reminder_view", "Callback", q,
+        "MessageId" => msg_id,
+        "Prefix" => prefix
+    );
+
+    // Extract the callback data
+    let data = match retrieve_callback_data(&bot, dialogue.chat_id(), &q).await {
+        Ok(data) => data,
+        Err(_) => { return Ok(()); }
+    };
+
+    // Acknowledge the callback to remove the loading state
+    if let Err(e) = bot.answer_callback_query(q.id).await {
+        log::error!("Failed to answer callback query: {}", e);
+    }
+
+    // Deserialize the callback data into the enum
+    let callback = match match_callback_data(&bot, dialogue.chat_id(), &q.from.username, &data, &prefix).await {
+        Ok(callback) => callback,
+        Err(_) => { return Ok(()); }
+    };
+
+    match callback {
+        ReminderCallbacks::Done => {
+            log_try_remove_markup(&bot, dialogue.chat_id(), msg_id).await;
+            dialogue.update(State::Start).await?;
+        }
+        ReminderCallbacks::Add => {
+            log_try_remove_markup(&bot, dialogue.chat_id(), msg_id).await;
+            match send_msg(
+                bot.send_message(
+                    dialogue.chat_id(),
+                    "Enter the reminder as <days prior> <time> [role] [user type], or \"now\" to remind immediately when planned. Type /cancel to abort.\n\nExamples:\n3 0900\n1 1800 PILOT NS\nnow ARO"
+                ),
+                &q.from.username
+            ).await {
+                None => dialogue.update(State::ErrorState).await?,
+                Some(msg_id) => dialogue.update(State::ReminderAdd { msg_id, prefix }).await?
+            }
+        }
+        ReminderCallbacks::Delete { id } => {
+            match controllers::reminders::remove_reminder_rule_by_uuid(&pool, id).await {
+                Ok(_) => handle_show_reminder_rules(&bot, &dialogue, &q.from.username, prefix, &pool, Some(msg_id)).await?,
+                Err(_) => handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub(super) async fn reminder_add(
+    bot: Bot,
+    dialogue: MyDialogue,
+    (msg_id, prefix): (MessageId, String),
+    msg: Message,
+    pool: PgPool
+) -> HandlerResult {
+    log_endpoint_hit!(dialogue.chat_id(), "reminder_add", "Message", msg,
+        "MessageId" => msg_id,
+        "Prefix" => prefix
+    );
+
+    // Early return if the message has no sender (msg.from() is None)
+    let user = if let Some(ref user) = msg.from {
+        user
+    } else {
+        log::error!("Cannot get user from message");
+        dialogue.update(State::ErrorState).await?;
+        return Ok(());
+    };
+
+    let (role_types, usr_types) = match (
+        controllers::crew_types::get_role_types(&pool).await,
+        controllers::crew_types::get_usr_types(&pool).await
+    ) {
+        (Ok(role_types), Ok(usr_types)) => (role_types, usr_types),
+        _ => {
+            handle_error(&bot, &dialogue, dialogue.chat_id(), &user.username).await;
+            return Ok(());
+        }
+    };
+
+    match msg.text().map(|text| parse_reminder_rule(text, &role_types, &usr_types)) {
+        Some(Ok((role_type, usr_type, days_prior, time_of_day))) => {
+            match controllers::reminders::add_reminder_rule(&pool, role_type, usr_type, days_prior, time_of_day).await {
+                Ok(_) => handle_show_reminder_rules(&bot, &dialogue, &user.username, prefix, &pool, None).await?,
+                Err(_) => handle_error(&bot, &dialogue, dialogue.chat_id(), &user.username).await
+            }
+        }
+        Some(Err(e)) => {
+            send_msg(
+                bot.send_message(dialogue.chat_id(), format!("{}. Please try again, or type /cancel to abort.", e)),
+                &user.username
+            ).await;
+        }
+        None => {
+            send_msg(
+                bot.send_message(dialogue.chat_id(), "Please enter the reminder as text, or type /cancel to abort."),
+                &user.username
+            ).await;
+        }
+    }
+
+    Ok(())
+}
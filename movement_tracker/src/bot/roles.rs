@@ -0,0 +1,246 @@
+use sqlx::PgPool;
+
+use teloxide::prelude::*;
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, MessageId};
+
+use crate::bot::state::State;
+use crate::bot::{check_actor_admin, handle_error, log_try_remove_markup, match_callback_data, retrieve_callback_data, send_msg, send_or_edit_msg, HandlerResult, MyDialogue};
+use crate::types::{RoleType, UsrType};
+use crate::{controllers, log_endpoint_hit, utils};
+
+use serde::{Serialize, Deserialize};
+use strum::EnumProperty;
+use callback_data::CallbackData;
+use callback_data::CallbackDataHandler;
+
+// Crew types are shared by every unit, so only admins may change them
+const ADMIN_ONLY_TEXT: &str = "Only admins can manage crew roles and user types, as they are shared by every unit.";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) enum CrewTypeKind {
+    Role,
+    User
+}
+
+// Represents callback actions with optional associated data.
+#[derive(Debug, Clone, Serialize, Deserialize, EnumProperty, CallbackData)]
+enum RoleTypeCallbacks {
+    AddRole,
+    AddUser,
+    Done,
+
+    // Remove the role or user type with the associated name
+    RemoveRole { role_type: RoleType },
+    RemoveUser { usr_type: UsrType },
+}
+
+async fn display_crew_types(
+    bot: &Bot,
+    chat_id: ChatId,
+    username: &Option<String>,
+    role_types: &[RoleType],
+    usr_types: &[UsrType],
+    prefix: &str,
+    msg_id: Option<MessageId>
+) -> Option<MessageId> {
+    let mut entries: Vec<Vec<InlineKeyboardButton>> = Vec::new();
+    entries.extend(role_types.iter().map(|role_type| vec![InlineKeyboardButton::callback(
+        format!("REMOVE ROLE: {}", role_type.as_ref()),
+        RoleTypeCallbacks::RemoveRole { role_type: role_type.clone() }.to_callback_data(prefix)
+    )]));
+    // NS is built in as SAF100 tracking depends on it
+    entries.extend(usr_types.iter().filter(|usr_type| !usr_type.is_ns()).map(|usr_type| vec![InlineKeyboardButton::callback(
+        format!("REMOVE TYPE: {}", usr_type.as_ref()),
+        RoleTypeCallbacks::RemoveUser { usr_type: usr_type.clone() }.to_callback_data(prefix)
+    )]));
+    entries.push(vec![
+        InlineKeyboardButton::callback("ADD ROLE", RoleTypeCallbacks::AddRole.to_callback_data(prefix)),
+        InlineKeyboardButton::callback("ADD TYPE", RoleTypeCallbacks::AddUser.to_callback_data(prefix)),
+        InlineKeyboardButton::callback("DONE", RoleTypeCallbacks::Done.to_callback_data(prefix))
+    ]);
+
+    let message_text = format!(
+        "Roles: {}\nUser types: {}\n\nRoles and user types still held by a user or application cannot be removed.",
+        role_types.iter().map(|role_type| role_type.as_ref()).collect::<Vec<&str>>().join(", "),
+        usr_types.iter().map(|usr_type| usr_type.as_ref()).collect::<Vec<&str>>().join(", ")
+    );
+
+    send_or_edit_msg(bot, chat_id, username, msg_id, message_text, Some(InlineKeyboardMarkup::new(entries)), None).await
+}
+
+async fn handle_show_crew_types(
+    bot: &Bot,
+    dialogue: &MyDialogue,
+    username: &Option<String>,
+    prefix: String,
+    pool: &PgPool,
+    msg_id: Option<MessageId>
+) -> HandlerResult {
+    match (controllers::crew_types::get_role_types(pool).await, controllers::crew_types::get_usr_types(pool).await) {
+        (Ok(role_types), Ok(usr_types)) => {
+            match display_crew_types(bot, dialogue.chat_id(), username, &role_types, &usr_types, &prefix, msg_id).await {
+                None => dialogue.update(State::ErrorState).await?,
+                Some(new_msg_id) => dialogue.update(State::RoleTypeView { msg_id: new_msg_id, prefix }).await?
+            }
+        }
+        _ => handle_error(bot, dialogue, dialogue.chat_id(), username).await
+    }
+
+    Ok(())
+}
+
+pub(super) async fn roles(bot: Bot, dialogue: MyDialogue, msg: Message, pool: PgPool) -> HandlerResult {
+    log_endpoint_hit!(dialogue.chat_id(), "roles", "Command", msg);
+
+    // Early return if the message has no sender (msg.from() is None)
+    let user = if let Some(ref user) = msg.from {
+        user
+    } else {
+        log::error!("Cannot get user from message");
+        dialogue.update(State::ErrorState).await?;
+        return Ok(());
+    };
+
+    if !check_actor_admin(&bot, &dialogue, user, &pool, ADMIN_ONLY_TEXT).await {
+        return Ok(());
+    }
+
+    let prefix = utils::generate_prefix(utils::CALLBACK_PREFIX_LEN);
+    handle_show_crew_types(&bot, &dialogue, &user.username, prefix, &pool, None).await
+}
+
+pub(super) async fn role_type_view(
+    bot: Bot,
+    dialogue: MyDialogue,
+    (msg_id, prefix): (MessageId, String),
+    q: CallbackQuery,
+    pool: PgPool
+) -> HandlerResult {
+    log_endpoint_hit!(dialogue.chat_id(), "role_type_view", "Callback", q,
+        "MessageId" => msg_id,
+        "Prefix" => prefix
+    );
+
+    // Extract the callback data
+    let data = match retrieve_callback_data(&bot, dialogue.chat_id(), &q).await {
+        Ok(data) => data,
+        Err(_) => { return Ok(()); }
+    };
+
+    // Acknowledge the callback to remove the loading state
+    if let Err(e) = bot.answer_callback_query(q.id).await {
+        log::error!("Failed to answer callback query: {}", e);
+    }
+
+    // Deserialize the callback data into the enum
+    let callback = match match_callback_data(&bot, dialogue.chat_id(), &q.from.username, &data, &prefix).await {
+        Ok(callback) => callback,
+        Err(_) => { return Ok(()); }
+    };
+
+    if !matches!(callback, RoleTypeCallbacks::Done) && !check_actor_admin(&bot, &dialogue, &q.from, &pool, ADMIN_ONLY_TEXT).await {
+        return Ok(());
+    }
+
+    let (kind, removed) = match callback {
+        RoleTypeCallbacks::Done => {
+            log_try_remove_markup(&bot, dialogue.chat_id(), msg_id).await;
+            dialogue.update(State::Start).await?;
+            return Ok(());
+        }
+        RoleTypeCallbacks::AddRole | RoleTypeCallbacks::AddUser => {
+            let kind = if let RoleTypeCallbacks::AddRole = callback { CrewTypeKind::Role } else { CrewTypeKind::User };
+            log_try_remove_markup(&bot, dialogue.chat_id(), msg_id).await;
+            match send_msg(
+                bot.send_message(
+                    dialogue.chat_id(),
+                    format!(
+                        "Enter the name of the new {} using letters and digits, up to {} characters. Type /cancel to abort.",
+                        match kind { CrewTypeKind::Role => "role", CrewTypeKind::User => "user type" },
+                        utils::MAX_CREW_TYPE_NAME_LENGTH
+                    )
+                ),
+                &q.from.username
+            ).await {
+                None => dialogue.update(State::ErrorState).await?,
+                Some(msg_id) => dialogue.update(State::RoleTypeAdd { msg_id, prefix, kind }).await?
+            }
+            return Ok(());
+        }
+        RoleTypeCallbacks::RemoveRole { role_type } => (CrewTypeKind::Role, controllers::crew_types::remove_role_type(&pool, &role_type).await),
+        RoleTypeCallbacks::RemoveUser { usr_type } => (CrewTypeKind::User, controllers::crew_types::remove_usr_type(&pool, &usr_type).await)
+    };
+
+    match removed {
+        Ok(true) => {}
+        Ok(false) => {
+            send_msg(
+                bot.send_message(
+                    dialogue.chat_id(),
+                    format!("That {} is still in use and was not removed.", match kind { CrewTypeKind::Role => "role", CrewTypeKind::User => "user type" })
+                ),
+                &q.from.username
+            ).await;
+        }
+        Err(_) => {
+            handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await;
+            return Ok(());
+        }
+    }
+    handle_show_crew_types(&bot, &dialogue, &q.from.username, prefix, &pool, Some(msg_id)).await
+}
+
+pub(super) async fn role_type_add(
+    bot: Bot,
+    dialogue: MyDialogue,
+    (msg_id, prefix, kind): (MessageId, String, CrewTypeKind),
+    msg: Message,
+    pool: PgPool
+) -> HandlerResult {
+    log_endpoint_hit!(dialogue.chat_id(), "role_type_add", "Message", msg,
+        "MessageId" => msg_id,
+        "Prefix" => prefix,
+        "Kind" => kind
+    );
+
+    // Early return if the message has no sender (msg.from() is None)
+    let user = if let Some(ref user) = msg.from {
+        user
+    } else {
+        log::error!("Cannot get user from message");
+        dialogue.update(State::ErrorState).await?;
+        return Ok(());
+    };
+
+    if !check_actor_admin(&bot, &dialogue, user, &pool, ADMIN_ONLY_TEXT).await {
+        dialogue.update(State::Start).await?;
+        return Ok(());
+    }
+
+    let name = msg.text().map(|text| text.trim().to_uppercase()).unwrap_or_default();
+    if !utils::is_valid_crew_type_name(&name) || name.len() > utils::MAX_CREW_TYPE_NAME_LENGTH {
+        send_msg(
+            bot.send_message(
+                dialogue.chat_id(),
+                format!(
+                    "Invalid name. Please use only letters and digits, up to {} characters. Try again or type /cancel to abort.",
+                    utils::MAX_CREW_TYPE_NAME_LENGTH
+                )
+            ),
+            &user.username
+        ).await;
+        return Ok(());
+    }
+
+    let result = match kind {
+        CrewTypeKind::Role => controllers::crew_types::add_role_type(&pool, &RoleType::new(&name)).await,
+        CrewTypeKind::User => controllers::crew_types::add_usr_type(&pool, &UsrType::new(&name)).await
+    };
+
+    match result {
+        Ok(_) => handle_show_crew_types(&bot, &dialogue, &user.username, prefix, &pool, None).await?,
+        Err(_) => handle_error(&bot, &dialogue, dialogue.chat_id(), &user.username).await
+    }
+
+    Ok(())
+}
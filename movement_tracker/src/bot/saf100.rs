@@ -182,16 +182,17 @@ async fn handle_re_show_options(
     show: usize,
     action: Saf100ViewType,
     msg_id: Option<MessageId>,
+    unit_id: Uuid,
     pool: &PgPool
 ) -> HandlerResult {
 
     // Fetch the relevant availability entries
     let availability_result = match action {
         Saf100ViewType::SeeAvail => {
-            controllers::attendance::get_future_valid_availability_for_ns(pool).await
+            controllers::attendance::get_future_valid_availability_for_ns(pool, unit_id).await
         }
         Saf100ViewType::SeePlanned => {
-            controllers::attendance::get_future_planned_availability_for_ns(pool).await
+            controllers::attendance::get_future_planned_availability_for_ns(pool, Some(unit_id)).await
         }
     };
 
@@ -318,10 +319,18 @@ pub(super) async fn saf100_select(
         Err(_) => { return Ok(()); }
     };
 
+    let unit_id = match controllers::user::get_unit_id_by_tele_id(&pool, q.from.id.0).await {
+        Ok(unit_id) => unit_id,
+        Err(_) => {
+            handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await;
+            return Ok(());
+        }
+    };
+
     // Handle based on the variant
     match callback {
         Saf100CallbackData::See { view_type } => {
-            handle_re_show_options(&bot, &dialogue, &q.from.username, 0, utils::MAX_SHOW_ENTRIES, view_type, Some(msg_id), &pool).await?;
+            handle_re_show_options(&bot, &dialogue, &q.from.username, 0, utils::MAX_SHOW_ENTRIES, view_type, Some(msg_id), unit_id, &pool).await?;
         }
         Saf100CallbackData::Cancel => {
             // Handle cancellation by reverting to the start state
@@ -379,17 +388,25 @@ pub(super) async fn saf100_view(
         Err(_) => { return Ok(()); }
     };
 
+    let unit_id = match controllers::user::get_unit_id_by_tele_id(&pool, q.from.id.0).await {
+        Ok(unit_id) => unit_id,
+        Err(_) => {
+            handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await;
+            return Ok(());
+        }
+    };
+
     let show = utils::MAX_SHOW_ENTRIES;
 
     match callback {
         Saf100CallbackData::Prev => {
             let new_start = if start >= show { start - show } else { 0 };
-            handle_re_show_options(&bot, &dialogue, &q.from.username, new_start, show, action, Some(msg_id), &pool).await?;
+            handle_re_show_options(&bot, &dialogue, &q.from.username, new_start, show, action, Some(msg_id), unit_id, &pool).await?;
         }
         Saf100CallbackData::Next => {
             let entries_len = availability_list.len();
             let new_start = if start + show < entries_len { start + show } else { start };
-            handle_re_show_options(&bot, &dialogue, &q.from.username, new_start, show, action, Some(msg_id), &pool).await?;
+            handle_re_show_options(&bot, &dialogue, &q.from.username, new_start, show, action, Some(msg_id), unit_id, &pool).await?;
         }
         Saf100CallbackData::Done => {
             log_try_remove_markup(&bot, dialogue.chat_id(), msg_id).await;
@@ -471,9 +488,17 @@ pub(super) async fn saf100_confirm(
         Err(_) => { return Ok(()); }
     };
 
+    let unit_id = match controllers::user::get_unit_id_by_tele_id(&pool, q.from.id.0).await {
+        Ok(unit_id) => unit_id,
+        Err(_) => {
+            handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await;
+            return Ok(());
+        }
+    };
+
     match callback {
         Saf100CallbackData::ConfirmYes => {
-            match controllers::attendance::set_saf100_true_by_uuid(&pool, availability.id).await {
+            match controllers::attendance::set_saf100_true_by_uuid(&pool, q.from.id.0, availability.id).await {
                 Ok(details) => {
                     notifier::emit::plan_notifications(
                         &bot,
@@ -484,6 +509,7 @@ pub(super) async fn saf100_confirm(
                             utils::escape_special_characters(&details.avail.format("%Y-%m-%d").to_string())
                         ).as_str(),
                         &pool,
+                        Some(unit_id),
                         q.from.id.0 as i64
                     ).await;
                     
@@ -545,14 +571,14 @@ pub(super) async fn saf100_confirm(
                     
                     // Send or edit message
                     send_or_edit_msg(&bot, dialogue.chat_id(), &q.from.username, Some(msg_id), message_text, None, Some(ParseMode::MarkdownV2)).await;
-                    handle_re_show_options(&bot, &dialogue, &q.from.username, start, utils::MAX_SHOW_ENTRIES, action, None, &pool).await?;
+                    handle_re_show_options(&bot, &dialogue, &q.from.username, start, utils::MAX_SHOW_ENTRIES, action, None, unit_id, &pool).await?;
                 }
                 Err(_) => handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await,
             }
         }
         Saf100CallbackData::ConfirmNo => {
             // logic to go back
-            handle_re_show_options(&bot, &dialogue, &q.from.username, start, utils::MAX_SHOW_ENTRIES, action, Some(msg_id), &pool).await?;
+            handle_re_show_options(&bot, &dialogue, &q.from.username, start, utils::MAX_SHOW_ENTRIES, action, Some(msg_id), unit_id, &pool).await?;
         }
         _ => {
             send_msg(
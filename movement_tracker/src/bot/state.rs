@@ -1,47 +1,66 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use super::commands::{cancel, help, set_menu_buttons, Commands, PrivilegedCommands};
 use super::{send_msg, HandlerResult, MyDialogue};
 use crate::bot::apply::{apply_edit_admin, apply_edit_name, apply_edit_ops_name, apply_edit_prompt, apply_edit_role, apply_edit_type, apply_view, approve};
-use chrono::NaiveDate;
+use chrono::{NaiveDate, NaiveTime};
 use sqlx::PgPool;
-use teloxide::dispatching::dialogue::InMemStorage;
+use serde::{Deserialize, Serialize};
 use teloxide::dispatching::{dialogue, UpdateHandler};
 use teloxide::dptree::{case, endpoint};
 use teloxide::prelude::*;
 use teloxide::types::{ChatKind, MessageId, ReplyParameters};
 use uuid::Uuid;
-use super::register::{register, register_complete, register_name, register_ops_name, register_role, register_type};
-use super::user::{user, user_edit_admin, user_edit_delete, user_edit_name, user_edit_ops_name, user_edit_prompt, user_edit_role, user_edit_type, user_select};
+use super::register::{register, register_complete, register_name, register_ops_name, register_role, register_type, register_unit};
+use super::user::{user, user_edit_admin, user_edit_delete, user_edit_permissions, user_edit_name, user_edit_ops_name, user_edit_prompt, user_edit_role, user_edit_type, user_select};
 use crate::bot::availability::{availability, availability_add_callback, availability_add_change_type, availability_add_complete, availability_add_message, availability_add_remarks, availability_delete_confirm, availability_modify, availability_modify_remarks, availability_modify_type, availability_select, availability_view, AvailabilityAction};
 use crate::bot::forecast::{forecast, forecast_view};
 use crate::bot::plan::{plan, plan_select, plan_view, plan_view_availability};
-use crate::types::{Apply, Availability, AvailabilityDetails, Ict, NotificationSettings, RoleType, Usr, UsrType};
+use crate::types::{AdminRole, Apply, Availability, AvailabilityDetails, Ict, Movement, NotificationSettings, PlanStatus, RoleType, TimeSlot, Usr, UsrType};
 use crate::{controllers, log_endpoint_hit};
 use crate::bot::notify::{notify, notify_settings};
 use crate::bot::saf100::{saf100, saf100_confirm, saf100_select, saf100_view, Saf100ViewType};
 use crate::bot::upcoming::upcoming;
+use crate::bot::storage::PgStorage;
+use crate::bot::attendance::{attendance, attendance_view};
+use crate::bot::import::{import, import_confirm, import_upload, ImportEntry};
+use crate::bot::reminders::{reminder_add, reminder_view, reminders};
+use crate::bot::campaign::{campaign, campaign_add, campaign_status, campaign_view};
+use crate::bot::roles::{role_type_add, role_type_view, roles, CrewTypeKind};
+use crate::bot::calendar::{calendar, calendar_view};
+use crate::bot::history::{history, history_view};
+use crate::bot::stats::{stats, stats_view};
+use crate::bot::acknowledge::{acknowledge, is_reminder_response};
+use crate::bot::movement::{movement, movement_add_activity, movement_add_complete, movement_add_date, movement_add_location, movement_add_location_callback, movement_add_remarks, movement_add_time, movement_delete_confirm, movement_modify, movement_modify_field, movement_select, movement_view, MovementField};
 
-#[derive(Clone, Default)]
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub(super) enum State {
     #[default]
     Start,
     // States used for registering for an account
+    RegisterUnit {
+        msg_id: MessageId,
+        prefix: String,
+    },
     RegisterRole {
         msg_id: MessageId,
         prefix: String,
+        unit_id: Uuid,
     },
     RegisterType {
         msg_id: MessageId,
         prefix: String,
+        unit_id: Uuid,
         role_type: RoleType,
     },
     RegisterName {
         msg_id: MessageId,
+        unit_id: Uuid,
         role_type: RoleType,
         user_type: UsrType,
     },
     RegisterOpsName {
         msg_id: MessageId,
+        unit_id: Uuid,
         role_type: RoleType,
         user_type: UsrType,
         name: String,
@@ -49,6 +68,7 @@ pub(super) enum State {
     RegisterComplete {
         msg_id: MessageId,
         prefix: String,
+        unit_id: Uuid,
         role_type: RoleType,
         user_type: UsrType,
         name: String,
@@ -100,13 +120,74 @@ pub(super) enum State {
         application: Apply,
         admin: bool
     },
-    // TODO: States used for adding personal movement details 
-    // MovementView,
-    // EditMovement,
-    // AddMovement,
-    // AddMovementDetails {
-    //     details: String,
-    // },
+    // States used for adding and modifying personal movement details
+    MovementView {
+        msg_id: MessageId,
+        prefix: String,
+        month: NaiveDate
+    },
+    MovementSelect {
+        msg_id: MessageId,
+        movement_list: Vec<Movement>,
+        prefix: String,
+        start: usize,
+        month: NaiveDate
+    },
+    MovementModify {
+        msg_id: MessageId,
+        prefix: String,
+        movement_entry: Movement,
+        start: usize,
+        month: NaiveDate
+    },
+    MovementModifyField {
+        msg_id: MessageId,
+        change_msg_id: MessageId,
+        movement_entry: Movement,
+        field: MovementField,
+        start: usize,
+        month: NaiveDate
+    },
+    MovementDeleteConfirm {
+        msg_id: MessageId,
+        prefix: String,
+        movement_entry: Movement,
+        start: usize,
+        month: NaiveDate
+    },
+    MovementAddDate {
+        msg_id: MessageId,
+        prefix: String
+    },
+    MovementAddTime {
+        msg_id: MessageId,
+        prefix: String,
+        date: NaiveDate
+    },
+    MovementAddActivity {
+        msg_id: MessageId,
+        prefix: String,
+        date: NaiveDate,
+        start_time: NaiveTime,
+        end_time: NaiveTime
+    },
+    MovementAddLocation {
+        msg_id: MessageId,
+        prefix: String,
+        date: NaiveDate,
+        start_time: NaiveTime,
+        end_time: NaiveTime,
+        activity: String
+    },
+    MovementAddRemarks {
+        msg_id: MessageId,
+        prefix: String,
+        date: NaiveDate,
+        start_time: NaiveTime,
+        end_time: NaiveTime,
+        activity: String,
+        location: Option<String>
+    },
     // States used for adding and modifying availability for SANS
     AvailabilityView {
         msg_id: MessageId,
@@ -170,6 +251,7 @@ pub(super) enum State {
         change_msg_id: MessageId,
         avail_type: Ict,
         avail_dates: Vec<NaiveDate>,
+        slot: Option<TimeSlot>,
     },
     // States meant for viewing the forecast
     ForecastView {
@@ -187,7 +269,7 @@ pub(super) enum State {
         user_details: Option<Usr>,
         selected_date: Option<NaiveDate>,
         availability_list: Vec<AvailabilityDetails>,
-        changes: HashSet<Uuid>,
+        changes: HashMap<Uuid, PlanStatus>,
         role_type: RoleType,
         prefix: String,
         start: usize
@@ -196,12 +278,21 @@ pub(super) enum State {
         msg_id: MessageId,
         user_details: Option<Usr>,
         selected_date: Option<NaiveDate>,
-        changes: HashSet<Uuid>,
+        changes: HashMap<Uuid, PlanStatus>,
         role_type: RoleType,
         prefix: String,
         start: usize
     },
-    // TODO: States meant for SANS attendance confirmation
+    // States meant for SANS attendance confirmation
+    AttendanceView {
+        msg_id: MessageId,
+        prefix: String,
+        date: NaiveDate,
+        availability_list: Vec<AvailabilityDetails>,
+        changes: HashSet<Uuid>,
+        role_type: RoleType,
+        start: usize
+    },
     // States meant for editing users
     UserSelect,
     UserEdit {
@@ -239,6 +330,13 @@ pub(super) enum State {
         user_details: Usr,
         prefix: String
     },
+    UserEditPermissions {
+        msg_id: MessageId,
+        change_msg_id: MessageId,
+        user_details: Usr,
+        prefix: String,
+        admin_roles: Vec<AdminRole>
+    },
     UserEditDeleteConfirm {
         msg_id: MessageId,
         change_msg_id: MessageId,
@@ -264,6 +362,70 @@ pub(super) enum State {
         start: usize,
         action: Saf100ViewType
     },
+    // States meant for importing availability in bulk
+    ImportUpload {
+        msg_id: MessageId,
+        prefix: String
+    },
+    ImportConfirm {
+        msg_id: MessageId,
+        prefix: String,
+        entries: Vec<ImportEntry>
+    },
+    // States meant for viewing the calendar link
+    CalendarView {
+        msg_id: MessageId,
+        prefix: String
+    },
+    // States meant for configuring reminders for planned availability
+    ReminderView {
+        msg_id: MessageId,
+        prefix: String
+    },
+    ReminderAdd {
+        msg_id: MessageId,
+        prefix: String
+    },
+    // States meant for requesting availability from users and tracking their responses
+    CampaignView {
+        msg_id: MessageId,
+        prefix: String
+    },
+    CampaignAdd {
+        msg_id: MessageId,
+        prefix: String
+    },
+    CampaignStatus {
+        msg_id: MessageId,
+        prefix: String,
+        campaign_id: Uuid
+    },
+    // States meant for managing crew roles and user types
+    RoleTypeView {
+        msg_id: MessageId,
+        prefix: String
+    },
+    RoleTypeAdd {
+        msg_id: MessageId,
+        prefix: String,
+        kind: CrewTypeKind
+    },
+    // States meant for viewing the planning stats of users
+    StatsView {
+        msg_id: MessageId,
+        prefix: String,
+        role_type: RoleType,
+        start: NaiveDate,
+        end: NaiveDate
+    },
+    // States meant for browsing the audit history of a user
+    HistoryView {
+        msg_id: MessageId,
+        prefix: String,
+        tele_id: i64,
+        ops_name: String,
+        start: usize
+    },
     // States meant for editing the notification settings
     NotifySettings {
         notification_settings: NotificationSettings,
@@ -277,11 +439,18 @@ pub(super) enum State {
 pub(super) fn schema() -> UpdateHandler<Box<dyn std::error::Error + Send + Sync + 'static>> {
     // Privileged Command Handler
     let admin_command_handler = teloxide::filter_command::<PrivilegedCommands, _>()
-        .branch(case![PrivilegedCommands::User { ops_name }].branch(dptree::filter_async(check_private).endpoint(user)))
-        .branch(case![PrivilegedCommands::Approve].branch(dptree::filter_async(check_private).endpoint(approve)))
-        .branch(case![PrivilegedCommands::Plan { ops_name_or_date }].branch(dptree::filter_async(check_private).endpoint(plan)))
-        .branch(case![PrivilegedCommands::SAF100].branch(dptree::filter_async(check_private).endpoint(saf100)))
-        .branch(case![PrivilegedCommands::Notify].endpoint(notify));
+        .branch(case![PrivilegedCommands::User { ops_name }].chain(require_role(AdminRole::UserManager)).branch(dptree::filter_async(check_private).endpoint(user)))
+        .branch(case![PrivilegedCommands::Approve].chain(require_role(AdminRole::UserManager)).branch(dptree::filter_async(check_private).endpoint(approve)))
+        .branch(case![PrivilegedCommands::Plan { ops_name_or_date }].chain(require_role(AdminRole::Planner)).branch(dptree::filter_async(check_private).endpoint(plan)))
+        .branch(case![PrivilegedCommands::SAF100].chain(require_role(AdminRole::Saf100Clerk)).branch(dptree::filter_async(check_private).endpoint(saf100)))
+        .branch(case![PrivilegedCommands::Attendance].chain(require_role(AdminRole::Planner)).branch(dptree::filter_async(check_private).endpoint(attendance)))
+        .branch(case![PrivilegedCommands::Import].chain(require_role(AdminRole::Planner)).branch(dptree::filter_async(check_private).endpoint(import)))
+        .branch(case![PrivilegedCommands::Reminders].chain(require_role(AdminRole::Planner)).branch(dptree::filter_async(check_private).endpoint(reminders)))
+        .branch(case![PrivilegedCommands::Campaign].chain(require_role(AdminRole::Planner)).branch(dptree::filter_async(check_private).endpoint(campaign)))
+        .branch(case![PrivilegedCommands::Roles].chain(require_role(AdminRole::UserManager)).branch(dptree::filter_async(check_private).endpoint(roles)))
+        .branch(case![PrivilegedCommands::History { ops_name }].chain(require_role(AdminRole::UserManager)).branch(dptree::filter_async(check_private).endpoint(history)))
+        .branch(case![PrivilegedCommands::Stats].chain(require_role(AdminRole::Planner)).branch(dptree::filter_async(check_private).endpoint(stats)))
+        .branch(case![PrivilegedCommands::Notify].chain(require_role(AdminRole::NotificationManager)).endpoint(notify));
 
     // Public Commands: Accessible to All Users (excluding /cancel)
     let public_commands = teloxide::filter_command::<Commands, _>()
@@ -295,6 +464,8 @@ pub(super) fn schema() -> UpdateHandler<Box<dyn std::error::Error + Send + Sync
             .branch(case![Commands::Forecast].endpoint(forecast))
             .branch(case![Commands::Availability].branch(dptree::filter_async(check_private).endpoint(availability)))
             .branch(case![Commands::Upcoming].branch(dptree::filter_async(check_private).endpoint(upcoming)))
+            .branch(case![Commands::Movement].branch(dptree::filter_async(check_private).endpoint(movement)))
+            .branch(case![Commands::Calendar].branch(dptree::filter_async(check_private).endpoint(calendar)))
         );
 
     // Combine Public and Registered Commands
@@ -308,24 +479,33 @@ pub(super) fn schema() -> UpdateHandler<Box<dyn std::error::Error + Send + Sync
         .branch(case![State::ErrorState].endpoint(error_state))
         .branch(command_handler)
         .branch(cancel_handler)
-        .branch(case![State::RegisterName { msg_id, role_type, user_type }].endpoint(register_name))
-        .branch(case![State::RegisterOpsName { msg_id, role_type, user_type, name }].endpoint(register_ops_name))
-        .branch(dptree::filter_async(check_admin)
-            .branch(admin_command_handler)
-            .branch(case![State::ApplyEditName { msg_id, change_msg_id, application, admin }].endpoint(apply_edit_name))
-            .branch(case![State::ApplyEditOpsName { msg_id, change_msg_id, application, admin }].endpoint(apply_edit_ops_name))
-            .branch(case![State::UserEditName { msg_id, change_msg_id, user_details, prefix }].endpoint(user_edit_name))
-            .branch(case![State::UserEditOpsName { msg_id, change_msg_id, user_details, prefix }].endpoint(user_edit_ops_name))
-            .branch(case![State::PlanSelect].endpoint(plan_select))
-            .branch(case![State::UserSelect].endpoint(user_select))
-        )
+        .branch(case![State::RegisterName { msg_id, unit_id, role_type, user_type }].endpoint(register_name))
+        .branch(case![State::RegisterOpsName { msg_id, unit_id, role_type, user_type, name }].endpoint(register_ops_name))
+        .branch(admin_command_handler)
+        .branch(case![State::ApplyEditName { msg_id, change_msg_id, application, admin }].chain(require_role(AdminRole::UserManager)).endpoint(apply_edit_name))
+        .branch(case![State::ApplyEditOpsName { msg_id, change_msg_id, application, admin }].chain(require_role(AdminRole::UserManager)).endpoint(apply_edit_ops_name))
+        .branch(case![State::UserEditName { msg_id, change_msg_id, user_details, prefix }].chain(require_role(AdminRole::UserManager)).endpoint(user_edit_name))
+        .branch(case![State::UserEditOpsName { msg_id, change_msg_id, user_details, prefix }].chain(require_role(AdminRole::UserManager)).endpoint(user_edit_ops_name))
+        .branch(case![State::PlanSelect].chain(require_role(AdminRole::Planner)).endpoint(plan_select))
+        .branch(case![State::UserSelect].chain(require_role(AdminRole::UserManager)).endpoint(user_select))
+        .branch(case![State::ImportUpload { msg_id, prefix }].chain(require_role(AdminRole::Planner)).endpoint(import_upload))
+        .branch(case![State::ReminderAdd { msg_id, prefix }].chain(require_role(AdminRole::Planner)).endpoint(reminder_add))
+        .branch(case![State::CampaignAdd { msg_id, prefix }].chain(require_role(AdminRole::Planner)).endpoint(campaign_add))
+        .branch(case![State::RoleTypeAdd { msg_id, prefix, kind }].chain(require_role(AdminRole::UserManager)).endpoint(role_type_add))
         .branch(case![State::AvailabilityModifyRemarks { msg_id, change_msg_id, availability_entry, action, start, month }].endpoint(availability_modify_remarks))
         .branch(case![State::AvailabilityAdd { msg_id, prefix, avail_type }].endpoint(availability_add_message))
-        .branch(case![State::AvailabilityAddRemarks { msg_id, prefix, change_msg_id, avail_type, avail_dates }].endpoint(availability_add_remarks))
+        .branch(case![State::AvailabilityAddRemarks { msg_id, prefix, change_msg_id, avail_type, avail_dates, slot }].endpoint(availability_add_remarks))
+        .branch(case![State::MovementModifyField { msg_id, change_msg_id, movement_entry, field, start, month }].endpoint(movement_modify_field))
+        .branch(case![State::MovementAddDate { msg_id, prefix }].endpoint(movement_add_date))
+        .branch(case![State::MovementAddTime { msg_id, prefix, date }].endpoint(movement_add_time))
+        .branch(case![State::MovementAddActivity { msg_id, prefix, date, start_time, end_time }].endpoint(movement_add_activity))
+        .branch(case![State::MovementAddLocation { msg_id, prefix, date, start_time, end_time, activity }].endpoint(movement_add_location))
+        .branch(case![State::MovementAddRemarks { msg_id, prefix, date, start_time, end_time, activity, location }].endpoint(movement_add_remarks))
         //everything below is a catchall case to tell the user they should use a callback button rather than send a message
-        .branch(case![State::RegisterRole { msg_id, prefix }].endpoint(press_button_prompt))
-        .branch(case![State::RegisterType { msg_id, prefix, role_type }].endpoint(press_button_prompt))
-        .branch(case![State::RegisterComplete { msg_id, prefix, role_type, user_type, name, ops_name }].endpoint(press_button_prompt))
+        .branch(case![State::RegisterUnit { msg_id, prefix }].endpoint(press_button_prompt))
+        .branch(case![State::RegisterRole { msg_id, prefix, unit_id }].endpoint(press_button_prompt))
+        .branch(case![State::RegisterType { msg_id, prefix, unit_id, role_type }].endpoint(press_button_prompt))
+        .branch(case![State::RegisterComplete { msg_id, prefix, unit_id, role_type, user_type, name, ops_name }].endpoint(press_button_prompt))
         .branch(case![State::NotifySettings { notification_settings, chat_id, prefix, msg_id }].endpoint(press_button_prompt))
         .branch(case![State::ApplyView { msg_id, applications, prefix, start }].endpoint(press_button_prompt))
         .branch(case![State::ApplyEditPrompt { msg_id, prefix, application, admin }].endpoint(press_button_prompt))
@@ -337,53 +517,83 @@ pub(super) fn schema() -> UpdateHandler<Box<dyn std::error::Error + Send + Sync
         .branch(case![State::UserEditType { msg_id, change_msg_id, user_details, prefix }].endpoint(press_button_prompt))
         .branch(case![State::UserEditAdmin { msg_id, change_msg_id, user_details, prefix }].endpoint(press_button_prompt))
         .branch(case![State::UserEditDeleteConfirm { msg_id, change_msg_id, user_details, prefix }].endpoint(press_button_prompt))
+        .branch(case![State::UserEditPermissions { msg_id, change_msg_id, user_details, prefix, admin_roles }].endpoint(press_button_prompt))
         .branch(case![State::PlanView { msg_id, user_details, selected_date, availability_list, changes, role_type, prefix, start }].endpoint(press_button_prompt))
         .branch(case![State::PlanViewAvailability { msg_id, user_details, selected_date, changes, role_type, prefix, start }].endpoint(press_button_prompt))
         .branch(case![State::Saf100Select { msg_id, prefix }].endpoint(press_button_prompt))
         .branch(case![State::Saf100View { msg_id, availability_list, prefix, start, action }].endpoint(press_button_prompt))
         .branch(case![State::Saf100Confirm { msg_id, availability, prefix, start, action }].endpoint(press_button_prompt))
+        .branch(case![State::AttendanceView { msg_id, prefix, date, availability_list, changes, role_type, start }].endpoint(press_button_prompt))
+        .branch(case![State::ImportConfirm { msg_id, prefix, entries }].endpoint(press_button_prompt))
+        .branch(case![State::ReminderView { msg_id, prefix }].endpoint(press_button_prompt))
+        .branch(case![State::CampaignView { msg_id, prefix }].endpoint(press_button_prompt))
+        .branch(case![State::CampaignStatus { msg_id, prefix, campaign_id }].endpoint(press_button_prompt))
+        .branch(case![State::RoleTypeView { msg_id, prefix }].endpoint(press_button_prompt))
+        .branch(case![State::HistoryView { msg_id, prefix, tele_id, ops_name, start }].endpoint(press_button_prompt))
+        .branch(case![State::StatsView { msg_id, prefix, role_type, start, end }].endpoint(press_button_prompt))
         .branch(case![State::AvailabilityView { msg_id, prefix, month }].endpoint(press_button_prompt))
         .branch(case![State::AvailabilitySelect { msg_id, availability_list, prefix, start, month }].endpoint(press_button_prompt))
         .branch(case![State::AvailabilityModify { msg_id, prefix, availability_entry, action, start, month }].endpoint(press_button_prompt))
         .branch(case![State::AvailabilityModifyType { msg_id, prefix, change_msg_id, availability_entry, action, start, month }].endpoint(press_button_prompt))
         .branch(case![State::AvailabilityAddChangeType { msg_id, prefix, change_type_msg_id, avail_type }].endpoint(press_button_prompt))
         .branch(case![State::AvailabilityDeleteConfirm { msg_id, prefix, availability_entry, action, start, month }].endpoint(press_button_prompt))
-        .branch(case![State::ForecastView { msg_id, prefix, availability_list, role_type, start, end }].endpoint(press_button_prompt));
+        .branch(case![State::MovementView { msg_id, prefix, month }].endpoint(press_button_prompt))
+        .branch(case![State::MovementSelect { msg_id, movement_list, prefix, start, month }].endpoint(press_button_prompt))
+        .branch(case![State::MovementModify { msg_id, prefix, movement_entry, start, month }].endpoint(press_button_prompt))
+        .branch(case![State::MovementDeleteConfirm { msg_id, prefix, movement_entry, start, month }].endpoint(press_button_prompt))
+        .branch(case![State::ForecastView { msg_id, prefix, availability_list, role_type, start, end }].endpoint(press_button_prompt))
+        .branch(case![State::CalendarView { msg_id, prefix }].endpoint(press_button_prompt));
     
 
     let callback_query_handler = Update::filter_callback_query()
-        .branch(case![State::RegisterRole { msg_id, prefix }].endpoint(register_role))
-        .branch(case![State::RegisterType { msg_id, prefix, role_type }].endpoint(register_type))
-        .branch(case![State::RegisterComplete { msg_id, prefix, role_type, user_type, name, ops_name }].endpoint(register_complete))
-        .branch(dptree::filter_async(check_admin_callback)
-            .branch(case![State::NotifySettings { notification_settings, chat_id, prefix, msg_id }].endpoint(notify_settings))
-            .branch(case![State::ApplyView { msg_id, applications, prefix, start }].endpoint(apply_view))
-            .branch(case![State::ApplyEditPrompt { msg_id, prefix, application, admin }].endpoint(apply_edit_prompt))
-            .branch(case![State::ApplyEditRole { msg_id, prefix, change_msg_id, application, admin }].endpoint(apply_edit_role))
-            .branch(case![State::ApplyEditType { msg_id, prefix, change_msg_id, application, admin }].endpoint(apply_edit_type))
-            .branch(case![State::ApplyEditAdmin { msg_id, prefix, change_msg_id, application, admin }].endpoint(apply_edit_admin))
-            .branch(case![State::UserEdit { msg_id, user_details, prefix }].endpoint(user_edit_prompt))
-            .branch(case![State::UserEditRole { msg_id, change_msg_id, user_details, prefix }].endpoint(user_edit_role))
-            .branch(case![State::UserEditType { msg_id, change_msg_id, user_details, prefix }].endpoint(user_edit_type))
-            .branch(case![State::UserEditAdmin { msg_id, change_msg_id, user_details, prefix }].endpoint(user_edit_admin))
-            .branch(case![State::UserEditDeleteConfirm { msg_id, change_msg_id, user_details, prefix }].endpoint(user_edit_delete))
-            .branch(case![State::PlanView { msg_id, user_details, selected_date, availability_list, changes, role_type, prefix, start }].endpoint(plan_view))
-            .branch(case![State::PlanViewAvailability { msg_id, user_details, selected_date, changes, role_type, prefix, start }].endpoint(plan_view_availability))
-            .branch(case![State::Saf100Select { msg_id, prefix }].endpoint(saf100_select))
-            .branch(case![State::Saf100View { msg_id, availability_list, prefix, start, action }].endpoint(saf100_view))
-            .branch(case![State::Saf100Confirm { msg_id, availability, prefix, start, action }].endpoint(saf100_confirm))
-        )
+        .branch(dptree::filter(is_reminder_response).endpoint(acknowledge))
+        .branch(case![State::RegisterUnit { msg_id, prefix }].endpoint(register_unit))
+        .branch(case![State::RegisterRole { msg_id, prefix, unit_id }].endpoint(register_role))
+        .branch(case![State::RegisterType { msg_id, prefix, unit_id, role_type }].endpoint(register_type))
+        .branch(case![State::RegisterComplete { msg_id, prefix, unit_id, role_type, user_type, name, ops_name }].endpoint(register_complete))
+        .branch(case![State::NotifySettings { notification_settings, chat_id, prefix, msg_id }].chain(require_role_callback(AdminRole::NotificationManager)).endpoint(notify_settings))
+        .branch(case![State::ApplyView { msg_id, applications, prefix, start }].chain(require_role_callback(AdminRole::UserManager)).endpoint(apply_view))
+        .branch(case![State::ApplyEditPrompt { msg_id, prefix, application, admin }].chain(require_role_callback(AdminRole::UserManager)).endpoint(apply_edit_prompt))
+        .branch(case![State::ApplyEditRole { msg_id, prefix, change_msg_id, application, admin }].chain(require_role_callback(AdminRole::UserManager)).endpoint(apply_edit_role))
+        .branch(case![State::ApplyEditType { msg_id, prefix, change_msg_id, application, admin }].chain(require_role_callback(AdminRole::UserManager)).endpoint(apply_edit_type))
+        .branch(case![State::ApplyEditAdmin { msg_id, prefix, change_msg_id, application, admin }].chain(require_role_callback(AdminRole::UserManager)).endpoint(apply_edit_admin))
+        .branch(case![State::UserEdit { msg_id, user_details, prefix }].chain(require_role_callback(AdminRole::UserManager)).endpoint(user_edit_prompt))
+        .branch(case![State::UserEditRole { msg_id, change_msg_id, user_details, prefix }].chain(require_role_callback(AdminRole::UserManager)).endpoint(user_edit_role))
+        .branch(case![State::UserEditType { msg_id, change_msg_id, user_details, prefix }].chain(require_role_callback(AdminRole::UserManager)).endpoint(user_edit_type))
+        .branch(case![State::UserEditAdmin { msg_id, change_msg_id, user_details, prefix }].chain(require_role_callback(AdminRole::UserManager)).endpoint(user_edit_admin))
+        .branch(case![State::UserEditDeleteConfirm { msg_id, change_msg_id, user_details, prefix }].chain(require_role_callback(AdminRole::UserManager)).endpoint(user_edit_delete))
+        .branch(case![State::UserEditPermissions { msg_id, change_msg_id, user_details, prefix, admin_roles }].chain(require_role_callback(AdminRole::UserManager)).endpoint(user_edit_permissions))
+        .branch(case![State::PlanView { msg_id, user_details, selected_date, availability_list, changes, role_type, prefix, start }].chain(require_role_callback(AdminRole::Planner)).endpoint(plan_view))
+        .branch(case![State::PlanViewAvailability { msg_id, user_details, selected_date, changes, role_type, prefix, start }].chain(require_role_callback(AdminRole::Planner)).endpoint(plan_view_availability))
+        .branch(case![State::Saf100Select { msg_id, prefix }].chain(require_role_callback(AdminRole::Saf100Clerk)).endpoint(saf100_select))
+        .branch(case![State::Saf100View { msg_id, availability_list, prefix, start, action }].chain(require_role_callback(AdminRole::Saf100Clerk)).endpoint(saf100_view))
+        .branch(case![State::Saf100Confirm { msg_id, availability, prefix, start, action }].chain(require_role_callback(AdminRole::Saf100Clerk)).endpoint(saf100_confirm))
+        .branch(case![State::AttendanceView { msg_id, prefix, date, availability_list, changes, role_type, start }].chain(require_role_callback(AdminRole::Planner)).endpoint(attendance_view))
+        .branch(case![State::ImportConfirm { msg_id, prefix, entries }].chain(require_role_callback(AdminRole::Planner)).endpoint(import_confirm))
+        .branch(case![State::ReminderView { msg_id, prefix }].chain(require_role_callback(AdminRole::Planner)).endpoint(reminder_view))
+        .branch(case![State::CampaignView { msg_id, prefix }].chain(require_role_callback(AdminRole::Planner)).endpoint(campaign_view))
+        .branch(case![State::CampaignStatus { msg_id, prefix, campaign_id }].chain(require_role_callback(AdminRole::Planner)).endpoint(campaign_status))
+        .branch(case![State::RoleTypeView { msg_id, prefix }].chain(require_role_callback(AdminRole::UserManager)).endpoint(role_type_view))
+        .branch(case![State::HistoryView { msg_id, prefix, tele_id, ops_name, start }].chain(require_role_callback(AdminRole::UserManager)).endpoint(history_view))
+        .branch(case![State::StatsView { msg_id, prefix, role_type, start, end }].chain(require_role_callback(AdminRole::Planner)).endpoint(stats_view))
         .branch(case![State::AvailabilityView { msg_id, prefix, month }].endpoint(availability_view))
         .branch(case![State::AvailabilitySelect { msg_id, availability_list, prefix, start, month }].endpoint(availability_select))
         .branch(case![State::AvailabilityModify { msg_id, prefix, availability_entry, action, start, month }].endpoint(availability_modify))
         .branch(case![State::AvailabilityModifyType { msg_id, prefix, change_msg_id, availability_entry, action, start, month }].endpoint(availability_modify_type))
         .branch(case![State::AvailabilityAdd { msg_id, prefix, avail_type }].endpoint(availability_add_callback))
         .branch(case![State::AvailabilityAddChangeType { msg_id, prefix, change_type_msg_id, avail_type }].endpoint(availability_add_change_type))
-        .branch(case![State::AvailabilityAddRemarks { msg_id, prefix, change_msg_id, avail_type, avail_dates }].endpoint(availability_add_complete))
+        .branch(case![State::AvailabilityAddRemarks { msg_id, prefix, change_msg_id, avail_type, avail_dates, slot }].endpoint(availability_add_complete))
         .branch(case![State::AvailabilityDeleteConfirm { msg_id, prefix, availability_entry, action, start, month }].endpoint(availability_delete_confirm))
-        .branch(case![State::ForecastView { msg_id, prefix, availability_list, role_type, start, end }].endpoint(forecast_view));
+        .branch(case![State::MovementView { msg_id, prefix, month }].endpoint(movement_view))
+        .branch(case![State::MovementSelect { msg_id, movement_list, prefix, start, month }].endpoint(movement_select))
+        .branch(case![State::MovementModify { msg_id, prefix, movement_entry, start, month }].endpoint(movement_modify))
+        .branch(case![State::MovementDeleteConfirm { msg_id, prefix, movement_entry, start, month }].endpoint(movement_delete_confirm))
+        .branch(case![State::MovementAddLocation { msg_id, prefix, date, start_time, end_time, activity }].endpoint(movement_add_location_callback))
+        .branch(case![State::MovementAddRemarks { msg_id, prefix, date, start_time, end_time, activity, location }].endpoint(movement_add_complete))
+        .branch(case![State::ForecastView { msg_id, prefix, availability_list, role_type, start, end }].endpoint(forecast_view))
+        .branch(case![State::CalendarView { msg_id, prefix }].endpoint(calendar_view));
 
-    dialogue::enter::<Update, InMemStorage<State>, State, _>()
+    dialogue::enter::<Update, PgStorage, State, _>()
         .branch(message_handler)
         .branch(callback_query_handler)
         .branch(endpoint(invalid_state))
@@ -473,7 +683,7 @@ async fn check_registered(bot: Bot, msg: Message, pool: PgPool) -> bool {
     }
 }
 
-async fn check_admin(bot: Bot, dialogue: MyDialogue, msg: Message, pool: PgPool) -> bool {
+async fn check_admin_role(bot: Bot, dialogue: MyDialogue, msg: Message, pool: PgPool, admin_role: AdminRole) -> bool {
     // Early return if the message has no sender (msg.from() is None)
     let user = if let Some(user) = msg.from {
         user
@@ -491,52 +701,47 @@ async fn check_admin(bot: Bot, dialogue: MyDialogue, msg: Message, pool: PgPool)
         false
     };
 
-    // Check if the telegram ID exists in the database
-    match controllers::user::user_exists_tele_id(&pool, user.id.0).await{
-        Ok(true) => {
-            // Check if the user is an admin
-            match controllers::user::get_user_by_tele_id(&pool, user.id.0).await {
-                Ok(retrieved_usr) => {
-                    // Set menu buttons based on the user's admin status
-                    // Determine the kind of chat the message was sent in
-                    let is_public_chat = matches!(&msg.chat.kind, ChatKind::Public(_));
-                    set_menu_buttons(bot, dialogue.chat_id(), user.id, retrieved_usr.admin, is_public_chat).await;
-                    retrieved_usr.admin
-                }
-                Err(_) => handle_error().await,
-            }
-        },
-        Ok(false) => false,
+    // Fetch every role the user holds, admins hold all of them
+    match controllers::permissions::get_effective_admin_roles_by_tele_id(&pool, user.id.0).await {
+        Ok(admin_roles) => {
+            // Set menu buttons based on the user's roles
+            // Determine the kind of chat the message was sent in
+            let is_public_chat = matches!(&msg.chat.kind, ChatKind::Public(_));
+            set_menu_buttons(bot, dialogue.chat_id(), user.id, &admin_roles, is_public_chat).await;
+            admin_roles.contains(&admin_role)
+        }
         Err(_) => handle_error().await
     }
 }
 
-async fn check_admin_callback(bot: Bot, dialogue: MyDialogue, q: CallbackQuery, pool: PgPool) -> bool {
-    // Helper function to log and return false on any errors
-    let handle_error = || async {
-        send_msg(
-            bot.send_message(dialogue.chat_id(), "Error occurred accessing the database"),
-            &q.from.username
-        ).await;
-        false
-    };
-
-    // Check if the telegram ID exists in the database
-    match controllers::user::user_exists_tele_id(&pool, q.from.id.0).await{
-        Ok(true) => {
-            // Check if the user is an admin
-            match controllers::user::get_user_by_tele_id(&pool, q.from.id.0).await {
-                Ok(retrieved_usr) => {
-                    retrieved_usr.admin
-                }
-                Err(_) => handle_error().await,
-            }
-        },
-        Ok(false) => false,
-        Err(_) => handle_error().await
+async fn check_admin_role_callback(bot: Bot, dialogue: MyDialogue, q: CallbackQuery, pool: PgPool, admin_role: AdminRole) -> bool {
+    // Check if the user holds the role, admins hold all of them
+    match controllers::permissions::has_admin_role(&pool, q.from.id.0, admin_role).await {
+        Ok(has_role) => has_role,
+        Err(_) => {
+            send_msg(
+                bot.send_message(dialogue.chat_id(), "Error occurred accessing the database"),
+                &q.from.username
+            ).await;
+            false
+        }
     }
 }
 
+// Only continues down the handler if the sender of the message holds the role
+fn require_role(admin_role: AdminRole) -> UpdateHandler<Box<dyn std::error::Error + Send + Sync + 'static>> {
+    dptree::filter_async(move |bot: Bot, dialogue: MyDialogue, msg: Message, pool: PgPool| {
+        check_admin_role(bot, dialogue, msg, pool, admin_role)
+    })
+}
+
+// Only continues down the handler if the user pressing the button holds the role
+fn require_role_callback(admin_role: AdminRole) -> UpdateHandler<Box<dyn std::error::Error + Send + Sync + 'static>> {
+    dptree::filter_async(move |bot: Bot, dialogue: MyDialogue, q: CallbackQuery, pool: PgPool| {
+        check_admin_role_callback(bot, dialogue, q, pool, admin_role)
+    })
+}
+
 async fn error_state(bot: Bot, dialogue: MyDialogue) -> HandlerResult {
     let chat_id = dialogue.chat_id();
 
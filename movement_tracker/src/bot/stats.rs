@@ -0,0 +1,328 @@
+use crate::APP_TIMEZONE;
+use chrono::{NaiveDate, Utc};
+
+use sqlx::PgPool;
+
+use teloxide::prelude::*;
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, InputFile, MessageId, ParseMode};
+
+use crate::bot::state::State;
+use crate::bot::{handle_error, log_try_remove_markup, match_callback_data, retrieve_callback_data, send_msg, send_or_edit_msg, HandlerResult, MyDialogue};
+use crate::types::{RoleType, UserStats};
+use crate::{controllers, log_endpoint_hit, now, utils};
+
+use serde::{Deserialize, Serialize};
+use strum_macros::EnumProperty;
+use callback_data::CallbackData;
+use callback_data::CallbackDataHandler;
+
+// Represents callback actions with optional associated data.
+#[derive(Debug, Clone, Serialize, Deserialize, EnumProperty, CallbackData)]
+enum StatsCallbackData {
+    // View range Actions
+    ViewThisMonth,
+    ViewLastMonth,
+    IncPrevMonth,
+
+    // Select Role Actions
+    ChangeRole { role_type: RoleType },
+
+    // Export Action
+    Export,
+
+    // Completion Action
+    Done
+}
+
+// Percentage of the offered days which were planned
+fn planned_percentage(stats: &UserStats) -> Option<i64> {
+    if stats.offered > 0 {
+        Some(stats.planned * 100 / stats.offered)
+    } else {
+        None
+    }
+}
+
+fn generate_stats_csv(stats_list: &[UserStats], start: NaiveDate, end: NaiveDate) -> String {
+    let mut output = String::from("start,end,ops_name,role,usr_type,offered,planned,attended,saf100,no_shows\n");
+
+    for stats in stats_list {
+        output.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{},{}\n",
+            start.format("%Y-%m-%d"),
+            end.format("%Y-%m-%d"),
+            utils::escape_csv_field(&stats.ops_name),
+            stats.role_type.as_ref(),
+            stats.usr_type.as_ref(),
+            stats.offered,
+            stats.planned,
+            stats.attended,
+            stats.saf100,
+            stats.no_shows
+        ));
+    }
+
+    output
+}
+
+async fn send_stats_export(
+    bot: &Bot,
+    chat_id: ChatId,
+    role_type: &RoleType,
+    stats_list: &[UserStats],
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Option<MessageId> {
+    let file_name = format!(
+        "stats_{}_{}_{}.csv",
+        role_type.as_ref().to_lowercase(),
+        start.format("%Y%m%d"),
+        end.format("%Y%m%d")
+    );
+    let document = InputFile::memory(generate_stats_csv(stats_list, start, end).into_bytes()).file_name(file_name);
+
+    match bot.send_document(chat_id, document)
+        .caption(format!(
+            "Planning stats for {} from {} to {}",
+            role_type.as_ref(),
+            start.format("%b-%d-%Y"),
+            end.format("%b-%d-%Y")
+        ))
+        .await
+    {
+        Ok(msg) => Some(msg.id),
+        Err(e) => {
+            log::error!("Failed to send stats export to chat ({}): {}", chat_id, e);
+            None
+        }
+    }
+}
+
+fn get_stats_text(role_type: &RoleType, stats_list: &[UserStats], start: NaiveDate, end: NaiveDate) -> String {
+    let mut output_text = format!(
+        "*Planning stats for role:* __{}__ *from* __{}__ *to* __{}__\n\n",
+        utils::escape_special_characters(role_type.as_ref()),
+        start.format("%b\\-%d\\-%Y"),
+        end.format("%b\\-%d\\-%Y")
+    );
+
+    if stats_list.is_empty() {
+        output_text.push_str("No users with this role\\.\n");
+        return output_text;
+    }
+
+    // Align the columns in a monospace block
+    let max_len = stats_list.iter()
+        .map(|stats| stats.ops_name.len())
+        .max()
+        .unwrap_or(0)
+        .max("OPS NAME".len());
+
+    output_text.push_str("```\n");
+    output_text.push_str(&format!(
+        "{:<width$} {:>3} {:>3} {:>3} {:>4} {:>4} {:>4}\n",
+        "OPS NAME", "OFF", "PLN", "ATT", "S100", "MISS", "PLN%",
+        width = max_len
+    ));
+    for stats in stats_list {
+        // SAF100 only applies to NS users
+        let saf100_str = if stats.usr_type.is_ns() { stats.saf100.to_string() } else { "-".into() };
+        let percentage_str = planned_percentage(stats).map_or("-".into(), |percentage| format!("{}%", percentage));
+        output_text.push_str(&format!(
+            "{:<width$} {:>3} {:>3} {:>3} {:>4} {:>4} {:>4}\n",
+            stats.ops_name,
+            stats.offered,
+            stats.planned,
+            stats.attended,
+            saf100_str,
+            stats.no_shows,
+            percentage_str,
+            width = max_len
+        ));
+    }
+    output_text.push_str("```\n");
+    output_text.push_str("OFF: days offered, PLN: days planned, ATT: days attended, S100: SAF100 issued, MISS: planned days not attended\n");
+
+    output_text.push_str(
+        format!(
+            "\nUpdated: {}",
+            now!().format("%d%m %H%M\\.%S")
+        ).as_ref(),
+    );
+
+    output_text
+}
+
+async fn display_stats(
+    bot: &Bot,
+    chat_id: ChatId,
+    username: &Option<String>,
+    (role_type, role_types): (&RoleType, Vec<RoleType>),
+    (stats_list, start, end): (&[UserStats], NaiveDate, NaiveDate),
+    prefix: &str,
+    edit_msg: Option<MessageId>
+) -> Option<MessageId> {
+    let change_view_roles: Vec<InlineKeyboardButton> = role_types.into_iter()
+        .filter(|role| role != role_type)
+        .map(|role| InlineKeyboardButton::callback(
+            format!("VIEW {}", role.as_ref()),
+            StatsCallbackData::ChangeRole { role_type: role }.to_callback_data(prefix)
+        ))
+        .collect();
+
+    let this_month_str = now!().format("%b").to_string().to_uppercase();
+    let last_month_str = utils::add_month_safe(now!().date_naive(), -1).format("%b").to_string().to_uppercase();
+    let options = vec![
+        change_view_roles,
+        [(this_month_str.as_str(), StatsCallbackData::ViewThisMonth), (last_month_str.as_str(), StatsCallbackData::ViewLastMonth), ("-1 MONTH", StatsCallbackData::IncPrevMonth)]
+            .into_iter()
+            .map(|(text, data)| InlineKeyboardButton::callback(text, data.to_callback_data(prefix)))
+            .collect(),
+        [("DONE", StatsCallbackData::Done), ("EXPORT", StatsCallbackData::Export)]
+            .into_iter()
+            .map(|(text, data)| InlineKeyboardButton::callback(text, data.to_callback_data(prefix)))
+            .collect(),
+    ];
+
+    send_or_edit_msg(
+        bot, chat_id, username, edit_msg,
+        get_stats_text(role_type, stats_list, start, end),
+        Some(InlineKeyboardMarkup::new(options)),
+        Some(ParseMode::MarkdownV2)
+    ).await
+}
+
+pub(super) async fn stats(bot: Bot, dialogue: MyDialogue, msg: Message, pool: PgPool) -> HandlerResult {
+    log_endpoint_hit!(dialogue.chat_id(), "stats", "Command", msg);
+    // Early return if the message has no sender (msg.from() is None)
+    let user = if let Some(user) = msg.from {
+        user
+    } else {
+        log::error!("Cannot get user from message");
+        dialogue.update(State::ErrorState).await?;
+        return Ok(());
+    };
+
+    // Get the user in the database
+    match controllers::user::get_user_by_tele_id(&pool, user.id.0).await {
+        Ok(retrieved_user) => {
+            // start with this month for the planner's own role
+            let role_type = retrieved_user.role_type;
+            let (start, end) = utils::this_month_bound();
+            match (
+                controllers::stats::get_user_stats(&pool, Some(retrieved_user.unit_id), Some(&role_type), start, end, now!().date_naive()).await,
+                controllers::crew_types::get_role_types(&pool).await
+            ) {
+                (Ok(stats_list), Ok(role_types)) => {
+                    let prefix = utils::generate_prefix(utils::CALLBACK_PREFIX_LEN);
+                    match display_stats(&bot, dialogue.chat_id(), &user.username, (&role_type, role_types), (&stats_list, start, end), &prefix, None).await {
+                        None => dialogue.update(State::ErrorState).await?,
+                        Some(msg_id) => dialogue.update(State::StatsView { msg_id, prefix, role_type, start, end }).await?
+                    };
+                }
+                _ => handle_error(&bot, &dialogue, dialogue.chat_id(), &user.username).await
+            }
+        },
+        Err(_) => handle_error(&bot, &dialogue, dialogue.chat_id(), &user.username).await
+    }
+
+    Ok(())
+}
+
+pub(super) async fn stats_view(
+    bot: Bot,
+    dialogue: MyDialogue,
+    (msg_id, prefix, role_type, start, end): (MessageId, String, RoleType, NaiveDate, NaiveDate),
+    q: CallbackQuery,
+    pool: PgPool
+) -> HandlerResult {
+    log_endpoint_hit!(dialogue.chat_id(), "stats_view", "Callback", q,
+        "MessageId" => msg_id,
+        "Prefix" => prefix,
+        "RoleType" => role_type,
+        "Start" => start,
+        "End" => end
+    );
+
+    // Extract the callback data
+    let data = match retrieve_callback_data(&bot, dialogue.chat_id(), &q).await {
+        Ok(data) => data,
+        Err(_) => { return Ok(()); }
+    };
+
+    // Acknowledge the callback to remove the loading state
+    if let Err(e) = bot.answer_callback_query(q.id).await {
+        log::error!("Failed to answer callback query: {}", e);
+    }
+
+    // Deserialize the callback data into the enum
+    let callback = match match_callback_data(&bot, dialogue.chat_id(), &q.from.username, &data, &prefix).await {
+        Ok(callback) => callback,
+        Err(_) => { return Ok(()); }
+    };
+
+    // The stats only cover the user's own unit
+    let unit_id = match controllers::user::get_unit_id_by_tele_id(&pool, q.from.id.0).await {
+        Ok(unit_id) => unit_id,
+        Err(_) => {
+            handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await;
+            return Ok(());
+        }
+    };
+    let today = now!().date_naive();
+
+    let mut new_role = role_type.clone();
+    let mut new_start = start;
+    let mut new_end = end;
+
+    match callback {
+        StatsCallbackData::ChangeRole { role_type } => {
+            new_role = role_type;
+        }
+        StatsCallbackData::Done => {
+            log_try_remove_markup(&bot, dialogue.chat_id(), msg_id).await;
+            dialogue.update(State::Start).await?;
+            return Ok(());
+        }
+        StatsCallbackData::Export => {
+            match controllers::stats::get_user_stats(&pool, Some(unit_id), Some(&role_type), start, end, today).await {
+                Ok(stats_list) => {
+                    if send_stats_export(&bot, dialogue.chat_id(), &role_type, &stats_list, start, end).await.is_none() {
+                        send_msg(
+                            bot.send_message(dialogue.chat_id(), "Failed to export the stats. Please try again."),
+                            &q.from.username,
+                        ).await;
+                    }
+                }
+                Err(_) => handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await
+            }
+            return Ok(());
+        }
+        StatsCallbackData::ViewThisMonth => {
+            (new_start, new_end) = utils::this_month_bound();
+        }
+        StatsCallbackData::ViewLastMonth => {
+            let (this_month_start, _) = utils::this_month_bound();
+            new_start = utils::add_month_safe(this_month_start, -1);
+            new_end = utils::last_day_of_month(new_start);
+        }
+        StatsCallbackData::IncPrevMonth => {
+            new_start = utils::add_month_safe(new_start, -1);
+        }
+    }
+
+    match (
+        controllers::stats::get_user_stats(&pool, Some(unit_id), Some(&new_role), new_start, new_end, today).await,
+        controllers::crew_types::get_role_types(&pool).await
+    ) {
+        (Ok(stats_list), Ok(role_types)) => {
+            match display_stats(&bot, dialogue.chat_id(), &q.from.username, (&new_role, role_types), (&stats_list, new_start, new_end), &prefix, Some(msg_id)).await {
+                None => dialogue.update(State::ErrorState).await?,
+                Some(new_msg_id) => dialogue.update(State::StatsView { msg_id: new_msg_id, prefix, role_type: new_role, start: new_start, end: new_end }).await?
+            };
+        }
+        _ => handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await
+    }
+
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sqlx::PgPool;
+use teloxide::dispatching::dialogue::Storage;
+use teloxide::prelude::*;
+
+use crate::controllers;
+
+// Dialogue storage backed by the application database, so that dialogues survive bot restarts
+pub(crate) struct PgStorage {
+    pool: PgPool,
+    ttl_secs: i64,
+}
+
+impl PgStorage {
+    pub(crate) fn new(pool: PgPool, ttl_secs: i64) -> Arc<Self> {
+        Arc::new(Self { pool, ttl_secs })
+    }
+}
+
+#[derive(Debug)]
+pub(crate) enum PgStorageError {
+    SerdeError(serde_json::Error),
+    DatabaseError(sqlx::Error),
+    DialogueNotFound,
+}
+
+impl Display for PgStorageError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PgStorageError::SerdeError(e) => write!(f, "dialogue serialization error: {}", e),
+            PgStorageError::DatabaseError(e) => write!(f, "dialogue database error: {}", e),
+            PgStorageError::DialogueNotFound => write!(f, "dialogue not found"),
+        }
+    }
+}
+
+impl std::error::Error for PgStorageError {}
+
+impl<D> Storage<D> for PgStorage
+where
+    D: Serialize + DeserializeOwned + Send + 'static,
+{
+    type Error = PgStorageError;
+
+    fn remove_dialogue(self: Arc<Self>, chat_id: ChatId) -> BoxFuture<'static, Result<(), Self::Error>>
+    where
+        D: Send + 'static,
+    {
+        async move {
+            match controllers::dialogue::remove_dialogue(&self.pool, chat_id.0).await {
+                Ok(true) => Ok(()),
+                Ok(false) => Err(PgStorageError::DialogueNotFound),
+                Err(e) => Err(PgStorageError::DatabaseError(e)),
+            }
+        }
+            .boxed()
+    }
+
+    fn update_dialogue(self: Arc<Self>, chat_id: ChatId, dialogue: D) -> BoxFuture<'static, Result<(), Self::Error>>
+    where
+        D: Send + 'static,
+    {
+        async move {
+            let value = serde_json::to_value(&dialogue).map_err(PgStorageError::SerdeError)?;
+            controllers::dialogue::update_dialogue(&self.pool, chat_id.0, value)
+                .await
+                .map_err(PgStorageError::DatabaseError)
+        }
+            .boxed()
+    }
+
+    fn get_dialogue(self: Arc<Self>, chat_id: ChatId) -> BoxFuture<'static, Result<Option<D>, Self::Error>> {
+        async move {
+            let value = controllers::dialogue::get_dialogue(&self.pool, chat_id.0, self.ttl_secs)
+                .await
+                .map_err(PgStorageError::DatabaseError)?;
+
+            match value {
+                Some(value) => match serde_json::from_value(value) {
+                    Ok(dialogue) => Ok(Some(dialogue)),
+                    Err(e) => {
+                        // A dialogue saved by an older version of the bot may no longer deserialize; start afresh
+                        log::warn!("Discarding undecodable dialogue for chat ({}): {}", chat_id, e);
+                        Ok(None)
+                    }
+                },
+                None => Ok(None),
+            }
+        }
+            .boxed()
+    }
+}
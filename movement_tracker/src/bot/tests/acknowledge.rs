@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+use chrono::{Duration, Utc};
+use sqlx::types::Uuid;
+
+use super::harness::Harness;
+use crate::types::{Ack, Ict, PlanStatus};
+use crate::{controllers, now, APP_TIMEZONE};
+
+const PLANNER: u64 = 1001;
+const USER: u64 = 3001;
+const STANDBY_USER: u64 = 3002;
+
+// Brings forward the reminder sent a minute after the entry is planned
+async fn schedule_reminder(harness: &Harness, avail_id: Uuid) {
+    sqlx::query("UPDATE scheduled_notifications SET scheduled_time = NOW() WHERE avail_id = $1 AND is_valid = TRUE AND scheduled_time <= NOW() + INTERVAL '1 min'")
+        .bind(avail_id)
+        .execute(harness.pool())
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn acknowledge_reminder_shows_in_plan_view() {
+    let mut harness = Harness::start().await;
+    harness.seed_user(PLANNER, "PLANNER", "PILOT", "ACTIVE", true).await;
+    harness.seed_user(USER, "GOLF", "PILOT", "ACTIVE", false).await;
+    let date = now!().date_naive() + Duration::days(10);
+    let planned = controllers::scheduling::add_user_avail(harness.pool(), PLANNER, USER, (date, None), &Ict::LIVE, None, Some(true)).await.unwrap();
+
+    schedule_reminder(&harness, planned.id).await;
+    harness.run_notifier().await;
+    harness.assert_sent(USER, "Reminder: Upcoming Planned Event");
+    harness.press(USER, "ACKNOWLEDGE").await;
+
+    harness.assert_sent(USER, "Acknowledged for");
+    assert!(harness.buttons(USER).is_empty());
+    let saved = controllers::scheduling::get_availability_details_by_uuid(harness.pool(), planned.id).await.unwrap();
+    assert_eq!(saved.ack, Some(Ack::Acknowledged));
+
+    harness.send_text(PLANNER, &format!("/plan {}", date.format("%Y %b %d"))).await;
+    harness.assert_sent(PLANNER, "PLANNED ✅ ACKNOWLEDGED");
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn decline_reminder_raises_conflict() {
+    let mut harness = Harness::start().await;
+    harness.seed_user(PLANNER, "PLANNER", "PILOT", "ACTIVE", true).await;
+    harness.seed_user(USER, "GOLF", "PILOT", "ACTIVE", false).await;
+    harness.seed_user(STANDBY_USER, "HOTEL", "PILOT", "ACTIVE", false).await;
+    let unit_id = harness.unit_id().await;
+    sqlx::query("INSERT INTO notification_settings (chat_id, notif_conflict, unit_id) VALUES ($1, TRUE, $2)")
+        .bind(PLANNER as i64)
+        .bind(unit_id)
+        .execute(harness.pool())
+        .await
+        .unwrap();
+
+    let date = now!().date_naive() + Duration::days(10);
+    let planned = controllers::scheduling::add_user_avail(harness.pool(), USER, USER, (date, None), &Ict::LIVE, None, None).await.unwrap();
+    let standby = controllers::scheduling::add_user_avail(harness.pool(), STANDBY_USER, STANDBY_USER, (date, None), &Ict::LIVE, None, None).await.unwrap();
+    controllers::scheduling::set_plan_status_multiple(
+        harness.pool(), PLANNER,
+        HashMap::from([(planned.id, PlanStatus::Planned), (standby.id, PlanStatus::Standby)])
+    ).await.unwrap();
+
+    schedule_reminder(&harness, planned.id).await;
+    harness.run_notifier().await;
+    harness.press(USER, "CAN'T MAKE IT").await;
+
+    harness.assert_sent(USER, "The planners have been told");
+    harness.assert_sent(PLANNER, "CAN'T MAKE IT");
+    harness.assert_sent(PLANNER, "Standby: `HOTEL`");
+
+    // The declined planned user can be replaced by the standby
+    harness.send_text(PLANNER, &format!("/plan {}", date.format("%Y %b %d"))).await;
+    harness.assert_sent(PLANNER, "DECLINED");
+    harness.press(PLANNER, "PROMOTE STANDBY").await;
+    harness.press(PLANNER, "DONE").await;
+    let promoted = controllers::scheduling::get_availability_details_by_uuid(harness.pool(), standby.id).await.unwrap();
+    assert!(promoted.planned);
+    let replaced = controllers::scheduling::get_availability_details_by_uuid(harness.pool(), planned.id).await.unwrap();
+    assert!(!replaced.planned && replaced.ack.is_none());
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn reminder_response_requires_owner() {
+    let harness = Harness::start().await;
+    harness.seed_user(PLANNER, "PLANNER", "PILOT", "ACTIVE", true).await;
+    harness.seed_user(USER, "GOLF", "PILOT", "ACTIVE", false).await;
+    let date = now!().date_naive() + Duration::days(10);
+    let planned = controllers::scheduling::add_user_avail(harness.pool(), PLANNER, USER, (date, None), &Ict::LIVE, None, Some(true)).await.unwrap();
+
+    let other = controllers::scheduling::set_availability_ack(harness.pool(), PLANNER, planned.id, Ack::Declined).await.unwrap();
+    assert!(other.is_none());
+    let owner = controllers::scheduling::set_availability_ack(harness.pool(), USER, planned.id, Ack::Declined).await.unwrap();
+    assert_eq!(owner.unwrap().ack, Some(Ack::Declined));
+}
@@ -0,0 +1,68 @@
+use super::harness::Harness;
+use crate::controllers;
+use crate::types::{RoleType, UsrType};
+
+const ADMIN: u64 = 1001;
+const APPLICANT: u64 = 2001;
+
+async fn seed_application(harness: &Harness) {
+    controllers::apply::apply_user(
+        harness.pool(),
+        APPLICANT,
+        "Applicant".into(),
+        "Bravo Lim".into(),
+        "BRAVO".into(),
+        RoleType::new("ARO"),
+        UsrType::new("NS"),
+        harness.unit_id().await
+    ).await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn approve_adds_user() {
+    let mut harness = Harness::start().await;
+    harness.seed_user(ADMIN, "ADMIN", "PILOT", "ACTIVE", true).await;
+    seed_application(&harness).await;
+
+    harness.send_text(ADMIN, "/approve").await;
+    harness.assert_sent(ADMIN, "Pending applications: 1");
+    harness.press(ADMIN, "BRAVO").await;
+    harness.press(ADMIN, "APPROVE").await;
+
+    harness.assert_sent(ADMIN, "Approved application");
+    harness.assert_sent(APPLICANT, "Registered successfully.");
+    let user = controllers::user::get_user_by_tele_id(harness.pool(), APPLICANT).await.unwrap();
+    assert_eq!(user.ops_name, "BRAVO");
+    assert_eq!(user.role_type.as_ref(), "ARO");
+    assert!(user.usr_type.is_ns());
+    assert!(!user.admin);
+    assert!(!controllers::apply::apply_exists_tele_id(harness.pool(), APPLICANT).await.unwrap());
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn reject_removes_application() {
+    let mut harness = Harness::start().await;
+    harness.seed_user(ADMIN, "ADMIN", "PILOT", "ACTIVE", true).await;
+    seed_application(&harness).await;
+
+    harness.send_text(ADMIN, "/approve").await;
+    harness.press(ADMIN, "BRAVO").await;
+    harness.press(ADMIN, "REJECT").await;
+
+    harness.assert_sent(ADMIN, "Application rejected.");
+    assert!(!controllers::apply::apply_exists_tele_id(harness.pool(), APPLICANT).await.unwrap());
+    assert!(!controllers::user::user_exists_tele_id(harness.pool(), APPLICANT).await.unwrap());
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn approve_requires_user_manager() {
+    let mut harness = Harness::start().await;
+    harness.seed_user(APPLICANT, "CHARLIE", "PILOT", "ACTIVE", false).await;
+
+    harness.send_text(APPLICANT, "/approve").await;
+
+    assert!(harness.texts(APPLICANT).iter().all(|text| !text.contains("Pending applications")));
+}
@@ -0,0 +1,76 @@
+use chrono::{Duration, Utc};
+
+use super::harness::Harness;
+use crate::types::Ict;
+use crate::{controllers, now, utils, APP_TIMEZONE};
+
+const USER: u64 = 3001;
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn availability_add_saves_dates() {
+    let mut harness = Harness::start().await;
+    harness.seed_user(USER, "CHARLIE", "PILOT", "ACTIVE", false).await;
+    let dates = vec![now!().date_naive() + Duration::days(3), now!().date_naive() + Duration::days(5)];
+
+    harness.send_text(USER, "/availability").await;
+    harness.press(USER, "ADD").await;
+    harness.send_text(USER, &dates.iter().map(|date| date.format("%d/%m/%Y").to_string()).collect::<Vec<String>>().join(", ")).await;
+    harness.assert_sent(USER, "Selected dates:");
+    harness.press(USER, "DONE").await;
+
+    let saved = controllers::scheduling::check_user_avail_multiple(harness.pool(), USER, dates.clone(), None).await.unwrap();
+    for (date, entry) in dates.iter().zip(saved) {
+        let entry = entry.unwrap_or_else(|| panic!("No availability saved for {}", date));
+        assert!(matches!(entry.ict_type, Ict::LIVE));
+        assert!(entry.remarks.is_none());
+        assert!(!entry.planned);
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn availability_add_with_remarks() {
+    let mut harness = Harness::start().await;
+    harness.seed_user(USER, "CHARLIE", "PILOT", "ACTIVE", false).await;
+    let date = now!().date_naive() + Duration::days(4);
+
+    harness.send_text(USER, "/availability").await;
+    harness.press(USER, "ADD").await;
+    harness.send_text(USER, &date.format("%d/%m/%Y").to_string()).await;
+    harness.send_text(USER, "afternoon only").await;
+
+    let saved = controllers::scheduling::check_user_avail_multiple(harness.pool(), USER, vec![date], None).await.unwrap();
+    let entry = saved[0].as_ref().expect("No availability saved");
+    assert_eq!(entry.remarks.as_deref(), Some("afternoon only"));
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn availability_add_time_slots() {
+    let mut harness = Harness::start().await;
+    harness.seed_user(USER, "CHARLIE", "PILOT", "ACTIVE", false).await;
+    let date = now!().date_naive() + Duration::days(6);
+    let am = utils::parse_slot("AM").unwrap();
+    let pm = utils::parse_slot("PM").unwrap();
+
+    for slot in ["AM", "1300-1700"] {
+        harness.send_text(USER, "/availability").await;
+        harness.press(USER, "ADD").await;
+        harness.send_text(USER, &format!("{} @ {}", date.format("%d/%m/%Y"), slot)).await;
+        harness.press(USER, "DONE").await;
+    }
+
+    // Both slots are kept as separate entries on the same date
+    let saved_am = controllers::scheduling::check_user_avail_multiple(harness.pool(), USER, vec![date], Some(am)).await.unwrap();
+    let entry = saved_am[0].as_ref().expect("No availability saved for the AM slot");
+    assert_eq!((entry.start_time, entry.end_time), (Some(am.0), Some(am.1)));
+    let saved_pm = controllers::scheduling::check_user_avail_multiple(harness.pool(), USER, vec![date], Some(pm)).await.unwrap();
+    let entry = saved_pm[0].as_ref().expect("No availability saved for the PM slot");
+    assert_eq!((entry.start_time, entry.end_time), (Some(pm.0), Some(pm.1)));
+
+    harness.send_text(USER, "/availability").await;
+    harness.press(USER, "ADD").await;
+    harness.send_text(USER, &format!("{} @ brunch", date.format("%d/%m/%Y"))).await;
+    harness.assert_sent(USER, "Failed to parse the slot");
+}
@@ -0,0 +1,22 @@
+use super::harness::Harness;
+use crate::controllers;
+
+const USER: u64 = 4001;
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn concurrent_calendar_token_requests_share_one_token() {
+    let harness = Harness::start().await;
+    harness.seed_user(USER, "GOLF", "PILOT", "NS", false).await;
+
+    let (first, second) = tokio::join!(
+        controllers::calendar::get_or_create_calendar_token(harness.pool(), USER, "first-token"),
+        controllers::calendar::get_or_create_calendar_token(harness.pool(), USER, "second-token")
+    );
+    let (first, second) = (first.unwrap(), second.unwrap());
+    assert_eq!(first, second);
+
+    // Later requests keep the existing token
+    let again = controllers::calendar::get_or_create_calendar_token(harness.pool(), USER, "third-token").await.unwrap();
+    assert_eq!(again, first);
+}
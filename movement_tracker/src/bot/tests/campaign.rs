@@ -0,0 +1,135 @@
+use chrono::{Duration, NaiveDate, Utc};
+
+use super::harness::Harness;
+use crate::types::Ict;
+use crate::{controllers, now, APP_TIMEZONE};
+
+const PLANNER: u64 = 1001;
+const NS_USER: u64 = 4001;
+const OTHER_NS_USER: u64 = 4002;
+const ARO_USER: u64 = 5001;
+
+fn format_input_date(date: NaiveDate) -> String {
+    date.format("%Y %b %d").to_string()
+}
+
+async fn start_campaign(harness: &mut Harness, start: NaiveDate, end: NaiveDate, filter: &str) {
+    harness.send_text(PLANNER, "/campaign").await;
+    harness.press(PLANNER, "NEW").await;
+    harness.send_text(PLANNER, &format!("{} to {} {}", format_input_date(start), format_input_date(end), filter)).await;
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn campaign_requests_matching_users_and_tracks_responses() {
+    let mut harness = Harness::start().await;
+    harness.seed_user(PLANNER, "PLANNER", "PILOT", "ACTIVE", true).await;
+    harness.seed_user(NS_USER, "GOLF", "PILOT", "NS", false).await;
+    harness.seed_user(OTHER_NS_USER, "INDIA", "PILOT", "NS", false).await;
+    harness.seed_user(ARO_USER, "HOTEL", "ARO", "NS", false).await;
+
+    let start = now!().date_naive() + Duration::days(20);
+    let end = start + Duration::days(13);
+    start_campaign(&mut harness, start, end, "PILOT NS").await;
+
+    harness.assert_sent(NS_USER, "The planners are requesting your availability");
+    harness.assert_sent(OTHER_NS_USER, "The planners are requesting your availability");
+    // Users outside the filter are not asked
+    assert!(harness.texts(ARO_USER).is_empty());
+    assert!(harness.texts(PLANNER).iter().all(|text| !text.contains("The planners are requesting")));
+    harness.assert_sent(PLANNER, "Requested availability from 2 users.");
+    harness.assert_sent(PLANNER, "Responded: 0 of 2");
+    harness.assert_sent(PLANNER, "Not responded:\n- GOLF\n- INDIA");
+
+    // Availability within the window counts as a response
+    controllers::scheduling::add_user_avail(harness.pool(), NS_USER, NS_USER, (start + Duration::days(2), None), &Ict::LIVE, None, None).await.unwrap();
+    harness.press(PLANNER, "REFRESH").await;
+    let status = harness.texts(PLANNER).pop().unwrap();
+    assert!(status.contains("Responded: 1 of 2"), "{}", status);
+    assert!(status.contains("Not responded:\n- INDIA"), "{}", status);
+
+    // Manual nudges wait for the interval like the scheduled ones
+    harness.press(PLANNER, "NUDGE NOW").await;
+    harness.assert_sent(PLANNER, "Users were asked less than 48 hours ago, nobody was nudged.");
+    assert!(harness.texts(OTHER_NS_USER).iter().all(|text| !text.starts_with("Reminder")));
+    harness.assert_sent(PLANNER, "Nudges sent: 0 of 3");
+
+    // Nudges only go to users who have not responded
+    sqlx::query("UPDATE campaigns SET created = NOW() - INTERVAL '3 days'")
+        .execute(harness.pool())
+        .await
+        .unwrap();
+    harness.press(PLANNER, "NUDGE NOW").await;
+    harness.assert_sent(PLANNER, "Nudged 1 of 1 users who have not responded.");
+    harness.assert_sent(OTHER_NS_USER, "Reminder: the planners have yet to receive your availability");
+    assert!(harness.texts(NS_USER).iter().all(|text| !text.starts_with("Reminder")));
+    harness.assert_sent(PLANNER, "Nudges sent: 1 of 3");
+
+    // The scheduled nudge counts from the manual one
+    harness.run_campaign_nudges().await;
+    assert_eq!(harness.texts(OTHER_NS_USER).iter().filter(|text| text.starts_with("Reminder")).count(), 1);
+
+    // No more manual nudges once all have been sent
+    sqlx::query("UPDATE campaigns SET nudges = 3, last_nudged = NOW() - INTERVAL '3 days'")
+        .execute(harness.pool())
+        .await
+        .unwrap();
+    harness.press(PLANNER, "NUDGE NOW").await;
+    harness.assert_sent(PLANNER, "All 3 nudges for this campaign have been sent, nobody was nudged.");
+
+    harness.press(PLANNER, "CLOSE CAMPAIGN").await;
+    harness.assert_sent(PLANNER, "No availability campaigns are open.");
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn campaign_nudges_are_sent_at_the_interval() {
+    let mut harness = Harness::start().await;
+    harness.seed_user(PLANNER, "PLANNER", "PILOT", "ACTIVE", true).await;
+    harness.seed_user(NS_USER, "GOLF", "PILOT", "NS", false).await;
+    harness.seed_user(OTHER_NS_USER, "INDIA", "PILOT", "NS", false).await;
+
+    let start = now!().date_naive() + Duration::days(20);
+    // Users who already responded are not asked at all
+    controllers::scheduling::add_user_avail(harness.pool(), NS_USER, NS_USER, (start, None), &Ict::LIVE, None, None).await.unwrap();
+    start_campaign(&mut harness, start, start + Duration::days(6), "NS").await;
+    harness.assert_sent(PLANNER, "Requested availability from 1 users, 1 had already responded.");
+    assert!(harness.texts(NS_USER).is_empty());
+
+    // Not due until the interval has passed
+    harness.run_campaign_nudges().await;
+    assert_eq!(harness.texts(OTHER_NS_USER).len(), 1);
+
+    sqlx::query("UPDATE campaigns SET created = NOW() - INTERVAL '3 days'")
+        .execute(harness.pool())
+        .await
+        .unwrap();
+    harness.run_campaign_nudges().await;
+    harness.assert_sent(OTHER_NS_USER, "Reminder: the planners have yet to receive your availability");
+    assert!(harness.texts(NS_USER).is_empty());
+
+    // The next nudge waits for another interval
+    harness.run_campaign_nudges().await;
+    assert_eq!(harness.texts(OTHER_NS_USER).len(), 2);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn campaign_nudges_stop_once_the_window_starts() {
+    let mut harness = Harness::start().await;
+    harness.seed_user(PLANNER, "PLANNER", "PILOT", "ACTIVE", true).await;
+    harness.seed_user(NS_USER, "GOLF", "PILOT", "NS", false).await;
+
+    let today = now!().date_naive();
+    start_campaign(&mut harness, today, today + Duration::days(6), "NS").await;
+    harness.assert_sent(PLANNER, "Requested availability from 1 users.");
+    sqlx::query("UPDATE campaigns SET created = NOW() - INTERVAL '3 days'")
+        .execute(harness.pool())
+        .await
+        .unwrap();
+
+    harness.press(PLANNER, "NUDGE NOW").await;
+    harness.assert_sent(PLANNER, "The campaign has started, nobody was nudged.");
+    harness.run_campaign_nudges().await;
+    assert!(harness.texts(NS_USER).iter().all(|text| !text.starts_with("Reminder")));
+}
@@ -0,0 +1,73 @@
+use chrono::{Duration, NaiveTime, Utc};
+
+use super::harness::Harness;
+use crate::types::{Ack, Ict};
+use crate::{controllers, now, APP_TIMEZONE};
+
+const PLANNER: u64 = 1001;
+const NS_USER: u64 = 4001;
+const ARO_USER: u64 = 5001;
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn digest_lists_planned_crew_by_role_and_ict_type() {
+    let harness = Harness::start().await;
+    harness.seed_user(PLANNER, "PLANNER", "PILOT", "ACTIVE", true).await;
+    harness.seed_user(NS_USER, "GOLF", "PILOT", "NS", false).await;
+    harness.seed_user(ARO_USER, "HOTEL", "ARO", "ACTIVE", false).await;
+    let unit_id = harness.unit_id().await;
+    controllers::notifications::update_notification_settings(harness.pool(), PLANNER as i64, unit_id, None, None, None, None, None, Some(true)).await.unwrap();
+
+    let tomorrow = now!().date_naive() + Duration::days(1);
+    let am = (NaiveTime::from_hms_opt(8, 0, 0).unwrap(), NaiveTime::from_hms_opt(12, 0, 0).unwrap());
+    controllers::scheduling::add_user_avail(harness.pool(), PLANNER, NS_USER, (tomorrow, Some(am)), &Ict::SIMS, None, Some(true)).await.unwrap();
+    let declined = controllers::scheduling::add_user_avail(harness.pool(), PLANNER, ARO_USER, (tomorrow, None), &Ict::LIVE, None, Some(true)).await.unwrap();
+    controllers::scheduling::set_availability_ack(harness.pool(), ARO_USER, declined.id, Ack::Declined).await.unwrap().unwrap();
+    // Only planned crew are listed
+    controllers::scheduling::add_user_avail(harness.pool(), PLANNER, PLANNER, (tomorrow, None), &Ict::LIVE, None, None).await.unwrap();
+
+    harness.run_digest(tomorrow).await;
+
+    let digest = harness.texts(PLANNER).into_iter().find(|text| text.contains("Crew planned for")).expect("No digest posted");
+    assert!(digest.contains("*PILOT*\n__SIMS__\n\\- `GOLF` \\(NS\\) AM \\(0800\\-1200\\) *PENDING SAF100*"), "{}", digest);
+    // Crew who declined their reminder are marked for the planners to replace
+    assert!(digest.contains("*ARO*\n__LIVE__\n\\- `HOTEL` ❌ *DECLINED*\n"), "{}", digest);
+    assert!(!digest.contains("PLANNER"));
+    // Chats which did not opt in get nothing
+    assert!(harness.texts(NS_USER).is_empty());
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn digest_without_planned_crew() {
+    let harness = Harness::start().await;
+    harness.seed_user(PLANNER, "PLANNER", "PILOT", "ACTIVE", true).await;
+    let unit_id = harness.unit_id().await;
+    controllers::notifications::update_notification_settings(harness.pool(), PLANNER as i64, unit_id, None, None, None, None, None, Some(true)).await.unwrap();
+
+    harness.run_digest(now!().date_naive() + Duration::days(1)).await;
+    harness.assert_sent(PLANNER, "No crew planned");
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn digest_lists_each_user_once_with_their_slots() {
+    let harness = Harness::start().await;
+    harness.seed_user(PLANNER, "PLANNER", "PILOT", "ACTIVE", true).await;
+    harness.seed_user(NS_USER, "GOLF", "PILOT", "NS", false).await;
+    let unit_id = harness.unit_id().await;
+    controllers::notifications::update_notification_settings(harness.pool(), PLANNER as i64, unit_id, None, None, None, None, None, Some(true)).await.unwrap();
+
+    let tomorrow = now!().date_naive() + Duration::days(1);
+    let am = (NaiveTime::from_hms_opt(8, 0, 0).unwrap(), NaiveTime::from_hms_opt(12, 0, 0).unwrap());
+    let pm = (NaiveTime::from_hms_opt(13, 0, 0).unwrap(), NaiveTime::from_hms_opt(17, 0, 0).unwrap());
+    for slot in [am, pm] {
+        controllers::scheduling::add_user_avail(harness.pool(), PLANNER, NS_USER, (tomorrow, Some(slot)), &Ict::LIVE, None, Some(true)).await.unwrap();
+    }
+
+    harness.run_digest(tomorrow).await;
+
+    let digest = harness.texts(PLANNER).into_iter().find(|text| text.contains("Crew planned for")).expect("No digest posted");
+    assert!(digest.contains("\\- `GOLF` \\(NS\\) AM \\(0800\\-1200\\), PM \\(1300\\-1700\\) *PENDING SAF100*"), "{}", digest);
+    assert_eq!(digest.matches("GOLF").count(), 1);
+}
@@ -0,0 +1,381 @@
+use std::collections::BTreeMap;
+use std::ops::ControlFlow;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+use axum::body::Bytes;
+use axum::extract::{Path, State as AxumState};
+use axum::routing::post;
+use axum::{Json, Router};
+use chrono::{NaiveDate, Utc};
+use serde_json::{json, Value};
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use sqlx::types::Uuid;
+use sqlx::{Connection, Executor, PgConnection, PgPool};
+
+use teloxide::prelude::*;
+use teloxide::types::{Me, Update};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+use crate::bot::state::schema;
+use crate::bot::storage::PgStorage;
+use crate::types::{RoleType, UsrType};
+use crate::{controllers, notifier, DIALOGUE_TTL_SECS};
+
+const BOT_ID: u64 = 1;
+const BOT_USERNAME: &str = "movement_tracker_test_bot";
+
+// A throwaway database with every migration applied, uniquely named so tests can run in parallel
+pub(super) struct TestDb {
+    server_options: PgConnectOptions,
+    name: String,
+    pool: PgPool
+}
+
+impl TestDb {
+    // Panics if TEST_DATABASE_URL is not set, so the ignored scenarios cannot pass without checking anything
+    async fn create() -> Self {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set to run the integration tests");
+
+        let server_options = PgConnectOptions::from_str(&url).expect("Invalid TEST_DATABASE_URL");
+        let name = format!("movement_tracker_test_{}", Uuid::new_v4().simple());
+
+        let mut conn = PgConnection::connect_with(&server_options).await.expect("Failed to connect to the test database server");
+        conn.execute(format!(r#"CREATE DATABASE "{}""#, name).as_str()).await.expect("Failed to create test database");
+        conn.close().await.ok();
+
+        let pool = PgPoolOptions::new()
+            .max_connections(5)
+            .connect_with(server_options.clone().database(&name))
+            .await
+            .expect("Failed to connect to test database");
+        sqlx::migrate!("./migrations").run(&pool).await.expect("Failed to run migrations on test database");
+
+        Self { server_options, name, pool }
+    }
+}
+
+// Dropped even when a test panics, so failed runs do not leave databases behind
+impl Drop for TestDb {
+    fn drop(&mut self) {
+        let server_options = self.server_options.clone();
+        let name = self.name.clone();
+
+        // The test's runtime cannot be blocked on from here, so drop the database from a runtime of its own
+        let result = std::thread::spawn(move || {
+            tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap().block_on(async move {
+                let mut conn = PgConnection::connect_with(&server_options).await?;
+                conn.execute(format!(r#"DROP DATABASE IF EXISTS "{}" WITH (FORCE)"#, name).as_str()).await?;
+                conn.close().await
+            })
+        }).join();
+
+        if !matches!(result, Ok(Ok(()))) {
+            eprintln!("Failed to drop test database {}", self.name);
+        }
+    }
+}
+
+// A message as the chat would currently show it, after any edits
+#[derive(Clone, Debug)]
+struct ChatMessage {
+    text: String,
+    reply_markup: Option<Value>
+}
+
+#[derive(Default)]
+struct Recorder {
+    // Every Bot API call made, in order
+    calls: Vec<(String, Value)>,
+    // Messages keyed by (chat id, message id)
+    messages: BTreeMap<(i64, i32), ChatMessage>,
+    next_message_id: i32
+}
+
+impl Recorder {
+    fn next_message_id(&mut self) -> i32 {
+        self.next_message_id += 1;
+        self.next_message_id
+    }
+}
+
+fn user_json(tele_id: u64) -> Value {
+    json!({
+        "id": tele_id,
+        "is_bot": false,
+        "first_name": format!("User{}", tele_id),
+        "username": format!("user{}", tele_id)
+    })
+}
+
+fn chat_json(chat_id: i64) -> Value {
+    json!({
+        "id": chat_id,
+        "type": "private",
+        "first_name": format!("User{}", chat_id),
+        "username": format!("user{}", chat_id)
+    })
+}
+
+fn message_json(chat_id: i64, message_id: i32, text: &str, from: Value) -> Value {
+    json!({
+        "message_id": message_id,
+        "date": Utc::now().timestamp(),
+        "chat": chat_json(chat_id),
+        "from": from,
+        "text": text
+    })
+}
+
+fn bot_json() -> Value {
+    json!({
+        "id": BOT_ID,
+        "is_bot": true,
+        "first_name": "Movement Tracker",
+        "username": BOT_USERNAME
+    })
+}
+
+// Answers Bot API calls the way Telegram would for a private chat, recording each call
+async fn handle_api_call(
+    AxumState(recorder): AxumState<Arc<Mutex<Recorder>>>,
+    Path((_token, method)): Path<(String, String)>,
+    body: Bytes
+) -> Json<Value> {
+    // Method names are case-insensitive
+    let method = method.to_lowercase();
+    let body: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+    let chat_id = body["chat_id"].as_i64().unwrap_or_default();
+    let text = body["text"].as_str().unwrap_or_default().to_string();
+
+    let mut recorder = recorder.lock().unwrap();
+    recorder.calls.push((method.clone(), body.clone()));
+
+    let result = match method.as_str() {
+        "getme" => {
+            let mut me = bot_json();
+            me["can_join_groups"] = json!(false);
+            me["can_read_all_group_messages"] = json!(false);
+            me["supports_inline_queries"] = json!(false);
+            me
+        }
+        "getchat" => {
+            let mut chat = chat_json(chat_id);
+            chat["accent_color_id"] = json!(0);
+            chat["max_reaction_count"] = json!(11);
+            chat
+        }
+        "sendmessage" => {
+            let message_id = recorder.next_message_id();
+            recorder.messages.insert((chat_id, message_id), ChatMessage { text: text.clone(), reply_markup: body.get("reply_markup").cloned() });
+            message_json(chat_id, message_id, &text, bot_json())
+        }
+        "editmessagetext" | "editmessagereplymarkup" => {
+            let message_id = body["message_id"].as_i64().unwrap_or_default() as i32;
+            let message = recorder.messages.entry((chat_id, message_id)).or_insert(ChatMessage { text: String::new(), reply_markup: None });
+            if method == "editmessagetext" {
+                message.text = text;
+            }
+            // Telegram drops the inline keyboard when an edit does not include one
+            message.reply_markup = body.get("reply_markup").cloned();
+            let text = message.text.clone();
+            message_json(chat_id, message_id, &text, bot_json())
+        }
+        "deletemessage" => {
+            let message_id = body["message_id"].as_i64().unwrap_or_default() as i32;
+            recorder.messages.remove(&(chat_id, message_id));
+            json!(true)
+        }
+        _ => json!(true)
+    };
+
+    Json(json!({ "ok": true, "result": result }))
+}
+
+// Local stand-in for the Telegram Bot API
+struct FakeBotApi {
+    recorder: Arc<Mutex<Recorder>>,
+    url: url::Url,
+    server: JoinHandle<()>
+}
+
+impl Drop for FakeBotApi {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+impl FakeBotApi {
+    async fn start() -> Self {
+        let recorder = Arc::new(Mutex::new(Recorder::default()));
+        let app = Router::new()
+            .route("/:token/:method", post(handle_api_call))
+            .with_state(recorder.clone());
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("Failed to bind fake Bot API");
+        let url = url::Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+        let server = tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("Fake Bot API stopped");
+        });
+
+        Self { recorder, url, server }
+    }
+}
+
+// Drives the bot's dispatch tree with one update at a time, against a fake Bot API and a throwaway database
+pub(super) struct Harness {
+    db: TestDb,
+    api: FakeBotApi,
+    bot: Bot,
+    me: Me,
+    next_update_id: i32
+}
+
+impl Harness {
+    pub(super) async fn start() -> Self {
+        let db = TestDb::create().await;
+        let api = FakeBotApi::start().await;
+        let bot = Bot::new(format!("{}:TEST", BOT_ID)).set_api_url(api.url.clone());
+        let me = bot.get_me().await.expect("Failed to get bot details from fake Bot API");
+
+        Self { db, api, bot, me, next_update_id: 0 }
+    }
+
+    pub(super) fn pool(&self) -> &PgPool {
+        &self.db.pool
+    }
+
+    pub(super) async fn unit_id(&self) -> Uuid {
+        controllers::units::get_default_unit(self.pool()).await.expect("Failed to get default unit").id
+    }
+
+    // Adds an approved user directly, bypassing registration
+    pub(super) async fn seed_user(&self, tele_id: u64, ops_name: &str, role_type: &str, usr_type: &str, admin: bool) {
+        let unit_id = self.unit_id().await;
+        controllers::user::add_user(
+            self.pool(),
+            tele_id,
+            tele_id,
+            format!("User {}", ops_name),
+            ops_name.to_string(),
+            RoleType::new(role_type),
+            UsrType::new(usr_type),
+            admin,
+            unit_id
+        ).await.expect("Failed to seed user");
+    }
+
+    async fn dispatch(&mut self, mut update: Value) {
+        self.next_update_id += 1;
+        update["update_id"] = json!(self.next_update_id);
+        // Update only recognises its kind when parsed from text, not from an already parsed Value
+        let update: Update = serde_json::from_str(&update.to_string()).expect("Invalid update");
+
+        let deps = dptree::deps![
+            update,
+            self.bot.clone(),
+            self.me.clone(),
+            PgStorage::new(self.db.pool.clone(), *DIALOGUE_TTL_SECS),
+            self.db.pool.clone()
+        ];
+        match schema().dispatch(deps).await {
+            ControlFlow::Break(result) => result.expect("Handler returned an error"),
+            ControlFlow::Continue(_) => panic!("Update was not handled")
+        }
+    }
+
+    // Sends a text message from the user in their private chat with the bot
+    pub(super) async fn send_text(&mut self, tele_id: u64, text: &str) {
+        let message_id = self.api.recorder.lock().unwrap().next_message_id();
+        self.dispatch(json!({
+            "message": message_json(tele_id as i64, message_id, text, user_json(tele_id))
+        })).await;
+    }
+
+    // Sends every scheduled reminder which is due
+    pub(super) async fn run_notifier(&self) {
+        notifier::scheduled::process_scheduled_notifications(self.pool(), &self.bot).await.expect("Failed to process scheduled notifications");
+    }
+
+    // Posts the crew digest for the date to the chats which opted in
+    pub(super) async fn run_digest(&self, date: NaiveDate) {
+        notifier::digest::send_crew_digests(self.pool(), &self.bot, date).await.expect("Failed to send crew digests");
+    }
+
+    // Nudges the recipients of campaigns which are due another nudge
+    pub(super) async fn run_campaign_nudges(&self) {
+        notifier::campaign::send_campaign_nudges(self.pool(), &self.bot).await.expect("Failed to send campaign nudges");
+    }
+
+    // Presses the inline button with the given label on the latest message in the user's chat showing it
+    pub(super) async fn press(&mut self, tele_id: u64, label: &str) {
+        let chat_id = tele_id as i64;
+        let found = {
+            let recorder = self.api.recorder.lock().unwrap();
+            recorder.messages.iter().rev()
+                .filter(|((chat, _), _)| *chat == chat_id)
+                .find_map(|((_, message_id), message)| {
+                    buttons_of(message).into_iter()
+                        .find(|(text, _)| text == label)
+                        .map(|(_, data)| (*message_id, message.text.clone(), data))
+                })
+        };
+
+        let (message_id, text, data) = match found {
+            Some(found) => found,
+            None => panic!("No button labelled {:?} in chat {}, buttons shown: {:?}", label, chat_id, self.buttons(tele_id))
+        };
+
+        self.dispatch(json!({
+            "callback_query": {
+                "id": format!("callback-{}", self.next_update_id + 1),
+                "from": user_json(tele_id),
+                "chat_instance": format!("instance-{}", chat_id),
+                "data": data,
+                "message": message_json(chat_id, message_id, &text, bot_json())
+            }
+        })).await;
+    }
+
+    // Labels of every inline button currently shown in the user's chat, oldest message first
+    pub(super) fn buttons(&self, tele_id: u64) -> Vec<String> {
+        let recorder = self.api.recorder.lock().unwrap();
+        recorder.messages.iter()
+            .filter(|((chat, _), _)| *chat == tele_id as i64)
+            .flat_map(|(_, message)| buttons_of(message).into_iter().map(|(text, _)| text))
+            .collect()
+    }
+
+    // Text of every message sent or edited in the user's chat, in the order the bot produced them
+    pub(super) fn texts(&self, tele_id: u64) -> Vec<String> {
+        let recorder = self.api.recorder.lock().unwrap();
+        recorder.calls.iter()
+            .filter(|(method, body)| {
+                (method == "sendmessage" || method == "editmessagetext") && body["chat_id"].as_i64() == Some(tele_id as i64)
+            })
+            .filter_map(|(_, body)| body["text"].as_str().map(ToOwned::to_owned))
+            .collect()
+    }
+
+    pub(super) fn assert_sent(&self, tele_id: u64, needle: &str) {
+        let texts = self.texts(tele_id);
+        assert!(
+            texts.iter().any(|text| text.contains(needle)),
+            "Expected a message containing {:?} in chat {}, got: {:#?}", needle, tele_id, texts
+        );
+    }
+}
+
+fn buttons_of(message: &ChatMessage) -> Vec<(String, String)> {
+    let rows = match message.reply_markup.as_ref().and_then(|markup| markup["inline_keyboard"].as_array()) {
+        Some(rows) => rows,
+        None => return Vec::new()
+    };
+
+    rows.iter()
+        .filter_map(Value::as_array)
+        .flatten()
+        .filter_map(|button| Some((button["text"].as_str()?.to_string(), button["callback_data"].as_str()?.to_string())))
+        .collect()
+}
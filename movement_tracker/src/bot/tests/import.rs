@@ -0,0 +1,48 @@
+use chrono::{Duration, NaiveTime, Utc};
+
+use super::harness::Harness;
+use crate::bot::import::validate_rows;
+use crate::types::Ict;
+use crate::{controllers, now, APP_TIMEZONE};
+
+const PLANNER: u64 = 1001;
+const NS_USER: u64 = 4001;
+
+fn row(fields: &[&str]) -> Vec<String> {
+    fields.iter().map(|field| field.to_string()).collect()
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn import_rows_keep_to_their_slots() {
+    let harness = Harness::start().await;
+    harness.seed_user(PLANNER, "PLANNER", "PILOT", "ACTIVE", true).await;
+    harness.seed_user(NS_USER, "GOLF", "PILOT", "NS", false).await;
+    let unit_id = harness.unit_id().await;
+    let date = now!().date_naive() + Duration::days(10);
+    let am = (NaiveTime::from_hms_opt(8, 0, 0).unwrap(), NaiveTime::from_hms_opt(12, 0, 0).unwrap());
+    for offered in [date, date + Duration::days(2)] {
+        controllers::scheduling::add_user_avail(harness.pool(), NS_USER, NS_USER, (offered, Some(am)), &Ict::LIVE, None, None).await.unwrap();
+    }
+
+    let date_str = date.format("%Y %b %d").to_string();
+    let next_date_str = (date + Duration::days(1)).format("%Y %b %d").to_string();
+    let last_date_str = (date + Duration::days(2)).format("%Y %b %d").to_string();
+    let rows = vec![
+        row(&["ops_name", "date", "ict_type", "remarks", "slot"]),
+        row(&["GOLF", &date_str, "LIVE", "", "PM"]),
+        row(&["GOLF", &date_str, "SIMS", "replaces the morning", "AM"]),
+        // A whole day would double up with the slots already offered
+        row(&["GOLF", &last_date_str, "LIVE"]),
+        row(&["GOLF", &next_date_str, "LIVE", "", "AM"]),
+        row(&["GOLF", &next_date_str, "LIVE", "", "0900-1000"]),
+        row(&["GOLF", &next_date_str, "LIVE", "", "noon"]),
+    ];
+
+    let (entries, errors) = validate_rows(rows, unit_id, harness.pool()).await.unwrap();
+    assert_eq!(entries.len(), 3, "{:?}", entries);
+    assert_eq!(errors.len(), 3, "{:?}", errors);
+    assert!(errors.contains(&"Row 7: invalid slot noon".to_string()), "{:?}", errors);
+    assert!(errors.contains(&format!("Row 6: overlaps another entry for GOLF on {}", (date + Duration::days(1)).format("%Y-%m-%d"))), "{:?}", errors);
+    assert!(errors.contains(&format!("Row 4: GOLF already has availability on {} in another slot", (date + Duration::days(2)).format("%Y-%m-%d"))), "{:?}", errors);
+}
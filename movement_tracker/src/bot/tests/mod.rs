@@ -0,0 +1,16 @@
+// End-to-end scenarios driving the dispatch tree against a fake Bot API and a throwaway database.
+// They are ignored by default, run them with `cargo test -- --ignored` and TEST_DATABASE_URL set to a Postgres server the tests may create databases on.
+mod harness;
+mod register;
+mod approve;
+mod availability;
+mod plan;
+mod saf100;
+mod stats;
+mod acknowledge;
+mod digest;
+mod calendar;
+mod permissions;
+mod notify;
+mod campaign;
+mod import;
@@ -0,0 +1,53 @@
+use sqlx::types::Uuid;
+
+use super::harness::Harness;
+use crate::controllers;
+
+const ADMIN: u64 = 1001;
+const GROUP_CHAT: i64 = -5001;
+
+async fn add_unit(harness: &Harness, name: &str) -> Uuid {
+    sqlx::query_scalar("INSERT INTO units (name) VALUES ($1) RETURNING id")
+        .bind(name)
+        .fetch_one(harness.pool())
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn shared_chat_stays_with_its_unit() {
+    let harness = Harness::start().await;
+    let unit_id = harness.unit_id().await;
+    let other_unit_id = add_unit(&harness, "SQN B").await;
+
+    controllers::notifications::update_notification_settings(harness.pool(), GROUP_CHAT, unit_id, Some(true), None, None, None, None, None).await.unwrap();
+
+    // Another unit cannot take the chat over
+    let updated = controllers::notifications::update_notification_settings(harness.pool(), GROUP_CHAT, other_unit_id, Some(false), None, None, None, None, None).await.unwrap();
+    assert!(updated.is_none());
+    let settings = controllers::notifications::get_notification_settings(harness.pool(), GROUP_CHAT).await.unwrap().unwrap();
+    assert_eq!(settings.unit_id, unit_id);
+    assert!(settings.notif_system);
+    assert_eq!(controllers::notifications::get_system_notifications_enabled(harness.pool(), Some(other_unit_id)).await.unwrap(), Vec::<i64>::new());
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn user_dm_follows_their_unit() {
+    let harness = Harness::start().await;
+    harness.seed_user(ADMIN, "ADMIN", "PILOT", "ACTIVE", true).await;
+    let unit_id = harness.unit_id().await;
+    let other_unit_id = add_unit(&harness, "SQN B").await;
+    controllers::notifications::update_notification_settings(harness.pool(), ADMIN as i64, unit_id, Some(true), None, None, None, None, None).await.unwrap();
+
+    sqlx::query("UPDATE usrs SET unit_id = $1 WHERE tele_id = $2")
+        .bind(other_unit_id)
+        .bind(ADMIN as i64)
+        .execute(harness.pool())
+        .await
+        .unwrap();
+
+    let updated = controllers::notifications::update_notification_settings(harness.pool(), ADMIN as i64, other_unit_id, None, None, None, None, None, None).await.unwrap();
+    assert_eq!(updated.unwrap().unit_id, other_unit_id);
+}
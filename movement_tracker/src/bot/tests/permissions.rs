@@ -0,0 +1,130 @@
+use super::harness::Harness;
+use crate::controllers;
+use crate::types::{AdminRole, RoleType, UsrType};
+
+const ADMIN: u64 = 1001;
+const MANAGER: u64 = 2001;
+const USER: u64 = 4001;
+const APPLICANT: u64 = 5001;
+
+async fn grant_user_manager(harness: &Harness) {
+    let manager = controllers::user::get_user_by_tele_id(harness.pool(), MANAGER).await.unwrap();
+    controllers::permissions::set_admin_roles(harness.pool(), ADMIN, manager.id, &[AdminRole::UserManager]).await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn user_manager_cannot_change_permissions() {
+    let mut harness = Harness::start().await;
+    harness.seed_user(ADMIN, "ADMIN", "PILOT", "ACTIVE", true).await;
+    harness.seed_user(MANAGER, "MANAGER", "PILOT", "ACTIVE", false).await;
+    grant_user_manager(&harness).await;
+
+    harness.send_text(MANAGER, "/user MANAGER").await;
+    harness.press(MANAGER, "PERMISSIONS").await;
+
+    harness.assert_sent(MANAGER, "Only admins can change permissions.");
+    assert!(!harness.buttons(MANAGER).contains(&"SAVE".to_string()));
+    assert_eq!(
+        controllers::permissions::get_effective_admin_roles_by_tele_id(harness.pool(), MANAGER).await.unwrap(),
+        vec![AdminRole::UserManager]
+    );
+}
+
+async fn seed_application(harness: &Harness) {
+    controllers::apply::apply_user(
+        harness.pool(),
+        APPLICANT,
+        "Applicant".into(),
+        "Bravo Lim".into(),
+        "BRAVO".into(),
+        RoleType::new("ARO"),
+        UsrType::new("NS"),
+        harness.unit_id().await
+    ).await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn user_manager_cannot_approve_applicant_as_admin() {
+    let mut harness = Harness::start().await;
+    harness.seed_user(ADMIN, "ADMIN", "PILOT", "ACTIVE", true).await;
+    harness.seed_user(MANAGER, "MANAGER", "PILOT", "ACTIVE", false).await;
+    grant_user_manager(&harness).await;
+    seed_application(&harness).await;
+
+    harness.send_text(MANAGER, "/approve").await;
+    harness.press(MANAGER, "BRAVO").await;
+    harness.press(MANAGER, "ADMIN").await;
+    harness.assert_sent(MANAGER, "Only admins can change admin status.");
+    assert!(!harness.buttons(MANAGER).contains(&"YES".to_string()));
+
+    harness.press(MANAGER, "APPROVE").await;
+    harness.assert_sent(APPLICANT, "Registered successfully.");
+    assert!(!controllers::user::get_user_by_tele_id(harness.pool(), APPLICANT).await.unwrap().admin);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn approving_as_admin_is_checked_again_on_approval() {
+    let mut harness = Harness::start().await;
+    harness.seed_user(MANAGER, "MANAGER", "PILOT", "ACTIVE", true).await;
+    grant_user_manager(&harness).await;
+    seed_application(&harness).await;
+
+    harness.send_text(MANAGER, "/approve").await;
+    harness.press(MANAGER, "BRAVO").await;
+    harness.press(MANAGER, "ADMIN").await;
+    harness.press(MANAGER, "YES").await;
+
+    // Admin status is lost before the application is approved
+    sqlx::query("UPDATE usrs SET admin = FALSE WHERE tele_id = $1")
+        .bind(MANAGER as i64)
+        .execute(harness.pool())
+        .await
+        .unwrap();
+    harness.press(MANAGER, "APPROVE").await;
+
+    harness.assert_sent(MANAGER, "Only admins can approve a user as an admin.");
+    assert!(controllers::apply::apply_exists_tele_id(harness.pool(), APPLICANT).await.unwrap());
+    assert!(controllers::user::get_user_by_tele_id(harness.pool(), APPLICANT).await.is_err());
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn admin_permission_changes_are_audited() {
+    let mut harness = Harness::start().await;
+    harness.seed_user(ADMIN, "ADMIN", "PILOT", "ACTIVE", true).await;
+    harness.seed_user(USER, "GOLF", "PILOT", "NS", false).await;
+
+    harness.send_text(ADMIN, "/user GOLF").await;
+    harness.press(ADMIN, "PERMISSIONS").await;
+    harness.press(ADMIN, "PLANNER").await;
+    harness.press(ADMIN, "SAVE").await;
+
+    harness.assert_sent(ADMIN, "Saved permissions: PLANNER");
+    let history = controllers::audit::get_audit_log_by_subject(harness.pool(), USER as i64, 10, 0).await.unwrap();
+    let entry = history.first().expect("No audit entry recorded");
+    assert_eq!(entry.action, "EDIT");
+    assert_eq!(entry.before.as_ref().unwrap()["admin_roles"], serde_json::json!([]));
+    assert_eq!(entry.after.as_ref().unwrap()["admin_roles"], serde_json::json!(["planner"]));
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn only_admins_manage_crew_types() {
+    let mut harness = Harness::start().await;
+    harness.seed_user(ADMIN, "ADMIN", "PILOT", "ACTIVE", true).await;
+    harness.seed_user(MANAGER, "MANAGER", "PILOT", "ACTIVE", false).await;
+    grant_user_manager(&harness).await;
+
+    // Crew types are shared by every unit
+    harness.send_text(MANAGER, "/roles").await;
+    harness.assert_sent(MANAGER, "Only admins can manage crew roles and user types");
+    assert!(!harness.buttons(MANAGER).contains(&"ADD ROLE".to_string()));
+
+    harness.send_text(ADMIN, "/roles").await;
+    harness.press(ADMIN, "ADD ROLE").await;
+    harness.send_text(ADMIN, "medic").await;
+    assert!(controllers::crew_types::get_role_types(harness.pool()).await.unwrap().iter().any(|role_type| role_type.as_ref() == "MEDIC"));
+}
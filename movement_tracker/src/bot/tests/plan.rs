@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+use chrono::{Duration, NaiveTime, Utc};
+
+use super::harness::Harness;
+use crate::types::{Ict, PlanStatus};
+use crate::{controllers, now, APP_TIMEZONE};
+
+const PLANNER: u64 = 1001;
+const USER: u64 = 3001;
+const OTHER_USERS: [u64; 2] = [3002, 3003];
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn plan_user_by_ops_name() {
+    let mut harness = Harness::start().await;
+    harness.seed_user(PLANNER, "PLANNER", "PILOT", "ACTIVE", true).await;
+    harness.seed_user(USER, "DELTA", "PILOT", "ACTIVE", false).await;
+    let date = now!().date_naive() + Duration::days(2);
+    controllers::scheduling::add_user_avail(harness.pool(), USER, USER, (date, None), &Ict::LIVE, None, None).await.unwrap();
+
+    harness.send_text(PLANNER, "/plan DELTA").await;
+    harness.press(PLANNER, &format!("PLAN {}: LIVE", date.format("%b-%d"))).await;
+    harness.press(PLANNER, "PLAN").await;
+    harness.press(PLANNER, &format!("UNPLAN {}: LIVE", date.format("%b-%d"))).await;
+    harness.press(PLANNER, "BACK").await;
+
+    // Nothing is saved until the changes are committed
+    let saved = controllers::scheduling::check_user_avail_multiple(harness.pool(), USER, vec![date], None).await.unwrap();
+    assert!(!saved[0].as_ref().unwrap().planned);
+
+    harness.press(PLANNER, "DONE").await;
+
+    harness.assert_sent(PLANNER, "has been planned");
+    let saved = controllers::scheduling::check_user_avail_multiple(harness.pool(), USER, vec![date], None).await.unwrap();
+    assert!(saved[0].as_ref().unwrap().planned);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn plan_warns_of_conflicts_before_commit() {
+    let mut harness = Harness::start().await;
+    harness.seed_user(PLANNER, "PLANNER", "PILOT", "ACTIVE", true).await;
+    harness.seed_user(USER, "FOXTROT", "PILOT", "NS", false).await;
+    let date = now!().date_naive() + Duration::days(2);
+    controllers::scheduling::add_user_avail(harness.pool(), USER, USER, (date, None), &Ict::LIVE, None, None).await.unwrap();
+    controllers::movement::add_user_movement(
+        harness.pool(), USER, date,
+        NaiveTime::from_hms_opt(9, 0, 0).unwrap(), NaiveTime::from_hms_opt(12, 0, 0).unwrap(),
+        "MEDICAL".into(), None, None
+    ).await.unwrap();
+
+    harness.send_text(PLANNER, "/plan FOXTROT").await;
+    harness.press(PLANNER, &format!("PLAN {}: LIVE", date.format("%b-%d"))).await;
+    harness.press(PLANNER, "PLAN").await;
+    harness.press(PLANNER, "DONE").await;
+
+    harness.assert_sent(PLANNER, "has movement on");
+    harness.assert_sent(PLANNER, "too late for SAF100");
+    let saved = controllers::scheduling::check_user_avail_multiple(harness.pool(), USER, vec![date], None).await.unwrap();
+    assert!(!saved[0].as_ref().unwrap().planned);
+
+    // Going back keeps the pending change
+    harness.press(PLANNER, "BACK").await;
+    harness.press(PLANNER, "DONE").await;
+    harness.press(PLANNER, "PLAN ANYWAY").await;
+
+    harness.assert_sent(PLANNER, "has been planned");
+    let saved = controllers::scheduling::check_user_avail_multiple(harness.pool(), USER, vec![date], None).await.unwrap();
+    assert!(saved[0].as_ref().unwrap().planned);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn plan_accepts_suggestion_for_requirements() {
+    let mut harness = Harness::start().await;
+    harness.seed_user(PLANNER, "PLANNER", "PILOT", "ACTIVE", true).await;
+    harness.seed_user(USER, "ALPHA", "PILOT", "ACTIVE", false).await;
+    harness.seed_user(OTHER_USERS[0], "BRAVO", "PILOT", "ACTIVE", false).await;
+    harness.seed_user(OTHER_USERS[1], "CHARLIE", "PILOT", "ACTIVE", false).await;
+    let date = now!().date_naive() + Duration::days(10);
+    for tele_id in [USER, OTHER_USERS[0], OTHER_USERS[1]] {
+        controllers::scheduling::add_user_avail(harness.pool(), tele_id, tele_id, (date, None), &Ict::LIVE, None, None).await.unwrap();
+    }
+
+    // ALPHA was planned recently, so the others are suggested first
+    let earlier = controllers::scheduling::add_user_avail(harness.pool(), USER, USER, (date - Duration::days(7), None), &Ict::LIVE, None, None).await.unwrap();
+    controllers::scheduling::set_plan_status_multiple(harness.pool(), PLANNER, HashMap::from([(earlier.id, PlanStatus::Planned)])).await.unwrap();
+
+    harness.send_text(PLANNER, &format!("/plan {}", date.format("%Y %b %d"))).await;
+    assert!(!harness.buttons(PLANNER).contains(&"ACCEPT SUGGESTION".to_string()));
+
+    harness.press(PLANNER, "REQUIREMENTS").await;
+    harness.press(PLANNER, "+ LIVE").await;
+    harness.press(PLANNER, "+ LIVE").await;
+    harness.assert_sent(PLANNER, "- LIVE: 2");
+    harness.press(PLANNER, "BACK").await;
+
+    harness.assert_sent(PLANNER, "Required: LIVE 2 \\(0 planned\\)");
+    harness.assert_sent(PLANNER, "Suggested: `BRAVO`, `CHARLIE`");
+
+    harness.press(PLANNER, "ACCEPT SUGGESTION").await;
+    harness.assert_sent(PLANNER, "Required: LIVE 2 \\(2 planned\\)");
+    harness.press(PLANNER, "DONE").await;
+
+    harness.assert_sent(PLANNER, "has been planned");
+    for (tele_id, planned) in [(USER, false), (OTHER_USERS[0], true), (OTHER_USERS[1], true)] {
+        let saved = controllers::scheduling::check_user_avail_multiple(harness.pool(), tele_id, vec![date], None).await.unwrap();
+        assert_eq!(saved[0].as_ref().unwrap().planned, planned);
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn plan_user_on_standby() {
+    let mut harness = Harness::start().await;
+    harness.seed_user(PLANNER, "PLANNER", "PILOT", "ACTIVE", true).await;
+    harness.seed_user(USER, "ECHO", "PILOT", "ACTIVE", false).await;
+    let date = now!().date_naive() + Duration::days(3);
+    controllers::scheduling::add_user_avail(harness.pool(), USER, USER, (date, None), &Ict::LIVE, None, None).await.unwrap();
+
+    harness.send_text(PLANNER, "/plan ECHO").await;
+    harness.press(PLANNER, &format!("PLAN {}: LIVE", date.format("%b-%d"))).await;
+    harness.press(PLANNER, "STANDBY").await;
+    harness.assert_sent(PLANNER, "UNPLANNED ➡️ STANDBY");
+    harness.press(PLANNER, "DONE").await;
+
+    harness.assert_sent(PLANNER, "is on standby");
+    let saved = controllers::scheduling::check_user_avail_multiple(harness.pool(), USER, vec![date], None).await.unwrap();
+    let entry = saved[0].as_ref().unwrap();
+    assert!(entry.standby && !entry.planned);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn plan_promotes_standby_when_planned_user_withdraws() {
+    let mut harness = Harness::start().await;
+    harness.seed_user(PLANNER, "PLANNER", "PILOT", "ACTIVE", true).await;
+    harness.seed_user(USER, "GOLF", "PILOT", "ACTIVE", false).await;
+    harness.seed_user(OTHER_USERS[0], "HOTEL", "PILOT", "ACTIVE", false).await;
+    let date = now!().date_naive() + Duration::days(5);
+    let planned = controllers::scheduling::add_user_avail(harness.pool(), USER, USER, (date, None), &Ict::LIVE, None, None).await.unwrap();
+    let standby = controllers::scheduling::add_user_avail(harness.pool(), OTHER_USERS[0], OTHER_USERS[0], (date, None), &Ict::LIVE, None, None).await.unwrap();
+    controllers::scheduling::set_plan_status_multiple(
+        harness.pool(), PLANNER,
+        HashMap::from([(planned.id, PlanStatus::Planned), (standby.id, PlanStatus::Standby)])
+    ).await.unwrap();
+
+    harness.send_text(PLANNER, &format!("/plan {}", date.format("%Y %b %d"))).await;
+    assert!(!harness.buttons(PLANNER).contains(&"PROMOTE STANDBY".to_string()));
+
+    // The planned user withdraws, so the standby can take their place
+    controllers::scheduling::set_user_unavail(harness.pool(), USER, planned.id).await.unwrap();
+    harness.send_text(PLANNER, &format!("/plan {}", date.format("%Y %b %d"))).await;
+    harness.press(PLANNER, "PROMOTE STANDBY").await;
+    harness.assert_sent(PLANNER, "STANDBY ➡️ PLANNED");
+    harness.press(PLANNER, "DONE").await;
+
+    harness.assert_sent(PLANNER, "has been planned");
+    let promoted = controllers::scheduling::get_availability_details_by_uuid(harness.pool(), standby.id).await.unwrap();
+    assert!(promoted.planned && !promoted.standby);
+    // Withdrawn entries can only be fetched while they are still planned
+    assert!(controllers::scheduling::get_availability_details_by_uuid(harness.pool(), planned.id).await.is_err());
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn slots_on_one_date_count_as_one_user() {
+    let harness = Harness::start().await;
+    harness.seed_user(PLANNER, "PLANNER", "PILOT", "ACTIVE", true).await;
+    harness.seed_user(USER, "DELTA", "PILOT", "ACTIVE", false).await;
+    harness.seed_user(OTHER_USERS[0], "ECHO", "PILOT", "ACTIVE", false).await;
+    let date = now!().date_naive() + Duration::days(20);
+    let am = (NaiveTime::from_hms_opt(8, 0, 0).unwrap(), NaiveTime::from_hms_opt(12, 0, 0).unwrap());
+    let pm = (NaiveTime::from_hms_opt(13, 0, 0).unwrap(), NaiveTime::from_hms_opt(17, 0, 0).unwrap());
+
+    // Planned for both slots of an earlier date
+    for slot in [am, pm] {
+        controllers::scheduling::add_user_avail(harness.pool(), PLANNER, USER, (date - Duration::days(5), Some(slot)), &Ict::LIVE, None, Some(true)).await.unwrap();
+    }
+    controllers::scheduling::add_user_avail(harness.pool(), PLANNER, USER, (date, Some(am)), &Ict::LIVE, None, Some(true)).await.unwrap();
+    let afternoon = controllers::scheduling::add_user_avail(harness.pool(), USER, USER, (date, Some(pm)), &Ict::LIVE, None, None).await.unwrap();
+    controllers::scheduling::add_user_avail(harness.pool(), PLANNER, OTHER_USERS[0], (date, None), &Ict::LIVE, None, Some(true)).await.unwrap();
+
+    let history = controllers::scheduling::get_plan_history(harness.pool(), &[afternoon.id], 90).await.unwrap();
+    assert_eq!(history[0].times_planned, 1);
+
+    // Planning the afternoon keeps the headcount at two users
+    let changes = HashMap::from([(afternoon.id, PlanStatus::Planned)]);
+    let conflicts = controllers::scheduling::get_plan_conflicts(harness.pool(), &changes, Some(2), 0).await.unwrap();
+    assert!(conflicts.is_empty(), "{:?}", conflicts);
+    let conflicts = controllers::scheduling::get_plan_conflicts(harness.pool(), &changes, Some(1), 0).await.unwrap();
+    assert_eq!(conflicts.len(), 1);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn withdrawn_entries_do_not_count_toward_capacity() {
+    let harness = Harness::start().await;
+    harness.seed_user(PLANNER, "PLANNER", "PILOT", "ACTIVE", true).await;
+    harness.seed_user(USER, "DELTA", "PILOT", "ACTIVE", false).await;
+    harness.seed_user(OTHER_USERS[0], "ECHO", "PILOT", "ACTIVE", false).await;
+    let date = now!().date_naive() + Duration::days(20);
+
+    let withdrawn = controllers::scheduling::add_user_avail(harness.pool(), PLANNER, OTHER_USERS[0], (date, None), &Ict::LIVE, None, Some(true)).await.unwrap();
+    sqlx::query("UPDATE availability SET is_valid = FALSE WHERE id = $1")
+        .bind(withdrawn.id)
+        .execute(harness.pool())
+        .await
+        .unwrap();
+    let entry = controllers::scheduling::add_user_avail(harness.pool(), USER, USER, (date, None), &Ict::LIVE, None, None).await.unwrap();
+
+    let changes = HashMap::from([(entry.id, PlanStatus::Planned)]);
+    let conflicts = controllers::scheduling::get_plan_conflicts(harness.pool(), &changes, Some(1), 0).await.unwrap();
+    assert!(conflicts.is_empty(), "{:?}", conflicts);
+}
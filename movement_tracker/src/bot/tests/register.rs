@@ -0,0 +1,39 @@
+use super::harness::Harness;
+use crate::controllers;
+
+const APPLICANT: u64 = 2001;
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn register_submits_application() {
+    let mut harness = Harness::start().await;
+
+    harness.send_text(APPLICANT, "/register").await;
+    harness.press(APPLICANT, "PILOT").await;
+    harness.press(APPLICANT, "ACTIVE").await;
+    harness.send_text(APPLICANT, "John Tan").await;
+    harness.send_text(APPLICANT, "alpha").await;
+    harness.assert_sent(APPLICANT, "Confirm registration?");
+    harness.press(APPLICANT, "YES").await;
+
+    harness.assert_sent(APPLICANT, "Please wait for approval");
+    let applications = controllers::apply::get_all_apply_requests(harness.pool(), harness.unit_id().await).await.unwrap();
+    assert_eq!(applications.len(), 1);
+    assert_eq!(applications[0].tele_id as u64, APPLICANT);
+    assert_eq!(applications[0].name, "John Tan");
+    assert_eq!(applications[0].ops_name, "ALPHA");
+    assert_eq!(applications[0].role_type.as_ref(), "PILOT");
+    assert_eq!(applications[0].usr_type.as_ref(), "ACTIVE");
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn register_rejects_existing_user() {
+    let mut harness = Harness::start().await;
+    harness.seed_user(APPLICANT, "ALPHA", "PILOT", "ACTIVE", false).await;
+
+    harness.send_text(APPLICANT, "/register").await;
+
+    harness.assert_sent(APPLICANT, "You have already registered.");
+    assert!(!controllers::apply::apply_exists_tele_id(harness.pool(), APPLICANT).await.unwrap());
+}
@@ -0,0 +1,28 @@
+use chrono::{Duration, Utc};
+
+use super::harness::Harness;
+use crate::types::Ict;
+use crate::{controllers, now, APP_TIMEZONE};
+
+const CLERK: u64 = 1001;
+const NS_USER: u64 = 4001;
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn saf100_confirm_issues_saf100() {
+    let mut harness = Harness::start().await;
+    harness.seed_user(CLERK, "CLERK", "PILOT", "ACTIVE", true).await;
+    harness.seed_user(NS_USER, "ECHO", "PILOT", "NS", false).await;
+    let date = now!().date_naive() + Duration::days(6);
+    controllers::scheduling::add_user_avail(harness.pool(), CLERK, NS_USER, (date, None), &Ict::LIVE, None, Some(true)).await.unwrap();
+
+    harness.send_text(CLERK, "/saf100").await;
+    harness.press(CLERK, "SEE PLANNED").await;
+    harness.assert_sent(CLERK, "SAF100 Pending");
+    harness.press(CLERK, &format!("ECHO: {} LIVE", date.format("%b-%d"))).await;
+    harness.press(CLERK, "YES").await;
+
+    harness.assert_sent(NS_USER, &format!("SAF100 for {} sent to NS branch", date.format("%Y-%m-%d")));
+    let saved = controllers::scheduling::check_user_avail_multiple(harness.pool(), NS_USER, vec![date], None).await.unwrap();
+    assert!(saved[0].as_ref().unwrap().saf100);
+}
@@ -0,0 +1,61 @@
+use chrono::{Duration, NaiveTime, Utc};
+
+use super::harness::Harness;
+use crate::types::{Ict, RoleType};
+use crate::{controllers, now, APP_TIMEZONE};
+
+const PLANNER: u64 = 1001;
+const NS_USER: u64 = 4001;
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn stats_count_offered_planned_and_no_shows() {
+    let mut harness = Harness::start().await;
+    harness.seed_user(PLANNER, "PLANNER", "PILOT", "ACTIVE", true).await;
+    harness.seed_user(NS_USER, "GOLF", "PILOT", "NS", false).await;
+    let today = now!().date_naive();
+
+    // One missed, one attended with SAF100 issued and one only offered
+    controllers::scheduling::add_user_avail(harness.pool(), PLANNER, NS_USER, (today - Duration::days(3), None), &Ict::LIVE, None, Some(true)).await.unwrap();
+    let attended = controllers::scheduling::add_user_avail(harness.pool(), PLANNER, NS_USER, (today - Duration::days(2), None), &Ict::LIVE, None, Some(true)).await.unwrap();
+    controllers::scheduling::add_user_avail(harness.pool(), NS_USER, NS_USER, (today - Duration::days(1), None), &Ict::SIMS, None, None).await.unwrap();
+    sqlx::query("UPDATE availability SET attended = TRUE, saf100 = TRUE WHERE id = $1")
+        .bind(attended.id)
+        .execute(harness.pool())
+        .await
+        .unwrap();
+
+    let unit_id = harness.unit_id().await;
+    let stats_list = controllers::stats::get_user_stats(harness.pool(), Some(unit_id), Some(&RoleType::new("PILOT")), today - Duration::days(7), today, today).await.unwrap();
+    let stats = stats_list.iter().find(|stats| stats.ops_name == "GOLF").unwrap();
+    assert_eq!((stats.offered, stats.planned, stats.attended, stats.saf100, stats.no_shows), (3, 2, 1, 1, 1));
+    let planner = stats_list.iter().find(|stats| stats.ops_name == "PLANNER").unwrap();
+    assert_eq!((planner.offered, planner.planned), (0, 0));
+
+    harness.send_text(PLANNER, "/stats").await;
+    harness.assert_sent(PLANNER, "Planning stats for role");
+    harness.assert_sent(PLANNER, "GOLF");
+    harness.press(PLANNER, "DONE").await;
+    assert!(harness.buttons(PLANNER).is_empty());
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn stats_count_slots_on_one_date_as_one_day() {
+    let harness = Harness::start().await;
+    harness.seed_user(PLANNER, "PLANNER", "PILOT", "ACTIVE", true).await;
+    harness.seed_user(NS_USER, "GOLF", "PILOT", "NS", false).await;
+    let today = now!().date_naive();
+    let date = today - Duration::days(2);
+
+    // Planned morning and afternoon slots on the same date, neither attended
+    let am = (NaiveTime::from_hms_opt(8, 0, 0).unwrap(), NaiveTime::from_hms_opt(12, 0, 0).unwrap());
+    let pm = (NaiveTime::from_hms_opt(13, 0, 0).unwrap(), NaiveTime::from_hms_opt(17, 0, 0).unwrap());
+    controllers::scheduling::add_user_avail(harness.pool(), PLANNER, NS_USER, (date, Some(am)), &Ict::LIVE, None, Some(true)).await.unwrap();
+    controllers::scheduling::add_user_avail(harness.pool(), PLANNER, NS_USER, (date, Some(pm)), &Ict::LIVE, None, Some(true)).await.unwrap();
+
+    let unit_id = harness.unit_id().await;
+    let stats_list = controllers::stats::get_user_stats(harness.pool(), Some(unit_id), None, today - Duration::days(7), today, today).await.unwrap();
+    let stats = stats_list.iter().find(|stats| stats.ops_name == "GOLF").unwrap();
+    assert_eq!((stats.offered, stats.planned, stats.attended, stats.no_shows), (1, 1, 0, 1));
+}
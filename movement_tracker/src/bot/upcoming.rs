@@ -5,7 +5,6 @@ use teloxide::prelude::*;
 use crate::bot::{handle_error, send_msg, HandlerResult, MyDialogue};
 use crate::bot::state::State;
 use crate::{controllers, log_endpoint_hit, utils};
-use crate::types::UsrType;
 
 pub(super) async fn upcoming(bot: Bot, dialogue: MyDialogue, msg: Message, pool: PgPool) -> HandlerResult {
     log_endpoint_hit!(dialogue.chat_id(), "upcoming", "Command", msg);
@@ -33,7 +32,11 @@ pub(super) async fn upcoming(bot: Bot, dialogue: MyDialogue, msg: Message, pool:
                 // Iterate over each availability entry and append formatted details
                 for availability in availability_list {
                     // Format the date (e.g., "Sep 20, 2024")
-                    let date_str = availability.avail.format("%b %d\\, %Y").to_string();
+                    let date_str = format!(
+                        "{}{}",
+                        availability.avail.format("%b %d\\, %Y"),
+                        utils::escape_special_characters(&utils::format_slot_suffix(availability.start_time, availability.end_time))
+                    );
 
                     // ICT Type (e.g., "Type A")
                     let ict_type_str = availability.ict_type.as_ref();
@@ -50,23 +53,21 @@ pub(super) async fn upcoming(bot: Bot, dialogue: MyDialogue, msg: Message, pool:
                     };
 
                     // SAF100 Status
-                    let saf100_str = match availability.usr_type {
-                        UsrType::NS => {
-                            if availability.saf100 {
-                                "*SAF100 Issued*"
-                            } else if availability.planned {
-                                "*SAF100 Pending*"
-                            } else {
-                                ""
-                            }
-                        }
-                        _ => "",
+                    let saf100_str = if !availability.usr_type.is_ns() {
+                        ""
+                    } else if availability.saf100 {
+                        "*SAF100 Issued*"
+                    } else if availability.planned {
+                        "*SAF100 Pending*"
+                    } else {
+                        ""
                     };
 
                     // Compile the entry into a formatted string
                     message.push_str(&format!(
-                        "\\- *Date*: {}\n  *ICT Type*: {}\n  *Remarks*: {}\n  {}\n\n",
+                        "\\- *Date*: {}\n  *Status*: {}\n  *ICT Type*: {}\n  *Remarks*: {}\n  {}\n\n",
                         date_str,
+                        availability.plan_status().as_ref(),
                         ict_type_str,
                         remarks_str,
                         saf100_str
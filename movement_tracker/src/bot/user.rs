@@ -6,9 +6,9 @@ use sqlx::PgPool;
 use teloxide::prelude::*;
 use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, MessageId, ParseMode, User};
 
-use super::{handle_error, log_try_delete_msg, log_try_remove_markup, match_callback_data, retrieve_callback_data, send_msg, send_or_edit_msg, validate_name, validate_ops_name, HandlerResult, MyDialogue};
+use super::{check_actor_admin, handle_error, log_try_delete_msg, log_try_remove_markup, match_callback_data, retrieve_callback_data, send_msg, send_or_edit_msg, validate_name, validate_ops_name, HandlerResult, MyDialogue};
 use crate::bot::state::State;
-use crate::types::{RoleType, UserInfo, Usr, UsrType};
+use crate::types::{AdminRole, RoleType, UserInfo, Usr, UsrType};
 use crate::{controllers, log_endpoint_hit, notifier, now, utils};
 
 use serde::{Deserialize, Serialize};
@@ -30,6 +30,7 @@ enum UserEditCallbacks {
     RoleType,
     UserType,
     Admin,
+    Permissions,
 
     // Role and User type selection Actions
     SelectRoleType { role_type: RoleType },
@@ -39,6 +40,10 @@ enum UserEditCallbacks {
     AdminYes,
     AdminNo,
 
+    // Admin role selection Actions
+    ToggleAdminRole { admin_role: AdminRole },
+    SaveAdminRoles,
+
     // Delete confirmation Actions
     DeleteYes,
     DeleteNo
@@ -49,7 +54,8 @@ fn get_inline_keyboard(is_last_admin: bool, prefix: &String) -> InlineKeyboardMa
         ("NAME", UserEditCallbacks::Name),
         ("OPS NAME", UserEditCallbacks::OpsName),
         ("ROLE", UserEditCallbacks::RoleType),
-        ("TYPE", UserEditCallbacks::UserType)
+        ("TYPE", UserEditCallbacks::UserType),
+        ("PERMISSIONS", UserEditCallbacks::Permissions)
     ]
         .into_iter()
         .map(|(text, data)| vec![InlineKeyboardButton::callback(text, data.to_callback_data(&prefix))])
@@ -101,8 +107,8 @@ async fn display_user_edit_prompt(
     send_or_edit_msg(&bot, chat_id, username, msg_id, get_user_edit_text(user_details), Some(get_inline_keyboard(is_last_admin, prefix)), Some(ParseMode::MarkdownV2)).await
 }
 
-async fn display_edit_user_types(bot: &Bot, chat_id: ChatId, username: &Option<String>, prefix: &String) -> Option<MessageId> {
-    let usrtypes = UsrType::iter()
+async fn display_edit_user_types(bot: &Bot, chat_id: ChatId, username: &Option<String>, usr_types: Vec<UsrType>, prefix: &String) -> Option<MessageId> {
+    let usrtypes = usr_types.into_iter()
         .map(|usrtype| InlineKeyboardButton::callback(usrtype.clone().as_ref(), UserEditCallbacks::SelectUserType { user_type: usrtype }.to_callback_data(&prefix)));
 
     send_msg(
@@ -112,8 +118,8 @@ async fn display_edit_user_types(bot: &Bot, chat_id: ChatId, username: &Option<S
     ).await
 }
 
-async fn display_edit_role_types(bot: &Bot, chat_id: ChatId, username: &Option<String>, prefix: &String) -> Option<MessageId> {
-    let role_types = RoleType::iter()
+async fn display_edit_role_types(bot: &Bot, chat_id: ChatId, username: &Option<String>, role_types: Vec<RoleType>, prefix: &String) -> Option<MessageId> {
+    let role_types = role_types.into_iter()
         .map(|roletype| InlineKeyboardButton::callback(roletype.clone().as_ref(), UserEditCallbacks::SelectRoleType { role_type: roletype }.to_callback_data(&prefix)));
 
     send_msg(
@@ -148,6 +154,36 @@ async fn display_edit_admin(bot: &Bot, chat_id: ChatId, username: &Option<String
     ).await
 }
 
+fn get_admin_roles_text(user_details: &Usr, admin_roles: &[AdminRole]) -> String {
+    let held: Vec<&str> = admin_roles.iter().map(|admin_role| admin_role.as_ref()).collect();
+    format!(
+        "Permissions of {}: {}\n{}Toggle the roles to grant, then press SAVE.",
+        user_details.ops_name,
+        if held.is_empty() { "NONE".to_string() } else { held.join(", ") },
+        if user_details.admin { "This user is an admin and holds every role regardless.\n" } else { "" }
+    )
+}
+
+async fn display_edit_admin_roles(
+    bot: &Bot,
+    chat_id: ChatId,
+    username: &Option<String>,
+    user_details: &Usr,
+    admin_roles: &[AdminRole],
+    prefix: &str,
+    msg_id: Option<MessageId>
+) -> Option<MessageId> {
+    let mut options: Vec<Vec<InlineKeyboardButton>> = AdminRole::iter()
+        .map(|admin_role| vec![InlineKeyboardButton::callback(
+            format!("{}{}", if admin_roles.contains(&admin_role) { "✅ " } else { "" }, admin_role.as_ref()),
+            UserEditCallbacks::ToggleAdminRole { admin_role }.to_callback_data(prefix)
+        )])
+        .collect();
+    options.push(vec![InlineKeyboardButton::callback("SAVE", UserEditCallbacks::SaveAdminRoles.to_callback_data(prefix))]);
+
+    send_or_edit_msg(bot, chat_id, username, msg_id, get_admin_roles_text(user_details, admin_roles), Some(InlineKeyboardMarkup::new(options)), None).await
+}
+
 async fn display_delete_confirmation(bot: &Bot, chat_id: ChatId, username: &Option<String>, prefix: &String) -> Option<MessageId> {
     let confirm = [("YES", UserEditCallbacks::DeleteYes), ("NO", UserEditCallbacks::DeleteNo)]
         .map(|(text, data)| InlineKeyboardButton::callback(text, data.to_callback_data(&prefix)));
@@ -251,11 +287,20 @@ async fn display_enter_ops_name(bot: &Bot, dialogue: &MyDialogue, username: &Opt
 }
 
 async fn handle_show_prompt(bot: &Bot, dialogue: &MyDialogue, pool: &PgPool, user: &User) -> HandlerResult {
-    if let Ok(result) = controllers::user::get_all_user_info(pool).await {
+    // Only users of the admin's own unit are listed
+    let unit_id = match controllers::user::get_unit_id_by_tele_id(pool, user.id.0).await {
+        Ok(unit_id) => unit_id,
+        Err(_) => {
+            handle_error(bot, dialogue, dialogue.chat_id(), &user.username).await;
+            return Ok(());
+        }
+    };
+
+    if let Ok(result) = controllers::user::get_all_user_info(pool, unit_id).await {
         display_enter_ops_name(&bot, &dialogue, &user.username, result).await;
         dialogue.update(State::UserSelect).await?;
     } else {
-        handle_error(&bot, &dialogue, dialogue.chat_id(), &user.username).await;
+        handle_error(bot, dialogue, dialogue.chat_id(), &user.username).await;
     }
 
     Ok(())
@@ -271,11 +316,19 @@ async fn display_retry_user_select(bot: &Bot, chat_id: ChatId, username: &Option
 async fn handle_ops_name_input(bot: &Bot, dialogue: &MyDialogue, pool: &PgPool, user: &User, ops_name: String, show_users_on_err: bool) -> HandlerResult {
     let cleaned_ops_name = ops_name.trim().to_uppercase();
 
+    let unit_id = match controllers::user::get_unit_id_by_tele_id(pool, user.id.0).await {
+        Ok(unit_id) => unit_id,
+        Err(_) => {
+            handle_error(bot, dialogue, dialogue.chat_id(), &user.username).await;
+            return Ok(());
+        }
+    };
+
     // Get the user in the database
-    match controllers::user::user_exists_ops_name(&pool, cleaned_ops_name.as_ref()).await{
+    match controllers::user::user_exists_ops_name(pool, unit_id, cleaned_ops_name.as_ref()).await{
         Ok(exists) => {
             if exists {
-                match controllers::user::get_user_by_ops_name(&pool, cleaned_ops_name.as_ref()).await {
+                match controllers::user::get_user_by_ops_name(pool, unit_id, cleaned_ops_name.as_ref()).await {
                     Ok(user_details) => {
                         // Generate random prefix to make the IDs only applicable to this dialogue instance
                         let prefix = utils::generate_prefix(utils::CALLBACK_PREFIX_LEN);
@@ -410,6 +463,7 @@ pub(super) async fn user_edit_prompt(
             };
             match controllers::user::update_user(
                 &pool,
+                q.from.id.0,
                 &user_details
             ).await {
                 Ok(user_updated) => {
@@ -474,6 +528,7 @@ pub(super) async fn user_edit_prompt(
                                 changes_message
                             ),
                             &pool,
+                            Some(user_details.unit_id),
                             q.from.id.0 as i64
                         ).await;
                     }
@@ -534,25 +589,59 @@ pub(super) async fn user_edit_prompt(
         }
         UserEditCallbacks::RoleType => {
             log_try_remove_markup(&bot, dialogue.chat_id(), msg_id).await;
-            match display_edit_role_types(&bot, dialogue.chat_id(), &q.from.username, &prefix).await {
+            let role_types = match controllers::crew_types::get_role_types(&pool).await {
+                Ok(role_types) => role_types,
+                Err(_) => {
+                    handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await;
+                    return Ok(());
+                }
+            };
+            match display_edit_role_types(&bot, dialogue.chat_id(), &q.from.username, role_types, &prefix).await {
                 None => dialogue.update(State::ErrorState).await?,
                 Some(change_msg_id) => dialogue.update(State::UserEditRole { msg_id, change_msg_id, user_details, prefix }).await?
             };
         }
         UserEditCallbacks::UserType => {
             log_try_remove_markup(&bot, dialogue.chat_id(), msg_id).await;
-            match display_edit_user_types(&bot, dialogue.chat_id(), &q.from.username, &prefix).await {
+            let usr_types = match controllers::crew_types::get_usr_types(&pool).await {
+                Ok(usr_types) => usr_types,
+                Err(_) => {
+                    handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await;
+                    return Ok(());
+                }
+            };
+            match display_edit_user_types(&bot, dialogue.chat_id(), &q.from.username, usr_types, &prefix).await {
                 None => dialogue.update(State::ErrorState).await?,
                 Some(change_msg_id) => dialogue.update(State::UserEditType { msg_id, change_msg_id, user_details, prefix }).await?
             };
         }
         UserEditCallbacks::Admin => {
+            if !check_actor_admin(&bot, &dialogue, &q.from, &pool, "Only admins can change admin status.").await {
+                return Ok(());
+            }
             log_try_remove_markup(&bot, dialogue.chat_id(), msg_id).await;
             match display_edit_admin(&bot, dialogue.chat_id(), &q.from.username, &prefix).await {
                 None => dialogue.update(State::ErrorState).await?,
                 Some(change_msg_id) => dialogue.update(State::UserEditAdmin { msg_id, change_msg_id, user_details, prefix }).await?
             };
         }
+        UserEditCallbacks::Permissions => {
+            if !check_actor_admin(&bot, &dialogue, &q.from, &pool, "Only admins can change permissions.").await {
+                return Ok(());
+            }
+            log_try_remove_markup(&bot, dialogue.chat_id(), msg_id).await;
+            let admin_roles = match controllers::permissions::get_admin_roles_by_uuid(&pool, user_details.id).await {
+                Ok(admin_roles) => admin_roles,
+                Err(_) => {
+                    handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await;
+                    return Ok(());
+                }
+            };
+            match display_edit_admin_roles(&bot, dialogue.chat_id(), &q.from.username, &user_details, &admin_roles, &prefix, None).await {
+                None => dialogue.update(State::ErrorState).await?,
+                Some(change_msg_id) => dialogue.update(State::UserEditPermissions { msg_id, change_msg_id, user_details, prefix, admin_roles }).await?
+            };
+        }
         _ => {
             send_msg(
                 bot.send_message(dialogue.chat_id(), "Invalid option. Type /cancel to abort."),
@@ -644,7 +733,7 @@ pub(super) async fn user_edit_ops_name(
 
     match msg.text().map(ToOwned::to_owned) {
         Some(input_ops_name_raw) => {
-            match validate_ops_name(&bot, &dialogue, &user.username, input_ops_name_raw, &pool).await {
+            match validate_ops_name(&bot, &dialogue, &user.username, input_ops_name_raw, user_details.unit_id, &pool).await {
                 Ok(ops_name) => {
                     user_details.ops_name = ops_name.clone();
                     send_msg(
@@ -836,6 +925,123 @@ pub(super) async fn user_edit_admin(
     Ok(())
 }
 
+pub(super) async fn user_edit_permissions(
+    bot: Bot,
+    dialogue: MyDialogue,
+    (msg_id, change_msg_id, user_details, prefix, mut admin_roles): (MessageId, MessageId, Usr, String, Vec<AdminRole>),
+    q: CallbackQuery,
+    pool: PgPool
+) -> HandlerResult {
+    log_endpoint_hit!(
+        dialogue.chat_id(), "user_edit_permissions", "Callback", q,
+        "MessageId" => msg_id,
+        "Change MessageId" => change_msg_id,
+        "User Details" => user_details,
+        "Prefix" => prefix,
+        "Admin Roles" => admin_roles
+    );
+
+    // Extract the callback data
+    let data = match retrieve_callback_data(&bot, dialogue.chat_id(), &q).await {
+        Ok(data) => data,
+        Err(_) => { return Ok(()); }
+    };
+
+    // Acknowledge the callback to remove the loading state
+    if let Err(e) = bot.answer_callback_query(q.id).await {
+        log::error!("Failed to answer callback query: {}", e);
+    }
+
+    // Deserialize the callback data into the enum
+    let callback = match match_callback_data(&bot, dialogue.chat_id(), &q.from.username, &data, &prefix).await {
+        Ok(callback) => callback,
+        Err(_) => { return Ok(()); }
+    };
+
+    match callback {
+        UserEditCallbacks::ToggleAdminRole { admin_role } => {
+            if let Some(index) = admin_roles.iter().position(|held| *held == admin_role) {
+                admin_roles.remove(index);
+            } else {
+                admin_roles.push(admin_role);
+            }
+            match display_edit_admin_roles(&bot, dialogue.chat_id(), &q.from.username, &user_details, &admin_roles, &prefix, Some(change_msg_id)).await {
+                None => dialogue.update(State::ErrorState).await?,
+                Some(change_msg_id) => dialogue.update(State::UserEditPermissions { msg_id, change_msg_id, user_details, prefix, admin_roles }).await?
+            };
+        }
+        UserEditCallbacks::SaveAdminRoles => {
+            // Checked again in case the actor lost admin status while editing
+            if !check_actor_admin(&bot, &dialogue, &q.from, &pool, "Only admins can change permissions.").await {
+                return Ok(());
+            }
+
+            let original_roles = match controllers::permissions::get_admin_roles_by_uuid(&pool, user_details.id).await {
+                Ok(original_roles) => original_roles,
+                Err(_) => {
+                    handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await;
+                    return Ok(());
+                }
+            };
+
+            if controllers::permissions::set_admin_roles(&pool, q.from.id.0, user_details.id, &admin_roles).await.is_err() {
+                handle_error(&bot, &dialogue, dialogue.chat_id(), &q.from.username).await;
+                return Ok(());
+            }
+
+            let format_roles = |roles: &[AdminRole]| if roles.is_empty() {
+                "NONE".to_string()
+            } else {
+                roles.iter().map(|role| role.as_ref()).collect::<Vec<&str>>().join(", ")
+            };
+            let mut sorted_roles = admin_roles.clone();
+            sorted_roles.sort_by_key(|role| role.as_ref().to_string());
+            let mut sorted_original = original_roles.clone();
+            sorted_original.sort_by_key(|role| role.as_ref().to_string());
+
+            if sorted_roles != sorted_original {
+                notifier::emit::system_notifications(
+                    &bot,
+                    &format!(
+                        "{} has amended permissions for *{}*:\n`{}` ➡️ `{}`",
+                        utils::username_link_tag(&q.from),
+                        utils::escape_special_characters(&user_details.ops_name),
+                        utils::escape_special_characters(&format_roles(&sorted_original)),
+                        utils::escape_special_characters(&format_roles(&sorted_roles))
+                    ),
+                    &pool,
+                    Some(user_details.unit_id),
+                    q.from.id.0 as i64
+                ).await;
+
+                // Inform the user of their new permissions
+                send_msg(
+                    bot.send_message(
+                        ChatId(user_details.tele_id),
+                        format!("Your permissions are now: {}. Use /help to see available actions.", format_roles(&sorted_roles))
+                    ),
+                    &q.from.username,
+                ).await;
+            }
+
+            log_try_delete_msg(&bot, dialogue.chat_id(), change_msg_id).await;
+            send_msg(
+                bot.send_message(dialogue.chat_id(), format!("Saved permissions: {}", format_roles(&sorted_roles))),
+                &q.from.username,
+            ).await;
+            handle_go_back(&bot, &dialogue, &q.from.username, user_details, &pool, prefix, msg_id).await?;
+        }
+        _ => {
+            send_msg(
+                bot.send_message(dialogue.chat_id(), "Invalid option. Type /cancel to abort."),
+                &q.from.username,
+            ).await;
+        }
+    }
+
+    Ok(())
+}
+
 pub(super) async fn user_edit_delete(
     bot: Bot,
     dialogue: MyDialogue,
@@ -870,7 +1076,7 @@ pub(super) async fn user_edit_delete(
 
     match callback {
         UserEditCallbacks::DeleteYes => {
-            match controllers::user::remove_user_by_uuid(&pool, user_details.id).await {
+            match controllers::user::remove_user_by_uuid(&pool, q.from.id.0, user_details.id).await {
                 Ok(success) => {
                     log_try_delete_msg(&bot, dialogue.chat_id(), change_msg_id).await;
                     log_try_remove_markup(&bot, dialogue.chat_id(), msg_id).await;
@@ -885,6 +1091,7 @@ pub(super) async fn user_edit_delete(
                                 format!("[{}](tg://user?id={})", utils::escape_special_characters(&user_details.name), user_details.tele_id as u64)
                             ).as_str(),
                             &pool,
+                            Some(user_details.unit_id),
                             q.from.id.0 as i64
                         ).await;
 
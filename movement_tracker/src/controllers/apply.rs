@@ -1,3 +1,4 @@
+use crate::controllers::audit::{add_audit_log, get_snapshot, AuditAction, AuditEntity};
 use crate::types::{Apply, RoleType, UsrType};
 use sqlx::types::Uuid;
 use sqlx::PgPool;
@@ -9,8 +10,11 @@ pub(crate) async fn apply_user(
     name: String,
     ops_name: String,
     role_type: RoleType,
-    user_type: UsrType
+    user_type: UsrType,
+    unit_id: Uuid
 ) -> Result<bool, sqlx::Error> {
+    let mut tx = conn.begin().await?;
+
     let result = sqlx::query!(
         r#"
         WITH invalidated AS (
@@ -20,8 +24,8 @@ pub(crate) async fn apply_user(
             WHERE tele_id = $1 AND is_valid = TRUE
             RETURNING *
         )
-        INSERT INTO apply (tele_id, chat_username, name, ops_name, role_type, usr_type)
-        VALUES ($1, $2, $3, $4, $5, $6)
+        INSERT INTO apply (tele_id, chat_username, name, ops_name, role_type, usr_type, unit_id)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
         RETURNING id;
         "#,
         tele_id as i64,   // `tele_id` is INT8 (bigint), so we cast `u64` to `i64`.
@@ -29,25 +33,34 @@ pub(crate) async fn apply_user(
         name,
         ops_name,
         role_type as RoleType,
-        user_type as UsrType 
+        user_type as UsrType,
+        unit_id
     )
-        .fetch_one(conn)
+        .fetch_one(&mut *tx)
         .await;
 
-    match result {
-        Ok(_) => {
-            log::info!("User with tele_id: ({}) and name: ({}) successfully applied", tele_id, name);
-            Ok(true)  // Application was successful
-        }
+    let id = match result {
+        Ok(record) => record.id,
         Err(e) => {
             log::error!("Error inserting user application: {}", e);
-            Err(e)  // Return the SQL error
+            tx.rollback().await?;
+            return Err(e);  // Return the SQL error
         }
+    };
+
+    // The applicant is the actor of their own application
+    if let Err(e) = add_audit_log(&mut tx, tele_id, AuditAction::Add, AuditEntity::Apply, id, None).await {
+        tx.rollback().await?;
+        return Err(e);
     }
+
+    tx.commit().await?;
+    log::info!("User with tele_id: ({}) and name: ({}) successfully applied", tele_id, name);
+    Ok(true)  // Application was successful
 }
 
-// Function to fetch all apply requests
-pub async fn get_all_apply_requests(pool: &PgPool) -> Result<Vec<Apply>, sqlx::Error> {
+// Function to fetch all apply requests to a unit
+pub async fn get_all_apply_requests(pool: &PgPool, unit_id: Uuid) -> Result<Vec<Apply>, sqlx::Error> {
     let result = sqlx::query_as!(
         Apply,
         r#"
@@ -59,11 +72,14 @@ pub async fn get_all_apply_requests(pool: &PgPool) -> Result<Vec<Apply>, sqlx::E
             apply.ops_name AS ops_name,
             apply.usr_type AS "usr_type: _",
             apply.role_type AS "role_type: _",
+            apply.unit_id AS unit_id,
             apply.created AS created,
             apply.updated AS updated
         FROM apply
-        WHERE apply.is_valid = TRUE;  -- Fetch only valid apply requests
-        "#
+        WHERE apply.is_valid = TRUE  -- Fetch only valid apply requests
+          AND apply.unit_id = $1;
+        "#,
+        unit_id
     )
         .fetch_all(pool)
         .await;
@@ -93,6 +109,7 @@ pub(crate) async fn get_apply_by_uuid(conn: &PgPool, id: Uuid) -> Result<Apply,
             ops_name,
             usr_type AS "usr_type: _",
             role_type AS "role_type: _",
+            unit_id,
             created,
             updated
         FROM apply
@@ -116,7 +133,10 @@ pub(crate) async fn get_apply_by_uuid(conn: &PgPool, id: Uuid) -> Result<Apply,
 }
 
 
-pub(crate) async fn remove_apply_by_uuid(conn: &PgPool, id: Uuid) -> Result<bool, sqlx::Error> {
+pub(crate) async fn remove_apply_by_uuid(conn: &PgPool, actor: u64, id: Uuid) -> Result<bool, sqlx::Error> {
+    let mut tx = conn.begin().await?;
+    let before = get_snapshot(&mut tx, AuditEntity::Apply, id).await?;
+
     let result = sqlx::query!(
         r#"
         UPDATE apply
@@ -125,12 +145,14 @@ pub(crate) async fn remove_apply_by_uuid(conn: &PgPool, id: Uuid) -> Result<bool
         "#,
         id
     )
-        .execute(conn)
+        .execute(&mut *tx)
         .await;
 
     match result {
         Ok(query_result) => {
             if query_result.rows_affected() == 1 {
+                add_audit_log(&mut tx, actor, AuditAction::Remove, AuditEntity::Apply, id, before).await?;
+                tx.commit().await?;
                 log::info!("Successfully soft-deleted apply request with id: {}", id);
                 Ok(true)
             } else {
@@ -140,6 +162,7 @@ pub(crate) async fn remove_apply_by_uuid(conn: &PgPool, id: Uuid) -> Result<bool
         }
         Err(e) => {
             log::error!("Error soft-deleting apply request by id: {}", e);
+            tx.rollback().await?;
             Err(e)
         }
     }
@@ -4,32 +4,38 @@ use sqlx::types::chrono::NaiveDate;
 use sqlx::PgPool;
 use sqlx::types::Uuid;
 
-use crate::types::{AvailabilityDetails, UsrType};
+use crate::controllers::audit::{add_audit_log, get_snapshot, AuditAction, AuditEntity};
+use crate::types::{AvailabilityDetails, RoleType, UsrType};
 use crate::{now, APP_TIMEZONE};
 
-async fn set_attendance(
+pub(crate) async fn set_attendance_by_uuid(
     conn: &PgPool,
-    tele_id: i64,
-    date: NaiveDate,
+    actor: u64,
+    id: Uuid,
     attended: bool,
 ) -> Result<AvailabilityDetails, sqlx::Error> {
+    let mut tx = conn.begin().await?;
+    let before = get_snapshot(&mut tx, AuditEntity::Availability, id).await?;
+
     let result = sqlx::query_as!(
         AvailabilityDetails,
         r#"
         WITH update_statement AS (
             UPDATE availability
             SET attended = $1
-            FROM usrs
-            WHERE usr_id = usrs.id 
-            AND usrs.tele_id = $2
-            AND availability.avail = $3
+            WHERE availability.id = $2
             AND availability.is_valid = TRUE  -- Only update valid entries
             RETURNING
                 availability.id,
+                availability.usr_id,
                 avail,
                 ict_type,
+                start_time,
+                end_time,
                 remarks,
                 planned,
+                standby,
+                ack,
                 saf100,
                 attended,
                 availability.is_valid,
@@ -42,28 +48,33 @@ async fn set_attendance(
             usrs.usr_type AS "usr_type: _",
             update_statement.avail,
             update_statement.ict_type AS "ict_type: _",
+            update_statement.start_time,
+            update_statement.end_time,
             update_statement.remarks,
             update_statement.planned,
+            update_statement.standby,
+            update_statement.ack AS "ack: _",
             update_statement.saf100,
             update_statement.attended,
             update_statement.is_valid,
             update_statement.created,
             update_statement.updated
         FROM usrs, update_statement
-        WHERE usrs.tele_id = $2 AND usrs.is_valid = TRUE;
+        WHERE usrs.id = update_statement.usr_id AND usrs.is_valid = TRUE;
         "#,
         attended,
-        tele_id,
-        date,
+        id,
     )
-        .fetch_one(conn)
+        .fetch_one(&mut *tx)
         .await;
 
     match result {
         Ok(res) => {
+            add_audit_log(&mut tx, actor, AuditAction::Attendance, AuditEntity::Availability, id, before).await?;
+            tx.commit().await?;
             log::info!(
                 "Attendance status ({}) updated for user ({}) on: ({})",
-                res.attended, res.ops_name, date
+                res.attended, res.ops_name, res.avail
             );
             Ok(res)
         }
@@ -74,8 +85,73 @@ async fn set_attendance(
     }
 }
 
+pub(crate) async fn get_planned_availability_by_role_on_date(
+    conn: &PgPool,
+    unit_id: Uuid,
+    date: &NaiveDate,
+    role_type: &RoleType,
+) -> Result<Vec<AvailabilityDetails>, sqlx::Error> {
+    let result = sqlx::query_as!(
+        AvailabilityDetails,
+        r#"
+        SELECT
+            availability.id,
+            usrs.ops_name,
+            usrs.usr_type AS "usr_type: _",
+            availability.avail,
+            availability.ict_type AS "ict_type: _",
+            availability.start_time,
+            availability.end_time,
+            availability.remarks,
+            availability.planned,
+            availability.standby,
+            availability.ack AS "ack: _",
+            availability.saf100,
+            availability.attended,
+            availability.is_valid,
+            availability.created,
+            availability.updated
+        FROM availability
+        JOIN usrs ON usrs.id = availability.usr_id
+        WHERE availability.avail = $1 AND usrs.is_valid = TRUE
+          AND usrs.role_type = $2
+          AND usrs.unit_id = $3
+          AND availability.planned = TRUE
+          AND availability.is_valid = TRUE
+        ORDER BY usrs.ops_name ASC;
+        "#,
+        date,
+        role_type as _,  // Map RoleType enum
+        unit_id
+    )
+        .fetch_all(conn)
+        .await;
+
+    match result {
+        Ok(availability_list) => {
+            log::info!(
+                "Found {} users with role {:?} planned on {}",
+                availability_list.len(),
+                role_type,
+                date
+            );
+            Ok(availability_list)
+        }
+        Err(e) => {
+            log::error!(
+                "Error fetching users with role {:?} planned on {}: {}",
+                role_type,
+                date,
+                e
+            );
+            Err(e)
+        }
+    }
+}
+
 pub(crate) async fn get_future_planned_availability_for_ns(
     conn: &PgPool,
+    unit_id: Option<Uuid>,
 ) -> Result<Vec<AvailabilityDetails>, sqlx::Error> {
     let today = now!().date_naive(); // Get today's date
 
@@ -88,8 +164,12 @@ pub(crate) async fn get_future_planned_availability_for_ns(
             usrs.usr_type AS "usr_type: _",
             availability.avail,
             availability.ict_type AS "ict_type: _",
+            availability.start_time,
+            availability.end_time,
             availability.remarks,
             availability.planned,
+            availability.standby,
+            availability.ack AS "ack: _",
             availability.saf100,
             availability.attended,
             availability.is_valid,
@@ -100,10 +180,12 @@ pub(crate) async fn get_future_planned_availability_for_ns(
         WHERE usrs.usr_type = $1 AND usrs.is_valid = TRUE
           AND availability.planned = TRUE
           AND availability.avail >= $2
+          AND ($3::UUID IS NULL OR usrs.unit_id = $3)
         ORDER BY availability.avail ASC;
         "#,
-        UsrType::NS as _,
-        today
+        UsrType::ns() as _,
+        today,
+        unit_id
     )
         .fetch_all(conn)
         .await;
@@ -128,6 +210,7 @@ pub(crate) async fn get_future_planned_availability_for_ns(
 
 pub(crate) async fn get_future_valid_availability_for_ns(
     conn: &PgPool,
+    unit_id: Uuid,
 ) -> Result<Vec<AvailabilityDetails>, sqlx::Error> {
     let today = now!().date_naive(); // Get today's date
 
@@ -140,8 +223,12 @@ pub(crate) async fn get_future_valid_availability_for_ns(
             usrs.usr_type AS "usr_type: _",
             availability.avail,
             availability.ict_type AS "ict_type: _",
+            availability.start_time,
+            availability.end_time,
             availability.remarks,
             availability.planned,
+            availability.standby,
+            availability.ack AS "ack: _",
             availability.saf100,
             availability.attended,
             availability.is_valid,
@@ -152,10 +239,12 @@ pub(crate) async fn get_future_valid_availability_for_ns(
         WHERE usrs.usr_type = $1 AND usrs.is_valid = TRUE
           AND availability.is_valid = TRUE
           AND availability.avail >= $2
+          AND usrs.unit_id = $3
         ORDER BY availability.avail ASC;
         "#,
-        UsrType::NS as _,
-        today
+        UsrType::ns() as _,
+        today,
+        unit_id
     )
         .fetch_all(conn)
         .await;
@@ -181,8 +270,12 @@ pub(crate) async fn get_future_valid_availability_for_ns(
 
 pub(crate) async fn set_saf100_true_by_uuid(
     conn: &PgPool,
+    actor: u64,
     id: Uuid,
 ) -> Result<AvailabilityDetails, sqlx::Error> {
+    let mut tx = conn.begin().await?;
+    let before = get_snapshot(&mut tx, AuditEntity::Availability, id).await?;
+
     let result = sqlx::query_as!(
         AvailabilityDetails,
         r#"
@@ -198,8 +291,12 @@ pub(crate) async fn set_saf100_true_by_uuid(
                 availability.id,
                 availability.avail,
                 availability.ict_type,
+                availability.start_time,
+                availability.end_time,
                 availability.remarks,
                 availability.planned,
+                availability.standby,
+                availability.ack,
                 availability.saf100,
                 availability.attended,
                 availability.is_valid,
@@ -212,8 +309,12 @@ pub(crate) async fn set_saf100_true_by_uuid(
             usrs.usr_type AS "usr_type: _",
             update_statement.avail,
             update_statement.ict_type AS "ict_type: _",
+            update_statement.start_time,
+            update_statement.end_time,
             update_statement.remarks,
             update_statement.planned,
+            update_statement.standby,
+            update_statement.ack AS "ack: _",
             update_statement.saf100,
             update_statement.attended,
             update_statement.is_valid,
@@ -226,11 +327,13 @@ pub(crate) async fn set_saf100_true_by_uuid(
         "#,
         id,
     )
-        .fetch_one(conn)
+        .fetch_one(&mut *tx)
         .await;
 
     match result {
         Ok(res) => {
+            add_audit_log(&mut tx, actor, AuditAction::Saf100, AuditEntity::Availability, id, before).await?;
+            tx.commit().await?;
             log::info!(
                 "Set SAF100 to TRUE for user '{}' on date '{}', Availability ID: {}",
                 res.ops_name,
@@ -0,0 +1,195 @@
+use serde_json::Value;
+use sqlx::types::Uuid;
+use sqlx::{PgConnection, PgPool};
+use strum_macros::AsRefStr;
+
+use crate::types::AuditLog;
+
+// Tables whose changes are recorded in the audit log
+#[derive(Clone, Copy, Debug, AsRefStr)]
+#[strum(serialize_all = "lowercase")]
+pub(crate) enum AuditEntity {
+    Usrs,
+    Apply,
+    Availability,
+}
+
+impl AuditEntity {
+    // SQL expression resolving the tele_id of the user a row `t` belongs to
+    fn subject_expr(&self) -> &'static str {
+        match self {
+            AuditEntity::Usrs | AuditEntity::Apply => "t.tele_id",
+            AuditEntity::Availability => "(SELECT usrs.tele_id FROM usrs WHERE usrs.id = t.usr_id)",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, AsRefStr)]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub(crate) enum AuditAction {
+    Add,
+    Edit,
+    Remove,
+    Plan,
+    Unplan,
+    Standby,
+    Acknowledge,
+    Decline,
+    Attendance,
+    Saf100,
+}
+
+// Returns the current row as JSON, or None if it does not exist
+pub(crate) async fn get_snapshot(
+    conn: &mut PgConnection,
+    entity: AuditEntity,
+    id: Uuid,
+) -> Result<Option<Value>, sqlx::Error> {
+    // The table name comes from a fixed enum, so formatting it into the query is safe
+    let query = format!("SELECT to_jsonb(t) FROM {} t WHERE t.id = $1", entity.as_ref());
+    let result = sqlx::query_scalar::<_, Value>(&query)
+        .bind(id)
+        .fetch_optional(conn)
+        .await;
+
+    match result {
+        Ok(snapshot) => Ok(snapshot),
+        Err(e) => {
+            log::error!("Error fetching {} snapshot for id {}: {}", entity.as_ref(), id, e);
+            Err(e)
+        }
+    }
+}
+
+// Records a change, taking the current state of the row as the after value
+pub(crate) async fn add_audit_log(
+    conn: &mut PgConnection,
+    actor: u64,
+    action: AuditAction,
+    entity: AuditEntity,
+    id: Uuid,
+    before: Option<Value>,
+) -> Result<(), sqlx::Error> {
+    let query = format!(
+        r#"
+        INSERT INTO audit_log (actor_tele_id, subject_tele_id, action, entity, entity_id, before, after)
+        SELECT $1, {}, $2, $3, t.id, $5, to_jsonb(t)
+        FROM {} t
+        WHERE t.id = $4
+        "#,
+        entity.subject_expr(),
+        entity.as_ref()
+    );
+    let result = sqlx::query(&query)
+        .bind(actor as i64)
+        .bind(action.as_ref())
+        .bind(entity.as_ref())
+        .bind(id)
+        .bind(before)
+        .execute(conn)
+        .await;
+
+    match result {
+        Ok(query_result) => {
+            if query_result.rows_affected() == 1 {
+                log::info!("Recorded {} on {} ({}) by tele_id: {}", action.as_ref(), entity.as_ref(), id, actor);
+            } else {
+                log::warn!("No {} row found with id {} to record {}", entity.as_ref(), id, action.as_ref());
+            }
+            Ok(())
+        }
+        Err(e) => {
+            log::error!("Error recording {} on {} ({}): {}", action.as_ref(), entity.as_ref(), id, e);
+            Err(e)
+        }
+    }
+}
+
+// Records a change whose after value is not the row itself, such as the roles held by a user
+pub(crate) async fn add_audit_log_with_after(
+    conn: &mut PgConnection,
+    actor: u64,
+    action: AuditAction,
+    entity: AuditEntity,
+    id: Uuid,
+    (before, after): (Option<Value>, Option<Value>),
+) -> Result<(), sqlx::Error> {
+    let query = format!(
+        r#"
+        INSERT INTO audit_log (actor_tele_id, subject_tele_id, action, entity, entity_id, before, after)
+        SELECT $1, {}, $2, $3, t.id, $5, $6
+        FROM {} t
+        WHERE t.id = $4
+        "#,
+        entity.subject_expr(),
+        entity.as_ref()
+    );
+    let result = sqlx::query(&query)
+        .bind(actor as i64)
+        .bind(action.as_ref())
+        .bind(entity.as_ref())
+        .bind(id)
+        .bind(before)
+        .bind(after)
+        .execute(conn)
+        .await;
+
+    match result {
+        Ok(query_result) => {
+            if query_result.rows_affected() == 1 {
+                log::info!("Recorded {} on {} ({}) by tele_id: {}", action.as_ref(), entity.as_ref(), id, actor);
+            } else {
+                log::warn!("No {} row found with id {} to record {}", entity.as_ref(), id, action.as_ref());
+            }
+            Ok(())
+        }
+        Err(e) => {
+            log::error!("Error recording {} on {} ({}): {}", action.as_ref(), entity.as_ref(), id, e);
+            Err(e)
+        }
+    }
+}
+
+pub(crate) async fn get_audit_log_by_subject(
+    conn: &PgPool,
+    tele_id: i64,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<AuditLog>, sqlx::Error> {
+    let result = sqlx::query_as!(
+        AuditLog,
+        r#"
+        SELECT
+            audit_log.actor_tele_id,
+            actor.ops_name AS "actor_ops_name?",
+            audit_log.action,
+            audit_log.entity,
+            audit_log.before,
+            audit_log.after,
+            audit_log.created
+        FROM audit_log
+        LEFT JOIN usrs actor
+            ON actor.tele_id = audit_log.actor_tele_id
+           AND actor.is_valid = TRUE
+        WHERE audit_log.subject_tele_id = $1
+        ORDER BY audit_log.created DESC
+        LIMIT $2 OFFSET $3;
+        "#,
+        tele_id,
+        limit,
+        offset
+    )
+        .fetch_all(conn)
+        .await;
+
+    match result {
+        Ok(entries) => {
+            log::info!("Fetched {} audit log entries for tele_id: {}", entries.len(), tele_id);
+            Ok(entries)
+        }
+        Err(e) => {
+            log::error!("Error fetching audit log for tele_id {}: {}", tele_id, e);
+            Err(e)
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use sqlx::PgPool;
+
+use crate::types::Usr;
+
+pub(crate) async fn get_or_create_calendar_token(
+    conn: &PgPool,
+    tele_id: u64,
+    new_token: &str,
+) -> Result<String, sqlx::Error> {
+    // Only uses the new token if the user does not already have one, a concurrent insert is left in place
+    let inserted = sqlx::query_scalar!(
+        r#"
+        INSERT INTO calendar_tokens (usr_id, token)
+        SELECT id, $2 FROM usrs WHERE tele_id = $1 AND is_valid = TRUE
+        ON CONFLICT (usr_id) WHERE is_valid = TRUE DO NOTHING
+        RETURNING token;
+        "#,
+        tele_id as i64,
+        new_token
+    )
+        .fetch_optional(conn)
+        .await;
+
+    let result = match inserted {
+        Ok(Some(token)) => Ok(token),
+        Ok(None) => {
+            sqlx::query_scalar!(
+                r#"
+                SELECT calendar_tokens.token
+                FROM calendar_tokens
+                JOIN usrs ON usrs.id = calendar_tokens.usr_id
+                WHERE usrs.tele_id = $1 AND usrs.is_valid = TRUE
+                  AND calendar_tokens.is_valid = TRUE;
+                "#,
+                tele_id as i64
+            )
+                .fetch_one(conn)
+                .await
+        }
+        Err(e) => Err(e)
+    };
+
+    match result {
+        Ok(token) => {
+            log::info!("Retrieved calendar token for tele_id: {}", tele_id);
+            Ok(token)
+        }
+        Err(e) => {
+            log::error!("Error retrieving calendar token for tele_id {}: {}", tele_id, e);
+            Err(e)
+        }
+    }
+}
+
+pub(crate) async fn reset_calendar_token(
+    conn: &PgPool,
+    tele_id: u64,
+    new_token: &str,
+) -> Result<String, sqlx::Error> {
+    let mut tx = conn.begin().await?;
+
+    let invalidated = sqlx::query!(
+        r#"
+        UPDATE calendar_tokens
+        SET is_valid = FALSE
+        WHERE is_valid = TRUE
+          AND usr_id = (SELECT id FROM usrs WHERE tele_id = $1 AND is_valid = TRUE);
+        "#,
+        tele_id as i64
+    )
+        .execute(&mut *tx)
+        .await;
+
+    if let Err(e) = invalidated {
+        log::error!("Error invalidating calendar token for tele_id {}: {}", tele_id, e);
+        tx.rollback().await?;
+        return Err(e);
+    }
+
+    let result = sqlx::query_scalar!(
+        r#"
+        INSERT INTO calendar_tokens (usr_id, token)
+        SELECT id, $2 FROM usrs WHERE tele_id = $1 AND is_valid = TRUE
+        RETURNING token;
+        "#,
+        tele_id as i64,
+        new_token
+    )
+        .fetch_one(&mut *tx)
+        .await;
+
+    match result {
+        Ok(token) => {
+            tx.commit().await?;
+            log::info!("Reset calendar token for tele_id: {}", tele_id);
+            Ok(token)
+        }
+        Err(e) => {
+            log::error!("Error resetting calendar token for tele_id {}: {}", tele_id, e);
+            tx.rollback().await?;
+            Err(e)
+        }
+    }
+}
+
+pub(crate) async fn get_user_by_calendar_token(conn: &PgPool, token: &str) -> Result<Option<Usr>, sqlx::Error> {
+    let result = sqlx::query_as!(
+        Usr,
+        r#"
+        SELECT
+            usrs.id AS id,
+            usrs.tele_id AS tele_id,
+            usrs.name AS name,
+            usrs.ops_name AS ops_name,
+            usrs.usr_type AS "usr_type: _",
+            usrs.role_type AS "role_type: _",
+            usrs.admin AS admin,
+            usrs.unit_id AS unit_id,
+            usrs.created AS created,
+            usrs.updated AS updated
+        FROM calendar_tokens
+        JOIN usrs ON usrs.id = calendar_tokens.usr_id
+        WHERE calendar_tokens.token = $1
+          AND calendar_tokens.is_valid = TRUE
+          AND usrs.is_valid = TRUE;
+        "#,
+        token
+    )
+        .fetch_optional(conn)
+        .await;
+
+    match result {
+        Ok(user) => Ok(user),
+        Err(e) => {
+            log::error!("Error getting user by calendar token: {}", e);
+            Err(e)
+        }
+    }
+}
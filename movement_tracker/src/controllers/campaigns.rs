@@ -0,0 +1,310 @@
+use chrono::{NaiveDate, Utc};
+use sqlx::types::Uuid;
+use sqlx::PgPool;
+
+use crate::types::{Campaign, RoleType, Usr, UsrType};
+use crate::{now, utils, APP_TIMEZONE};
+
+// Starts a campaign and records the users of the unit matching the filter as its recipients
+pub(crate) async fn add_campaign(
+    conn: &PgPool,
+    unit_id: Uuid,
+    (start_date, end_date): (NaiveDate, NaiveDate),
+    role_type: Option<RoleType>,
+    usr_type: Option<UsrType>,
+) -> Result<Campaign, sqlx::Error> {
+    let mut tx = conn.begin().await?;
+
+    let result = sqlx::query_as!(
+        Campaign,
+        r#"
+        INSERT INTO campaigns (unit_id, start_date, end_date, role_type, usr_type)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING
+            id,
+            unit_id,
+            start_date,
+            end_date,
+            role_type AS "role_type: _",
+            usr_type AS "usr_type: _",
+            nudges,
+            last_nudged,
+            created,
+            updated;
+        "#,
+        unit_id,
+        start_date,
+        end_date,
+        role_type as _,
+        usr_type as _
+    )
+        .fetch_one(&mut *tx)
+        .await;
+
+    let campaign = match result {
+        Ok(campaign) => campaign,
+        Err(e) => {
+            log::error!("Error adding campaign: {}", e);
+            tx.rollback().await?;
+            return Err(e);
+        }
+    };
+
+    let recipients = sqlx::query!(
+        r#"
+        INSERT INTO campaign_recipients (campaign_id, usr_id)
+        SELECT $1, usrs.id
+        FROM usrs
+        WHERE usrs.unit_id = $2 AND usrs.is_valid = TRUE
+          AND ($3::TEXT IS NULL OR usrs.role_type = $3)
+          AND ($4::TEXT IS NULL OR usrs.usr_type = $4);
+        "#,
+        campaign.id,
+        unit_id,
+        campaign.role_type.as_ref().map(|role_type| role_type.as_ref()),
+        campaign.usr_type.as_ref().map(|usr_type| usr_type.as_ref())
+    )
+        .execute(&mut *tx)
+        .await;
+
+    match recipients {
+        Ok(res) => {
+            tx.commit().await?;
+            log::info!("Added campaign ({}) with {} recipients", campaign.id, res.rows_affected());
+            Ok(campaign)
+        }
+        Err(e) => {
+            log::error!("Error adding recipients of campaign ({}): {}", campaign.id, e);
+            tx.rollback().await?;
+            Err(e)
+        }
+    }
+}
+
+// Campaigns of the unit whose window has not ended
+pub(crate) async fn get_open_campaigns(conn: &PgPool, unit_id: Uuid) -> Result<Vec<Campaign>, sqlx::Error> {
+    let result = sqlx::query_as!(
+        Campaign,
+        r#"
+        SELECT
+            id,
+            unit_id,
+            start_date,
+            end_date,
+            role_type AS "role_type: _",
+            usr_type AS "usr_type: _",
+            nudges,
+            last_nudged,
+            created,
+            updated
+        FROM campaigns
+        WHERE unit_id = $1 AND is_valid = TRUE
+          AND end_date >= $2
+        ORDER BY start_date ASC, created ASC;
+        "#,
+        unit_id,
+        now!().date_naive()
+    )
+        .fetch_all(conn)
+        .await;
+
+    match result {
+        Ok(campaigns) => {
+            log::info!("Found {} open campaigns", campaigns.len());
+            Ok(campaigns)
+        }
+        Err(e) => {
+            log::error!("Error fetching open campaigns: {}", e);
+            Err(e)
+        }
+    }
+}
+
+pub(crate) async fn get_campaign_by_uuid(conn: &PgPool, id: Uuid) -> Result<Campaign, sqlx::Error> {
+    let result = sqlx::query_as!(
+        Campaign,
+        r#"
+        SELECT
+            id,
+            unit_id,
+            start_date,
+            end_date,
+            role_type AS "role_type: _",
+            usr_type AS "usr_type: _",
+            nudges,
+            last_nudged,
+            created,
+            updated
+        FROM campaigns
+        WHERE id = $1 AND is_valid = TRUE;
+        "#,
+        id
+    )
+        .fetch_one(conn)
+        .await;
+
+    match result {
+        Ok(campaign) => Ok(campaign),
+        Err(e) => {
+            log::error!("Error fetching campaign ({}): {}", id, e);
+            Err(e)
+        }
+    }
+}
+
+// Campaigns still waiting on responses before their window starts, due for another nudge
+pub(crate) async fn get_campaigns_due_nudge(conn: &PgPool) -> Result<Vec<Campaign>, sqlx::Error> {
+    let result = sqlx::query_as!(
+        Campaign,
+        r#"
+        SELECT
+            id,
+            unit_id,
+            start_date,
+            end_date,
+            role_type AS "role_type: _",
+            usr_type AS "usr_type: _",
+            nudges,
+            last_nudged,
+            created,
+            updated
+        FROM campaigns
+        WHERE is_valid = TRUE
+          AND start_date > $1
+          AND nudges < $2
+          AND COALESCE(last_nudged, created) <= NOW() - make_interval(hours => $3);
+        "#,
+        now!().date_naive(),
+        utils::MAX_CAMPAIGN_NUDGES,
+        utils::CAMPAIGN_NUDGE_INTERVAL_HOURS
+    )
+        .fetch_all(conn)
+        .await;
+
+    match result {
+        Ok(campaigns) => {
+            log::debug!("Found {} campaigns due a nudge", campaigns.len());
+            Ok(campaigns)
+        }
+        Err(e) => {
+            log::error!("Error fetching campaigns due a nudge: {}", e);
+            Err(e)
+        }
+    }
+}
+
+// Each recipient of the campaign, and whether they have indicated availability within its window
+pub(crate) async fn get_campaign_responses(conn: &PgPool, campaign: &Campaign) -> Result<Vec<(Usr, bool)>, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        SELECT
+            usrs.id,
+            usrs.tele_id,
+            usrs.name,
+            usrs.ops_name,
+            usrs.usr_type AS "usr_type: UsrType",
+            usrs.role_type AS "role_type: RoleType",
+            usrs.admin,
+            usrs.unit_id,
+            usrs.created,
+            usrs.updated,
+            EXISTS (
+                SELECT 1
+                FROM availability
+                WHERE availability.usr_id = usrs.id
+                  AND availability.avail >= $2
+                  AND availability.avail <= $3
+                  AND (availability.is_valid = TRUE OR availability.planned = TRUE)
+            ) AS "responded!"
+        FROM campaign_recipients
+        JOIN usrs ON usrs.id = campaign_recipients.usr_id
+        WHERE campaign_recipients.campaign_id = $1 AND usrs.is_valid = TRUE
+        ORDER BY usrs.ops_name ASC;
+        "#,
+        campaign.id,
+        campaign.start_date,
+        campaign.end_date
+    )
+        .fetch_all(conn)
+        .await;
+
+    match result {
+        Ok(rows) => Ok(rows.into_iter()
+            .map(|row| (
+                Usr {
+                    id: row.id,
+                    tele_id: row.tele_id,
+                    name: row.name,
+                    ops_name: row.ops_name,
+                    usr_type: row.usr_type,
+                    role_type: row.role_type,
+                    admin: row.admin,
+                    unit_id: row.unit_id,
+                    created: row.created,
+                    updated: row.updated
+                },
+                row.responded
+            ))
+            .collect()),
+        Err(e) => {
+            log::error!("Error fetching responses of campaign ({}): {}", campaign.id, e);
+            Err(e)
+        }
+    }
+}
+
+// Records a nudge if the campaign has nudges left, its window has not started and the interval has passed since users
+// were last asked, returns false if it is not yet due so that scheduled and manual nudges share the same limits
+pub(crate) async fn claim_campaign_nudge(conn: &PgPool, id: Uuid) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE campaigns
+        SET nudges = nudges + 1, last_nudged = NOW()
+        WHERE id = $1 AND is_valid = TRUE
+          AND start_date > $2
+          AND nudges < $3
+          AND COALESCE(last_nudged, created) <= NOW() - make_interval(hours => $4);
+        "#,
+        id,
+        now!().date_naive(),
+        utils::MAX_CAMPAIGN_NUDGES,
+        utils::CAMPAIGN_NUDGE_INTERVAL_HOURS
+    )
+        .execute(conn)
+        .await;
+
+    match result {
+        Ok(res) => {
+            log::info!("Recorded nudge for campaign ({}): {}", id, res.rows_affected() > 0);
+            Ok(res.rows_affected() > 0)
+        }
+        Err(e) => {
+            log::error!("Error recording nudge for campaign ({}): {}", id, e);
+            Err(e)
+        }
+    }
+}
+
+pub(crate) async fn close_campaign(conn: &PgPool, id: Uuid) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE campaigns
+        SET is_valid = FALSE
+        WHERE id = $1 AND is_valid = TRUE;
+        "#,
+        id
+    )
+        .execute(conn)
+        .await;
+
+    match result {
+        Ok(res) => {
+            log::info!("Closed campaign ({})", id);
+            Ok(res.rows_affected() > 0)
+        }
+        Err(e) => {
+            log::error!("Error closing campaign ({}): {}", id, e);
+            Err(e)
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use sqlx::PgPool;
+
+use crate::types::{RoleType, UsrType};
+
+pub(crate) async fn get_role_types(conn: &PgPool) -> Result<Vec<RoleType>, sqlx::Error> {
+    let result = sqlx::query_scalar!(
+        r#"
+        SELECT name AS "name: RoleType"
+        FROM role_types
+        WHERE is_valid = TRUE
+        ORDER BY created ASC, name ASC;
+        "#
+    )
+        .fetch_all(conn)
+        .await;
+
+    match result {
+        Ok(role_types) => {
+            log::info!("Found {} role types", role_types.len());
+            Ok(role_types)
+        }
+        Err(e) => {
+            log::error!("Error fetching role types: {}", e);
+            Err(e)
+        }
+    }
+}
+
+pub(crate) async fn get_usr_types(conn: &PgPool) -> Result<Vec<UsrType>, sqlx::Error> {
+    let result = sqlx::query_scalar!(
+        r#"
+        SELECT name AS "name: UsrType"
+        FROM usr_types
+        WHERE is_valid = TRUE
+        ORDER BY created ASC, name ASC;
+        "#
+    )
+        .fetch_all(conn)
+        .await;
+
+    match result {
+        Ok(usr_types) => {
+            log::info!("Found {} user types", usr_types.len());
+            Ok(usr_types)
+        }
+        Err(e) => {
+            log::error!("Error fetching user types: {}", e);
+            Err(e)
+        }
+    }
+}
+
+// Adds the role type, restoring it if it was previously removed
+pub(crate) async fn add_role_type(conn: &PgPool, role_type: &RoleType) -> Result<(), sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO role_types (name)
+        VALUES ($1)
+        ON CONFLICT (name) DO UPDATE SET is_valid = TRUE;
+        "#,
+        role_type as _
+    )
+        .execute(conn)
+        .await;
+
+    match result {
+        Ok(_) => {
+            log::info!("Added role type: {}", role_type.as_ref());
+            Ok(())
+        }
+        Err(e) => {
+            log::error!("Error adding role type {}: {}", role_type.as_ref(), e);
+            Err(e)
+        }
+    }
+}
+
+// Adds the user type, restoring it if it was previously removed
+pub(crate) async fn add_usr_type(conn: &PgPool, usr_type: &UsrType) -> Result<(), sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO usr_types (name)
+        VALUES ($1)
+        ON CONFLICT (name) DO UPDATE SET is_valid = TRUE;
+        "#,
+        usr_type as _
+    )
+        .execute(conn)
+        .await;
+
+    match result {
+        Ok(_) => {
+            log::info!("Added user type: {}", usr_type.as_ref());
+            Ok(())
+        }
+        Err(e) => {
+            log::error!("Error adding user type {}: {}", usr_type.as_ref(), e);
+            Err(e)
+        }
+    }
+}
+
+// Returns false without removing anything if a user or application still has the role type
+pub(crate) async fn remove_role_type(conn: &PgPool, role_type: &RoleType) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE role_types
+        SET is_valid = FALSE
+        WHERE name = $1
+          AND is_valid = TRUE
+          AND NOT EXISTS (SELECT 1 FROM usrs WHERE usrs.role_type = $1 AND usrs.is_valid = TRUE)
+          AND NOT EXISTS (SELECT 1 FROM apply WHERE apply.role_type = $1 AND apply.is_valid = TRUE);
+        "#,
+        role_type as _
+    )
+        .execute(conn)
+        .await;
+
+    match result {
+        Ok(query_result) => {
+            let removed = query_result.rows_affected() == 1;
+            if removed {
+                log::info!("Removed role type: {}", role_type.as_ref());
+            } else {
+                log::info!("Role type {} is in use or does not exist", role_type.as_ref());
+            }
+            Ok(removed)
+        }
+        Err(e) => {
+            log::error!("Error removing role type {}: {}", role_type.as_ref(), e);
+            Err(e)
+        }
+    }
+}
+
+// Returns false without removing anything if a user or application still has the user type
+pub(crate) async fn remove_usr_type(conn: &PgPool, usr_type: &UsrType) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE usr_types
+        SET is_valid = FALSE
+        WHERE name = $1
+          AND name <> $2
+          AND is_valid = TRUE
+          AND NOT EXISTS (SELECT 1 FROM usrs WHERE usrs.usr_type = $1 AND usrs.is_valid = TRUE)
+          AND NOT EXISTS (SELECT 1 FROM apply WHERE apply.usr_type = $1 AND apply.is_valid = TRUE);
+        "#,
+        usr_type as _,
+        UsrType::NS
+    )
+        .execute(conn)
+        .await;
+
+    match result {
+        Ok(query_result) => {
+            let removed = query_result.rows_affected() == 1;
+            if removed {
+                log::info!("Removed user type: {}", usr_type.as_ref());
+            } else {
+                log::info!("User type {} is in use, built in or does not exist", usr_type.as_ref());
+            }
+            Ok(removed)
+        }
+        Err(e) => {
+            log::error!("Error removing user type {}: {}", usr_type.as_ref(), e);
+            Err(e)
+        }
+    }
+}
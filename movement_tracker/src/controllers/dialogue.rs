@@ -0,0 +1,112 @@
+use serde_json::Value;
+use sqlx::PgPool;
+
+pub(crate) async fn get_dialogue(
+    conn: &PgPool,
+    chat_id: i64,
+    ttl_secs: i64,
+) -> Result<Option<Value>, sqlx::Error> {
+    // Dialogues which have not been updated within the TTL are treated as expired
+    let result = sqlx::query_scalar!(
+        r#"
+        SELECT dialogue
+        FROM dialogues
+        WHERE chat_id = $1
+          AND updated > NOW() - ($2::BIGINT * INTERVAL '1 second');
+        "#,
+        chat_id,
+        ttl_secs
+    )
+        .fetch_optional(conn)
+        .await;
+
+    match result {
+        Ok(dialogue) => Ok(dialogue),
+        Err(e) => {
+            log::error!("Error fetching dialogue for chat ({}): {}", chat_id, e);
+            Err(e)
+        }
+    }
+}
+
+pub(crate) async fn update_dialogue(
+    conn: &PgPool,
+    chat_id: i64,
+    dialogue: Value,
+) -> Result<(), sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO dialogues (chat_id, dialogue)
+        VALUES ($1, $2)
+        ON CONFLICT (chat_id) DO UPDATE
+            SET dialogue = EXCLUDED.dialogue;
+        "#,
+        chat_id,
+        dialogue
+    )
+        .execute(conn)
+        .await;
+
+    match result {
+        Ok(_) => {
+            log::debug!("Updated dialogue for chat ({})", chat_id);
+            Ok(())
+        }
+        Err(e) => {
+            log::error!("Error updating dialogue for chat ({}): {}", chat_id, e);
+            Err(e)
+        }
+    }
+}
+
+pub(crate) async fn remove_dialogue(
+    conn: &PgPool,
+    chat_id: i64,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM dialogues
+        WHERE chat_id = $1;
+        "#,
+        chat_id
+    )
+        .execute(conn)
+        .await;
+
+    match result {
+        Ok(res) => {
+            log::debug!("Removed dialogue for chat ({})", chat_id);
+            Ok(res.rows_affected() > 0)
+        }
+        Err(e) => {
+            log::error!("Error removing dialogue for chat ({}): {}", chat_id, e);
+            Err(e)
+        }
+    }
+}
+
+pub(crate) async fn remove_expired_dialogues(
+    conn: &PgPool,
+    ttl_secs: i64,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM dialogues
+        WHERE updated <= NOW() - ($1::BIGINT * INTERVAL '1 second');
+        "#,
+        ttl_secs
+    )
+        .execute(conn)
+        .await;
+
+    match result {
+        Ok(res) => {
+            log::info!("Removed {} expired dialogues", res.rows_affected());
+            Ok(res.rows_affected())
+        }
+        Err(e) => {
+            log::error!("Error removing expired dialogues: {}", e);
+            Err(e)
+        }
+    }
+}
@@ -4,3 +4,14 @@ pub(super) mod db;
 pub(crate) mod scheduling;
 pub(crate) mod user;
 pub(crate) mod notifications;
+pub(crate) mod movement;
+pub(crate) mod dialogue;
+pub(crate) mod reminders;
+pub(crate) mod calendar;
+pub(crate) mod audit;
+pub(crate) mod permissions;
+pub(crate) mod units;
+pub(crate) mod crew_types;
+pub(crate) mod requirements;
+pub(crate) mod stats;
+pub(crate) mod campaigns;
@@ -0,0 +1,307 @@
+use sqlx::types::chrono::{NaiveDate, NaiveTime};
+use sqlx::types::Uuid;
+use sqlx::PgPool;
+
+use crate::types::Movement;
+
+pub(crate) async fn add_user_movement(
+    conn: &PgPool,
+    tele_id: u64,
+    date: NaiveDate,
+    start_time: NaiveTime,
+    end_time: NaiveTime,
+    activity: String,
+    location: Option<String>,
+    remarks: Option<String>,
+) -> Result<Movement, sqlx::Error> {
+    let result = sqlx::query_as!(
+        Movement,
+        r#"
+        INSERT INTO movement (usr_id, avail, start_time, end_time, activity, location, remarks)
+        VALUES (
+            (SELECT id FROM usrs WHERE tele_id = $1 AND is_valid = TRUE),
+            $2,
+            $3,
+            $4,
+            $5,
+            $6,
+            $7
+        )
+        ON CONFLICT (usr_id, avail) DO UPDATE
+            SET
+                start_time = EXCLUDED.start_time,
+                end_time = EXCLUDED.end_time,
+                activity = EXCLUDED.activity,
+                location = EXCLUDED.location,
+                remarks = EXCLUDED.remarks,
+                is_valid = TRUE
+        RETURNING
+            id,
+            usr_id AS user_id,
+            avail,
+            start_time,
+            end_time,
+            activity,
+            location,
+            remarks,
+            is_valid,
+            created,
+            updated;
+        "#,
+        tele_id as i64,
+        date,
+        start_time,
+        end_time,
+        activity,
+        location,
+        remarks
+    )
+        .fetch_one(conn)
+        .await;
+
+    match result {
+        Ok(res) => {
+            log::info!("Added or updated movement for tele_id: {} on {}", tele_id, date);
+            Ok(res)
+        }
+        Err(e) => {
+            log::error!("Error inserting or updating movement for tele_id {}: {}", tele_id, e);
+            Err(e)
+        }
+    }
+}
+
+pub(crate) async fn get_movement_by_tele_id_and_date(
+    conn: &PgPool,
+    tele_id: u64,
+    date: NaiveDate,
+) -> Result<Option<Movement>, sqlx::Error> {
+    let result = sqlx::query_as!(
+        Movement,
+        r#"
+        SELECT
+            movement.id,
+            movement.usr_id AS user_id,
+            movement.avail,
+            movement.start_time,
+            movement.end_time,
+            movement.activity,
+            movement.location,
+            movement.remarks,
+            movement.is_valid,
+            movement.created,
+            movement.updated
+        FROM movement
+        JOIN usrs ON usrs.id = movement.usr_id
+        WHERE usrs.tele_id = $1 AND usrs.is_valid = TRUE
+          AND movement.avail = $2
+          AND movement.is_valid = TRUE;
+        "#,
+        tele_id as i64,
+        date
+    )
+        .fetch_optional(conn)
+        .await;
+
+    match result {
+        Ok(movement) => Ok(movement),
+        Err(e) => {
+            log::error!("Error fetching movement for tele_id ({}) on {}: {}", tele_id, date, e);
+            Err(e)
+        }
+    }
+}
+
+pub(crate) async fn get_movement_by_tele_id_and_dates(
+    conn: &PgPool,
+    tele_id: u64,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Vec<Movement>, sqlx::Error> {
+    let result = sqlx::query_as!(
+        Movement,
+        r#"
+        SELECT
+            movement.id,
+            movement.usr_id AS user_id,
+            movement.avail,
+            movement.start_time,
+            movement.end_time,
+            movement.activity,
+            movement.location,
+            movement.remarks,
+            movement.is_valid,
+            movement.created,
+            movement.updated
+        FROM movement
+        JOIN usrs ON usrs.id = movement.usr_id
+        WHERE usrs.tele_id = $1 AND usrs.is_valid = TRUE
+          AND movement.avail >= $2
+          AND movement.avail <= $3
+          AND movement.is_valid = TRUE
+        ORDER BY movement.avail ASC;
+        "#,
+        tele_id as i64,
+        start,
+        end
+    )
+        .fetch_all(conn)
+        .await;
+
+    match result {
+        Ok(movement_list) => {
+            log::info!(
+                "Found {} movement entries for tele_id ({}) within date range {} to {}",
+                movement_list.len(),
+                tele_id,
+                start,
+                end
+            );
+            Ok(movement_list)
+        }
+        Err(e) => {
+            log::error!(
+                "Error fetching movement for tele_id ({}) within date range {} to {}: {}",
+                tele_id,
+                start,
+                end,
+                e
+            );
+            Err(e)
+        }
+    }
+}
+
+pub(crate) async fn get_movement_by_uuid(
+    conn: &PgPool,
+    movement_id: Uuid,
+) -> Result<Movement, sqlx::Error> {
+    let result = sqlx::query_as!(
+        Movement,
+        r#"
+        SELECT
+            id,
+            usr_id AS user_id,
+            avail,
+            start_time,
+            end_time,
+            activity,
+            location,
+            remarks,
+            is_valid,
+            created,
+            updated
+        FROM movement
+        WHERE id = $1;
+        "#,
+        movement_id
+    )
+        .fetch_one(conn)
+        .await;
+
+    match result {
+        Ok(movement) => {
+            log::info!("Found movement with UUID: {}", movement_id);
+            Ok(movement)
+        }
+        Err(e) => {
+            log::error!("Error fetching movement with UUID {}: {}", movement_id, e);
+            Err(e)
+        }
+    }
+}
+
+pub(crate) async fn edit_movement_by_uuid(
+    conn: &PgPool,
+    movement_id: Uuid,
+    start_time: Option<NaiveTime>,
+    end_time: Option<NaiveTime>,
+    activity: Option<String>,
+    location: Option<String>,
+    remarks: Option<String>,
+) -> Result<Movement, sqlx::Error> {
+    let result = sqlx::query_as!(
+        Movement,
+        r#"
+        UPDATE movement
+        SET
+            start_time = COALESCE($2, start_time),
+            end_time = COALESCE($3, end_time),
+            activity = COALESCE($4, activity),
+            location = COALESCE($5, location),
+            remarks = COALESCE($6, remarks)
+        WHERE id = $1
+        RETURNING
+            id,
+            usr_id AS user_id,
+            avail,
+            start_time,
+            end_time,
+            activity,
+            location,
+            remarks,
+            is_valid,
+            created,
+            updated;
+        "#,
+        movement_id,
+        start_time,
+        end_time,
+        activity,
+        location,
+        remarks
+    )
+        .fetch_one(conn)
+        .await;
+
+    match result {
+        Ok(res) => {
+            log::info!("Updated movement with UUID: {}", movement_id);
+            Ok(res)
+        }
+        Err(e) => {
+            log::error!("Error updating movement with UUID {}: {}", movement_id, e);
+            Err(e)
+        }
+    }
+}
+
+pub(crate) async fn remove_movement_by_uuid(
+    conn: &PgPool,
+    movement_id: Uuid,
+) -> Result<Movement, sqlx::Error> {
+    let result = sqlx::query_as!(
+        Movement,
+        r#"
+        UPDATE movement
+        SET is_valid = FALSE
+        WHERE id = $1
+        RETURNING
+            id,
+            usr_id AS user_id,
+            avail,
+            start_time,
+            end_time,
+            activity,
+            location,
+            remarks,
+            is_valid,
+            created,
+            updated;
+        "#,
+        movement_id
+    )
+        .fetch_one(conn)
+        .await;
+
+    match result {
+        Ok(res) => {
+            log::info!("Removed movement with UUID: {}", movement_id);
+            Ok(res)
+        }
+        Err(e) => {
+            log::error!("Error removing movement with UUID {}: {}", movement_id, e);
+            Err(e)
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use sqlx::types::Uuid;
 use sqlx::PgPool;
 use crate::types::NotificationSettings;
 
@@ -16,6 +17,8 @@ pub(crate) async fn get_notification_settings(
             notif_availability,
             notif_plan,
             notif_conflict,
+            notif_digest,
+            unit_id,
             created,
             updated,
             is_valid
@@ -44,15 +47,19 @@ pub(crate) async fn get_notification_settings(
     }
 }
 
+// Chats stay with the unit which first configured them, except the DM of a user who moved to the unit.
+// Returns None if the chat belongs to another unit.
 pub(crate) async fn update_notification_settings(
     conn: &PgPool,
     chat_id: i64,
+    unit_id: Uuid,
     notif_system: Option<bool>,
     notif_register: Option<bool>,
     notif_availability: Option<bool>,
     notif_plan: Option<bool>,
     notif_conflict: Option<bool>,
-) -> Result<NotificationSettings, sqlx::Error> {
+    notif_digest: Option<bool>,
+) -> Result<Option<NotificationSettings>, sqlx::Error> {
     let result = sqlx::query_as!(
         NotificationSettings,
         r#"
@@ -62,16 +69,27 @@ pub(crate) async fn update_notification_settings(
             notif_register,
             notif_availability,
             notif_plan,
-            notif_conflict
+            notif_conflict,
+            notif_digest,
+            unit_id
         )
-        VALUES ($1, COALESCE($2, FALSE), COALESCE($3, FALSE), COALESCE($4, FALSE), COALESCE($5, FALSE), COALESCE($6, FALSE))
+        VALUES ($1, COALESCE($2, FALSE), COALESCE($3, FALSE), COALESCE($4, FALSE), COALESCE($5, FALSE), COALESCE($6, FALSE), COALESCE($8, FALSE), $7)
         ON CONFLICT (chat_id) DO UPDATE SET
+            unit_id = EXCLUDED.unit_id,
             notif_system = COALESCE($2, notification_settings.notif_system),
             notif_register = COALESCE($3, notification_settings.notif_register),
             notif_availability = COALESCE($4, notification_settings.notif_availability),
             notif_plan = COALESCE($5, notification_settings.notif_plan),
             notif_conflict = COALESCE($6, notification_settings.notif_conflict),
+            notif_digest = COALESCE($8, notification_settings.notif_digest),
             updated = NOW()
+        WHERE notification_settings.unit_id = EXCLUDED.unit_id
+           OR EXISTS (
+               SELECT 1 FROM usrs
+               WHERE usrs.tele_id = EXCLUDED.chat_id
+                 AND usrs.unit_id = EXCLUDED.unit_id
+                 AND usrs.is_valid = TRUE
+           )
         RETURNING
             id,
             chat_id,
@@ -80,6 +98,8 @@ pub(crate) async fn update_notification_settings(
             notif_availability,
             notif_plan,
             notif_conflict,
+            notif_digest,
+            unit_id,
             created,
             updated,
             is_valid;
@@ -89,15 +109,21 @@ pub(crate) async fn update_notification_settings(
         notif_register,
         notif_availability,
         notif_plan,
-        notif_conflict
+        notif_conflict,
+        unit_id,
+        notif_digest
     )
-        .fetch_one(conn)
+        .fetch_optional(conn)
         .await;
 
     match result {
-        Ok(settings) => {
+        Ok(Some(settings)) => {
             log::info!("Updated notification settings for chat_id: {}", chat_id);
-            Ok(settings)
+            Ok(Some(settings))
+        }
+        Ok(None) => {
+            log::warn!("Chat {} belongs to another unit, not updating its notification settings for unit {}", chat_id, unit_id);
+            Ok(None)
         }
         Err(e) => {
             log::error!("Error updating notification settings for chat_id {}: {}", chat_id, e);
@@ -106,15 +132,18 @@ pub(crate) async fn update_notification_settings(
     }
 }
 
-pub(crate) async fn get_system_notifications_enabled(conn: &PgPool) -> Result<Vec<i64>, sqlx::Error> {
+// Chats of the unit with system notifications enabled, or of every unit if none is given
+pub(crate) async fn get_system_notifications_enabled(conn: &PgPool, unit_id: Option<Uuid>) -> Result<Vec<i64>, sqlx::Error> {
     // Execute the query to fetch all chat_id values where notifications are enabled
     let chat_ids = sqlx::query_scalar!(
         r#"
         SELECT
             chat_id
         FROM notification_settings
-        WHERE notif_system = TRUE AND is_valid = TRUE;
-        "#
+        WHERE notif_system = TRUE AND is_valid = TRUE
+          AND ($1::UUID IS NULL OR unit_id = $1);
+        "#,
+        unit_id
     )
         .fetch_all(conn)
         .await;
@@ -138,15 +167,18 @@ pub(crate) async fn get_system_notifications_enabled(conn: &PgPool) -> Result<Ve
     }
 }
 
-pub(crate) async fn get_register_notifications_enabled(conn: &PgPool) -> Result<Vec<i64>, sqlx::Error> {
+// Chats of the unit with register notifications enabled, or of every unit if none is given
+pub(crate) async fn get_register_notifications_enabled(conn: &PgPool, unit_id: Option<Uuid>) -> Result<Vec<i64>, sqlx::Error> {
     // Execute the query to fetch all chat_id values where notifications are enabled
     let chat_ids = sqlx::query_scalar!(
         r#"
         SELECT
             chat_id
         FROM notification_settings
-        WHERE notif_register = TRUE AND is_valid = TRUE;
-        "#
+        WHERE notif_register = TRUE AND is_valid = TRUE
+          AND ($1::UUID IS NULL OR unit_id = $1);
+        "#,
+        unit_id
     )
         .fetch_all(conn)
         .await;
@@ -170,15 +202,18 @@ pub(crate) async fn get_register_notifications_enabled(conn: &PgPool) -> Result<
     }
 }
 
-pub(crate) async fn get_availability_notifications_enabled(conn: &PgPool) -> Result<Vec<i64>, sqlx::Error> {
+// Chats of the unit with availability notifications enabled, or of every unit if none is given
+pub(crate) async fn get_availability_notifications_enabled(conn: &PgPool, unit_id: Option<Uuid>) -> Result<Vec<i64>, sqlx::Error> {
     // Execute the query to fetch all chat_id values where notifications are enabled
     let chat_ids = sqlx::query_scalar!(
         r#"
         SELECT
             chat_id
         FROM notification_settings
-        WHERE notif_availability = TRUE AND is_valid = TRUE;
-        "#
+        WHERE notif_availability = TRUE AND is_valid = TRUE
+          AND ($1::UUID IS NULL OR unit_id = $1);
+        "#,
+        unit_id
     )
         .fetch_all(conn)
         .await;
@@ -202,15 +237,18 @@ pub(crate) async fn get_availability_notifications_enabled(conn: &PgPool) -> Res
     }
 }
 
-pub(crate) async fn get_plan_notifications_enabled(conn: &PgPool) -> Result<Vec<i64>, sqlx::Error> {
+// Chats of the unit with plan notifications enabled, or of every unit if none is given
+pub(crate) async fn get_plan_notifications_enabled(conn: &PgPool, unit_id: Option<Uuid>) -> Result<Vec<i64>, sqlx::Error> {
     // Execute the query to fetch all chat_id values where notifications are enabled
     let chat_ids = sqlx::query_scalar!(
         r#"
         SELECT
             chat_id
         FROM notification_settings
-        WHERE notif_plan = TRUE AND is_valid = TRUE;
-        "#
+        WHERE notif_plan = TRUE AND is_valid = TRUE
+          AND ($1::UUID IS NULL OR unit_id = $1);
+        "#,
+        unit_id
     )
         .fetch_all(conn)
         .await;
@@ -234,15 +272,18 @@ pub(crate) async fn get_plan_notifications_enabled(conn: &PgPool) -> Result<Vec<
     }
 }
 
-pub(crate) async fn get_conflict_notifications_enabled(conn: &PgPool) -> Result<Vec<i64>, sqlx::Error> {
+// Chats of the unit with conflict notifications enabled, or of every unit if none is given
+pub(crate) async fn get_conflict_notifications_enabled(conn: &PgPool, unit_id: Option<Uuid>) -> Result<Vec<i64>, sqlx::Error> {
     // Execute the query to fetch all chat_id values where notifications are enabled
     let chat_ids = sqlx::query_scalar!(
         r#"
         SELECT
             chat_id
         FROM notification_settings
-        WHERE notif_conflict = TRUE AND is_valid = TRUE;
-        "#
+        WHERE notif_conflict = TRUE AND is_valid = TRUE
+          AND ($1::UUID IS NULL OR unit_id = $1);
+        "#,
+        unit_id
     )
         .fetch_all(conn)
         .await;
@@ -266,6 +307,41 @@ pub(crate) async fn get_conflict_notifications_enabled(conn: &PgPool) -> Result<
     }
 }
 
+// Chats of the unit with the daily crew digest enabled, or of every unit if none is given
+pub(crate) async fn get_digest_notifications_enabled(conn: &PgPool, unit_id: Option<Uuid>) -> Result<Vec<i64>, sqlx::Error> {
+    // Execute the query to fetch all chat_id values where notifications are enabled
+    let chat_ids = sqlx::query_scalar!(
+        r#"
+        SELECT
+            chat_id
+        FROM notification_settings
+        WHERE notif_digest = TRUE AND is_valid = TRUE
+          AND ($1::UUID IS NULL OR unit_id = $1);
+        "#,
+        unit_id
+    )
+        .fetch_all(conn)
+        .await;
+
+    // Handle the result of the query
+    match chat_ids {
+        Ok(ids) => {
+            if !ids.is_empty() {
+                log::info!("Retrieved chat_ids with digest notifications enabled: {:?} IDs retrieved", ids.len());
+            } else {
+                log::info!("No chats with digest notifications were retrieved.");
+            }
+            // Return the vector of chat_ids
+            Ok(ids)
+        }
+        Err(e) => {
+            // Log the error without referencing chat_id
+            log::error!("Error retrieving notification settings: {}", e);
+            Err(e)
+        }
+    }
+}
+
 pub(crate) async fn soft_delete_notification_settings(
     conn: &PgPool,
     chat_id: i64,
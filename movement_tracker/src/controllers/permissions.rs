@@ -0,0 +1,177 @@
+use serde_json::Value;
+use sqlx::types::Uuid;
+use sqlx::{PgConnection, PgPool};
+
+use crate::controllers::audit::{add_audit_log_with_after, AuditAction, AuditEntity};
+use crate::types::AdminRole;
+
+pub(crate) async fn get_admin_roles_by_uuid(conn: &PgPool, usr_id: Uuid) -> Result<Vec<AdminRole>, sqlx::Error> {
+    let result = sqlx::query_scalar!(
+        r#"
+        SELECT admin_role AS "admin_role: AdminRole"
+        FROM usr_admin_roles
+        WHERE usr_id = $1 AND is_valid = TRUE
+        ORDER BY admin_role;
+        "#,
+        usr_id
+    )
+        .fetch_all(conn)
+        .await;
+
+    match result {
+        Ok(admin_roles) => {
+            log::info!("Fetched {} admin roles for user with id: {}", admin_roles.len(), usr_id);
+            Ok(admin_roles)
+        }
+        Err(e) => {
+            log::error!("Error fetching admin roles for user with id {}: {}", usr_id, e);
+            Err(e)
+        }
+    }
+}
+
+// Returns every role held by the user, admins hold all of them
+pub(crate) async fn get_effective_admin_roles_by_tele_id(conn: &PgPool, tele_id: u64) -> Result<Vec<AdminRole>, sqlx::Error> {
+    let result = sqlx::query_scalar!(
+        r#"
+        SELECT role AS "role!: AdminRole"
+        FROM usrs
+        CROSS JOIN LATERAL unnest(enum_range(NULL::admin_role_enum)) AS role
+        WHERE usrs.tele_id = $1
+          AND usrs.is_valid = TRUE
+          AND (
+              usrs.admin = TRUE
+              OR EXISTS (
+                  SELECT 1 FROM usr_admin_roles
+                  WHERE usr_admin_roles.usr_id = usrs.id
+                    AND usr_admin_roles.admin_role = role
+                    AND usr_admin_roles.is_valid = TRUE
+              )
+          )
+        ORDER BY role;
+        "#,
+        tele_id as i64
+    )
+        .fetch_all(conn)
+        .await;
+
+    match result {
+        Ok(admin_roles) => Ok(admin_roles),
+        Err(e) => {
+            log::error!("Error fetching effective admin roles for tele_id {}: {}", tele_id, e);
+            Err(e)
+        }
+    }
+}
+
+pub(crate) async fn has_admin_role(conn: &PgPool, tele_id: u64, admin_role: AdminRole) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM usrs
+            WHERE usrs.tele_id = $1
+              AND usrs.is_valid = TRUE
+              AND (
+                  usrs.admin = TRUE
+                  OR EXISTS (
+                      SELECT 1 FROM usr_admin_roles
+                      WHERE usr_admin_roles.usr_id = usrs.id
+                        AND usr_admin_roles.admin_role = $2
+                        AND usr_admin_roles.is_valid = TRUE
+                  )
+              )
+        ) AS "exists!";
+        "#,
+        tele_id as i64,
+        admin_role as AdminRole
+    )
+        .fetch_one(conn)
+        .await;
+
+    match result {
+        Ok(exists) => Ok(exists),
+        Err(e) => {
+            log::error!("Error checking admin role {:?} for tele_id {}: {}", admin_role, tele_id, e);
+            Err(e)
+        }
+    }
+}
+
+// Roles held by a user as recorded in the audit log
+async fn get_admin_roles_snapshot(conn: &mut PgConnection, usr_id: Uuid) -> Result<Value, sqlx::Error> {
+    let result = sqlx::query_scalar!(
+        r#"
+        SELECT jsonb_build_object(
+            'admin_roles',
+            COALESCE(jsonb_agg(admin_role ORDER BY admin_role), '[]'::jsonb)
+        ) AS "snapshot!"
+        FROM usr_admin_roles
+        WHERE usr_id = $1 AND is_valid = TRUE;
+        "#,
+        usr_id
+    )
+        .fetch_one(conn)
+        .await;
+
+    match result {
+        Ok(snapshot) => Ok(snapshot),
+        Err(e) => {
+            log::error!("Error fetching admin roles snapshot for user with id {}: {}", usr_id, e);
+            Err(e)
+        }
+    }
+}
+
+// Replaces the roles held by a user with the given set
+pub(crate) async fn set_admin_roles(conn: &PgPool, actor: u64, usr_id: Uuid, admin_roles: &[AdminRole]) -> Result<(), sqlx::Error> {
+    let mut tx = conn.begin().await?;
+    let before = get_admin_roles_snapshot(&mut tx, usr_id).await?;
+
+    let revoked = sqlx::query!(
+        r#"
+        UPDATE usr_admin_roles
+        SET is_valid = FALSE
+        WHERE usr_id = $1
+          AND is_valid = TRUE
+          AND NOT (admin_role = ANY($2));
+        "#,
+        usr_id,
+        admin_roles as &[AdminRole]
+    )
+        .execute(&mut *tx)
+        .await;
+
+    if let Err(e) = revoked {
+        log::error!("Error revoking admin roles for user with id {}: {}", usr_id, e);
+        tx.rollback().await?;
+        return Err(e);
+    }
+
+    let granted = sqlx::query!(
+        r#"
+        INSERT INTO usr_admin_roles (usr_id, admin_role)
+        SELECT $1, role
+        FROM unnest($2::admin_role_enum[]) AS role
+        ON CONFLICT (usr_id, admin_role) WHERE is_valid = TRUE DO NOTHING;
+        "#,
+        usr_id,
+        admin_roles as &[AdminRole]
+    )
+        .execute(&mut *tx)
+        .await;
+
+    if let Err(e) = granted {
+        log::error!("Error granting admin roles for user with id {}: {}", usr_id, e);
+        tx.rollback().await?;
+        return Err(e);
+    }
+
+    let after = get_admin_roles_snapshot(&mut tx, usr_id).await?;
+    if before != after {
+        add_audit_log_with_after(&mut tx, actor, AuditAction::Edit, AuditEntity::Usrs, usr_id, (Some(before), Some(after))).await?;
+    }
+
+    tx.commit().await?;
+    log::info!("Set admin roles for user with id {}: {:?}", usr_id, admin_roles);
+    Ok(())
+}
@@ -0,0 +1,164 @@
+use sqlx::types::chrono::NaiveTime;
+use sqlx::types::Uuid;
+use sqlx::{PgConnection, PgPool};
+
+use crate::types::{ReminderRule, RoleType, UsrType};
+
+pub(crate) async fn get_reminder_rules(conn: &PgPool) -> Result<Vec<ReminderRule>, sqlx::Error> {
+    let result = sqlx::query_as!(
+        ReminderRule,
+        r#"
+        SELECT
+            id,
+            role_type AS "role_type: _",
+            usr_type AS "usr_type: _",
+            days_prior,
+            time_of_day,
+            created,
+            updated,
+            is_valid
+        FROM reminder_rules
+        WHERE is_valid = TRUE
+        ORDER BY days_prior DESC NULLS FIRST, time_of_day ASC, role_type ASC, usr_type ASC;
+        "#
+    )
+        .fetch_all(conn)
+        .await;
+
+    match result {
+        Ok(rules) => {
+            log::info!("Found {} reminder rules", rules.len());
+            Ok(rules)
+        }
+        Err(e) => {
+            log::error!("Error fetching reminder rules: {}", e);
+            Err(e)
+        }
+    }
+}
+
+pub(crate) async fn add_reminder_rule(
+    conn: &PgPool,
+    role_type: Option<RoleType>,
+    usr_type: Option<UsrType>,
+    days_prior: Option<i32>,
+    time_of_day: Option<NaiveTime>,
+) -> Result<ReminderRule, sqlx::Error> {
+    let result = sqlx::query_as!(
+        ReminderRule,
+        r#"
+        INSERT INTO reminder_rules (role_type, usr_type, days_prior, time_of_day)
+        VALUES ($1, $2, $3, $4)
+        RETURNING
+            id,
+            role_type AS "role_type: _",
+            usr_type AS "usr_type: _",
+            days_prior,
+            time_of_day,
+            created,
+            updated,
+            is_valid;
+        "#,
+        role_type as _,
+        usr_type as _,
+        days_prior,
+        time_of_day
+    )
+        .fetch_one(conn)
+        .await;
+
+    match result {
+        Ok(rule) => {
+            log::info!("Added reminder rule ({})", rule.id);
+            Ok(rule)
+        }
+        Err(e) => {
+            log::error!("Error adding reminder rule: {}", e);
+            Err(e)
+        }
+    }
+}
+
+pub(crate) async fn remove_reminder_rule_by_uuid(conn: &PgPool, id: Uuid) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE reminder_rules
+        SET is_valid = FALSE
+        WHERE id = $1 AND is_valid = TRUE;
+        "#,
+        id
+    )
+        .execute(conn)
+        .await;
+
+    match result {
+        Ok(res) => {
+            log::info!("Removed reminder rule ({})", id);
+            Ok(res.rows_affected() > 0)
+        }
+        Err(e) => {
+            log::error!("Error removing reminder rule ({}): {}", id, e);
+            Err(e)
+        }
+    }
+}
+
+// Replaces the unsent reminders of an availability entry with ones generated from the reminder rules.
+// Reminders are only generated if the entry is planned or on standby.
+pub(crate) async fn sync_scheduled_reminders(conn: &mut PgConnection, avail_id: Uuid) -> Result<(), sqlx::Error> {
+    let invalidated = sqlx::query!(
+        r#"
+        UPDATE scheduled_notifications
+        SET is_valid = FALSE
+        WHERE avail_id = $1
+          AND sent = FALSE
+          AND is_valid = TRUE;
+        "#,
+        avail_id
+    )
+        .execute(&mut *conn)
+        .await;
+
+    if let Err(e) = invalidated {
+        log::error!("Error invalidating reminders for availability ({}): {}", avail_id, e);
+        return Err(e);
+    }
+
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO scheduled_notifications (avail_id, scheduled_time)
+        SELECT
+            availability.id,
+            CASE
+                WHEN reminder_rules.days_prior IS NULL THEN NOW() + INTERVAL '1 mins'
+                -- Entries for a time slot are reminded at the start of the slot instead
+                ELSE availability.avail::timestamp
+                    + COALESCE(availability.start_time, reminder_rules.time_of_day)
+                    - make_interval(days => reminder_rules.days_prior)
+            END
+        FROM availability
+        JOIN usrs ON usrs.id = availability.usr_id
+        JOIN reminder_rules ON reminder_rules.is_valid = TRUE
+            AND (reminder_rules.role_type IS NULL OR reminder_rules.role_type = usrs.role_type)
+            AND (reminder_rules.usr_type IS NULL OR reminder_rules.usr_type = usrs.usr_type)
+        WHERE availability.id = $1
+          AND (availability.planned = TRUE OR availability.standby = TRUE)
+          -- Only schedule reminders which are not already in the past
+          AND (reminder_rules.days_prior IS NULL OR availability.avail - CURRENT_DATE >= reminder_rules.days_prior);
+        "#,
+        avail_id
+    )
+        .execute(&mut *conn)
+        .await;
+
+    match result {
+        Ok(res) => {
+            log::debug!("Scheduled {} reminders for availability ({})", res.rows_affected(), avail_id);
+            Ok(())
+        }
+        Err(e) => {
+            log::error!("Error scheduling reminders for availability ({}): {}", avail_id, e);
+            Err(e)
+        }
+    }
+}
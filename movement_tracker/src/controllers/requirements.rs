@@ -0,0 +1,80 @@
+use sqlx::types::chrono::NaiveDate;
+use sqlx::types::Uuid;
+use sqlx::PgPool;
+
+use crate::types::{CrewRequirement, Ict, RoleType};
+
+pub(crate) async fn get_requirements(
+    conn: &PgPool,
+    unit_id: Uuid,
+    date: &NaiveDate,
+    role_type: &RoleType
+) -> Result<Vec<CrewRequirement>, sqlx::Error> {
+    let result = sqlx::query_as!(
+        CrewRequirement,
+        r#"
+        SELECT
+            ict_type AS "ict_type: _",
+            headcount
+        FROM crew_requirements
+        WHERE unit_id = $1
+          AND avail = $2
+          AND role_type = $3
+          AND headcount > 0
+        ORDER BY ict_type ASC;
+        "#,
+        unit_id,
+        date,
+        role_type as _
+    )
+        .fetch_all(conn)
+        .await;
+
+    match result {
+        Ok(requirements) => {
+            log::info!("Found {} crew requirements for role {:?} on {}", requirements.len(), role_type, date);
+            Ok(requirements)
+        }
+        Err(e) => {
+            log::error!("Error fetching crew requirements for role {:?} on {}: {}", role_type, date, e);
+            Err(e)
+        }
+    }
+}
+
+// Adds delta to the crew required, never going below zero
+pub(crate) async fn change_requirement(
+    conn: &PgPool,
+    unit_id: Uuid,
+    date: &NaiveDate,
+    role_type: &RoleType,
+    ict_type: &Ict,
+    delta: i32
+) -> Result<(), sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO crew_requirements (unit_id, avail, role_type, ict_type, headcount)
+        VALUES ($1, $2, $3, $4, GREATEST($5, 0))
+        ON CONFLICT (unit_id, avail, role_type, ict_type)
+        DO UPDATE SET headcount = GREATEST(crew_requirements.headcount + $5, 0);
+        "#,
+        unit_id,
+        date,
+        role_type as _,
+        ict_type as _,
+        delta
+    )
+        .execute(conn)
+        .await;
+
+    match result {
+        Ok(_) => {
+            log::info!("Changed {:?} {:?} crew required on {} by {}", role_type, ict_type, date, delta);
+            Ok(())
+        }
+        Err(e) => {
+            log::error!("Error changing {:?} {:?} crew required on {}: {}", role_type, ict_type, date, e);
+            Err(e)
+        }
+    }
+}
@@ -1,18 +1,25 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use chrono::Utc;
 
 use sqlx::types::chrono::NaiveDate;
 use sqlx::types::Uuid;
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgPool};
 
-use crate::types::{Availability, AvailabilityDetails, Ict, RoleType};
+use crate::controllers::audit::{add_audit_log, get_snapshot, AuditAction, AuditEntity};
+use crate::controllers::reminders;
+use crate::types::{Ack, Availability, AvailabilityDetails, Ict, PlanConflict, PlanHistory, PlanStatus, RoleType, TimeSlot, UsrType};
 use crate::APP_TIMEZONE;
 use crate::now;
 
+// Availability to add for the user with the tele_id, in the slot or the whole day
+type NewAvailability = (u64, NaiveDate, Option<TimeSlot>, Ict, Option<String>);
+
+// Finds the user's availability on each date which overlaps the slot, any entry on the date overlaps a whole day
 pub(crate) async fn check_user_avail_multiple(
     conn: &PgPool,
     tele_id: u64,
-    dates: Vec<NaiveDate>
+    dates: Vec<NaiveDate>,
+    slot: Option<TimeSlot>
 ) -> Result<Vec<Option<Availability>>, sqlx::Error> {
     if dates.is_empty() {
         return Ok(Vec::new());
@@ -27,8 +34,12 @@ pub(crate) async fn check_user_avail_multiple(
             a.usr_id as user_id,
             a.avail,
             a.ict_type AS "ict_type: _",
+            a.start_time,
+            a.end_time,
             a.remarks,
             a.planned,
+            a.standby,
+            a.ack AS "ack: _",
             a.saf100,
             a.attended,
             a.is_valid,
@@ -39,9 +50,12 @@ pub(crate) async fn check_user_avail_multiple(
         WHERE u.tele_id = $1 AND u.is_valid = TRUE
           AND a.avail = ANY($2)
           AND a.is_valid = TRUE
+          AND ($3::TIME IS NULL OR a.start_time IS NULL OR (a.start_time < $4 AND $3 < a.end_time))
         "#,
         tele_id  as i64,
-        &dates[..] // Pass the dates as a slice
+        &dates[..], // Pass the dates as a slice
+        slot.map(|(start, _)| start),
+        slot.map(|(_, end)| end)
     )
         .fetch_all(conn)
         .await;
@@ -82,11 +96,15 @@ pub(crate) async fn check_user_avail_multiple(
 
 pub(crate) async fn edit_avail_by_uuid(
     conn: &PgPool,
+    actor: u64,
     availability_id: Uuid,
     planned: Option<bool>,
     ict_type: Option<Ict>,
     remarks: Option<String>,
 ) -> Result<AvailabilityDetails, sqlx::Error> {
+    let mut tx = conn.begin().await?;
+    let before = get_snapshot(&mut tx, AuditEntity::Availability, availability_id).await?;
+
     let result = sqlx::query_as!(
         AvailabilityDetails,
         r#"
@@ -98,47 +116,6 @@ pub(crate) async fn edit_avail_by_uuid(
                 remarks = COALESCE($4, remarks)
             WHERE id = $1
             RETURNING *
-        ),
-        notification_handling AS (
-            SELECT
-                update_availability.planned AS new_planned,
-                update_availability.id AS availability_id,
-                update_availability.avail AS avail_date
-            FROM update_availability
-        ),
-        invalidate_notifications AS (
-            UPDATE scheduled_notifications
-            SET is_valid = FALSE
-            WHERE avail_id = (SELECT availability_id FROM notification_handling)
-              AND sent = FALSE
-              AND (SELECT new_planned FROM notification_handling) = FALSE
-            RETURNING id
-        ),
-        schedule_notifications AS (
-            INSERT INTO scheduled_notifications (avail_id, scheduled_time)
-            SELECT
-                (SELECT availability_id FROM notification_handling),
-                times.scheduled_time
-            FROM (
-                -- Immediate Notification
-                SELECT NOW() + INTERVAL '1 mins' AS scheduled_time
-                UNION ALL
-                -- 5 Days Prior Notification (only if at least 5 days remain)
-                SELECT
-                    (SELECT avail_date FROM notification_handling)::timestamp
-                    + INTERVAL '09 hours'
-                    - INTERVAL '5 days' AS scheduled_time
-                WHERE (SELECT avail_date FROM notification_handling) - CURRENT_DATE >= 5
-                UNION ALL
-                -- 2 Days Prior Notification (only if at least 2 days remain)
-                SELECT
-                    (SELECT avail_date FROM notification_handling)::timestamp
-                    + INTERVAL '09 hours'
-                    - INTERVAL '2 days' AS scheduled_time
-                WHERE (SELECT avail_date FROM notification_handling) - CURRENT_DATE >= 2
-            ) AS times
-            WHERE (SELECT new_planned FROM notification_handling) = TRUE
-            RETURNING id
         )
         SELECT
             update_availability.id,
@@ -146,8 +123,12 @@ pub(crate) async fn edit_avail_by_uuid(
             usr.usr_type AS "usr_type: _",
             update_availability.avail,
             update_availability.ict_type AS "ict_type: _",
+            update_availability.start_time,
+            update_availability.end_time,
             update_availability.remarks,
             update_availability.planned,
+            update_availability.standby,
+            update_availability.ack AS "ack: _",
             update_availability.saf100,
             update_availability.attended,
             update_availability.is_valid,
@@ -161,25 +142,43 @@ pub(crate) async fn edit_avail_by_uuid(
         ict_type as _,
         remarks,
     )
-        .fetch_one(conn)
+        .fetch_one(&mut *tx)
         .await;
 
-    match result {
+    let details = match result {
         Ok(res) => {
             log::info!("Updated availability with UUID: {}", availability_id);
-            Ok(res)
+            res
         }
         Err(e) => {
             log::error!("Error updating availability with UUID {}: {}", availability_id, e);
-            Err(e)
+            tx.rollback().await?;
+            return Err(e);
         }
+    };
+
+    if let Err(e) = reminders::sync_scheduled_reminders(&mut tx, availability_id).await {
+        tx.rollback().await?;
+        return Err(e);
+    }
+
+    if let Err(e) = add_audit_log(&mut tx, actor, AuditAction::Edit, AuditEntity::Availability, availability_id, before).await {
+        tx.rollback().await?;
+        return Err(e);
     }
+
+    tx.commit().await?;
+    Ok(details)
 }
 
 pub(crate) async fn set_user_unavail(
     conn: &PgPool,
+    actor: u64,
     availability_id: Uuid,
 ) -> Result<AvailabilityDetails, sqlx::Error> {
+    let mut tx = conn.begin().await?;
+    let before = get_snapshot(&mut tx, AuditEntity::Availability, availability_id).await?;
+
     let result = sqlx::query_as!(
         AvailabilityDetails,
         r#"
@@ -200,8 +199,12 @@ pub(crate) async fn set_user_unavail(
             usr.usr_type AS "usr_type: _",
             update_availability.avail,
             update_availability.ict_type AS "ict_type: _",
+            update_availability.start_time,
+            update_availability.end_time,
             update_availability.remarks,
             update_availability.planned,
+            update_availability.standby,
+            update_availability.ack AS "ack: _",
             update_availability.saf100,
             update_availability.attended,
             update_availability.is_valid,
@@ -212,16 +215,100 @@ pub(crate) async fn set_user_unavail(
         "#,
         availability_id
     )
-        .fetch_one(conn)
+        .fetch_one(&mut *tx)
         .await;
 
     match result {
         Ok(res) => {
+            add_audit_log(&mut tx, actor, AuditAction::Remove, AuditEntity::Availability, availability_id, before).await?;
+            tx.commit().await?;
             log::info!("Soft deleted availability with id: {}", availability_id);
             Ok(res)
         }
         Err(e) => {
             log::error!("Error soft-deleting availability by id {}: {}", availability_id, e);
+            tx.rollback().await?;
+            Err(e)
+        }
+    }
+}
+
+// Records the owner's response to the reminder for a planned or standby entry, returns None if the entry
+// is not theirs or no longer planned
+pub(crate) async fn set_availability_ack(
+    conn: &PgPool,
+    tele_id: u64,
+    availability_id: Uuid,
+    ack: Ack,
+) -> Result<Option<AvailabilityDetails>, sqlx::Error> {
+    let mut tx = conn.begin().await?;
+    let before = get_snapshot(&mut tx, AuditEntity::Availability, availability_id).await?;
+
+    let result = sqlx::query_as!(
+        AvailabilityDetails,
+        r#"
+        WITH update_availability AS (
+            UPDATE availability
+            SET ack = $3
+            FROM usrs
+            WHERE availability.id = $1
+              AND availability.usr_id = usrs.id
+              AND usrs.tele_id = $2
+              AND availability.is_valid = TRUE
+              AND (availability.planned = TRUE OR availability.standby = TRUE)
+            RETURNING availability.*
+        ),
+        usr AS (
+            SELECT id, ops_name, usr_type
+            FROM usrs
+            WHERE id = (SELECT usr_id FROM update_availability)
+        )
+        SELECT
+            update_availability.id,
+            usr.ops_name,
+            usr.usr_type AS "usr_type: _",
+            update_availability.avail,
+            update_availability.ict_type AS "ict_type: _",
+            update_availability.start_time,
+            update_availability.end_time,
+            update_availability.remarks,
+            update_availability.planned,
+            update_availability.standby,
+            update_availability.ack AS "ack: _",
+            update_availability.saf100,
+            update_availability.attended,
+            update_availability.is_valid,
+            update_availability.created,
+            update_availability.updated
+        FROM update_availability
+        JOIN usr ON update_availability.usr_id = usr.id;
+        "#,
+        availability_id,
+        tele_id as i64,
+        ack as _
+    )
+        .fetch_optional(&mut *tx)
+        .await;
+
+    match result {
+        Ok(Some(res)) => {
+            let action = match ack {
+                Ack::Acknowledged => AuditAction::Acknowledge,
+                Ack::Declined => AuditAction::Decline,
+            };
+            add_audit_log(&mut tx, tele_id, action, AuditEntity::Availability, availability_id, before).await?;
+            tx.commit().await?;
+            log::info!("Set reminder response of availability with id {} to {}", availability_id, ack.as_ref());
+            Ok(Some(res))
+        }
+        Ok(None) => {
+            log::info!("No planned availability with id {} for user with tele_id {}", availability_id, tele_id);
+            tx.rollback().await?;
+            Ok(None)
+        }
+        Err(e) => {
+            log::error!("Error setting reminder response of availability by id {}: {}", availability_id, e);
+            tx.rollback().await?;
             Err(e)
         }
     }
@@ -241,7 +328,11 @@ pub(crate) async fn get_availability_by_tele_id_and_dates(
             availability.usr_id AS user_id,
             availability.avail,
             availability.planned,
+            availability.standby,
+            availability.ack AS "ack: _",
             availability.ict_type AS "ict_type: _",
+            availability.start_time,
+            availability.end_time,
             availability.remarks,
             availability.saf100,
             availability.attended,
@@ -301,7 +392,11 @@ pub(crate) async fn get_upcoming_availability_by_tele_id(
             availability.usr_id AS user_id,
             availability.avail,
             availability.planned,
+            availability.standby,
+            availability.ack AS "ack: _",
             availability.ict_type AS "ict_type: _",
+            availability.start_time,
+            availability.end_time,
             availability.remarks,
             availability.saf100,
             availability.attended,
@@ -356,8 +451,12 @@ pub(crate) async fn get_upcoming_availability_details_by_tele_id(
             usrs.usr_type AS "usr_type: _",
             availability.avail,
             availability.ict_type AS "ict_type: _",
+            availability.start_time,
+            availability.end_time,
             availability.remarks,
             availability.planned,
+            availability.standby,
+            availability.ack AS "ack: _",
             availability.saf100,
             availability.attended,
             availability.is_valid,
@@ -408,7 +507,11 @@ pub(crate) async fn get_availability_by_uuid(
             availability.usr_id AS user_id,
             availability.avail,
             availability.planned,
+            availability.standby,
+            availability.ack AS "ack: _",
             availability.ict_type AS "ict_type: _",
+            availability.start_time,
+            availability.end_time,
             availability.remarks,
             availability.saf100,
             availability.attended,
@@ -449,7 +552,11 @@ pub(crate) async fn get_availability_details_by_uuid(
             usrs.usr_type AS "usr_type: _",
             availability.avail,
             availability.planned,
+            availability.standby,
+            availability.ack AS "ack: _",
             availability.ict_type AS "ict_type: _",
+            availability.start_time,
+            availability.end_time,
             availability.remarks,
             availability.saf100,
             availability.attended,
@@ -478,14 +585,59 @@ pub(crate) async fn get_availability_details_by_uuid(
     }
 }
 
+// Adds availability for part of the date, or the whole date if there is no slot
 pub(crate) async fn add_user_avail(
     conn: &PgPool,
+    actor: u64,
+    tele_id: u64,
+    (date, slot): (NaiveDate, Option<TimeSlot>),
+    ict_type: &Ict,
+    remarks: Option<String>,
+    planned: Option<bool>,
+) -> Result<AvailabilityDetails, sqlx::Error> {
+    let mut tx = conn.begin().await?;
+
+    match add_user_avail_tx(&mut tx, actor, tele_id, (date, slot), ict_type, remarks, planned).await {
+        Ok(details) => {
+            tx.commit().await?;
+            Ok(details)
+        }
+        Err(e) => {
+            tx.rollback().await?;
+            Err(e)
+        }
+    }
+}
+
+async fn add_user_avail_tx(
+    conn: &mut PgConnection,
+    actor: u64,
     tele_id: u64,
-    date: NaiveDate,
+    (date, slot): (NaiveDate, Option<TimeSlot>),
     ict_type: &Ict,
     remarks: Option<String>,
     planned: Option<bool>,
 ) -> Result<AvailabilityDetails, sqlx::Error> {
+    // Snapshot any existing entry in the same slot, as the insert may overwrite it
+    let before = sqlx::query_scalar!(
+        r#"
+        SELECT to_jsonb(availability) AS "snapshot!"
+        FROM availability
+        JOIN usrs ON availability.usr_id = usrs.id
+        WHERE usrs.tele_id = $1 AND usrs.is_valid = TRUE AND availability.avail = $2
+          AND availability.start_time IS NOT DISTINCT FROM $3;
+        "#,
+        tele_id as i64,
+        date,
+        slot.map(|(start, _)| start)
+    )
+        .fetch_optional(&mut *conn)
+        .await?;
+    let was_valid = before.as_ref()
+        .and_then(|snapshot| snapshot.get("is_valid"))
+        .and_then(|is_valid| is_valid.as_bool())
+        .unwrap_or(false);
+
     let result = sqlx::query_as!(
         AvailabilityDetails,
         r#"
@@ -495,17 +647,20 @@ pub(crate) async fn add_user_avail(
             WHERE tele_id = $5 AND is_valid = TRUE
         ),
         upsert_availability AS (
-            INSERT INTO availability (usr_id, avail, ict_type, remarks, planned)
+            INSERT INTO availability (usr_id, avail, ict_type, remarks, planned, start_time, end_time)
             VALUES (
                 (SELECT id FROM usr),
                 $1,
                 $2,
                 $3,
-                COALESCE($4, FALSE)
+                COALESCE($4, FALSE),
+                $6,
+                $7
             )
-            ON CONFLICT (usr_id, avail) DO UPDATE
+            ON CONFLICT (usr_id, avail, start_time) DO UPDATE
                 SET
                     ict_type = EXCLUDED.ict_type,
+                    end_time = EXCLUDED.end_time,
                     remarks = CASE
                         WHEN availability.is_valid THEN COALESCE(EXCLUDED.remarks, availability.remarks)
                         ELSE EXCLUDED.remarks
@@ -516,47 +671,6 @@ pub(crate) async fn add_user_avail(
                     END,
                     is_valid = TRUE
             RETURNING *
-        ),
-        notification_handling AS (
-            SELECT
-                upsert_availability.planned AS new_planned,
-                upsert_availability.id AS availability_id,
-                upsert_availability.avail AS avail_date
-            FROM upsert_availability
-        ),
-        invalidate_notifications AS (
-            UPDATE scheduled_notifications
-            SET is_valid = FALSE
-            WHERE avail_id = (SELECT availability_id FROM notification_handling)
-              AND sent = FALSE
-              AND (SELECT new_planned FROM notification_handling) = FALSE
-            RETURNING id
-        ),
-        schedule_notifications AS (
-            INSERT INTO scheduled_notifications (avail_id, scheduled_time)
-            SELECT
-                (SELECT availability_id FROM notification_handling),
-                times.scheduled_time
-            FROM (
-                -- Immediate Notification
-                SELECT NOW() + INTERVAL '1 mins' AS scheduled_time
-                UNION ALL
-                -- 5 Days Prior Notification (only if at least 5 days remain)
-                SELECT
-                    (SELECT avail_date FROM notification_handling)::timestamp
-                    + INTERVAL '09 hours'
-                    - INTERVAL '5 days' AS scheduled_time
-                WHERE (SELECT avail_date FROM notification_handling) - CURRENT_DATE >= 5
-                UNION ALL
-                -- 2 Days Prior Notification (only if at least 2 days remain)
-                SELECT
-                    (SELECT avail_date FROM notification_handling)::timestamp
-                    + INTERVAL '09 hours'
-                    - INTERVAL '2 days' AS scheduled_time
-                WHERE (SELECT avail_date FROM notification_handling) - CURRENT_DATE >= 2
-            ) AS times
-            WHERE (SELECT new_planned FROM notification_handling) = TRUE
-            RETURNING id
         )
         SELECT
             upsert_availability.id,
@@ -564,8 +678,12 @@ pub(crate) async fn add_user_avail(
             usr.usr_type AS "usr_type: _",
             upsert_availability.avail,
             upsert_availability.ict_type AS "ict_type: _",
+            upsert_availability.start_time,
+            upsert_availability.end_time,
             upsert_availability.remarks,
             upsert_availability.planned,
+            upsert_availability.standby,
+            upsert_availability.ack AS "ack: _",
             upsert_availability.saf100,
             upsert_availability.attended,
             upsert_availability.is_valid,
@@ -579,18 +697,20 @@ pub(crate) async fn add_user_avail(
         remarks,
         planned,
         tele_id as i64,
+        slot.map(|(start, _)| start),
+        slot.map(|(_, end)| end)
     )
-        .fetch_one(conn)
+        .fetch_one(&mut *conn)
         .await;
 
-    match result {
+    let details = match result {
         Ok(res) => {
             log::info!(
                 "Added or updated availability for tele_id: {} on {}",
                 tele_id,
                 date
             );
-            Ok(res)
+            res
         }
         Err(e) => {
             log::error!(
@@ -598,13 +718,47 @@ pub(crate) async fn add_user_avail(
                 tele_id,
                 e
             );
-            Err(e)
+            return Err(e);
+        }
+    };
+
+    reminders::sync_scheduled_reminders(&mut *conn, details.id).await?;
+
+    let action = if was_valid { AuditAction::Edit } else { AuditAction::Add };
+    add_audit_log(conn, actor, action, AuditEntity::Availability, details.id, before).await?;
+
+    Ok(details)
+}
+
+pub(crate) async fn add_user_avail_multiple(
+    conn: &PgPool,
+    actor: u64,
+    entries: Vec<NewAvailability>,
+) -> Result<Vec<AvailabilityDetails>, sqlx::Error> {
+    // Start a transaction so that either every entry is added or none are
+    let mut tx = conn.begin().await?;
+
+    let mut added = Vec::new();
+
+    for (tele_id, date, slot, ict_type, remarks) in entries {
+        match add_user_avail_tx(&mut tx, actor, tele_id, (date, slot), &ict_type, remarks, None).await {
+            Ok(details) => added.push(details),
+            Err(e) => {
+                tx.rollback().await?;
+                return Err(e);
+            }
         }
     }
+
+    // Commit the transaction if all inserts succeed
+    tx.commit().await?;
+    log::info!("Added or updated {} availability entries", added.len());
+    Ok(added)
 }
 
 pub(crate) async fn get_availability_for_role_and_dates(
     conn: &PgPool,
+    unit_id: Option<Uuid>,
     role_type: RoleType,
     start: NaiveDate,
     end: NaiveDate,
@@ -618,8 +772,12 @@ pub(crate) async fn get_availability_for_role_and_dates(
             usrs.usr_type AS "usr_type: _",
             availability.avail,
             availability.ict_type AS "ict_type: _",
+            availability.start_time,
+            availability.end_time,
             availability.remarks,
             availability.planned,
+            availability.standby,
+            availability.ack AS "ack: _",
             availability.saf100,
             availability.attended,
             availability.is_valid,
@@ -631,11 +789,13 @@ pub(crate) async fn get_availability_for_role_and_dates(
         AND availability.avail >= $2
         AND availability.avail <= $3
         AND (availability.is_valid = TRUE OR availability.planned = TRUE)
+        AND ($4::UUID IS NULL OR usrs.unit_id = $4)
         ORDER BY availability.avail ASC;
         "#,
         role_type as _,  // RoleType enum
         start,           // Start date
-        end              // End date
+        end,             // End date
+        unit_id          // Unit, or None for every unit
     )
         .fetch_all(conn)
         .await;
@@ -664,46 +824,9 @@ pub(crate) async fn get_availability_for_role_and_dates(
     }
 }
 
-pub(crate) async fn get_furthest_avail_date_for_role(
-    conn: &PgPool,
-    role_type: &RoleType,
-) -> Result<Option<NaiveDate>, sqlx::Error> {
-    let result = sqlx::query_scalar!(
-        r#"
-        SELECT MAX(availability.avail)
-        FROM availability
-        JOIN usrs ON usrs.id = availability.usr_id
-        WHERE usrs.role_type = $1 AND usrs.is_valid = TRUE
-        AND (availability.is_valid = TRUE OR availability.planned = TRUE);
-        "#,
-        role_type as _  // RoleType enum
-    )
-        .fetch_one(conn)
-        .await;
-
-    match result {
-        Ok(date) => {
-            log::info!(
-                "Furthest availability date for role {:?}: {}",
-                role_type,
-                date.map_or("None".to_string(), |d| d.to_string())
-            );
-            Ok(date)
-        }
-        Err(e) => {
-            log::error!(
-                "Error retrieving furthest availability date for role {:?}: {}",
-                role_type,
-                e
-            );
-            Err(e)
-        }
-    }
-}
-
-
 pub(crate) async fn get_users_available_by_role_on_date(
     conn: &PgPool,
+    unit_id: Uuid,
     date: &NaiveDate,
     role_type: &RoleType,
 ) -> Result<Vec<AvailabilityDetails>, sqlx::Error> {
@@ -716,8 +839,12 @@ pub(crate) async fn get_users_available_by_role_on_date(
             usrs.usr_type AS "usr_type: _",
             availability.avail,
             availability.ict_type AS "ict_type: _",
+            availability.start_time,
+            availability.end_time,
             availability.remarks,
             availability.planned,
+            availability.standby,
+            availability.ack AS "ack: _",
             availability.saf100,
             availability.attended,
             availability.is_valid,
@@ -727,11 +854,13 @@ pub(crate) async fn get_users_available_by_role_on_date(
         JOIN usrs ON usrs.id = availability.usr_id
         WHERE availability.avail = $1 AND usrs.is_valid = TRUE
           AND usrs.role_type = $2
+          AND usrs.unit_id = $3
           AND (availability.is_valid = TRUE OR availability.planned = TRUE)
         ORDER BY usrs.ops_name ASC;
         "#,
         date,
-        role_type as _  // Map RoleType enum
+        role_type as _,  // Map RoleType enum
+        unit_id
     )
         .fetch_all(conn)
         .await;
@@ -758,66 +887,29 @@ pub(crate) async fn get_users_available_by_role_on_date(
     }
 }
 
-pub(crate) async fn toggle_planned_status_multiple(
+// Sets the planning status of each availability entry
+pub(crate) async fn set_plan_status_multiple(
     conn: &PgPool,
-    availability_ids: HashSet<Uuid>,
+    actor: u64,
+    changes: HashMap<Uuid, PlanStatus>,
 ) -> Result<Vec<AvailabilityDetails>, sqlx::Error> {
     // Start a transaction
     let mut tx = conn.begin().await?;
 
     let mut updated_availabilities = Vec::new();
 
-    // Loop through each availability ID and toggle the planned status
-    for availability_id in availability_ids {
+    // Loop through each availability ID and set the planning status
+    for (availability_id, plan_status) in changes {
+        let before = get_snapshot(&mut tx, AuditEntity::Availability, availability_id).await?;
+
         let result = sqlx::query_as!(
             AvailabilityDetails,
             r#"
             WITH update_statement AS (
                 UPDATE availability
-                SET planned = NOT planned
+                SET planned = $2, standby = $3, ack = NULL
                 WHERE id = $1
                 RETURNING *
-            ),
-            notification_handling AS (
-                SELECT
-                    update_statement.planned AS new_planned,
-                    update_statement.id AS availability_id,
-                    update_statement.avail AS avail_date
-                FROM update_statement
-            ),
-            invalidate_notifications AS (
-                UPDATE scheduled_notifications
-                SET is_valid = FALSE
-                WHERE avail_id = (SELECT availability_id FROM notification_handling)
-                  AND sent = FALSE
-                  AND (SELECT new_planned FROM notification_handling) = FALSE
-                RETURNING id
-            ),
-            schedule_notifications AS (
-                INSERT INTO scheduled_notifications (avail_id, scheduled_time)
-                SELECT
-                    (SELECT availability_id FROM notification_handling),
-                    times.scheduled_time
-                FROM (
-                    -- Immediate Notification
-                    SELECT NOW() + INTERVAL '1 mins' AS scheduled_time
-                    UNION ALL
-                    -- 5 Days Prior Notification
-                    SELECT
-                        (SELECT avail_date FROM notification_handling)::timestamp
-                        + INTERVAL '09 hours'
-                        - INTERVAL '5 days' AS scheduled_time
-                    WHERE (SELECT avail_date FROM notification_handling) - CURRENT_DATE >= 5
-                    UNION ALL
-                    -- 2 Days Prior Notification
-                    SELECT
-                        (SELECT avail_date FROM notification_handling)::timestamp
-                        + INTERVAL '09 hours'
-                        - INTERVAL '2 days' AS scheduled_time
-                    WHERE (SELECT avail_date FROM notification_handling) - CURRENT_DATE >= 2
-                ) AS times
-                WHERE (SELECT new_planned FROM notification_handling) = TRUE
-                RETURNING id
             )
             SELECT
                 update_statement.id,
@@ -825,8 +917,12 @@ pub(crate) async fn toggle_planned_status_multiple(
                 usrs.usr_type AS "usr_type: _",
                 update_statement.avail,
                 update_statement.ict_type AS "ict_type: _",
+                update_statement.start_time,
+                update_statement.end_time,
                 update_statement.remarks,
                 update_statement.planned,
+                update_statement.standby,
+                update_statement.ack AS "ack: _",
                 update_statement.saf100,
                 update_statement.attended,
                 update_statement.is_valid,
@@ -835,17 +931,36 @@ pub(crate) async fn toggle_planned_status_multiple(
             FROM update_statement
             JOIN usrs ON update_statement.usr_id = usrs.id;
             "#,
-            availability_id
+            availability_id,
+            plan_status == PlanStatus::Planned,
+            plan_status == PlanStatus::Standby
         ).fetch_one(&mut *tx)
             .await;
 
-        match result {
-            Ok(availability) => updated_availabilities.push(availability),
+        let action = match result {
+            Ok(availability) => {
+                updated_availabilities.push(availability);
+                match plan_status {
+                    PlanStatus::Planned => AuditAction::Plan,
+                    PlanStatus::Standby => AuditAction::Standby,
+                    PlanStatus::Unplanned => AuditAction::Unplan,
+                }
+            }
             Err(e) => {
-                log::error!("Error toggling planned status for availability ID {}: {}", availability_id, e);
+                log::error!("Error setting plan status for availability ID {}: {}", availability_id, e);
                 tx.rollback().await?;
                 return Err(e);
             }
+        };
+
+        if let Err(e) = reminders::sync_scheduled_reminders(&mut tx, availability_id).await {
+            tx.rollback().await?;
+            return Err(e);
+        }
+
+        if let Err(e) = add_audit_log(&mut tx, actor, action, AuditEntity::Availability, availability_id, before).await {
+            tx.rollback().await?;
+            return Err(e);
         }
     }
 
@@ -854,6 +969,150 @@ pub(crate) async fn toggle_planned_status_multiple(
     Ok(updated_availabilities)
 }
 
+// Counts the days the owner of each availability entry was planned within the window before its date
+pub(crate) async fn get_plan_history(
+    conn: &PgPool,
+    availability_ids: &[Uuid],
+    window_days: i32
+) -> Result<Vec<PlanHistory>, sqlx::Error> {
+    let result = sqlx::query_as!(
+        PlanHistory,
+        r#"
+        SELECT
+            a.id,
+            COUNT(DISTINCT p.avail) AS "times_planned!",
+            MAX(p.avail) AS last_planned
+        FROM availability a
+        LEFT JOIN availability p ON p.usr_id = a.usr_id
+            AND p.planned = TRUE
+            AND p.avail < a.avail
+            AND p.avail >= a.avail - $2::INT
+        WHERE a.id = ANY($1)
+        GROUP BY a.id;
+        "#,
+        availability_ids,
+        window_days
+    )
+        .fetch_all(conn)
+        .await;
+
+    match result {
+        Ok(history) => {
+            log::info!("Found plan history for {} availability entries", history.len());
+            Ok(history)
+        }
+        Err(e) => {
+            log::error!("Error fetching plan history: {}", e);
+            Err(e)
+        }
+    }
+}
+
+// Checks the entries that the planning status changes would newly plan, before the changes are committed
+pub(crate) async fn get_plan_conflicts(
+    conn: &PgPool,
+    changes: &HashMap<Uuid, PlanStatus>,
+    capacity: Option<i64>,
+    saf100_lead_days: i64,
+) -> Result<Vec<PlanConflict>, sqlx::Error> {
+    let ids: Vec<Uuid> = changes.iter()
+        .filter(|(_, plan_status)| **plan_status == PlanStatus::Planned)
+        .map(|(id, _)| *id)
+        .collect();
+    let changed_ids: Vec<Uuid> = changes.keys().cloned().collect();
+    let today = now!().date_naive();
+    let mut conflicts = Vec::new();
+
+    if let Some(capacity) = capacity {
+        // Planned headcount after the changes, for each role and date that the changes add crew to, counting each user once across their slots
+        let result = sqlx::query!(
+            r#"
+            WITH targets AS (
+                SELECT DISTINCT usrs.unit_id, usrs.role_type, availability.avail
+                FROM availability
+                JOIN usrs ON usrs.id = availability.usr_id
+                WHERE availability.id = ANY($1) AND availability.planned = FALSE
+            )
+            SELECT
+                targets.role_type AS "role_type: RoleType",
+                targets.avail,
+                COUNT(DISTINCT usrs.id) AS "planned!"
+            FROM targets
+            JOIN usrs ON usrs.unit_id = targets.unit_id AND usrs.role_type = targets.role_type AND usrs.is_valid = TRUE
+            JOIN availability ON availability.usr_id = usrs.id AND availability.avail = targets.avail
+            WHERE availability.id = ANY($1) OR (availability.planned AND availability.is_valid = TRUE AND NOT availability.id = ANY($2))
+            GROUP BY targets.role_type, targets.avail
+            ORDER BY targets.avail, targets.role_type;
+            "#,
+            &ids,
+            &changed_ids
+        )
+            .fetch_all(conn)
+            .await;
+
+        match result {
+            Ok(headcounts) => {
+                conflicts.extend(headcounts.into_iter()
+                    .filter(|headcount| headcount.planned > capacity)
+                    .map(|headcount| PlanConflict::OverCapacity {
+                        role_type: headcount.role_type,
+                        avail: headcount.avail,
+                        planned: headcount.planned,
+                        capacity
+                    }));
+            }
+            Err(e) => {
+                log::error!("Error fetching planned headcount for plan conflicts: {}", e);
+                return Err(e);
+            }
+        }
+    }
+
+    // Entries that the changes would plan, with any movement the user has on the same day
+    let result = sqlx::query!(
+        r#"
+        SELECT
+            usrs.ops_name,
+            usrs.usr_type AS "usr_type: UsrType",
+            availability.avail,
+            availability.saf100,
+            movement.activities
+        FROM availability
+        JOIN usrs ON usrs.id = availability.usr_id
+        LEFT JOIN LATERAL (
+            SELECT string_agg(movement.activity, ', ' ORDER BY movement.start_time) AS activities
+            FROM movement
+            WHERE movement.usr_id = usrs.id AND movement.avail = availability.avail AND movement.is_valid = TRUE
+        ) movement ON TRUE
+        WHERE availability.id = ANY($1) AND availability.planned = FALSE
+        ORDER BY availability.avail, usrs.ops_name;
+        "#,
+        &ids
+    )
+        .fetch_all(conn)
+        .await;
+
+    match result {
+        Ok(entries) => {
+            for entry in entries {
+                if let Some(activities) = entry.activities {
+                    conflicts.push(PlanConflict::Movement { ops_name: entry.ops_name.clone(), avail: entry.avail, activities });
+                }
+
+                let days_left = (entry.avail - today).num_days();
+                if entry.usr_type.is_ns() && !entry.saf100 && days_left < saf100_lead_days {
+                    conflicts.push(PlanConflict::Saf100LeadTime { ops_name: entry.ops_name, avail: entry.avail, days_left });
+                }
+            }
+            Ok(conflicts)
+        }
+        Err(e) => {
+            log::error!("Error fetching entries to plan for plan conflicts: {}", e);
+            Err(e)
+        }
+    }
+}
+
 pub(crate) async fn get_planned_availability_details_by_tele_id(
     conn: &PgPool,
     tele_id: u64,
@@ -867,8 +1126,12 @@ pub(crate) async fn get_planned_availability_details_by_tele_id(
             usrs.usr_type AS "usr_type: _",
             availability.avail,
             availability.ict_type AS "ict_type: _",
+            availability.start_time,
+            availability.end_time,
             availability.remarks,
             availability.planned,
+            availability.standby,
+            availability.ack AS "ack: _",
             availability.saf100,
             availability.attended,
             availability.is_valid,
@@ -877,7 +1140,7 @@ pub(crate) async fn get_planned_availability_details_by_tele_id(
         FROM availability
         JOIN usrs ON usrs.id = availability.usr_id
         WHERE usrs.tele_id = $1 AND usrs.is_valid = TRUE
-          AND availability.planned = TRUE
+          AND (availability.planned = TRUE OR availability.standby = TRUE)
         ORDER BY availability.avail ASC;
         "#,
         tele_id as i64
@@ -0,0 +1,57 @@
+use sqlx::types::chrono::NaiveDate;
+use sqlx::types::Uuid;
+use sqlx::PgPool;
+
+use crate::types::{RoleType, UserStats};
+
+// Counts the days of availability of each user between start and end inclusive, several slots on a date count as one day.
+// Planned dates before today without attendance are no-shows
+pub(crate) async fn get_user_stats(
+    conn: &PgPool,
+    unit_id: Option<Uuid>,
+    role_type: Option<&RoleType>,
+    start: NaiveDate,
+    end: NaiveDate,
+    today: NaiveDate
+) -> Result<Vec<UserStats>, sqlx::Error> {
+    let result = sqlx::query_as!(
+        UserStats,
+        r#"
+        SELECT
+            u.ops_name,
+            u.role_type AS "role_type: _",
+            u.usr_type AS "usr_type: _",
+            COUNT(DISTINCT a.avail) FILTER (WHERE a.is_valid = TRUE OR a.planned = TRUE) AS "offered!",
+            COUNT(DISTINCT a.avail) FILTER (WHERE a.planned = TRUE) AS "planned!",
+            COUNT(DISTINCT a.avail) FILTER (WHERE a.attended = TRUE) AS "attended!",
+            COUNT(DISTINCT a.avail) FILTER (WHERE a.saf100 = TRUE) AS "saf100!",
+            COUNT(DISTINCT a.avail) FILTER (WHERE a.planned = TRUE AND a.attended = FALSE AND a.avail < $5) AS "no_shows!"
+        FROM usrs u
+        LEFT JOIN availability a ON a.usr_id = u.id
+            AND a.avail BETWEEN $3 AND $4
+        WHERE u.is_valid = TRUE
+          AND ($1::UUID IS NULL OR u.unit_id = $1)
+          AND ($2::TEXT IS NULL OR u.role_type = $2)
+        GROUP BY u.id
+        ORDER BY u.role_type ASC, u.ops_name ASC;
+        "#,
+        unit_id,
+        role_type as _,
+        start,
+        end,
+        today
+    )
+        .fetch_all(conn)
+        .await;
+
+    match result {
+        Ok(stats) => {
+            log::info!("Computed stats for {} users from {} to {}", stats.len(), start, end);
+            Ok(stats)
+        }
+        Err(e) => {
+            log::error!("Error computing user stats from {} to {}: {}", start, end, e);
+            Err(e)
+        }
+    }
+}
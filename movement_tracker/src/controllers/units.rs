@@ -0,0 +1,75 @@
+use sqlx::types::Uuid;
+use sqlx::PgPool;
+
+use crate::types::Unit;
+
+pub(crate) async fn get_units(conn: &PgPool) -> Result<Vec<Unit>, sqlx::Error> {
+    let result = sqlx::query_as!(
+        Unit,
+        r#"
+        SELECT id, name, created, updated
+        FROM units
+        WHERE is_valid = TRUE
+        ORDER BY created ASC;
+        "#
+    )
+        .fetch_all(conn)
+        .await;
+
+    match result {
+        Ok(units) => {
+            log::info!("Found {} units", units.len());
+            Ok(units)
+        }
+        Err(e) => {
+            log::error!("Error fetching units: {}", e);
+            Err(e)
+        }
+    }
+}
+
+pub(crate) async fn get_unit_by_uuid(conn: &PgPool, id: Uuid) -> Result<Unit, sqlx::Error> {
+    let result = sqlx::query_as!(
+        Unit,
+        r#"
+        SELECT id, name, created, updated
+        FROM units
+        WHERE id = $1 AND is_valid = TRUE;
+        "#,
+        id
+    )
+        .fetch_one(conn)
+        .await;
+
+    match result {
+        Ok(unit) => Ok(unit),
+        Err(e) => {
+            log::error!("Error fetching unit with id {}: {}", id, e);
+            Err(e)
+        }
+    }
+}
+
+// The oldest unit receives users which are not assigned to any other unit
+pub(crate) async fn get_default_unit(conn: &PgPool) -> Result<Unit, sqlx::Error> {
+    let result = sqlx::query_as!(
+        Unit,
+        r#"
+        SELECT id, name, created, updated
+        FROM units
+        WHERE is_valid = TRUE
+        ORDER BY created ASC
+        LIMIT 1;
+        "#
+    )
+        .fetch_one(conn)
+        .await;
+
+    match result {
+        Ok(unit) => Ok(unit),
+        Err(e) => {
+            log::error!("Error fetching default unit: {}", e);
+            Err(e)
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use crate::controllers::audit::{add_audit_log, get_snapshot, AuditAction, AuditEntity};
 use crate::types::{RoleType, UserInfo, Usr, UsrType};
 use sqlx::types::Uuid;
 use sqlx::PgPool;
@@ -35,15 +36,16 @@ pub(crate) async fn user_exists_tele_id(conn: &PgPool, tele_id: u64) -> Result<b
     }
 }
 
-pub(crate) async fn user_exists_ops_name(conn: &PgPool, ops_name: &str) -> Result<bool, sqlx::Error> {
+pub(crate) async fn user_exists_ops_name(conn: &PgPool, unit_id: Uuid, ops_name: &str) -> Result<bool, sqlx::Error> {
     let result = sqlx::query!(
         r#"
         SELECT EXISTS(
             SELECT 1 FROM usrs
-            WHERE usrs.ops_name = $1 AND is_valid = TRUE
+            WHERE usrs.ops_name = $1 AND usrs.unit_id = $2 AND is_valid = TRUE
         ) AS "exists!";
         "#,
-        ops_name
+        ops_name,
+        unit_id
     )
     .fetch_one(conn)
     .await;
@@ -80,6 +82,7 @@ pub(crate) async fn get_user_by_tele_id(conn: &PgPool, tele_id: u64) -> Result<U
             usrs.usr_type AS "usr_type: _",
             usrs.role_type AS "role_type: _",
             usrs.admin AS admin,
+            usrs.unit_id AS unit_id,
             usrs.created AS created,
             usrs.updated AS updated
         FROM usrs
@@ -104,6 +107,28 @@ pub(crate) async fn get_user_by_tele_id(conn: &PgPool, tele_id: u64) -> Result<U
     }
 }
 
+// Returns the unit a registered user belongs to
+pub(crate) async fn get_unit_id_by_tele_id(conn: &PgPool, tele_id: u64) -> Result<Uuid, sqlx::Error> {
+    let result = sqlx::query_scalar!(
+        r#"
+        SELECT usrs.unit_id
+        FROM usrs
+        WHERE usrs.tele_id = $1 AND usrs.is_valid = TRUE;
+        "#,
+        tele_id as i64
+    )
+        .fetch_one(conn)
+        .await;
+
+    match result {
+        Ok(unit_id) => Ok(unit_id),
+        Err(e) => {
+            log::error!("Error getting unit of user with tele_id {}: {}", tele_id, e);
+            Err(e)
+        }
+    }
+}
+
 pub(crate) async fn get_user_by_uuid(conn: &PgPool, id: Uuid) -> Result<Usr, sqlx::Error> {
     let result = sqlx::query_as!(
         Usr,
@@ -116,6 +141,7 @@ pub(crate) async fn get_user_by_uuid(conn: &PgPool, id: Uuid) -> Result<Usr, sql
             usrs.usr_type AS "usr_type: _",
             usrs.role_type AS "role_type: _",
             usrs.admin AS admin,
+            usrs.unit_id AS unit_id,
             usrs.created AS created,
             usrs.updated AS updated
         FROM usrs
@@ -138,7 +164,7 @@ pub(crate) async fn get_user_by_uuid(conn: &PgPool, id: Uuid) -> Result<Usr, sql
     }
 }
 
-pub(crate) async fn get_user_by_ops_name(conn: &PgPool, ops_name: &str) -> Result<Usr, sqlx::Error> {
+pub(crate) async fn get_user_by_ops_name(conn: &PgPool, unit_id: Uuid, ops_name: &str) -> Result<Usr, sqlx::Error> {
     let result = sqlx::query_as!(
         Usr,
         r#"
@@ -150,12 +176,14 @@ pub(crate) async fn get_user_by_ops_name(conn: &PgPool, ops_name: &str) -> Resul
             usrs.usr_type AS "usr_type: _",
             usrs.role_type AS "role_type: _",
             usrs.admin AS admin,
+            usrs.unit_id AS unit_id,
             usrs.created AS created,
             usrs.updated AS updated
         FROM usrs
-        WHERE usrs.ops_name = $1 AND usrs.is_valid = TRUE;
+        WHERE usrs.ops_name = $1 AND usrs.unit_id = $2 AND usrs.is_valid = TRUE;
         "#,
-        ops_name
+        ops_name,
+        unit_id
     )
     .fetch_one(conn)
     .await;
@@ -176,13 +204,17 @@ pub(crate) async fn get_user_by_ops_name(conn: &PgPool, ops_name: &str) -> Resul
 
 pub(crate) async fn add_user(
     conn: &PgPool,
+    actor: u64,
     tele_id: u64,
     name: String,
     ops_name: String,
     role_type: RoleType,
     user_type: UsrType,
-    admin: bool
+    admin: bool,
+    unit_id: Uuid
 ) -> Result<Usr, sqlx::Error> {
+    let mut tx = conn.begin().await?;
+
     let result = sqlx::query_as!(
         Usr,
         r#"
@@ -191,8 +223,8 @@ pub(crate) async fn add_user(
             FROM usrs
             WHERE tele_id = $1 AND is_valid = TRUE
         )
-        INSERT INTO usrs (tele_id, name, ops_name, role_type, usr_type, admin)
-        SELECT $1, $2, $3, $4, $5, $6
+        INSERT INTO usrs (tele_id, name, ops_name, role_type, usr_type, admin, unit_id)
+        SELECT $1, $2, $3, $4, $5, $6, $7
         WHERE NOT EXISTS (SELECT * FROM existing_user)
         RETURNING
             id,
@@ -202,6 +234,7 @@ pub(crate) async fn add_user(
             usr_type AS "usr_type: _",
             role_type AS "role_type: _",
             admin,
+            unit_id,
             created,
             updated
         "#,
@@ -210,13 +243,16 @@ pub(crate) async fn add_user(
         ops_name,
         role_type as RoleType,
         user_type as UsrType,
-        admin
+        admin,
+        unit_id
     )
-        .fetch_one(conn)
+        .fetch_one(&mut *tx)
         .await;
 
     match result {
         Ok(user) => {
+            add_audit_log(&mut tx, actor, AuditAction::Add, AuditEntity::Usrs, user.id, None).await?;
+            tx.commit().await?;
             log::info!("Added user with tele_id: {}", tele_id);
             Ok(user)
         }
@@ -231,36 +267,63 @@ pub(crate) async fn add_user(
     }
 }
 
-pub(crate) async fn remove_user_by_tele_id(conn: &PgPool, tele_id: u64) -> Result<bool, sqlx::Error> {
+pub(crate) async fn remove_user_by_tele_id(conn: &PgPool, actor: u64, tele_id: u64) -> Result<bool, sqlx::Error> {
+    let mut tx = conn.begin().await?;
+
+    let result = sqlx::query_scalar!(
+        r#"
+        SELECT id FROM usrs
+        WHERE tele_id = $1 AND is_valid = TRUE
+        FOR UPDATE;
+        "#,
+        tele_id as i64
+    )
+        .fetch_optional(&mut *tx)
+        .await;
+
+    let id = match result {
+        Ok(Some(id)) => id,
+        Ok(None) => {
+            log::warn!("No user found with tele_id: {}", tele_id);
+            return Ok(false);
+        }
+        Err(e) => {
+            log::error!("Error soft deleting user: {}", e);
+            return Err(e);
+        }
+    };
+
+    let before = get_snapshot(&mut tx, AuditEntity::Usrs, id).await?;
     let result = sqlx::query!(
         r#"
         UPDATE usrs
         SET is_valid = FALSE
-        WHERE tele_id = $1 AND is_valid = TRUE;
+        WHERE id = $1;
         "#,
-        tele_id as i64
+        id
     )
-        .execute(conn)
+        .execute(&mut *tx)
         .await;
 
     match result {
-        Ok(query_result) => {
-            if query_result.rows_affected() == 1 {
-                log::info!("Soft deleted user with tele_id: {}", tele_id);
-                Ok(true)
-            } else {
-                log::warn!("No user found with tele_id: {}", tele_id);
-                Ok(false)
-            }
+        Ok(_) => {
+            add_audit_log(&mut tx, actor, AuditAction::Remove, AuditEntity::Usrs, id, before).await?;
+            tx.commit().await?;
+            log::info!("Soft deleted user with tele_id: {}", tele_id);
+            Ok(true)
         }
         Err(e) => {
             log::error!("Error soft deleting user: {}", e);
+            tx.rollback().await?;
             Err(e)
         }
     }
 }
 
-pub(crate) async fn remove_user_by_uuid(conn: &PgPool, id: Uuid) -> Result<bool, sqlx::Error> {
+pub(crate) async fn remove_user_by_uuid(conn: &PgPool, actor: u64, id: Uuid) -> Result<bool, sqlx::Error> {
+    let mut tx = conn.begin().await?;
+    let before = get_snapshot(&mut tx, AuditEntity::Usrs, id).await?;
+
     let result = sqlx::query!(
         r#"
         UPDATE usrs
@@ -269,12 +332,14 @@ pub(crate) async fn remove_user_by_uuid(conn: &PgPool, id: Uuid) -> Result<bool,
         "#,
         id
     )
-        .execute(conn)
+        .execute(&mut *tx)
         .await;
 
     match result {
         Ok(query_result) => {
             if query_result.rows_affected() == 1 {
+                add_audit_log(&mut tx, actor, AuditAction::Remove, AuditEntity::Usrs, id, before).await?;
+                tx.commit().await?;
                 log::info!("Successfully soft-deleted user with id: {}", id);
                 Ok(true)
             } else {
@@ -284,6 +349,7 @@ pub(crate) async fn remove_user_by_uuid(conn: &PgPool, id: Uuid) -> Result<bool,
         }
         Err(e) => {
             log::error!("Error soft deleting user by id: {}", e);
+            tx.rollback().await?;
             Err(e)
         }
     }
@@ -292,8 +358,12 @@ pub(crate) async fn remove_user_by_uuid(conn: &PgPool, id: Uuid) -> Result<bool,
 
 pub(crate) async fn update_user(
     conn: &PgPool,
+    actor: u64,
     user_details: &Usr,
 ) -> Result<Usr, sqlx::Error> {
+    let mut tx = conn.begin().await?;
+    let before = get_snapshot(&mut tx, AuditEntity::Usrs, user_details.id).await?;
+
     let result = sqlx::query_as!(
         Usr,
         r#"
@@ -320,6 +390,7 @@ pub(crate) async fn update_user(
             usr_type AS "usr_type: _",
             role_type AS "role_type: _",
             admin,
+            unit_id,
             created,
             updated
         "#,
@@ -331,11 +402,13 @@ pub(crate) async fn update_user(
         user_details.role_type.clone() as RoleType,
         user_details.admin
     )
-        .fetch_one(conn)
+        .fetch_one(&mut *tx)
         .await;
 
     match result {
         Ok(user) => {
+            add_audit_log(&mut tx, actor, AuditAction::Edit, AuditEntity::Usrs, user.id, before).await?;
+            tx.commit().await?;
             log::info!("Updated user with id: {}", user_details.id);
             Ok(user)
         }
@@ -350,16 +423,17 @@ pub(crate) async fn update_user(
     }
 }
 
-// Helper function to get and display all users
-pub(crate) async fn get_all_user_info(pool: &PgPool) -> Result<Vec<UserInfo>, sqlx::Error> {
+// Helper function to get and display all users of a unit
+pub(crate) async fn get_all_user_info(pool: &PgPool, unit_id: Uuid) -> Result<Vec<UserInfo>, sqlx::Error> {
     // Fetch ops_name, name, and tele_id from the database where is_valid is true
     let result = sqlx::query_as!(
         UserInfo,
         r#"
         SELECT ops_name, name, tele_id
         FROM usrs
-        WHERE is_valid = TRUE;
-        "#
+        WHERE is_valid = TRUE AND unit_id = $1;
+        "#,
+        unit_id
     )
         .fetch_all(pool)
         .await;
@@ -373,18 +447,55 @@ pub(crate) async fn get_all_user_info(pool: &PgPool) -> Result<Vec<UserInfo>, sq
     }
 }
 
+pub(crate) async fn get_all_users(conn: &PgPool) -> Result<Vec<Usr>, sqlx::Error> {
+    let result = sqlx::query_as!(
+        Usr,
+        r#"
+        SELECT
+            usrs.id AS id,
+            usrs.tele_id AS tele_id,
+            usrs.name AS name,
+            usrs.ops_name AS ops_name,
+            usrs.usr_type AS "usr_type: _",
+            usrs.role_type AS "role_type: _",
+            usrs.admin AS admin,
+            usrs.unit_id AS unit_id,
+            usrs.created AS created,
+            usrs.updated AS updated
+        FROM usrs
+        WHERE usrs.is_valid = TRUE
+        ORDER BY usrs.ops_name ASC;
+        "#
+    )
+        .fetch_all(conn)
+        .await;
+
+    match result {
+        Ok(users) => {
+            log::info!("Found {} users", users.len());
+            Ok(users)
+        }
+        Err(e) => {
+            log::error!("Error fetching users: {}", e);
+            Err(e)
+        }
+    }
+}
+
 pub(crate) async fn is_last_admin(conn: &PgPool, user_id: Uuid) -> Result<bool, sqlx::Error> {
     // SQL query to determine if the user is the last admin
     let result = sqlx::query!(
         r#"
         WITH user_admin AS (
-            SELECT admin
+            SELECT admin, unit_id
             FROM usrs
             WHERE id = $1 AND is_valid = TRUE
         ), other_admins AS (
+            -- Every unit needs at least one admin
             SELECT COUNT(*) AS count
             FROM usrs
             WHERE admin = TRUE AND is_valid = TRUE AND id != $1
+              AND unit_id = (SELECT unit_id FROM user_admin)
         )
         SELECT
             (user_admin.admin = TRUE) AND (other_admins.count = 0) AS is_last_admin
@@ -1,8 +1,8 @@
 use std::sync::Arc;
 use std::time::Duration;
-use sqlx::{Error, PgPool};
-use sqlx::postgres::PgQueryResult;
-use crate::AppState;
+use sqlx::PgPool;
+use crate::{controllers, AppState, DIALOGUE_TTL_SECS};
+use crate::healthcheck::metrics::METRICS;
 
 pub(crate) async fn start_audit_task(state: Arc<AppState>) -> Result<(), sqlx::Error> {
     loop {
@@ -67,6 +67,7 @@ async fn audit_data(conn: &PgPool) -> Result<(), sqlx::Error> {
         match result {
             Ok(_) => {
                 log::debug!("Notification ID {} invalidated", record.id);
+                METRICS.record_audit_invalidation();
             }
             Err(e) => {
                 log::error!("Failed to mark notification ID {} as invalid: {:?}", record.id, e);
@@ -74,5 +75,8 @@ async fn audit_data(conn: &PgPool) -> Result<(), sqlx::Error> {
         }
     }
 
+    // Clear out dialogues which have been inactive for longer than the TTL
+    controllers::dialogue::remove_expired_dialogues(conn, *DIALOGUE_TTL_SECS).await?;
+
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,130 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use axum::extract::Extension;
+use axum::http::{header, StatusCode};
+use axum::response::IntoResponse;
+use once_cell::sync::Lazy;
+use sqlx::PgPool;
+
+use crate::AppState;
+
+// Counters collected over the lifetime of the process, exposed in Prometheus text format
+pub(crate) struct Metrics {
+    endpoint_hits: Mutex<BTreeMap<String, u64>>,
+    notifications: Mutex<BTreeMap<(String, &'static str), u64>>,
+    audit_invalidations: AtomicU64,
+}
+
+pub(crate) static METRICS: Lazy<Metrics> = Lazy::new(|| Metrics {
+    endpoint_hits: Mutex::new(BTreeMap::new()),
+    notifications: Mutex::new(BTreeMap::new()),
+    audit_invalidations: AtomicU64::new(0),
+});
+
+impl Metrics {
+    pub(crate) fn record_endpoint_hit(&self, endpoint: &str) {
+        if let Ok(mut endpoint_hits) = self.endpoint_hits.lock() {
+            *endpoint_hits.entry(endpoint.to_string()).or_insert(0) += 1;
+        }
+    }
+
+    pub(crate) fn record_notification(&self, notification_type: &str, sent: bool) {
+        let status = if sent { "sent" } else { "failed" };
+        if let Ok(mut notifications) = self.notifications.lock() {
+            *notifications.entry((notification_type.to_string(), status)).or_insert(0) += 1;
+        }
+    }
+
+    pub(crate) fn record_audit_invalidation(&self) {
+        self.audit_invalidations.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+struct QueueDepth {
+    pending: i64,
+    failed: i64,
+}
+
+async fn get_queue_depth(conn: &PgPool) -> Result<QueueDepth, sqlx::Error> {
+    let result = sqlx::query_as!(
+        QueueDepth,
+        r#"
+        SELECT
+            COUNT(*) FILTER (WHERE sent = FALSE AND failed = FALSE) AS "pending!",
+            COUNT(*) FILTER (WHERE failed = TRUE) AS "failed!"
+        FROM scheduled_notifications
+        WHERE is_valid = TRUE;
+        "#
+    )
+        .fetch_one(conn)
+        .await;
+
+    if let Err(e) = &result {
+        log::error!("Error fetching scheduled notification queue depth: {}", e);
+    }
+
+    result
+}
+
+fn write_metric_header(output: &mut String, name: &str, help: &str, metric_type: &str) {
+    let _ = writeln!(output, "# HELP {} {}", name, help);
+    let _ = writeln!(output, "# TYPE {} {}", name, metric_type);
+}
+
+fn escape_label_value(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+async fn render_metrics(state: &AppState) -> String {
+    let mut output = String::new();
+
+    write_metric_header(&mut output, "bot_endpoint_hits_total", "Number of bot handler invocations per endpoint.", "counter");
+    if let Ok(endpoint_hits) = METRICS.endpoint_hits.lock() {
+        for (endpoint, count) in endpoint_hits.iter() {
+            let _ = writeln!(output, "bot_endpoint_hits_total{{endpoint=\"{}\"}} {}", escape_label_value(endpoint), count);
+        }
+    }
+
+    write_metric_header(&mut output, "notifications_total", "Number of notifications sent or failed per type.", "counter");
+    if let Ok(notifications) = METRICS.notifications.lock() {
+        for ((notification_type, status), count) in notifications.iter() {
+            let _ = writeln!(
+                output,
+                "notifications_total{{type=\"{}\",status=\"{}\"}} {}",
+                escape_label_value(notification_type),
+                status,
+                count
+            );
+        }
+    }
+
+    write_metric_header(&mut output, "audit_invalidations_total", "Number of scheduled notifications invalidated by the audit task.", "counter");
+    let _ = writeln!(output, "audit_invalidations_total {}", METRICS.audit_invalidations.load(Ordering::Relaxed));
+
+    if let Ok(queue_depth) = get_queue_depth(&state.db_pool).await {
+        write_metric_header(&mut output, "scheduled_notifications_pending", "Number of scheduled notifications waiting to be sent.", "gauge");
+        let _ = writeln!(output, "scheduled_notifications_pending {}", queue_depth.pending);
+        write_metric_header(&mut output, "scheduled_notifications_failed", "Number of scheduled notifications which failed delivery.", "gauge");
+        let _ = writeln!(output, "scheduled_notifications_failed {}", queue_depth.failed);
+    }
+
+    write_metric_header(&mut output, "db_pool_connections", "Number of database connections in the pool.", "gauge");
+    let _ = writeln!(output, "db_pool_connections{{state=\"total\"}} {}", state.db_pool.size());
+    let _ = writeln!(output, "db_pool_connections{{state=\"idle\"}} {}", state.db_pool.num_idle());
+    let _ = writeln!(output, "db_pool_connections{{state=\"max\"}} {}", state.db_pool.options().get_max_connections());
+
+    output
+}
+
+pub(crate) async fn metrics_handler(
+    Extension(state): Extension<Arc<AppState>>,
+) -> impl IntoResponse {
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        render_metrics(&state).await
+    )
+}
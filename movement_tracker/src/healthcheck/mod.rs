@@ -1,4 +1,5 @@
 pub(crate) mod monitor;
 pub mod audit;
 pub(crate) mod handler;
-pub(crate) mod bot;
\ No newline at end of file
+pub(crate) mod bot;
+pub(crate) mod metrics;
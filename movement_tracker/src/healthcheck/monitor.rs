@@ -145,7 +145,7 @@ async fn send_health_notification(
         bot_emoji, status.bot
     );
     
-    notifier::emit::system_notifications(bot, message.as_str(), pool, 0).await;
+    notifier::emit::system_notifications(bot, message.as_str(), pool, None, 0).await;
 
     Ok(())
 }
\ No newline at end of file
@@ -4,6 +4,7 @@ pub(crate) mod types;
 mod utils;
 mod notifier;
 mod healthcheck;
+mod api;
 
 use std::env;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
@@ -16,6 +17,7 @@ use axum::routing::get;
 use tower_http::{trace::TraceLayer};
 use tower::ServiceBuilder;
 
+use chrono::NaiveTime;
 use chrono_tz::{Tz, TZ_VARIANTS};
 use once_cell::sync::Lazy;
 use sqlx::PgPool;
@@ -39,6 +41,10 @@ struct AppState {
 }
 
 pub(crate) static APP_TIMEZONE: Lazy<Tz> = Lazy::new(get_timezone);
+pub(crate) static DIALOGUE_TTL_SECS: Lazy<i64> = Lazy::new(get_dialogue_ttl);
+pub(crate) static PLAN_CAPACITY: Lazy<Option<i64>> = Lazy::new(get_plan_capacity);
+pub(crate) static SAF100_LEAD_DAYS: Lazy<i64> = Lazy::new(get_saf100_lead_days);
+pub(crate) static DIGEST_TIME: Lazy<NaiveTime> = Lazy::new(get_digest_time);
 #[macro_export]
 macro_rules! now {
     () => {{
@@ -131,6 +137,20 @@ async fn main() {
         }
     });
 
+    // Spawn the Crew Digest Task
+    let digest_app_state = app_state.clone();
+    let digest_bot = bot.clone();
+    tokio::spawn(async move {
+        notifier::digest::start_digest(digest_bot, digest_app_state).await;
+    });
+
+    // Spawn the Campaign Nudge Task
+    let campaign_app_state = app_state.clone();
+    let campaign_bot = bot.clone();
+    tokio::spawn(async move {
+        notifier::campaign::start_campaign_nudges(campaign_bot, campaign_app_state).await;
+    });
+
     // Spawn the Audit Task
     tokio::spawn(async move {
         if let Err(e) = healthcheck::audit::start_audit_task(audit_app_state.clone()).await {
@@ -149,6 +169,8 @@ async fn main() {
     // Start Health Check Server on Port 8080
     let health_route = Router::new()
         .route("/health", get(healthcheck::handler::health_check_handler))
+        .route("/metrics", get(healthcheck::metrics::metrics_handler))
+        .nest("/api", api::router())
         .layer(
             ServiceBuilder::new()
                 .layer(TraceLayer::new_for_http())
@@ -228,6 +250,93 @@ fn get_timezone() -> Tz {
     chrono_tz::UTC
 }
 
+fn get_dialogue_ttl() -> i64 {
+    // Default to expiring dialogues after a day of inactivity
+    const DEFAULT_TTL_MINUTES: i64 = 24 * 60;
+
+    match env::var("DIALOGUE_TTL_MINUTES") {
+        Ok(ttl_env) => match ttl_env.parse::<i64>() {
+            Ok(ttl) if ttl > 0 => {
+                log::info!("Using dialogue TTL of {} minutes", ttl);
+                ttl * 60
+            }
+            _ => {
+                log::error!("Invalid DIALOGUE_TTL_MINUTES value: {}. Falling back to {} minutes.", ttl_env, DEFAULT_TTL_MINUTES);
+                DEFAULT_TTL_MINUTES * 60
+            }
+        },
+        Err(_) => {
+            log::info!("DIALOGUE_TTL_MINUTES is not set. Using default of {} minutes.", DEFAULT_TTL_MINUTES);
+            DEFAULT_TTL_MINUTES * 60
+        }
+    }
+}
+
+fn get_plan_capacity() -> Option<i64> {
+    // No limit on how many crew of a role can be planned on a date unless set, docker compose passes it empty when unset
+    match env::var("PLAN_CAPACITY").ok().filter(|capacity_env| !capacity_env.is_empty()) {
+        Some(capacity_env) => match capacity_env.parse::<i64>() {
+            Ok(capacity) if capacity > 0 => {
+                log::info!("Warning when more than {} crew of a role are planned on a date", capacity);
+                Some(capacity)
+            }
+            _ => {
+                log::error!("Invalid PLAN_CAPACITY value: {}. Not limiting planned crew.", capacity_env);
+                None
+            }
+        },
+        None => {
+            log::info!("PLAN_CAPACITY is not set. Not limiting planned crew.");
+            None
+        }
+    }
+}
+
+fn get_saf100_lead_days() -> i64 {
+    // Default to needing a week to issue SAF100 for NS members
+    const DEFAULT_LEAD_DAYS: i64 = 7;
+
+    match env::var("SAF100_LEAD_DAYS") {
+        Ok(lead_env) => match lead_env.parse::<i64>() {
+            Ok(lead) if lead >= 0 => {
+                log::info!("Using SAF100 lead time of {} days", lead);
+                lead
+            }
+            _ => {
+                log::error!("Invalid SAF100_LEAD_DAYS value: {}. Falling back to {} days.", lead_env, DEFAULT_LEAD_DAYS);
+                DEFAULT_LEAD_DAYS
+            }
+        },
+        Err(_) => {
+            log::info!("SAF100_LEAD_DAYS is not set. Using default of {} days.", DEFAULT_LEAD_DAYS);
+            DEFAULT_LEAD_DAYS
+        }
+    }
+}
+
+fn get_digest_time() -> NaiveTime {
+    // Default to posting the next day's crew in the evening
+    const DEFAULT_DIGEST_TIME: &str = "18:00";
+    let default_time = NaiveTime::parse_from_str(DEFAULT_DIGEST_TIME, "%H:%M").expect("Invalid default digest time");
+
+    match env::var("DIGEST_TIME") {
+        Ok(time_env) => match NaiveTime::parse_from_str(&time_env, "%H:%M") {
+            Ok(time) => {
+                log::info!("Posting the daily crew digest at {}", time.format("%H:%M"));
+                time
+            }
+            Err(_) => {
+                log::error!("Invalid DIGEST_TIME value: {}. Falling back to {}.", time_env, DEFAULT_DIGEST_TIME);
+                default_time
+            }
+        },
+        Err(_) => {
+            log::info!("DIGEST_TIME is not set. Using default of {}.", DEFAULT_DIGEST_TIME);
+            default_time
+        }
+    }
+}
+
 pub(crate) async fn add_default_user_from_env(conn: &PgPool) -> Result<(), sqlx::Error> {
     // Check if DEFAULT_TELEGRAM_ID is set
     let tele_id_env = match env::var("DEFAULT_TELEGRAM_ID") {
@@ -253,10 +362,13 @@ pub(crate) async fn add_default_user_from_env(conn: &PgPool) -> Result<(), sqlx:
     let ops_name = env::var("DEFAULT_OPS_NAME").unwrap_or_else(|_| "default_ops_name".to_string());
 
     // Assume default details
-    let role_type = RoleType::PILOT; // Adjust as needed
-    let usr_type = UsrType::ACTIVE;  // Set usr_type to Active
+    let role_type = RoleType::new("PILOT"); // Adjust as needed
+    let usr_type = UsrType::new("ACTIVE");  // Set usr_type to Active
     let admin = true;           // Set admin to true
 
+    // The default user belongs to the default unit
+    let unit = controllers::units::get_default_unit(conn).await?;
+
     // Check if the user already exists
     match controllers::user::user_exists_tele_id(conn, tele_id).await {
         Ok(exists) => {
@@ -268,22 +380,26 @@ pub(crate) async fn add_default_user_from_env(conn: &PgPool) -> Result<(), sqlx:
                 match controllers::user::add_user(
                     conn,
                     tele_id,
+                    tele_id,
                     name,
                     ops_name,
                     role_type,
                     usr_type,
                     admin,
+                    unit.id,
                 ).await {
                     Ok(user) => {
                         // Set default notification settings
                         match controllers::notifications::update_notification_settings(
-                            &conn,
+                            conn,
                             user.tele_id, // Assuming chat_id == tele_id
+                            user.unit_id,
                             Some(true),  // notif_system
                             Some(true),  // notif_register
                             None,        // notif_availability
                             Some(true),  // notif_plan
                             Some(true),  // notif_conflict
+                            None,        // notif_digest
                         ).await {
                             Ok(_) => {
                                 log::info!("Default notification settings configured for default user {}", user.ops_name);
@@ -0,0 +1,75 @@
+use std::sync::Arc;
+use std::time::Duration;
+use sqlx::PgPool;
+use teloxide::prelude::*;
+use crate::types::{Campaign, Usr};
+use crate::{controllers, AppState};
+use crate::healthcheck::metrics::METRICS;
+
+pub(crate) async fn start_campaign_nudges(bot: Bot, state: Arc<AppState>) {
+    loop {
+        tokio::time::sleep(Duration::from_secs(3600)).await;
+
+        if let Err(e) = send_campaign_nudges(&state.db_pool, &bot).await {
+            log::error!("Failed to send campaign nudges: {}", e);
+        }
+    }
+}
+
+// Nudges the recipients of each due campaign who have yet to indicate their availability
+pub(crate) async fn send_campaign_nudges(conn: &PgPool, bot: &Bot) -> Result<(), sqlx::Error> {
+    for campaign in controllers::campaigns::get_campaigns_due_nudge(conn).await? {
+        let pending: Vec<Usr> = controllers::campaigns::get_campaign_responses(conn, &campaign)
+            .await?
+            .into_iter()
+            .filter(|(_, responded)| !responded)
+            .map(|(user, _)| user)
+            .collect();
+        if pending.is_empty() {
+            continue;
+        }
+
+        // Skip the campaign if a planner nudged its users in the meantime
+        if controllers::campaigns::claim_campaign_nudge(conn, campaign.id).await? {
+            send_campaign_requests(bot, &campaign, &pending, true).await;
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) fn format_campaign_window(campaign: &Campaign) -> String {
+    format!("{} to {}", campaign.start_date.format("%d %b %Y"), campaign.end_date.format("%d %b %Y"))
+}
+
+// Asks each user to indicate their availability for the campaign, returning how many were reached
+pub(crate) async fn send_campaign_requests(bot: &Bot, campaign: &Campaign, users: &[Usr], nudge: bool) -> usize {
+    let message_text = if nudge {
+        format!(
+            "Reminder: the planners have yet to receive your availability from {}. Please indicate it with /availability.",
+            format_campaign_window(campaign)
+        )
+    } else {
+        format!(
+            "The planners are requesting your availability from {}. Please indicate it with /availability.",
+            format_campaign_window(campaign)
+        )
+    };
+
+    let mut sent = 0;
+    for user in users {
+        match bot.send_message(ChatId(user.tele_id), &message_text).await {
+            Ok(_) => {
+                METRICS.record_notification("CAMPAIGN", true);
+                sent += 1;
+            }
+            Err(e) => {
+                log::error!("Error sending campaign request to user {}: {}", user.ops_name, e);
+                METRICS.record_notification("CAMPAIGN", false);
+            }
+        }
+    }
+
+    log::info!("Sent {} of {} requests for campaign ({})", sent, users.len(), campaign.id);
+    sent
+}
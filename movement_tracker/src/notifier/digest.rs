@@ -0,0 +1,116 @@
+use std::sync::Arc;
+use chrono::{Duration, NaiveDate, Utc};
+use sqlx::PgPool;
+use strum::IntoEnumIterator;
+use teloxide::prelude::*;
+use crate::types::{Ack, AvailabilityDetails, Ict, RoleType};
+use crate::{controllers, notifier, now, utils, AppState, APP_TIMEZONE, DIGEST_TIME};
+
+// Planned crew of each role on a date
+type CrewByRole = Vec<(RoleType, Vec<AvailabilityDetails>)>;
+
+pub(crate) async fn start_digest(bot: Bot, state: Arc<AppState>) {
+    loop {
+        // Wait until the digest time, today if it has not passed yet
+        let current = now!().naive_local();
+        let mut next_run = current.date().and_time(*DIGEST_TIME);
+        if next_run <= current {
+            next_run += Duration::days(1);
+        }
+        tokio::time::sleep((next_run - current).to_std().unwrap_or_default()).await;
+
+        let tomorrow = now!().date_naive() + Duration::days(1);
+        match send_crew_digests(&state.db_pool, &bot, tomorrow).await {
+            Ok(_) => log::info!("Sent the crew digest for {}", tomorrow),
+            Err(e) => log::error!("Failed to send the crew digest for {}: {}", tomorrow, e)
+        }
+    }
+}
+
+// Posts the crew planned on the date to the chats of each unit which opted in
+pub(crate) async fn send_crew_digests(conn: &PgPool, bot: &Bot, date: NaiveDate) -> Result<(), sqlx::Error> {
+    let units = controllers::units::get_units(conn).await?;
+    let role_types = controllers::crew_types::get_role_types(conn).await?;
+
+    for unit in units {
+        let mut crew: CrewByRole = Vec::new();
+        for role_type in &role_types {
+            let planned: Vec<AvailabilityDetails> = controllers::scheduling::get_users_available_by_role_on_date(conn, unit.id, &date, role_type)
+                .await?
+                .into_iter()
+                .filter(|entry| entry.planned && entry.is_valid)
+                .collect();
+            if !planned.is_empty() {
+                crew.push((role_type.clone(), planned));
+            }
+        }
+
+        notifier::emit::digest_notifications(bot, &format_crew_digest(date, &crew), conn, Some(unit.id)).await;
+    }
+
+    Ok(())
+}
+
+// Response of the owner to the reminder for the entry, so that crew who declined stand out
+fn get_ack_text(entry: &AvailabilityDetails) -> &'static str {
+    match entry.ack {
+        Some(Ack::Acknowledged) => " ✅ ACKNOWLEDGED",
+        Some(Ack::Declined) => " ❌ *DECLINED*",
+        None => ""
+    }
+}
+
+// Lists the crew grouped by role and then ICT type, each user once with their slots and responses, with the SAF100 status of NS members
+fn format_crew_digest(date: NaiveDate, crew: &CrewByRole) -> String {
+    let mut message = format!("*Crew planned for {}*\n", utils::escape_special_characters(&date.format("%a %d %b %Y").to_string()));
+
+    if crew.is_empty() {
+        message.push_str("\nNo crew planned\\.");
+        return message;
+    }
+
+    for (role_type, entries) in crew {
+        message.push_str(&format!("\n*{}*\n", utils::escape_special_characters(role_type.as_ref())));
+        for ict_type in Ict::iter() {
+            let ict_entries: Vec<&AvailabilityDetails> = entries.iter().filter(|entry| entry.ict_type == ict_type).collect();
+            if ict_entries.is_empty() {
+                continue;
+            }
+
+            message.push_str(&format!("__{}__\n", ict_type.as_ref()));
+            // List each user once with all of their slots
+            let mut users: Vec<(&str, Vec<&AvailabilityDetails>)> = Vec::new();
+            for entry in ict_entries {
+                match users.iter_mut().find(|(ops_name, _)| *ops_name == entry.ops_name) {
+                    Some((_, slots)) => slots.push(entry),
+                    None => users.push((&entry.ops_name, vec![entry]))
+                }
+            }
+
+            for (ops_name, slots) in users {
+                let is_ns = slots[0].usr_type.is_ns();
+                let saf100_str = if !is_ns { "" }
+                else if slots.iter().all(|entry| entry.saf100) { " SAF100 ISSUED" }
+                else { " *PENDING SAF100*" };
+                let slots_str = slots.iter()
+                    .map(|entry| format!(
+                        "{}{}",
+                        utils::escape_special_characters(&utils::format_slot_suffix(entry.start_time, entry.end_time)),
+                        get_ack_text(entry)
+                    ))
+                    .filter(|slot| !slot.is_empty())
+                    .collect::<Vec<String>>()
+                    .join(",");
+                message.push_str(&format!(
+                    "\\- `{}`{}{}{}\n",
+                    utils::escape_special_characters(ops_name),
+                    if is_ns { " \\(NS\\)" } else { "" },
+                    slots_str,
+                    saf100_str
+                ));
+            }
+        }
+    }
+
+    message
+}
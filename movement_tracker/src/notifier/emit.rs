@@ -1,7 +1,9 @@
+use sqlx::types::Uuid;
 use sqlx::PgPool;
 use teloxide::prelude::*;
 use teloxide::types::ParseMode;
 use crate::controllers;
+use crate::healthcheck::metrics::METRICS;
 
 async fn send_helper(bot: &Bot, chats_to_send: Vec<i64>, message: &str, originator_id: Option<i64>, type_str: &str) {
     for chat in chats_to_send {
@@ -17,14 +19,16 @@ async fn send_helper(bot: &Bot, chats_to_send: Vec<i64>, message: &str, originat
 
         if let Err(e) = bot.send_message(chat_id.clone(), message).parse_mode(ParseMode::MarkdownV2).await {
             log::error!("Failed to send {} notification to chat_id ({}): {:?}", type_str, chat, e);
+            METRICS.record_notification(type_str, false);
         } else {
             log::info!("Successfully sent {} notification to chat_id ({}).", type_str, chat);
+            METRICS.record_notification(type_str, true);
         }
     }
 }
 
-pub(crate) async fn system_notifications(bot: &Bot, message: &str, pool: &PgPool, originator_id: i64) {
-    match controllers::notifications::get_system_notifications_enabled(pool).await {
+pub(crate) async fn system_notifications(bot: &Bot, message: &str, pool: &PgPool, unit_id: Option<Uuid>, originator_id: i64) {
+    match controllers::notifications::get_system_notifications_enabled(pool, unit_id).await {
         Ok(chats) => {
             if chats.is_empty() {
                 log::info!("No system notifications enabled for any chat.");
@@ -40,8 +44,8 @@ pub(crate) async fn system_notifications(bot: &Bot, message: &str, pool: &PgPool
     }
 }
 
-pub(crate) async fn register_notifications(bot: &Bot, message: &str, pool: &PgPool) {
-    match controllers::notifications::get_register_notifications_enabled(pool).await {
+pub(crate) async fn register_notifications(bot: &Bot, message: &str, pool: &PgPool, unit_id: Option<Uuid>) {
+    match controllers::notifications::get_register_notifications_enabled(pool, unit_id).await {
         Ok(chats) => {
             if chats.is_empty() {
                 log::info!("No register notifications enabled for any chat.");
@@ -57,8 +61,8 @@ pub(crate) async fn register_notifications(bot: &Bot, message: &str, pool: &PgPo
     }
 }
 
-pub(crate) async fn availability_notifications(bot: &Bot, message: &str, pool: &PgPool, originator_id: i64) {
-    match controllers::notifications::get_availability_notifications_enabled(pool).await {
+pub(crate) async fn availability_notifications(bot: &Bot, message: &str, pool: &PgPool, unit_id: Option<Uuid>, originator_id: i64) {
+    match controllers::notifications::get_availability_notifications_enabled(pool, unit_id).await {
         Ok(chats) => {
             if chats.is_empty() {
                 log::info!("No availability notifications enabled for any chat.");
@@ -74,8 +78,8 @@ pub(crate) async fn availability_notifications(bot: &Bot, message: &str, pool: &
     }
 }
 
-pub(crate) async fn plan_notifications(bot: &Bot, message: &str, pool: &PgPool, originator_id: i64) {
-    match controllers::notifications::get_plan_notifications_enabled(pool).await {
+pub(crate) async fn plan_notifications(bot: &Bot, message: &str, pool: &PgPool, unit_id: Option<Uuid>, originator_id: i64) {
+    match controllers::notifications::get_plan_notifications_enabled(pool, unit_id).await {
         Ok(chats) => {
             if chats.is_empty() {
                 log::info!("No plan notifications enabled for any chat.");
@@ -91,8 +95,8 @@ pub(crate) async fn plan_notifications(bot: &Bot, message: &str, pool: &PgPool,
     }
 }
 
-pub(crate) async fn conflict_notifications(bot: &Bot, message: &str, pool: &PgPool) {
-    match controllers::notifications::get_conflict_notifications_enabled(pool).await {
+pub(crate) async fn conflict_notifications(bot: &Bot, message: &str, pool: &PgPool, unit_id: Option<Uuid>) {
+    match controllers::notifications::get_conflict_notifications_enabled(pool, unit_id).await {
         Ok(chats) => {
             if chats.is_empty() {
                 log::info!("No conflict notifications enabled for any chat.");
@@ -106,4 +110,21 @@ pub(crate) async fn conflict_notifications(bot: &Bot, message: &str, pool: &PgPo
             log::error!("Failed to retrieve conflict notification settings: {:?}", e);
         }
     }
-}
\ No newline at end of file
+}
+
+pub(crate) async fn digest_notifications(bot: &Bot, message: &str, pool: &PgPool, unit_id: Option<Uuid>) {
+    match controllers::notifications::get_digest_notifications_enabled(pool, unit_id).await {
+        Ok(chats) => {
+            if chats.is_empty() {
+                log::info!("No digest notifications enabled for any chat.");
+                return;
+            }
+
+            log::info!("Sending digest notifications to {} chats. Message: {:?}", chats.len(), message);
+            send_helper(bot, chats, message, None, "DIGEST").await;
+        }
+        Err(e) => {
+            log::error!("Failed to retrieve digest notification settings: {:?}", e);
+        }
+    }
+}
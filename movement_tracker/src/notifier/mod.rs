@@ -1,2 +1,4 @@
 pub(crate) mod scheduled;
-pub(crate) mod emit;
\ No newline at end of file
+pub(crate) mod emit;
+pub(crate) mod digest;
+pub(crate) mod campaign;
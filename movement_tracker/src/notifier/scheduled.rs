@@ -1,9 +1,33 @@
+use std::collections::BTreeMap;
 use std::sync::Arc;
-use crate::types::{Availability, ScheduledNotifications, Usr, UsrType};
+use crate::types::{Availability, ScheduledNotifications, Usr};
+use sqlx::types::Uuid;
 use sqlx::PgPool;
 use std::time::Duration;
 use teloxide::prelude::*;
-use crate::{utils, AppState};
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup};
+use teloxide::{ApiError, RequestError};
+use crate::{notifier, utils, AppState};
+use crate::healthcheck::metrics::METRICS;
+
+use serde::{Serialize, Deserialize};
+use strum::EnumProperty;
+use callback_data::CallbackData;
+use callback_data::CallbackDataHandler;
+
+// Number of delivery attempts before a notification is marked as failed
+const MAX_DELIVERY_ATTEMPTS: i32 = 5;
+// Delay before the first retry, doubled on every subsequent attempt
+const RETRY_BASE_DELAY_SECS: i64 = 60;
+// Reminders are answered outside of any dialogue, so their buttons use a fixed prefix
+pub(crate) const REMINDER_CALLBACK_PREFIX: &str = "REMINDER";
+
+// Responses to the reminder for a planned or standby entry
+#[derive(Debug, Clone, Serialize, Deserialize, EnumProperty, CallbackData)]
+pub(crate) enum ReminderCallbacks {
+    Acknowledge { id: Uuid },
+    Decline { id: Uuid },
+}
 
 pub(crate) async fn start_notifier(bot: Bot, state: Arc<AppState>) -> Result<(), sqlx::Error> {
     loop {
@@ -28,9 +52,11 @@ pub(crate) async fn start_notifier(bot: Bot, state: Arc<AppState>) -> Result<(),
     }
 }
 
-async fn process_scheduled_notifications(conn: &PgPool, bot: &Bot) -> Result<(), sqlx::Error> {
+pub(crate) async fn process_scheduled_notifications(conn: &PgPool, bot: &Bot) -> Result<(), sqlx::Error> {
     // Start a transaction
     let mut tx = conn.begin().await?;
+    // Failed deliveries grouped by the unit of the user they were meant for
+    let mut failed_deliveries: BTreeMap<Uuid, Vec<String>> = BTreeMap::new();
 
     // Wrap the entire logic in a block that ensures rollback on error
     match async {
@@ -43,12 +69,18 @@ async fn process_scheduled_notifications(conn: &PgPool, bot: &Bot) -> Result<(),
                 sn.avail_id,
                 sn.scheduled_time,
                 sn.sent,
+                sn.attempts,
+                sn.last_error,
+                sn.next_retry,
+                sn.failed,
                 sn.created,
                 sn.updated,
                 sn.is_valid
             FROM scheduled_notifications sn
             WHERE sn.scheduled_time <= NOW()
+              AND (sn.next_retry IS NULL OR sn.next_retry <= NOW())
               AND sn.sent = FALSE
+              AND sn.failed = FALSE
               AND sn.is_valid = TRUE
             FOR UPDATE SKIP LOCKED;
             "#
@@ -74,8 +106,12 @@ async fn process_scheduled_notifications(conn: &PgPool, bot: &Bot) -> Result<(),
                     a.usr_id AS user_id,
                     a.avail,
                     a.ict_type AS "ict_type: _",
+                    a.start_time,
+                    a.end_time,
                     a.remarks,
                     a.planned,
+                    a.standby,
+                    a.ack AS "ack: _",
                     a.saf100,
                     a.attended,
                     a.is_valid,
@@ -112,6 +148,7 @@ async fn process_scheduled_notifications(conn: &PgPool, bot: &Bot) -> Result<(),
                     u.usr_type AS "usr_type: _",
                     u.role_type AS "role_type: _",
                     u.admin,
+                    u.unit_id,
                     u.created,
                     u.updated
                 FROM usrs u
@@ -146,23 +183,66 @@ async fn process_scheduled_notifications(conn: &PgPool, bot: &Bot) -> Result<(),
             let chat_id = ChatId(user.tele_id);
 
             // Send the formatted message to the user with MarkdownV2 parsing
-            if let Err(e) = bot.send_message(chat_id, message_text)
+            match bot.send_message(chat_id, message_text)
                 .parse_mode(teloxide::types::ParseMode::MarkdownV2)
+                .reply_markup(get_reminder_keyboard(availability.id))
                 .await {
-                log::error!("Error sending message to user {}: {}", user.ops_name, e);
-            }
+                Ok(_) => {
+                    METRICS.record_notification("SCHEDULED", true);
 
-            // Mark the notification as sent
-            sqlx::query!(
-                r#"
-                UPDATE scheduled_notifications
-                SET sent = TRUE
-                WHERE id = $1;
-                "#,
-                notification.id
-            )
-                .execute(&mut *tx)
-                .await?;
+                    // Mark the notification as sent
+                    sqlx::query!(
+                        r#"
+                        UPDATE scheduled_notifications
+                        SET sent = TRUE, attempts = attempts + 1
+                        WHERE id = $1;
+                        "#,
+                        notification.id
+                    )
+                        .execute(&mut *tx)
+                        .await?;
+                }
+                Err(e) => {
+                    log::error!("Error sending message to user {}: {}", user.ops_name, e);
+                    METRICS.record_notification("SCHEDULED", false);
+
+                    let attempts = notification.attempts + 1;
+                    let failed = attempts >= MAX_DELIVERY_ATTEMPTS || is_permanent_error(&e);
+                    // Exponential backoff between attempts
+                    let retry_delay_secs = RETRY_BASE_DELAY_SECS * 2_i64.pow((attempts - 1).max(0) as u32);
+
+                    sqlx::query!(
+                        r#"
+                        UPDATE scheduled_notifications
+                        SET attempts = $2,
+                            last_error = $3,
+                            failed = $4,
+                            next_retry = CASE WHEN $4 THEN NULL ELSE NOW() + ($5::BIGINT * INTERVAL '1 second') END
+                        WHERE id = $1;
+                        "#,
+                        notification.id,
+                        attempts,
+                        e.to_string(),
+                        failed,
+                        retry_delay_secs
+                    )
+                        .execute(&mut *tx)
+                        .await?;
+
+                    if failed {
+                        log::warn!("Notification ID {} failed after {} attempts", notification.id, attempts);
+                        failed_deliveries.entry(user.unit_id).or_default().push(format!(
+                            "`{}` for {} after {} attempts: {}",
+                            user.ops_name,
+                            utils::escape_special_characters(&availability.avail.format("%Y-%m-%d").to_string()),
+                            attempts,
+                            utils::escape_special_characters(&e.to_string())
+                        ));
+                    } else {
+                        log::info!("Notification ID {} will be retried in {} seconds", notification.id, retry_delay_secs);
+                    }
+                }
+            }
         }
 
         Ok(())
@@ -170,6 +250,20 @@ async fn process_scheduled_notifications(conn: &PgPool, bot: &Bot) -> Result<(),
         Ok(_) => {
             // If the block succeeds, commit the transaction
             tx.commit().await?;
+
+            // Surface notifications which could not be delivered
+            for (unit_id, unit_failed_deliveries) in failed_deliveries {
+                notifier::emit::system_notifications(
+                    bot,
+                    format!(
+                        "Failed to deliver scheduled reminders:\n{}",
+                        unit_failed_deliveries.join("\n")
+                    ).as_str(),
+                    conn,
+                    Some(unit_id),
+                    0
+                ).await;
+            }
             Ok(())
         }
         Err(e) => {
@@ -181,6 +275,27 @@ async fn process_scheduled_notifications(conn: &PgPool, bot: &Bot) -> Result<(),
     }
 }
 
+// Errors for which retrying delivery will not help
+fn is_permanent_error(error: &RequestError) -> bool {
+    matches!(
+        error,
+        RequestError::Api(ApiError::BotBlocked)
+            | RequestError::Api(ApiError::ChatNotFound)
+            | RequestError::Api(ApiError::UserDeactivated)
+            | RequestError::Api(ApiError::CantInitiateConversation)
+    )
+}
+
+fn get_reminder_keyboard(avail_id: Uuid) -> InlineKeyboardMarkup {
+    let options = [
+        ("ACKNOWLEDGE", ReminderCallbacks::Acknowledge { id: avail_id }),
+        ("CAN'T MAKE IT", ReminderCallbacks::Decline { id: avail_id })
+    ]
+        .map(|(text, data)| InlineKeyboardButton::callback(text, data.to_callback_data(REMINDER_CALLBACK_PREFIX)));
+
+    InlineKeyboardMarkup::new([options])
+}
+
 /// Formats a detailed notification message with proper MarkdownV2 escaping
 fn format_detailed_notification(availability: &Availability, user: &Usr) -> Result<String, String> {
     // Escape special characters to prevent Markdown parsing issues
@@ -201,34 +316,42 @@ fn format_detailed_notification(availability: &Availability, user: &Usr) -> Resu
     };
 
     // Determine SAF100 status based on user type and flags
-    let saf100_str = match user.usr_type {
-        UsrType::NS => {
-            if availability.saf100 {
-                "*SAF100 Issued*".to_string()
-            } else if availability.planned {
-                "*SAF100 Pending*".to_string()
-            } else {
-                "".to_string()
-            }
-        },
-        _ => "".to_string(),
+    let saf100_str = if !user.usr_type.is_ns() {
+        "".to_string()
+    } else if availability.saf100 {
+        "*SAF100 Issued*".to_string()
+    } else if availability.planned {
+        "*SAF100 Pending*".to_string()
+    } else {
+        "".to_string()
+    };
+
+    // Standby crew are only needed if a planned member withdraws
+    let title_str = if availability.standby {
+        "*Reminder: You are on Standby*\n_You will be told if you are needed_"
+    } else {
+        "*Reminder: Upcoming Planned Event*"
     };
 
     // Compile the message with MarkdownV2 formatting
     let mut message = format!(
-        "*Reminder: Upcoming Planned Event*\n\n\
+        "{}\n\n\
          *Date:* {}\n\
          *Type:* {}\n\
          *Remarks:* {}\n",
-        date_str, ict_type_str, remarks_str
+        title_str, date_str, ict_type_str, remarks_str
     );
 
+    if let (Some(start), Some(end)) = (availability.start_time, availability.end_time) {
+        message.push_str(&format!("*Slot:* {}\n", utils::escape_special_characters(&utils::format_slot(start, end))));
+    }
+
     if !saf100_str.is_empty() {
         message.push_str(&format!("{}\n", saf100_str));
     }
 
     // Add any additional information if necessary
-    message.push_str("\nPlease wait for the flight schedule to be sent\\.");
+    message.push_str("\nPlease wait for the flight schedule to be sent\\.\nLet the planners know if you can make it\\.");
 
     Ok(message)
 }
\ No newline at end of file
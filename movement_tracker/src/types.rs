@@ -1,24 +1,67 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use sqlx::postgres::{PgHasArrayType, PgTypeInfo};
-use sqlx::types::chrono::{DateTime, NaiveDate, Utc};
+use sqlx::types::chrono::{DateTime, NaiveDate, NaiveTime, Utc};
 use sqlx::types::Uuid;
 use strum_macros::{AsRefStr, EnumIter, EnumString};
 
-#[derive(Clone, Debug, sqlx::Type, Eq, PartialEq, Serialize, Deserialize, EnumString, EnumIter, AsRefStr)]
-#[sqlx(type_name = "user_type_enum", rename_all = "lowercase")]
-#[strum(serialize_all = "UPPERCASE")]
-pub(crate) enum UsrType {
-    ACTIVE,
-    STAFF,
-    NS,
+// Name of a row in the usr_types table
+#[derive(Clone, Debug, sqlx::Type, Eq, PartialEq, Hash, Serialize)]
+#[sqlx(transparent)]
+#[serde(transparent)]
+pub(crate) struct UsrType(String);
+
+// Names are stored in uppercase, so input such as "?usr_type=ns" has to be normalised
+impl<'de> Deserialize<'de> for UsrType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(|name| UsrType::new(&name))
+    }
 }
 
-#[derive(Clone, Debug, sqlx::Type, Eq, PartialEq, Serialize, Deserialize, EnumString, EnumIter, AsRefStr)]
-#[sqlx(type_name = "role_type_enum", rename_all = "lowercase")]
-#[strum(serialize_all = "UPPERCASE")]
-pub(crate) enum RoleType {
-    PILOT,
-    ARO
+impl UsrType {
+    // NS users additionally need a SAF100 issued when planned, so this type cannot be removed
+    pub(crate) const NS: &'static str = "NS";
+
+    pub(crate) fn new(name: &str) -> Self {
+        UsrType(name.to_uppercase())
+    }
+
+    pub(crate) fn ns() -> Self {
+        UsrType::new(UsrType::NS)
+    }
+
+    pub(crate) fn is_ns(&self) -> bool {
+        self.0 == UsrType::NS
+    }
+}
+
+impl AsRef<str> for UsrType {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+// Name of a row in the role_types table
+#[derive(Clone, Debug, sqlx::Type, Eq, PartialEq, Hash, Serialize)]
+#[sqlx(transparent)]
+#[serde(transparent)]
+pub(crate) struct RoleType(String);
+
+impl<'de> Deserialize<'de> for RoleType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(|name| RoleType::new(&name))
+    }
+}
+
+impl RoleType {
+    pub(crate) fn new(name: &str) -> Self {
+        RoleType(name.to_uppercase())
+    }
+}
+
+impl AsRef<str> for RoleType {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
 }
 
 #[derive(Clone, Debug, sqlx::Type, Eq, PartialEq, Serialize, Deserialize, EnumString, EnumIter, AsRefStr)]
@@ -30,21 +73,57 @@ pub(crate) enum Ict {
     OTHER,
 }
 
-#[derive(Clone, sqlx::FromRow, Debug)]
-pub(crate) struct Usr {
+// Grants access to a subset of the privileged commands, admins have every role
+#[derive(Clone, Copy, Debug, sqlx::Type, Eq, PartialEq, Serialize, Deserialize, EnumString, EnumIter, AsRefStr)]
+#[sqlx(type_name = "admin_role_enum", rename_all = "snake_case")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub(crate) enum AdminRole {
+    Planner,
+    Saf100Clerk,
+    UserManager,
+    NotificationManager,
+}
+
+// Planning status of an availability entry, standby crew are on reserve for planned crew who withdraw
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize, EnumIter, AsRefStr)]
+#[strum(serialize_all = "UPPERCASE")]
+pub(crate) enum PlanStatus {
+    Unplanned,
+    Standby,
+    Planned,
+}
+
+impl PlanStatus {
+    pub(crate) fn from_flags(planned: bool, standby: bool) -> Self {
+        if planned {
+            PlanStatus::Planned
+        } else if standby {
+            PlanStatus::Standby
+        } else {
+            PlanStatus::Unplanned
+        }
+    }
+}
+
+// Response of a user to the reminder for a planned or standby entry
+#[derive(Clone, Copy, Debug, sqlx::Type, Eq, PartialEq, Serialize, Deserialize, EnumString, EnumIter, AsRefStr)]
+#[sqlx(type_name = "ack_enum", rename_all = "lowercase")]
+#[strum(serialize_all = "UPPERCASE")]
+pub(crate) enum Ack {
+    Acknowledged,
+    Declined,
+}
+
+#[derive(Clone, sqlx::FromRow, Debug, Serialize, Deserialize)]
+pub(crate) struct Unit {
     pub id: Uuid,
-    pub tele_id: i64,
     pub name: String,
-    pub ops_name: String,
-    pub usr_type: UsrType,
-    pub role_type: RoleType,
-    pub admin: bool,
     pub created: DateTime<Utc>,
     pub updated: DateTime<Utc>,
 }
 
-#[derive(Clone, sqlx::FromRow, Debug)]
-pub(crate) struct UsrWithAvailability {
+#[derive(Clone, sqlx::FromRow, Debug, Serialize, Deserialize)]
+pub(crate) struct Usr {
     pub id: Uuid,
     pub tele_id: i64,
     pub name: String,
@@ -52,12 +131,12 @@ pub(crate) struct UsrWithAvailability {
     pub usr_type: UsrType,
     pub role_type: RoleType,
     pub admin: bool,
+    pub unit_id: Uuid,
     pub created: DateTime<Utc>,
     pub updated: DateTime<Utc>,
-    pub availability: Vec<Availability>,
 }
 
-#[derive(Clone, sqlx::FromRow, Debug)]
+#[derive(Clone, sqlx::FromRow, Debug, Serialize, Deserialize)]
 pub(crate) struct Apply {
     pub id: Uuid,
     pub tele_id: i64,
@@ -66,18 +145,26 @@ pub(crate) struct Apply {
     pub ops_name: String,
     pub usr_type: UsrType,
     pub role_type: RoleType,
+    pub unit_id: Uuid,
     pub created: DateTime<Utc>,
     pub updated: DateTime<Utc>,
 }
 
-#[derive(Clone, sqlx::FromRow, Debug)]
+// Start and end of the part of a date an availability entry covers
+pub(crate) type TimeSlot = (NaiveTime, NaiveTime);
+
+#[derive(Clone, sqlx::FromRow, Debug, Serialize, Deserialize)]
 pub(crate) struct Availability {
     pub id: Uuid,
     pub user_id: Uuid,
     pub avail: NaiveDate,
     pub ict_type: Ict,
+    pub start_time: Option<NaiveTime>,
+    pub end_time: Option<NaiveTime>,
     pub remarks: Option<String>,
     pub planned: bool,
+    pub standby: bool,
+    pub ack: Option<Ack>,
     pub saf100: bool,
     pub attended: bool,
     pub is_valid: bool,
@@ -85,15 +172,19 @@ pub(crate) struct Availability {
     pub updated: DateTime<Utc>,
 }
 
-#[derive(Clone, sqlx::FromRow, Debug)]
+#[derive(Clone, sqlx::FromRow, Debug, Serialize, Deserialize)]
 pub(crate) struct AvailabilityDetails {
     pub id: Uuid,
     pub ops_name: String,
     pub usr_type: UsrType,
     pub avail: NaiveDate,
     pub ict_type: Ict,
+    pub start_time: Option<NaiveTime>,
+    pub end_time: Option<NaiveTime>,
     pub remarks: Option<String>,
     pub planned: bool,
+    pub standby: bool,
+    pub ack: Option<Ack>,
     pub saf100: bool,
     pub attended: bool,
     pub is_valid: bool,
@@ -101,18 +192,109 @@ pub(crate) struct AvailabilityDetails {
     pub updated: DateTime<Utc>,
 }
 
+impl AvailabilityDetails {
+    pub(crate) fn plan_status(&self) -> PlanStatus {
+        PlanStatus::from_flags(self.planned, self.standby)
+    }
+}
+
+#[derive(Clone, sqlx::FromRow, Debug, Serialize, Deserialize)]
+pub(crate) struct Movement {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub avail: NaiveDate,
+    pub start_time: NaiveTime,
+    pub end_time: NaiveTime,
+    pub activity: String,
+    pub location: Option<String>,
+    pub remarks: Option<String>,
+    pub is_valid: bool,
+    pub created: DateTime<Utc>,
+    pub updated: DateTime<Utc>,
+}
+
+// A problem with planning changes, raised to the planner before they are committed
+#[derive(Clone, Debug)]
+pub(crate) enum PlanConflict {
+    // More crew of the role would be planned on the date than the configured capacity
+    OverCapacity { role_type: RoleType, avail: NaiveDate, planned: i64, capacity: i64 },
+    // The user has movement on the date they would be planned
+    Movement { ops_name: String, avail: NaiveDate, activities: String },
+    // The NS member would be planned too close to the date for SAF100 to be issued in time
+    Saf100LeadTime { ops_name: String, avail: NaiveDate, days_left: i64 },
+}
+
+// Crew of an ICT type needed for a role on a date
+#[derive(Clone, sqlx::FromRow, Debug)]
+pub(crate) struct CrewRequirement {
+    pub ict_type: Ict,
+    pub headcount: i32,
+}
+
+// Planning and attendance of a user over a period, computed from their availability
+#[derive(Clone, sqlx::FromRow, Debug, Serialize, Deserialize)]
+pub(crate) struct UserStats {
+    pub ops_name: String,
+    pub role_type: RoleType,
+    pub usr_type: UsrType,
+    pub offered: i64,
+    pub planned: i64,
+    pub attended: i64,
+    pub saf100: i64,
+    pub no_shows: i64,
+}
+
+// How often and how recently the owner of an availability entry was planned before its date
+#[derive(Clone, sqlx::FromRow, Debug)]
+pub(crate) struct PlanHistory {
+    pub id: Uuid,
+    pub times_planned: i64,
+    pub last_planned: Option<NaiveDate>,
+}
+
 #[derive(Clone, sqlx::FromRow, Debug)]
 pub(crate) struct ScheduledNotifications {
     pub id: Uuid,
     pub avail_id: Uuid,
     pub scheduled_time: DateTime<Utc>,
     pub sent: bool,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub next_retry: Option<DateTime<Utc>>,
+    pub failed: bool,
     pub created: DateTime<Utc>,
     pub updated: DateTime<Utc>,
     pub is_valid: bool,
 }
 
-#[derive(Clone, sqlx::FromRow, Debug)]
+#[derive(Clone, sqlx::FromRow, Debug, Serialize, Deserialize)]
+pub(crate) struct ReminderRule {
+    pub id: Uuid,
+    pub role_type: Option<RoleType>,
+    pub usr_type: Option<UsrType>,
+    pub days_prior: Option<i32>,
+    pub time_of_day: Option<NaiveTime>,
+    pub created: DateTime<Utc>,
+    pub updated: DateTime<Utc>,
+    pub is_valid: bool,
+}
+
+// Request for the availability of the matching users of a unit within a date window
+#[derive(Clone, sqlx::FromRow, Debug, Serialize, Deserialize)]
+pub(crate) struct Campaign {
+    pub id: Uuid,
+    pub unit_id: Uuid,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub role_type: Option<RoleType>,
+    pub usr_type: Option<UsrType>,
+    pub nudges: i32,
+    pub last_nudged: Option<DateTime<Utc>>,
+    pub created: DateTime<Utc>,
+    pub updated: DateTime<Utc>,
+}
+
+#[derive(Clone, sqlx::FromRow, Debug, Serialize, Deserialize)]
 pub(crate) struct NotificationSettings {
     pub id: Uuid,
     pub chat_id: i64,
@@ -121,6 +303,8 @@ pub(crate) struct NotificationSettings {
     pub notif_availability: bool,
     pub notif_plan: bool,
     pub notif_conflict: bool,
+    pub notif_digest: bool,
+    pub unit_id: Uuid,
     pub created: DateTime<Utc>,
     pub updated: DateTime<Utc>,
     pub is_valid: bool,
@@ -138,3 +322,14 @@ impl PgHasArrayType for Availability {
         PgTypeInfo::with_name("_availability")
     }
 }
+
+#[derive(Clone, sqlx::FromRow, Debug)]
+pub(crate) struct AuditLog {
+    pub actor_tele_id: i64,
+    pub actor_ops_name: Option<String>,
+    pub action: String,
+    pub entity: String,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+    pub created: DateTime<Utc>,
+}
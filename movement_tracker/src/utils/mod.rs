@@ -1,7 +1,8 @@
 use std::collections::{HashMap, HashSet};
 use rand::distributions::Alphanumeric;
 use rand::Rng;
-use chrono::{Datelike, Duration, Utc, NaiveDate};
+use std::cmp::{max, min};
+use chrono::{Datelike, Duration, Utc, NaiveDate, NaiveTime, Weekday};
 
 use once_cell::sync::Lazy;
 use regex::Regex;
@@ -9,6 +10,10 @@ use regex::Regex;
 use teloxide::types::User;
 use crate::APP_TIMEZONE;
 use crate::now;
+use crate::types::TimeSlot;
+
+#[cfg(test)]
+mod tests;
 
 //Regular expressions for parsing different date formats
 static FULL_MONTH_FIRST_PATTERN: Lazy<Regex> = Lazy::new(|| {
@@ -94,13 +99,124 @@ fn date_or_string(
     } else { Err(format!("{} is not a valid date (guessed: {}-{}-{})", input, year, month, day)) }
 }
 
+// Regular expressions for relative dates, e.g. "in 2 weeks" or "tomorrow to next fri"
+static RELATIVE_OFFSET_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^in (?P<count>\d+|a|an|one|two|three|four|five|six|seven|eight|nine|ten) (?P<unit>day|week|month)s?$")
+        .expect("Failed to compile RELATIVE_OFFSET_PATTERN regex")
+});
+
+static RELATIVE_RANGE_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^\s*(?P<start>.+?)\s+(?:-|–|to|until|till)\s+(?P<end>.+?)\s*$")
+        .expect("Failed to compile RELATIVE_RANGE_PATTERN regex")
+});
+
+fn parse_count(input: &str) -> Option<i64> {
+    match input {
+        "a" | "an" | "one" => Some(1),
+        "two" => Some(2),
+        "three" => Some(3),
+        "four" => Some(4),
+        "five" => Some(5),
+        "six" => Some(6),
+        "seven" => Some(7),
+        "eight" => Some(8),
+        "nine" => Some(9),
+        "ten" => Some(10),
+        _ => input.parse::<i64>().ok()
+    }
+}
+
+// Lower cases the input and collapses the whitespace between words
+fn normalize_relative(input: &str) -> String {
+    input.split_whitespace().collect::<Vec<&str>>().join(" ").to_lowercase()
+}
+
+fn monday_of_week(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+// Parses dates relative to today, such as "tomorrow", "fri", "next fri" or "in 2 weeks".
+// A bare or "this" weekday is the next such day from today, while "next" picks the day in the following calendar week
+fn parse_relative_date(input: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let normalized = normalize_relative(input);
+    match normalized.as_str() {
+        "today" | "tdy" => return Some(today),
+        "tomorrow" | "tmr" | "tmrw" => return today.succ_opt(),
+        "day after tomorrow" | "the day after tomorrow" => return Some(today + Duration::days(2)),
+        _ => {}
+    }
+
+    if let Some(caps) = RELATIVE_OFFSET_PATTERN.captures(&normalized) {
+        let count = parse_count(&caps["count"])?;
+        return match &caps["unit"] {
+            "day" => Some(today + Duration::days(count)),
+            "week" => Some(today + Duration::weeks(count)),
+            _ => Some(add_month_safe(today, i32::try_from(count).ok()?))
+        };
+    }
+
+    let (next_week, day) = match normalized.strip_prefix("next ") {
+        Some(day) => (true, day),
+        None => (false, ["this coming ", "this ", "coming "].iter().find_map(|prefix| normalized.strip_prefix(prefix)).unwrap_or(&normalized))
+    };
+    // Only single days, so "day" or "weekend" are not accepted here
+    let weekday = WEEKDAYS_MAP.get(day).filter(|days| days.len() == 1)?[0];
+    if next_week {
+        Some(monday_of_week(today) + Duration::days(7 + weekday.num_days_from_monday() as i64))
+    } else {
+        let days_ahead = (7 + weekday.num_days_from_monday() - today.weekday().num_days_from_monday()) % 7;
+        Some(today + Duration::days(days_ahead as i64))
+    }
+}
+
+// Parses relative ranges such as "this weekend" or "next week", and ranges where either end is a relative date.
+// Returns None if the input is not a relative range, so the absolute range patterns can be tried instead
+fn parse_relative_date_range(input: &str, today: NaiveDate) -> Option<Result<Vec<NaiveDate>, String>> {
+    let this_monday = monday_of_week(today);
+    let (start, end) = match normalize_relative(input).as_str() {
+        "this weekend" | "weekend" => (max(today, this_monday + Duration::days(5)), this_monday + Duration::days(6)),
+        "next weekend" => (this_monday + Duration::days(12), this_monday + Duration::days(13)),
+        "this week" => (today, this_monday + Duration::days(6)),
+        "next week" => (this_monday + Duration::days(7), this_monday + Duration::days(13)),
+        _ => {
+            let caps = RELATIVE_RANGE_PATTERN.captures(input)?;
+            let (start_str, end_str) = (&caps["start"], &caps["end"]);
+            if parse_relative_date(start_str, today).is_none() && parse_relative_date(end_str, today).is_none() {
+                return None;
+            }
+            match (parse_single_date_from(start_str, today), parse_single_date_from(end_str, today)) {
+                (Ok(start), Ok(end)) => (start, end),
+                (Err(e), _) | (_, Err(e)) => return Some(Err(format!("Invalid date in range '{}': {}", input, e)))
+            }
+        }
+    };
+
+    if end < start {
+        return Some(Err(format!("Date range '{}' ends before it starts", input)));
+    }
+    let num_days = (end - start).num_days() as usize + 1;
+    if num_days > MAX_DATE_RANGE_DAYS {
+        return Some(Err(format!("Date range is too large ({} days). Maximum allowed is {} days.", num_days, MAX_DATE_RANGE_DAYS)));
+    }
+
+    Some(Ok(start.iter_days().take(num_days).collect()))
+}
+
 // Parses a single date string into NaiveDate
 pub(crate) fn parse_single_date(input: &str) -> Result<NaiveDate, String> {
-    let today = now!().naive_local().date();
+    parse_single_date_from(input, now!().naive_local().date())
+}
+
+// Parses a single date string, resolving relative dates and inferred years against the given day
+fn parse_single_date_from(input: &str, today: NaiveDate) -> Result<NaiveDate, String> {
     let current_year = today.year();
 
+    // 0. Try relative dates, e.g., "tomorrow" or "next fri"
+    if let Some(date) = parse_relative_date(input, today) {
+        Ok(date)
+    }
     // 1. Try "year month day" pattern, e.g., "2024 nov 12"
-    if let Some(caps) = YEAR_MONTH_DAY_PATTERN.captures(input) {
+    else if let Some(caps) = YEAR_MONTH_DAY_PATTERN.captures(input) {
         let year = caps.get(1).unwrap().as_str().parse::<i32>().map_err(|_| input.to_string())?;
         let month_str = caps.get(2).unwrap().as_str().to_lowercase();
         let day = caps.get(3).unwrap().as_str().parse::<u32>().map_err(|_| input.to_string())?;
@@ -184,13 +300,41 @@ pub(crate) fn parse_single_date(input: &str) -> Result<NaiveDate, String> {
     }
 }
 
+// Splits the input on commas, keeping the dates excluded from a recurrence with it,
+// e.g. "every tue until dec except 26 nov, 3 dec" is a single segment
+fn split_date_segments(input: &str, today: NaiveDate) -> Vec<String> {
+    let mut segments: Vec<String> = Vec::new();
+    let mut in_except = false;
+
+    for part in input.split(',') {
+        let trimmed = part.trim();
+        let is_date = parse_single_date_from(trimmed, today).is_ok() || parse_date_range_from(trimmed, today).is_ok();
+        match segments.last_mut() {
+            Some(last) if in_except && is_date => {
+                last.push_str(", ");
+                last.push_str(trimmed);
+            }
+            _ => {
+                in_except = RECURRENCE_EXCEPT_KEYWORD.is_match(trimmed);
+                segments.push(trimmed.to_string());
+            }
+        }
+    }
+
+    segments
+}
+
 // Parses the input string into individual dates, handling single dates and ranges
 pub(crate) fn parse_dates(input: &str) -> (Vec<NaiveDate>, Vec<String>, Vec<NaiveDate>) {
+    parse_dates_from(input, now!().naive_local().date())
+}
+
+fn parse_dates_from(input: &str, today: NaiveDate) -> (Vec<NaiveDate>, Vec<String>, Vec<NaiveDate>) {
     let mut parsed_dates = Vec::new();
     let mut failed_parsing_dates = Vec::new();
     let mut duplicate_dates = Vec::new();
 
-    for segment in input.split(',') {
+    for segment in split_date_segments(input, today) {
         let trimmed = segment.trim();
 
         if trimmed.is_empty() {
@@ -198,20 +342,20 @@ pub(crate) fn parse_dates(input: &str) -> (Vec<NaiveDate>, Vec<String>, Vec<Naiv
         }
 
         // First, attempt to parse as a single date
-        match parse_single_date(trimmed) {
+        match parse_single_date_from(trimmed, today) {
             Ok(date) => {
                 parsed_dates.push(date);
             }
             Err(_) => {
-                // If single date parsing fails, attempt to parse as a date range
-                match parse_date_range(trimmed) {
+                // If single date parsing fails, attempt to parse as a date range, then as a recurrence
+                match parse_date_range_from(trimmed, today).or_else(|_| parse_recurring_dates_from(trimmed, today)) {
                     Ok(dates) => {
                         for date in dates {
                             parsed_dates.push(date);
                         }
                     }
                     Err(_) => {
-                        // If both parsing attempts fail, record the failure
+                        // If all parsing attempts fail, record the failure
                         failed_parsing_dates.push(trimmed.to_string());
                     }
                 }
@@ -351,9 +495,17 @@ static DATE_RANGE_PATTERNS: Lazy<Vec<Regex>> = Lazy::new(|| {
 
 // Parses a date range string into a Vec<NaiveDate>
 pub fn parse_date_range(input: &str) -> Result<Vec<NaiveDate>, String> {
-    let today = now!().naive_local().date();
+    parse_date_range_from(input, now!().naive_local().date())
+}
+
+fn parse_date_range_from(input: &str, today: NaiveDate) -> Result<Vec<NaiveDate>, String> {
     let current_year = today.year();
 
+    // Relative ranges such as "this weekend", or ranges with a relative end such as "tomorrow to next fri"
+    if let Some(dates) = parse_relative_date_range(input, today) {
+        return dates;
+    }
+
     // Vector to collect errors from each pattern
     let mut errors: Vec<String> = Vec::new();
 
@@ -429,9 +581,9 @@ pub fn parse_date_range(input: &str) -> Result<Vec<NaiveDate>, String> {
                 let duration = end_date - start_date;
                 let num_days = duration.num_days() as usize + 1; // +1 to include both start and end dates
 
-                // Prevent generating an array size > 15
-                if num_days > 15 {
-                    return Err(format!("Date range is too large ({} days). Maximum allowed is 15 days.", num_days));
+                // Prevent generating an array size > MAX_DATE_RANGE_DAYS
+                if num_days > MAX_DATE_RANGE_DAYS {
+                    return Err(format!("Date range is too large ({} days). Maximum allowed is {} days.", num_days, MAX_DATE_RANGE_DAYS));
                 }
 
                 // Generate all dates in the range
@@ -484,6 +636,262 @@ pub fn parse_date_range(input: &str) -> Result<Vec<NaiveDate>, String> {
 }
 
 
+// Regular expression for recurring dates, e.g. "every 2 weeks on sat until dec except 14 dec"
+static RECURRENCE_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^\s*(?:every\s+(?:(?P<interval>\d+)\s+weeks?\s+on\s+|(?P<other>other)\s+)?)?(?P<days>.+?)\s+(?P<period>(?:until|till|to|from|in|during|this|next|the|first)\b.*?)(?:\s+except\s+(?P<except>.+))?\s*$")
+        .expect("Failed to compile RECURRENCE_PATTERN regex")
+});
+
+static RECURRENCE_FROM_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^from\s+(?P<start>.+?)\s+(?:to|until|till|-)\s+(?P<end>.+)$")
+        .expect("Failed to compile RECURRENCE_FROM_PATTERN regex")
+});
+
+static RECURRENCE_FIRST_WEEKS_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^(?:(?:in|during)\s+)?(?:the\s+)?first\s+(?P<weeks>\d+|one|two|three|four)\s+weeks?(?:\s+of\s+(?P<month>.+))?$")
+        .expect("Failed to compile RECURRENCE_FIRST_WEEKS_PATTERN regex")
+});
+
+static RECURRENCE_EXCEPT_KEYWORD: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\bexcept\b")
+        .expect("Failed to compile RECURRENCE_EXCEPT_KEYWORD regex")
+});
+
+static RECURRENCE_EXCEPT_SEPARATOR: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\s*(?:\band\b|&|;|,)\s*")
+        .expect("Failed to compile RECURRENCE_EXCEPT_SEPARATOR regex")
+});
+
+static WEEKDAYS_MAP: Lazy<HashMap<&'static str, Vec<Weekday>>> = Lazy::new(|| {
+    [
+        ("mon", vec![Weekday::Mon]), ("monday", vec![Weekday::Mon]),
+        ("tue", vec![Weekday::Tue]), ("tues", vec![Weekday::Tue]), ("tuesday", vec![Weekday::Tue]),
+        ("wed", vec![Weekday::Wed]), ("wednesday", vec![Weekday::Wed]),
+        ("thu", vec![Weekday::Thu]), ("thur", vec![Weekday::Thu]), ("thurs", vec![Weekday::Thu]), ("thursday", vec![Weekday::Thu]),
+        ("fri", vec![Weekday::Fri]), ("friday", vec![Weekday::Fri]),
+        ("sat", vec![Weekday::Sat]), ("saturday", vec![Weekday::Sat]),
+        ("sun", vec![Weekday::Sun]), ("sunday", vec![Weekday::Sun]),
+        ("weekday", vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri]),
+        ("weekend", vec![Weekday::Sat, Weekday::Sun]),
+        ("day", vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri, Weekday::Sat, Weekday::Sun]),
+    ]
+        .into_iter()
+        .collect()
+});
+
+// Parses the days of a recurrence such as "sat and sun", "weekdays" or "mon/wed/fri"
+fn parse_recurrence_weekdays(input: &str) -> Result<HashSet<Weekday>, String> {
+    let mut weekdays = HashSet::new();
+    for token in input.split(|c: char| c.is_whitespace() || c == '&' || c == '/' || c == '+') {
+        let token = token.trim().to_lowercase();
+        if token.is_empty() || token == "and" {
+            continue;
+        }
+        // Accept plurals such as "saturdays" or "weekends"
+        let days = WEEKDAYS_MAP.get(token.as_str())
+            .or_else(|| token.strip_suffix('s').and_then(|singular| WEEKDAYS_MAP.get(singular)))
+            .ok_or(format!("Invalid day: '{}'", token))?;
+        weekdays.extend(days.iter().copied());
+    }
+
+    if weekdays.is_empty() {
+        Err(format!("No days found in '{}'", input))
+    } else {
+        Ok(weekdays)
+    }
+}
+
+// Resolves "this month", "next month" or a month name (optionally with a year) into the first and last day of that month
+fn parse_recurrence_month(input: &str, today: NaiveDate) -> Result<(NaiveDate, NaiveDate), String> {
+    let input = input.trim().to_lowercase();
+    let first_of_this_month = NaiveDate::from_ymd_opt(today.year(), today.month(), 1).ok_or(format!("Invalid month: '{}'", input))?;
+
+    let first_of_month = match input.as_str() {
+        "this month" => first_of_this_month,
+        "next month" => add_month_safe(first_of_this_month, 1),
+        _ => {
+            let mut parts = input.split_whitespace();
+            let month_str = parts.next().ok_or(format!("Invalid month: '{}'", input))?;
+            let month = *MONTHS_MAP.get(month_str).ok_or(format!("Invalid month: '{}'", input))?;
+            let year = match parts.next() {
+                Some(year_str) => year_str.parse::<i32>().map_err(|_| format!("Invalid year: '{}'", year_str))?,
+                // The month is this year unless it has already passed
+                None if month < today.month() => today.year() + 1,
+                None => today.year()
+            };
+            if parts.next().is_some() {
+                return Err(format!("Invalid month: '{}'", input));
+            }
+            NaiveDate::from_ymd_opt(year, month, 1).ok_or(format!("Invalid month: '{}'", input))?
+        }
+    };
+
+    Ok((first_of_month, last_day_of_month(first_of_month)))
+}
+
+// Resolves the end of a recurrence, either a date or the last day of a month
+fn parse_recurrence_end(input: &str, today: NaiveDate) -> Result<NaiveDate, String> {
+    parse_single_date_from(input, today)
+        .or_else(|_| parse_recurrence_month(input, today).map(|(_, end)| end))
+}
+
+// Resolves the period of a recurrence into its first and last dates
+fn parse_recurrence_period(input: &str, today: NaiveDate) -> Result<(NaiveDate, NaiveDate), String> {
+    let input = input.trim();
+    let lower = input.to_lowercase();
+
+    if let Some(caps) = RECURRENCE_FROM_PATTERN.captures(input) {
+        let start = parse_single_date_from(&caps["start"], today)?;
+        let end = parse_recurrence_end(&caps["end"], today)?;
+        Ok((start, end))
+    } else if let Some(caps) = RECURRENCE_FIRST_WEEKS_PATTERN.captures(input) {
+        let weeks = match caps["weeks"].to_lowercase().as_str() {
+            "one" => 1,
+            "two" => 2,
+            "three" => 3,
+            "four" => 4,
+            weeks => weeks.parse::<i64>().map_err(|_| format!("Invalid number of weeks: '{}'", weeks))?
+        };
+        let (start, month_end) = match caps.name("month") {
+            Some(month) => parse_recurrence_month(month.as_str(), today)?,
+            None => parse_recurrence_month("this month", today)?
+        };
+        Ok((start, min(month_end, start + Duration::days(7 * weeks - 1))))
+    } else if let Some(end) = ["until ", "till ", "to "].iter().find_map(|prefix| lower.strip_prefix(prefix)) {
+        Ok((today, parse_recurrence_end(end, today)?))
+    } else {
+        let month = ["in ", "during "].iter().find_map(|prefix| lower.strip_prefix(prefix)).unwrap_or(&lower);
+        parse_recurrence_month(month, today)
+    }
+}
+
+// Parses a recurrence such as "every saturday until december", "weekdays in the first two weeks of next month"
+// or "every 2 weeks on mon and thu from 4 nov to 20 dec except 25 nov" into the individual dates
+fn parse_recurring_dates_from(input: &str, today: NaiveDate) -> Result<Vec<NaiveDate>, String> {
+    let caps = RECURRENCE_PATTERN
+        .captures(input)
+        .ok_or_else(|| format!("Failed to parse recurrence from input: '{}'", input))?;
+
+    let interval_weeks = match (caps.name("interval"), caps.name("other")) {
+        (Some(interval), _) => interval.as_str().parse::<i64>().ok().filter(|weeks| *weeks > 0)
+            .ok_or(format!("Invalid interval: '{}'", interval.as_str()))?,
+        (None, Some(_)) => 2,
+        (None, None) => 1
+    };
+    let weekdays = parse_recurrence_weekdays(&caps["days"])?;
+    let (period_start, end) = parse_recurrence_period(&caps["period"], today)?;
+    // Past dates in the period are skipped
+    let start = max(period_start, today);
+
+    if end < start {
+        return Err(format!("Recurrence in '{}' ends before it starts", input));
+    }
+    if (end - start).num_days() > MAX_RECURRENCE_SPAN_DAYS {
+        return Err(format!("Recurrence is too long. Maximum allowed is {} days.", MAX_RECURRENCE_SPAN_DAYS));
+    }
+
+    let mut exclusions = HashSet::new();
+    if let Some(except) = caps.name("except") {
+        for segment in RECURRENCE_EXCEPT_SEPARATOR.split(except.as_str()) {
+            match parse_single_date_from(segment, today) {
+                Ok(date) => { exclusions.insert(date); }
+                Err(_) => { exclusions.extend(parse_date_range_from(segment, today)?); }
+            }
+        }
+    }
+
+    // Weeks are counted from the monday of the first week of the period, even if it has already started,
+    // so intervals stay aligned to the calendar week regardless of when the recurrence is entered
+    let first_monday = period_start - Duration::days(period_start.weekday().num_days_from_monday() as i64);
+    let dates: Vec<NaiveDate> = start.iter_days()
+        .take_while(|date| *date <= end)
+        .filter(|date| weekdays.contains(&date.weekday()))
+        .filter(|date| ((*date - first_monday).num_days() / 7) % interval_weeks == 0)
+        .filter(|date| !exclusions.contains(date))
+        .collect();
+
+    if dates.is_empty() {
+        Err(format!("Recurrence in '{}' does not contain any dates", input))
+    } else if dates.len() > MAX_RECURRING_DATES {
+        Err(format!("Recurrence has too many dates ({}). Maximum allowed is {} dates.", dates.len(), MAX_RECURRING_DATES))
+    } else {
+        Ok(dates)
+    }
+}
+
+// Regular expression for parsing a time range (e.g. 0800-1730, 08:00 to 17:30)
+static TIME_RANGE_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^\s*(\d{1,2}):?(\d{2})\s*(?:hrs?)?\s*(?:-|to)\s*(\d{1,2}):?(\d{2})\s*(?:hrs?)?\s*$")
+        .expect("Failed to compile TIME_RANGE_PATTERN regex")
+});
+
+pub(crate) fn parse_time_range(input: &str) -> Result<(NaiveTime, NaiveTime), String> {
+    let caps = TIME_RANGE_PATTERN
+        .captures(input)
+        .ok_or_else(|| format!("Failed to parse time range from input: '{}'", input))?;
+
+    // All four groups are guaranteed to be digits by the regex
+    let parse_group = |ind: usize| -> u32 { caps[ind].parse::<u32>().unwrap_or(u32::MAX) };
+
+    let start = NaiveTime::from_hms_opt(parse_group(1), parse_group(2), 0)
+        .ok_or_else(|| format!("Invalid start time in input: '{}'", input))?;
+    let end = NaiveTime::from_hms_opt(parse_group(3), parse_group(4), 0)
+        .ok_or_else(|| format!("Invalid end time in input: '{}'", input))?;
+
+    if end <= start {
+        return Err(format!("End time must be after start time in input: '{}'", input));
+    }
+
+    Ok((start, end))
+}
+
+// Named parts of a day which availability can be indicated for, as (name, start hour, end hour)
+pub(crate) const NAMED_SLOTS: [(&str, u32, u32); 3] = [
+    ("AM", 8, 12),
+    ("PM", 13, 17),
+    ("NIGHT", 19, 23),
+];
+
+fn named_slot_times(start_hour: u32, end_hour: u32) -> (NaiveTime, NaiveTime) {
+    (
+        NaiveTime::from_hms_opt(start_hour, 0, 0).expect("Invalid named slot start"),
+        NaiveTime::from_hms_opt(end_hour, 0, 0).expect("Invalid named slot end")
+    )
+}
+
+// Parses a named slot (e.g. AM, night) or a time range (e.g. 1900-2300)
+pub(crate) fn parse_slot(input: &str) -> Result<TimeSlot, String> {
+    let cleaned = input.trim().to_uppercase();
+    match NAMED_SLOTS.iter().find(|(name, _, _)| *name == cleaned) {
+        Some((_, start_hour, end_hour)) => Ok(named_slot_times(*start_hour, *end_hour)),
+        None => parse_time_range(input)
+    }
+}
+
+// Formats the slot by its name if it is a named slot, or as a time range (e.g. 1900-2300)
+pub(crate) fn format_slot(start: NaiveTime, end: NaiveTime) -> String {
+    match NAMED_SLOTS.iter().find(|(_, start_hour, end_hour)| named_slot_times(*start_hour, *end_hour) == (start, end)) {
+        Some((name, _, _)) => format!("{} ({}-{})", name, start.format("%H%M"), end.format("%H%M")),
+        None => format!("{}-{}", start.format("%H%M"), end.format("%H%M"))
+    }
+}
+
+// Formats the slot of an availability entry with a leading space, or nothing if it covers the whole day
+pub(crate) fn format_slot_suffix(start: Option<NaiveTime>, end: Option<NaiveTime>) -> String {
+    match (start, end) {
+        (Some(start), Some(end)) => format!(" {}", format_slot(start, end)),
+        _ => String::new()
+    }
+}
+
+// Splits the slot from the end of the input after '@' (e.g. "every Sat until Dec @ night")
+pub(crate) fn split_slot(input: &str) -> (&str, Option<&str>) {
+    match input.rsplit_once('@') {
+        Some((dates, slot)) => (dates, Some(slot.trim())),
+        None => (input, None)
+    }
+}
+
 pub(crate) fn format_dates_as_markdown(dates: &Vec<NaiveDate>) -> String {
     let mut markdown_list = String::new();
 
@@ -579,6 +987,56 @@ pub(crate) fn escape_special_characters(input: &str) -> String {
     })
 }
 
+// Splits CSV content into rows of fields, handling quoted fields and escaped quotes
+pub(crate) fn parse_csv(input: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = input.trim_start_matches('\u{feff}').chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if in_quotes {
+            match ch {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = false,
+                _ => field.push(ch),
+            }
+        } else {
+            match ch {
+                '"' => in_quotes = true,
+                ',' => row.push(std::mem::take(&mut field)),
+                '\r' => {}
+                '\n' => {
+                    row.push(std::mem::take(&mut field));
+                    rows.push(std::mem::take(&mut row));
+                }
+                _ => field.push(ch),
+            }
+        }
+    }
+
+    // Last row may not be terminated by a newline
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    rows
+}
+
+// Quotes a field for CSV output if it contains separators, quotes or newlines
+pub(crate) fn escape_csv_field(input: &str) -> String {
+    if input.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", input.replace('"', "\"\""))
+    } else {
+        input.to_string()
+    }
+}
+
 pub(crate) const MAX_NAME_LENGTH: usize = 64;
 pub(crate) const MAX_OPS_NAME_LENGTH: usize = 10;
 pub(crate) const MAX_SHOW_ENTRIES: usize = 6;
@@ -586,7 +1044,36 @@ pub(crate) const MAX_SHOW_ENTRIES: usize = 6;
 pub(crate) const MAX_REMARKS_SHOWN_CHARS_BUTTON: usize = 8;
 pub(crate) const MAX_REMARKS_SHOWN_CHARS_TEXT: usize = 22;
 
+pub(crate) const MAX_ACTIVITY_LENGTH: usize = 64;
+pub(crate) const MAX_LOCATION_LENGTH: usize = 64;
+pub(crate) const MAX_REMARKS_LENGTH: usize = 256;
+
+pub(crate) const MAX_IMPORT_FILE_SIZE: u32 = 1024 * 1024;
+pub(crate) const MAX_IMPORT_ROWS: usize = 1000;
+pub(crate) const MAX_IMPORT_PREVIEW_LINES: usize = 20;
+
+pub(crate) const MAX_REMINDER_DAYS_PRIOR: i32 = 60;
+
+pub(crate) const MAX_DATE_RANGE_DAYS: usize = 15;
+pub(crate) const MAX_RECURRENCE_SPAN_DAYS: i64 = 366;
+pub(crate) const MAX_RECURRING_DATES: usize = 62;
+
+pub(crate) const MAX_HISTORY_ENTRIES: usize = 8;
+pub(crate) const MAX_HISTORY_VALUE_CHARS: usize = 24;
+
+// Days of past planning weighed when suggesting who to plan
+pub(crate) const PLAN_FAIRNESS_WINDOW_DAYS: i32 = 90;
+
+// Users who have not responded to a campaign are nudged at this interval, up to the maximum times
+pub(crate) const CAMPAIGN_NUDGE_INTERVAL_HOURS: i32 = 48;
+pub(crate) const MAX_CAMPAIGN_NUDGES: i32 = 3;
+pub(crate) const MAX_CAMPAIGN_DAYS: i64 = 92;
+
+// Kept short so the name fits in callback data
+pub(crate) const MAX_CREW_TYPE_NAME_LENGTH: usize = 16;
+
 pub(crate) const CALLBACK_PREFIX_LEN: usize = 8;
+pub(crate) const CALENDAR_TOKEN_LEN: usize = 32;
 
 pub(crate) fn is_valid_name(name: &str) -> bool {
     name.chars().all(|c| c.is_ascii_alphabetic() || c.is_whitespace())
@@ -596,6 +1083,10 @@ pub(crate) fn is_valid_ops_name(name: &str) -> bool {
     name.chars().all(|c| c.is_ascii_alphabetic() || c.is_whitespace())
 }
 
+pub(crate) fn is_valid_crew_type_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+}
+
 pub(crate) fn cleanup_name(name: &str) -> String {
     // Trim the input (left and right)
     let trimmed_name = name.trim();
@@ -0,0 +1,271 @@
+use chrono::NaiveDate;
+
+use super::*;
+
+// Wednesday
+fn today() -> NaiveDate {
+    date(2024, 10, 16)
+}
+
+fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+fn dates(year: i32, month: u32, days: &[u32]) -> Vec<NaiveDate> {
+    days.iter().map(|day| date(year, month, *day)).collect()
+}
+
+#[test]
+fn relative_date_keywords() {
+    assert_eq!(parse_relative_date("today", today()), Some(date(2024, 10, 16)));
+    assert_eq!(parse_relative_date("Tomorrow", today()), Some(date(2024, 10, 17)));
+    assert_eq!(parse_relative_date("tmr", today()), Some(date(2024, 10, 17)));
+    assert_eq!(parse_relative_date("the day after tomorrow", today()), Some(date(2024, 10, 18)));
+}
+
+#[test]
+fn relative_date_weekdays() {
+    assert_eq!(parse_relative_date("fri", today()), Some(date(2024, 10, 18)));
+    assert_eq!(parse_relative_date("this friday", today()), Some(date(2024, 10, 18)));
+    assert_eq!(parse_relative_date("coming fri", today()), Some(date(2024, 10, 18)));
+    assert_eq!(parse_relative_date("wed", today()), Some(date(2024, 10, 16)));
+    assert_eq!(parse_relative_date("mon", today()), Some(date(2024, 10, 21)));
+}
+
+#[test]
+fn relative_date_next_weekday_is_in_next_week() {
+    assert_eq!(parse_relative_date("next fri", today()), Some(date(2024, 10, 25)));
+    assert_eq!(parse_relative_date("  Next   MON ", today()), Some(date(2024, 10, 21)));
+    assert_eq!(parse_relative_date("next sun", today()), Some(date(2024, 10, 27)));
+}
+
+#[test]
+fn relative_date_offsets() {
+    assert_eq!(parse_relative_date("in 3 days", today()), Some(date(2024, 10, 19)));
+    assert_eq!(parse_relative_date("in 2 weeks", today()), Some(date(2024, 10, 30)));
+    assert_eq!(parse_relative_date("in a week", today()), Some(date(2024, 10, 23)));
+    assert_eq!(parse_relative_date("in one month", today()), Some(date(2024, 11, 16)));
+}
+
+#[test]
+fn relative_date_rejects_other_input() {
+    assert_eq!(parse_relative_date("next month", today()), None);
+    assert_eq!(parse_relative_date("weekend", today()), None);
+    assert_eq!(parse_relative_date("days", today()), None);
+    assert_eq!(parse_relative_date("17 nov", today()), None);
+}
+
+#[test]
+fn single_date_accepts_relative_dates() {
+    assert_eq!(parse_single_date_from("next fri", today()), Ok(date(2024, 10, 25)));
+    assert_eq!(parse_single_date_from("tomorrow", today()), Ok(date(2024, 10, 17)));
+}
+
+#[test]
+fn single_date_absolute_formats() {
+    assert_eq!(parse_single_date_from("2024 nov 12", today()), Ok(date(2024, 11, 12)));
+    assert_eq!(parse_single_date_from("November 2nd", today()), Ok(date(2024, 11, 2)));
+    assert_eq!(parse_single_date_from("28/10", today()), Ok(date(2024, 10, 28)));
+    assert_eq!(parse_single_date_from("1st Sept 2025", today()), Ok(date(2025, 9, 1)));
+    assert_eq!(parse_single_date_from("17/04/25", today()), Ok(date(2025, 4, 17)));
+}
+
+#[test]
+fn single_date_without_year_rolls_over() {
+    assert_eq!(parse_single_date_from("Jan 2", today()), Ok(date(2025, 1, 2)));
+    assert_eq!(parse_single_date_from("15 oct", today()), Ok(date(2025, 10, 15)));
+}
+
+#[test]
+fn single_date_rejects_past_and_invalid_dates() {
+    assert!(parse_single_date_from("May 17 2023", today()).is_err());
+    assert!(parse_single_date_from("31 feb 2025", today()).is_err());
+    assert!(parse_single_date_from("someday", today()).is_err());
+}
+
+#[test]
+fn date_range_absolute() {
+    assert_eq!(parse_date_range_from("20 - 22 oct", today()), Ok(dates(2024, 10, &[20, 21, 22])));
+    let new_year = parse_date_range_from("Dec 30 - Jan 2", today()).unwrap();
+    assert_eq!(new_year.first(), Some(&date(2024, 12, 30)));
+    assert_eq!(new_year.last(), Some(&date(2025, 1, 2)));
+    assert_eq!(new_year.len(), 4);
+}
+
+#[test]
+fn date_range_relative() {
+    assert_eq!(parse_date_range_from("this weekend", today()), Ok(dates(2024, 10, &[19, 20])));
+    assert_eq!(parse_date_range_from("next weekend", today()), Ok(dates(2024, 10, &[26, 27])));
+    assert_eq!(parse_date_range_from("this week", today()), Ok(dates(2024, 10, &[16, 17, 18, 19, 20])));
+    assert_eq!(parse_date_range_from("next week", today()), Ok(dates(2024, 10, &[21, 22, 23, 24, 25, 26, 27])));
+    // Only the remaining day of the weekend is included on a sunday
+    assert_eq!(parse_date_range_from("this weekend", date(2024, 10, 20)), Ok(dates(2024, 10, &[20])));
+}
+
+#[test]
+fn date_range_with_relative_end() {
+    assert_eq!(parse_date_range_from("tomorrow to next mon", today()), Ok(dates(2024, 10, &[17, 18, 19, 20, 21])));
+    assert_eq!(parse_date_range_from("fri - 22 oct", today()), Ok(dates(2024, 10, &[18, 19, 20, 21, 22])));
+}
+
+#[test]
+fn date_range_resolves_against_today() {
+    let today = now!().naive_local().date();
+    assert_eq!(parse_date_range("this week"), parse_date_range_from("this week", today));
+}
+
+#[test]
+fn date_range_rejects_reversed_and_long_ranges() {
+    assert!(parse_date_range_from("next fri to tomorrow", today()).is_err());
+    assert!(parse_date_range_from("today to in 3 weeks", today()).is_err());
+    assert!(parse_date_range_from("1 nov - 30 nov", today()).is_err());
+}
+
+#[test]
+fn dates_mixed_input() {
+    let (parsed, failed, duplicates) = parse_dates_from("tomorrow, 20 oct, this weekend, 17 oct, junk", today());
+    assert_eq!(parsed, dates(2024, 10, &[17, 20, 19]));
+    assert_eq!(failed, vec!["junk".to_string()]);
+    assert_eq!(duplicates, dates(2024, 10, &[20, 17]));
+}
+
+#[test]
+fn dates_ignore_empty_segments() {
+    let (parsed, failed, duplicates) = parse_dates_from(" , next fri,, ", today());
+    assert_eq!(parsed, dates(2024, 10, &[25]));
+    assert!(failed.is_empty());
+    assert!(duplicates.is_empty());
+}
+
+#[test]
+fn recurring_dates_until_month() {
+    assert_eq!(
+        parse_recurring_dates_from("every saturday until november", today()),
+        Ok(vec![date(2024, 10, 19), date(2024, 10, 26), date(2024, 11, 2), date(2024, 11, 9), date(2024, 11, 16), date(2024, 11, 23), date(2024, 11, 30)])
+    );
+}
+
+#[test]
+fn recurring_dates_first_weeks_of_next_month() {
+    assert_eq!(
+        parse_recurring_dates_from("weekdays in the first two weeks of next month", today()),
+        Ok(dates(2024, 11, &[1, 4, 5, 6, 7, 8, 11, 12, 13, 14]))
+    );
+}
+
+#[test]
+fn recurring_dates_interval_and_exclusions() {
+    assert_eq!(
+        parse_recurring_dates_from("every 2 weeks on mon and thu from 21 oct to 30 nov except 4 nov", today()),
+        Ok(vec![date(2024, 10, 21), date(2024, 10, 24), date(2024, 11, 7), date(2024, 11, 18), date(2024, 11, 21)])
+    );
+    assert_eq!(
+        parse_recurring_dates_from("sat and sun in november except 9 nov and 16 - 17 nov", today()),
+        Ok(dates(2024, 11, &[2, 3, 10, 23, 24, 30]))
+    );
+}
+
+#[test]
+fn recurring_dates_with_comma_separated_exclusions() {
+    let (parsed, failed, duplicates) = parse_dates_from("every tue until dec except 26 nov, 3 dec, 10 - 17 dec", today());
+    assert_eq!(parsed, vec![
+        date(2024, 10, 22), date(2024, 10, 29), date(2024, 11, 5), date(2024, 11, 12), date(2024, 11, 19),
+        date(2024, 12, 24), date(2024, 12, 31)
+    ]);
+    assert!(failed.is_empty());
+    assert!(duplicates.is_empty());
+
+    // Dates after a recurrence without exclusions are still separate dates
+    let (parsed, _, _) = parse_dates_from("every tue until 29 oct, 31 oct", today());
+    assert_eq!(parsed, vec![date(2024, 10, 22), date(2024, 10, 29), date(2024, 10, 31)]);
+}
+
+#[test]
+fn recurring_dates_interval_counts_from_period_start() {
+    // October started on a tuesday, so the cycle runs from monday 30 sep whichever day it is entered
+    assert_eq!(parse_recurring_dates_from("every 2 weeks on mon in october", today()), Ok(dates(2024, 10, &[28])));
+    assert_eq!(parse_recurring_dates_from("every 2 weeks on mon in october", date(2024, 10, 23)), Ok(dates(2024, 10, &[28])));
+}
+
+#[test]
+fn recurring_dates_with_relative_end() {
+    assert_eq!(parse_recurring_dates_from("every other sat until next sun", today()), Ok(dates(2024, 10, &[19])));
+    assert_eq!(parse_recurring_dates_from("weekends until next sun", today()), Ok(dates(2024, 10, &[19, 20, 26, 27])));
+}
+
+#[test]
+fn recurring_dates_rejects_unbounded_and_empty_rules() {
+    assert!(parse_recurring_dates_from("every sat", today()).is_err());
+    assert!(parse_recurring_dates_from("every sat until tomorrow", today()).is_err());
+    assert!(parse_recurring_dates_from("every day until december 2025", today()).is_err());
+}
+
+#[test]
+fn time_range() {
+    assert_eq!(
+        parse_time_range("0800-1730"),
+        Ok((NaiveTime::from_hms_opt(8, 0, 0).unwrap(), NaiveTime::from_hms_opt(17, 30, 0).unwrap()))
+    );
+    assert_eq!(
+        parse_time_range("08:00 to 17:30hrs"),
+        Ok((NaiveTime::from_hms_opt(8, 0, 0).unwrap(), NaiveTime::from_hms_opt(17, 30, 0).unwrap()))
+    );
+    assert!(parse_time_range("1730-0800").is_err());
+    assert!(parse_time_range("2500-2600").is_err());
+}
+
+#[test]
+fn slots_named_and_timed() {
+    let time = |hour: u32, min: u32| NaiveTime::from_hms_opt(hour, min, 0).unwrap();
+    assert_eq!(parse_slot("am"), Ok((time(8, 0), time(12, 0))));
+    assert_eq!(parse_slot(" Night "), Ok((time(19, 0), time(23, 0))));
+    assert_eq!(parse_slot("0730-1130"), Ok((time(7, 30), time(11, 30))));
+    assert!(parse_slot("evening").is_err());
+
+    assert_eq!(format_slot(time(13, 0), time(17, 0)), "PM (1300-1700)");
+    assert_eq!(format_slot(time(7, 30), time(11, 30)), "0730-1130");
+    assert_eq!(format_slot_suffix(None, None), "");
+
+    assert_eq!(split_slot("every Sat until Dec @ night"), ("every Sat until Dec ", Some("night")));
+    assert_eq!(split_slot("Jan 2, 28/2"), ("Jan 2, 28/2", None));
+}
+
+#[test]
+fn month_arithmetic() {
+    assert_eq!(add_month_safe(date(2024, 1, 31), 1), date(2024, 2, 29));
+    assert_eq!(add_month_safe(date(2024, 12, 15), 1), date(2025, 1, 15));
+    assert_eq!(add_month_safe(date(2024, 1, 15), -1), date(2023, 12, 15));
+    assert_eq!(last_day_of_month(date(2023, 2, 10)), date(2023, 2, 28));
+}
+
+#[test]
+fn csv_parsing_and_escaping() {
+    assert_eq!(
+        parse_csv("a,\"b, c\",\"say \"\"hi\"\"\"\nd,e,f\n"),
+        vec![
+            vec!["a".to_string(), "b, c".to_string(), "say \"hi\"".to_string()],
+            vec!["d".to_string(), "e".to_string(), "f".to_string()],
+        ]
+    );
+    assert_eq!(escape_csv_field("plain"), "plain");
+    assert_eq!(escape_csv_field("a,b"), "\"a,b\"");
+}
+
+#[test]
+fn name_validation() {
+    assert_eq!(cleanup_name("  John   Tan "), "John Tan");
+    assert!(is_valid_name("John Tan"));
+    assert!(!is_valid_name("John 2"));
+    assert!(is_valid_crew_type_name("PILOT2"));
+    assert!(!is_valid_crew_type_name("pilot"));
+    assert!(!is_valid_crew_type_name(""));
+    assert_eq!(escape_special_characters("a.b-c"), "a\\.b\\-c");
+}
+
+#[test]
+fn crew_type_names_are_uppercased_when_deserialized() {
+    use crate::types::{RoleType, UsrType};
+
+    assert_eq!(serde_json::from_str::<RoleType>("\"pilot\"").unwrap(), RoleType::new("PILOT"));
+    assert_eq!(serde_json::from_str::<UsrType>("\"Ns\"").unwrap(), UsrType::ns());
+}